@@ -0,0 +1,191 @@
+//! Imports generated wav file and loads it as `AudioClip` asset,
+//! reading samples chunk by chunk as streaming playback would.
+
+use {
+    goods::{
+        bytes::Bytes,
+        source::{AssetData, Source},
+        Asset, AssetBuild, Infallible, Loader, Uuid,
+    },
+    goods_audio::{
+        stream::{decode_samples, AudioHeader, HeaderError, AUDIO_NATIVE_FORMAT},
+        WAV_IMPORTER,
+    },
+    goods_treasury_import::{eyre, Importer, Registry},
+    std::{
+        future::{ready, Ready},
+        path::Path,
+    },
+};
+
+/// Audio clip loaded from native audio file.
+#[derive(Clone)]
+pub struct AudioClip {
+    header: AudioHeader,
+    data: Bytes,
+}
+
+impl AudioClip {
+    pub fn header(&self) -> &AudioHeader {
+        &self.header
+    }
+
+    /// Returns iterator over chunks of interleaved samples.
+    pub fn chunks(&self) -> impl Iterator<Item = Vec<i16>> + '_ {
+        (0..self.header.chunk_count()).map(move |index| {
+            let range = self.header.chunk_range(index);
+            decode_samples(&self.data[range.start as usize..range.end as usize]).collect()
+        })
+    }
+}
+
+impl Asset for AudioClip {
+    const FORMAT: &'static str = AUDIO_NATIVE_FORMAT;
+
+    type Decoded = Self;
+    type DecodeError = HeaderError;
+    type BuildError = Infallible;
+    type Fut = Ready<Result<Self, HeaderError>>;
+
+    fn decode(bytes: Bytes, _loader: &Loader) -> Self::Fut {
+        ready(AudioHeader::decode(&bytes).and_then(|header| {
+            if (bytes.len() as u64) < header.file_len() {
+                Err(HeaderError::TooShort)
+            } else {
+                Ok(AudioClip {
+                    header,
+                    data: bytes,
+                })
+            }
+        }))
+    }
+}
+
+impl<B> AssetBuild<B> for AudioClip {
+    fn build(decoded: Self, _builder: &mut B) -> Result<Self, Infallible> {
+        Ok(decoded)
+    }
+}
+
+/// Serves single native file.
+struct FileSource {
+    uuid: Uuid,
+    bytes: Bytes,
+}
+
+impl Source for FileSource {
+    type Error = Infallible;
+    type Fut = Ready<Result<Option<AssetData>, Infallible>>;
+
+    fn load(&self, uuid: &Uuid, _locale: Option<&str>) -> Self::Fut {
+        ready(Ok(if *uuid == self.uuid {
+            Some(AssetData {
+                bytes: self.bytes.clone(),
+                version: 0,
+                format: Some(AUDIO_NATIVE_FORMAT.into()),
+                signature: None,
+                locale: None,
+                name: None,
+                schema: None,
+                deprecated: false,
+            })
+        } else {
+            None
+        }))
+    }
+
+    fn update(&self, _uuid: &Uuid, _locale: Option<&str>, _version: u64) -> Self::Fut {
+        ready(Ok(None))
+    }
+}
+
+/// Registry for importer running outside of treasury.
+struct NoRegistry;
+
+impl Registry for NoRegistry {
+    fn store(&mut self, _: &Path, _: &str, _: &str, _: &[&str]) -> eyre::Result<Uuid> {
+        Err(eyre::eyre!("Sub-assets are not supported"))
+    }
+
+    fn fetch(&mut self, _: &Uuid) -> eyre::Result<Box<Path>> {
+        Err(eyre::eyre!("Sub-assets are not supported"))
+    }
+
+    fn set_meta(&mut self, meta: &str) -> eyre::Result<()> {
+        println!("Metadata: {}", meta);
+        Ok(())
+    }
+
+    fn add_dependency(&mut self, _: &Path) -> eyre::Result<()> {
+        Ok(())
+    }
+
+    fn add_locale(&mut self, _: &str) -> eyre::Result<()> {
+        Err(eyre::eyre!("Localized assets are not supported"))
+    }
+
+    fn set_schema(&mut self, _: u32) -> eyre::Result<()> {
+        Ok(())
+    }
+}
+
+fn main() {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(async move { run().await.unwrap() })
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir();
+    let source_path = dir.join("goods-audio-example.wav");
+    let native_path = dir.join("goods-audio-example.audio");
+
+    // One second of 440Hz stereo tone.
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = hound::WavWriter::create(&source_path, spec)?;
+    for frame in 0..44100 {
+        let t = frame as f32 / 44100.0;
+        let sample = ((t * 440.0 * std::f32::consts::TAU).sin() * 8000.0) as i16;
+        writer.write_sample(sample)?;
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+
+    WAV_IMPORTER.import(
+        &source_path,
+        &native_path,
+        r#"{"chunk_frames": 4096}"#,
+        &mut NoRegistry,
+        &mut (),
+    )?;
+
+    let uuid = Uuid::from_u128(1);
+    let source = FileSource {
+        uuid,
+        bytes: std::fs::read(&native_path)?.into(),
+    };
+
+    let loader = Loader::builder().with(source).build();
+    let mut result = loader.load::<AudioClip>(&uuid).await;
+    let clip = result.get(&mut ())?;
+
+    println!(
+        "Loaded clip: {} channels, {}Hz, {:?}",
+        clip.header().channels,
+        clip.header().sample_rate,
+        clip.header().duration(),
+    );
+
+    for (index, chunk) in clip.chunks().enumerate() {
+        println!("Chunk {}: {} samples", index, chunk.len());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,264 @@
+//! Audio importers for treasury.
+//!
+//! Imports `wav`, `ogg` (Vorbis) and `flac` files into chunked native format
+//! described in [`stream`] module.
+//!
+//! Sample rate, channel count, frame count and duration in seconds
+//! are recorded as metadata of the asset.
+
+pub mod stream;
+
+use {
+    crate::stream::{AudioHeader, AUDIO_NATIVE_FORMAT},
+    goods_treasury_import::{
+        eyre::{self, WrapErr},
+        ImportProgress, Importer, Registry,
+    },
+    std::{
+        fs::File,
+        io::{BufReader, BufWriter, Write},
+        path::Path,
+    },
+};
+
+#[cfg(target_os = "wasi")]
+pub use goods_treasury_import_ffi::{
+    treasury_importer_alloc, treasury_importer_dealloc, treasury_importer_ffi_version,
+    treasury_importer_import_trampoline, treasury_importer_name_source_native_trampoline,
+};
+
+/// Import settings of audio assets.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioSettings {
+    /// Number of frames per chunk.
+    /// Defaults to 16384.
+    pub chunk_frames: u32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            chunk_frames: 16384,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum AudioFormat {
+    Wav,
+    Ogg,
+    Flac,
+}
+
+/// Identifiers of formats imported by this crate.
+pub mod formats {
+    use goods_treasury_import::FormatId;
+
+    /// WAV audio source format.
+    pub const WAV: FormatId = FormatId::new("wav");
+
+    /// Ogg Vorbis audio source format.
+    pub const OGG: FormatId = FormatId::new("ogg");
+
+    /// FLAC audio source format.
+    pub const FLAC: FormatId = FormatId::new("flac");
+
+    /// Chunked audio native format.
+    pub const AUDIO: FormatId = FormatId::new(crate::stream::AUDIO_NATIVE_FORMAT);
+}
+
+/// Importer of audio files in particular format.
+pub struct AudioImporter {
+    source: &'static str,
+    format: AudioFormat,
+}
+
+pub static WAV_IMPORTER: AudioImporter = AudioImporter {
+    source: formats::WAV.as_str(),
+    format: AudioFormat::Wav,
+};
+
+pub static OGG_IMPORTER: AudioImporter = AudioImporter {
+    source: formats::OGG.as_str(),
+    format: AudioFormat::Ogg,
+};
+
+pub static FLAC_IMPORTER: AudioImporter = AudioImporter {
+    source: formats::FLAC.as_str(),
+    format: AudioFormat::Flac,
+};
+
+/// Decoded audio.
+struct Samples {
+    channels: u16,
+    sample_rate: u32,
+
+    /// Interleaved samples.
+    samples: Vec<i16>,
+}
+
+impl Importer for AudioImporter {
+    fn name(&self) -> &str {
+        self.source
+    }
+
+    fn source(&self) -> &str {
+        self.source
+    }
+
+    fn native(&self) -> &str {
+        AUDIO_NATIVE_FORMAT
+    }
+
+    fn import(
+        &self,
+        source_path: &Path,
+        native_path: &Path,
+        settings: &str,
+        registry: &mut dyn Registry,
+        progress: &mut dyn ImportProgress,
+    ) -> eyre::Result<()> {
+        let settings: AudioSettings = if settings.is_empty() {
+            AudioSettings::default()
+        } else {
+            serde_json::from_str(settings).wrap_err("Invalid audio import settings")?
+        };
+
+        if settings.chunk_frames == 0 {
+            eyre::bail!("Chunk must contain at least one frame");
+        }
+
+        let source = File::open(source_path)
+            .wrap_err_with(|| format!("Failed to open '{}'", source_path.display()))?;
+
+        let source = BufReader::new(source);
+
+        let decoded = match self.format {
+            AudioFormat::Wav => decode_wav(source),
+            AudioFormat::Ogg => decode_ogg(source),
+            AudioFormat::Flac => decode_flac(source),
+        }
+        .wrap_err_with(|| format!("Failed to decode '{}'", source_path.display()))?;
+
+        if decoded.channels == 0 {
+            eyre::bail!("Audio has no channels");
+        }
+
+        let header = AudioHeader {
+            channels: decoded.channels,
+            sample_rate: decoded.sample_rate,
+            chunk_frames: settings.chunk_frames,
+            frames: (decoded.samples.len() / decoded.channels as usize) as u64,
+        };
+
+        write_native(native_path, &header, &decoded.samples, progress)
+            .wrap_err_with(|| format!("Failed to write '{}'", native_path.display()))?;
+
+        let meta = serde_json::json!({
+            "sample_rate": header.sample_rate,
+            "channels": header.channels,
+            "frames": header.frames,
+            "duration": header.duration().as_secs_f64(),
+        });
+
+        registry.set_meta(&meta.to_string())
+    }
+}
+
+fn write_native(
+    native_path: &Path,
+    header: &AudioHeader,
+    samples: &[i16],
+    progress: &mut dyn ImportProgress,
+) -> std::io::Result<()> {
+    let mut native = BufWriter::new(File::create(native_path)?);
+    native.write_all(&header.encode())?;
+
+    let chunk_count = header.chunk_count();
+    let chunk_samples = header.chunk_frames as usize * header.channels as usize;
+
+    for (index, chunk) in samples.chunks(chunk_samples).enumerate() {
+        progress.report(index as u64, chunk_count, "chunks");
+
+        for sample in chunk {
+            native.write_all(&sample.to_le_bytes())?;
+        }
+    }
+
+    progress.report(chunk_count, chunk_count, "chunks");
+    native.flush()
+}
+
+fn decode_wav(source: BufReader<File>) -> eyre::Result<Samples> {
+    let reader = hound::WavReader::new(source)?;
+    let spec = reader.spec();
+
+    let samples = match (spec.sample_format, spec.bits_per_sample) {
+        (hound::SampleFormat::Float, _) => reader
+            .into_samples::<f32>()
+            .map(|sample| sample.map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
+            .collect::<Result<_, _>>()?,
+        (hound::SampleFormat::Int, bits) if bits <= 16 => reader
+            .into_samples::<i16>()
+            .map(|sample| sample.map(|sample| sample << (16 - bits)))
+            .collect::<Result<_, _>>()?,
+        (hound::SampleFormat::Int, bits) => reader
+            .into_samples::<i32>()
+            .map(|sample| sample.map(|sample| (sample >> (bits - 16)) as i16))
+            .collect::<Result<_, _>>()?,
+    };
+
+    Ok(Samples {
+        channels: spec.channels,
+        sample_rate: spec.sample_rate,
+        samples,
+    })
+}
+
+fn decode_ogg(source: BufReader<File>) -> eyre::Result<Samples> {
+    let mut reader = lewton::inside_ogg::OggStreamReader::new(source)?;
+
+    let mut samples = Vec::new();
+    while let Some(packet) = reader.read_dec_packet_itl()? {
+        samples.extend_from_slice(&packet);
+    }
+
+    Ok(Samples {
+        channels: reader.ident_hdr.audio_channels.into(),
+        sample_rate: reader.ident_hdr.audio_sample_rate,
+        samples,
+    })
+}
+
+fn decode_flac(source: BufReader<File>) -> eyre::Result<Samples> {
+    let mut reader = claxon::FlacReader::new(source)?;
+    let info = reader.streaminfo();
+    let bits = info.bits_per_sample;
+
+    let samples = reader
+        .samples()
+        .map(|sample| {
+            sample.map(|sample| {
+                if bits <= 16 {
+                    (sample << (16 - bits)) as i16
+                } else {
+                    (sample >> (bits - 16)) as i16
+                }
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Samples {
+        channels: info.channels as u16,
+        sample_rate: info.sample_rate,
+        samples,
+    })
+}
+
+#[cfg(target_os = "wasi")]
+goods_treasury_import_ffi::generate_imports_and_exports! {
+    &WAV_IMPORTER,
+    &OGG_IMPORTER,
+    &FLAC_IMPORTER,
+}
@@ -0,0 +1,139 @@
+//! Native audio format produced by audio importers.
+//!
+//! Native file consists of [`HEADER_LEN`] bytes long [`AudioHeader`]
+//! followed by chunks of interleaved 16-bit signed samples.
+//! All integers are little-endian.
+//!
+//! Every chunk but the last one holds exactly [`AudioHeader::chunk_frames`] frames,
+//! so offset of any chunk is known from the header alone.
+//! This allows playback to start as soon as first chunk is read
+//! and to seek without decoding preceding chunks.
+
+use std::{ops::Range, time::Duration};
+
+/// Native format name of the imported audio.
+pub const AUDIO_NATIVE_FORMAT: &str = "audio";
+
+/// Magic bytes at the beginning of native file.
+pub const MAGIC: [u8; 4] = *b"GAUD";
+
+/// Version of the layout.
+pub const VERSION: u16 = 1;
+
+/// Size of encoded [`AudioHeader`] in bytes.
+pub const HEADER_LEN: usize = 24;
+
+/// Size of one sample in bytes.
+pub const SAMPLE_LEN: usize = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HeaderError {
+    #[error("Audio data is too short")]
+    TooShort,
+
+    #[error("Not an audio data")]
+    BadMagic,
+
+    #[error("Audio data version {found} is not supported, expected {expected}")]
+    UnsupportedVersion { expected: u16, found: u16 },
+
+    #[error("Audio data has zero channels or chunk size")]
+    Malformed,
+}
+
+/// Describes audio stored in native file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AudioHeader {
+    /// Number of interleaved channels.
+    pub channels: u16,
+
+    /// Frames per second.
+    pub sample_rate: u32,
+
+    /// Number of frames in every chunk except the last one.
+    pub chunk_frames: u32,
+
+    /// Total number of frames.
+    pub frames: u64,
+}
+
+impl AudioHeader {
+    pub fn encode(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        bytes[0..4].copy_from_slice(&MAGIC);
+        bytes[4..6].copy_from_slice(&VERSION.to_le_bytes());
+        bytes[6..8].copy_from_slice(&self.channels.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.sample_rate.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.chunk_frames.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.frames.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes header from the beginning of native file.
+    pub fn decode(bytes: &[u8]) -> Result<Self, HeaderError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(HeaderError::TooShort);
+        }
+
+        if bytes[0..4] != MAGIC {
+            return Err(HeaderError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(HeaderError::UnsupportedVersion {
+                expected: VERSION,
+                found: version,
+            });
+        }
+
+        let header = AudioHeader {
+            channels: u16::from_le_bytes([bytes[6], bytes[7]]),
+            sample_rate: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            chunk_frames: u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+            frames: u64::from_le_bytes([
+                bytes[16], bytes[17], bytes[18], bytes[19], bytes[20], bytes[21], bytes[22],
+                bytes[23],
+            ]),
+        };
+
+        if header.channels == 0 || header.chunk_frames == 0 {
+            return Err(HeaderError::Malformed);
+        }
+
+        Ok(header)
+    }
+
+    pub fn duration(&self) -> Duration {
+        if self.sample_rate == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(self.frames as f64 / self.sample_rate as f64)
+        }
+    }
+
+    pub fn chunk_count(&self) -> u64 {
+        self.frames.div_ceil(self.chunk_frames as u64)
+    }
+
+    /// Returns byte range of the chunk within native file.
+    pub fn chunk_range(&self, index: u64) -> Range<u64> {
+        let frame_len = self.channels as u64 * SAMPLE_LEN as u64;
+        let first = (index * self.chunk_frames as u64).min(self.frames);
+        let last = (first + self.chunk_frames as u64).min(self.frames);
+
+        HEADER_LEN as u64 + first * frame_len..HEADER_LEN as u64 + last * frame_len
+    }
+
+    /// Returns size of native file with this header.
+    pub fn file_len(&self) -> u64 {
+        HEADER_LEN as u64 + self.frames * self.channels as u64 * SAMPLE_LEN as u64
+    }
+}
+
+/// Decodes chunk bytes into interleaved samples.
+pub fn decode_samples(chunk: &[u8]) -> impl Iterator<Item = i16> + '_ {
+    chunk
+        .chunks_exact(SAMPLE_LEN)
+        .map(|sample| i16::from_le_bytes([sample[0], sample[1]]))
+}
@@ -0,0 +1,116 @@
+//! Loads assets into components of Bevy entities,
+//! building them with resource inserted only after few frames.
+
+use {
+    bevy_app::App,
+    bevy_ecs::system::Resource,
+    goods::{
+        bytes::Bytes,
+        source::{AssetData, Source},
+        Asset, AssetBuild, Loader, Uuid,
+    },
+    goods_bevy::{GoodsAppExt, GoodsHandle, GoodsLoader, GoodsPlugin},
+    std::{
+        collections::HashMap,
+        convert::Infallible,
+        future::{ready, Ready},
+        string::FromUtf8Error,
+    },
+};
+
+#[derive(Clone, Debug)]
+struct Greeting(String);
+
+impl Asset for Greeting {
+    type Decoded = String;
+    type DecodeError = FromUtf8Error;
+    type BuildError = Infallible;
+    type Fut = Ready<Result<String, FromUtf8Error>>;
+
+    fn decode(bytes: Bytes, _loader: &Loader) -> Self::Fut {
+        ready(String::from_utf8(bytes.to_vec()))
+    }
+}
+
+/// Builder of greetings.
+#[derive(Resource)]
+struct Greeter {
+    prefix: &'static str,
+}
+
+impl AssetBuild<Greeter> for Greeting {
+    fn build(name: String, greeter: &mut Greeter) -> Result<Self, Infallible> {
+        Ok(Greeting(format!("{}, {}!", greeter.prefix, name)))
+    }
+}
+
+/// Dummy source which just gives bytes from map.
+struct HashMapSource(HashMap<Uuid, Bytes>);
+
+impl Source for HashMapSource {
+    type Error = Infallible;
+    type Fut = Ready<Result<Option<AssetData>, Infallible>>;
+
+    fn load(&self, uuid: &Uuid, _locale: Option<&str>) -> Self::Fut {
+        ready(Ok(self.0.get(uuid).map(|data| AssetData {
+            bytes: data.clone(),
+            version: 0,
+            format: None,
+            signature: None,
+            locale: None,
+            name: None,
+            schema: None,
+            deprecated: false,
+        })))
+    }
+
+    fn update(&self, _uuid: &Uuid, _locale: Option<&str>, _version: u64) -> Self::Fut {
+        ready(Ok(None))
+    }
+}
+
+fn main() {
+    let world = Uuid::from_u128(1);
+    let goods = Uuid::from_u128(2);
+    let missing = Uuid::from_u128(3);
+
+    let source = HashMapSource(
+        vec![
+            (world, Bytes::from_static(b"World")),
+            (goods, Bytes::from_static(b"Goods")),
+        ]
+        .into_iter()
+        .collect(),
+    );
+
+    let mut app = App::new();
+    app.add_plugins(GoodsPlugin::new(Loader::builder().with(source)))
+        .add_goods_asset::<Greeting, Greeter>();
+
+    let loader = app.world().resource::<GoodsLoader>().clone();
+    for uuid in &[world, goods, missing] {
+        app.world_mut()
+            .spawn(GoodsHandle::<Greeting>::new(&loader, uuid));
+    }
+
+    for frame in 0.. {
+        if frame == 3 {
+            app.insert_resource(Greeter { prefix: "Hello" });
+        }
+
+        app.update();
+
+        let mut handles = app.world_mut().query::<&GoodsHandle<Greeting>>();
+        if handles.iter(app.world()).all(|handle| !handle.is_pending()) {
+            for handle in handles.iter(app.world()) {
+                match (handle.get(), handle.error()) {
+                    (Some(greeting), _) => println!("{}: {}", handle.uuid(), greeting.0),
+                    (_, Some(err)) => println!("{}: {}", handle.uuid(), err),
+                    _ => unreachable!(),
+                }
+            }
+            println!("Built in {} frames", frame + 1);
+            break;
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//!
+//! Bevy integration of goods.
+//!
+//! [`GoodsPlugin`] inserts [`GoodsLoader`] resource with loading tasks running on Bevy's `IoTaskPool`.
+//! Entities get assets through [`GoodsHandle`] components,
+//! which are built once loaded by systems added with [`GoodsAppExt::add_goods_asset`]
+//! using builder resource, e.g. `RenderDevice` for GPU resources.
+//!
+//! ```ignore
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugins(GoodsPlugin::new(Loader::builder().with(source)))
+//!     .add_goods_asset::<Mesh, RenderDevice>();
+//!
+//! fn spawn(mut commands: Commands, loader: Res<GoodsLoader>) {
+//!     commands.spawn(GoodsHandle::<Mesh>::new(&loader, &uuid));
+//! }
+//! ```
+//!
+
+use {
+    bevy_app::{App, Last, Plugin, PreUpdate},
+    bevy_ecs::{
+        component::Component,
+        system::{Query, Res, ResMut, Resource},
+    },
+    bevy_tasks::{IoTaskPool, TaskPool},
+    futures::{future::BoxFuture, task::noop_waker_ref},
+    goods::{Asset, AssetBuild, AssetHandle, AssetResult, Error, Loader, LoaderBuilder, Spawner},
+    std::{
+        future::Future,
+        ops::Deref,
+        pin::Pin,
+        sync::Mutex,
+        task::{Context, Poll},
+    },
+    uuid::Uuid,
+};
+
+/// Spawns loading tasks onto Bevy's `IoTaskPool`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IoTaskPoolSpawner;
+
+impl Spawner for IoTaskPoolSpawner {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        IoTaskPool::get_or_init(TaskPool::default)
+            .spawn(future)
+            .detach();
+    }
+}
+
+/// Plugin inserting [`GoodsLoader`] resource and maintaining the loader every frame.
+pub struct GoodsPlugin {
+    builder: Mutex<Option<LoaderBuilder>>,
+}
+
+impl GoodsPlugin {
+    /// Returns plugin building loader with provided builder.
+    /// Spawner of the builder is replaced with [`IoTaskPoolSpawner`].
+    pub fn new(builder: LoaderBuilder) -> Self {
+        GoodsPlugin {
+            builder: Mutex::new(Some(builder)),
+        }
+    }
+}
+
+impl Plugin for GoodsPlugin {
+    fn build(&self, app: &mut App) {
+        let builder = self
+            .builder
+            .lock()
+            .unwrap()
+            .take()
+            .expect("GoodsPlugin is added once");
+
+        let loader = builder.with_spawner(IoTaskPoolSpawner).build();
+        app.insert_resource(GoodsLoader(loader))
+            .add_systems(Last, maintain_loader);
+    }
+}
+
+/// Loader resource inserted by [`GoodsPlugin`].
+#[derive(Clone, Resource)]
+pub struct GoodsLoader(pub Loader);
+
+impl Deref for GoodsLoader {
+    type Target = Loader;
+
+    fn deref(&self) -> &Loader {
+        &self.0
+    }
+}
+
+fn maintain_loader(loader: Res<GoodsLoader>) {
+    loader.maintain();
+}
+
+enum HandleState<A: Asset> {
+    Loading,
+    Loaded(AssetResult<A>),
+    Built(A),
+    Failed(Error),
+}
+
+/// Component referencing asset.
+/// Asset is accessible once loaded and built by system
+/// added with [`GoodsAppExt::add_goods_asset`].
+///
+/// Keeps asset from being unloaded while exists.
+#[derive(Component)]
+pub struct GoodsHandle<A: Asset> {
+    handle: AssetHandle<A>,
+    state: HandleState<A>,
+}
+
+impl<A> GoodsHandle<A>
+where
+    A: Asset,
+{
+    /// Starts loading the asset.
+    pub fn new(loader: &Loader, uuid: &Uuid) -> Self {
+        GoodsHandle::from_handle(loader.load(uuid))
+    }
+
+    pub fn from_handle(handle: AssetHandle<A>) -> Self {
+        GoodsHandle {
+            handle,
+            state: HandleState::Loading,
+        }
+    }
+
+    /// Returns uuid of the asset.
+    pub fn uuid(&self) -> &Uuid {
+        self.handle.uuid()
+    }
+
+    /// Returns asset if it is loaded and built.
+    pub fn get(&self) -> Option<&A> {
+        match &self.state {
+            HandleState::Built(asset) => Some(asset),
+            _ => None,
+        }
+    }
+
+    /// Returns error if asset failed to load or build.
+    pub fn error(&self) -> Option<&Error> {
+        match &self.state {
+            HandleState::Failed(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if asset is neither built nor failed yet.
+    pub fn is_pending(&self) -> bool {
+        matches!(self.state, HandleState::Loading | HandleState::Loaded(_))
+    }
+
+    fn update<B>(&mut self, builder: Option<&mut B>)
+    where
+        A: AssetBuild<B>,
+    {
+        if let HandleState::Loading = self.state {
+            let mut ctx = Context::from_waker(noop_waker_ref());
+            if let Poll::Ready(result) = Pin::new(&mut self.handle).poll(&mut ctx) {
+                self.state = HandleState::Loaded(result);
+            }
+        }
+
+        if let (HandleState::Loaded(result), Some(builder)) = (&mut self.state, builder) {
+            self.state = match result.get(builder) {
+                Ok(asset) => HandleState::Built(asset.clone()),
+                Err(err) => HandleState::Failed(err),
+            };
+        }
+    }
+}
+
+/// Builds loaded assets of entities with builder resource.
+/// Assets stay pending while the resource is absent.
+fn build_assets<A, B>(mut builder: Option<ResMut<B>>, mut handles: Query<&mut GoodsHandle<A>>)
+where
+    A: AssetBuild<B>,
+    B: Resource,
+{
+    for mut handle in handles.iter_mut() {
+        if handle.is_pending() {
+            handle.update(builder.as_deref_mut());
+        }
+    }
+}
+
+/// Extension methods for Bevy [`App`].
+pub trait GoodsAppExt {
+    /// Adds system building assets of [`GoodsHandle<A>`] components
+    /// with resource `B` as builder.
+    fn add_goods_asset<A, B>(&mut self) -> &mut Self
+    where
+        A: AssetBuild<B>,
+        B: Resource;
+}
+
+impl GoodsAppExt for App {
+    fn add_goods_asset<A, B>(&mut self) -> &mut Self
+    where
+        A: AssetBuild<B>,
+        B: Resource,
+    {
+        self.add_systems(PreUpdate, build_assets::<A, B>)
+    }
+}
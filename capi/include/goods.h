@@ -0,0 +1,236 @@
+#ifndef GOODS_H
+#define GOODS_H
+
+/* Generated by cbindgen from goods-capi sources. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of API call.
+ */
+typedef enum GoodsStatus {
+  GOODS_STATUS_OK = 0,
+  /**
+   * Requested asset does not exist.
+   */
+  GOODS_STATUS_NOT_FOUND = 1,
+  /**
+   * Null pointer or invalid UTF-8 string passed.
+   */
+  GOODS_STATUS_INVALID_ARGUMENT = 2,
+  /**
+   * Operation failed, see [`goods_last_error`].
+   */
+  GOODS_STATUS_ERROR = 3,
+} GoodsStatus;
+
+/**
+ * Loader reading assets from treasury.
+ */
+typedef struct GoodsLoader GoodsLoader;
+
+/**
+ * Opened treasury.
+ */
+typedef struct GoodsTreasury GoodsTreasury;
+
+/**
+ * Byte buffer owned by the caller.
+ * Must be released with [`goods_bytes_free`].
+ */
+typedef struct GoodsBytes {
+  uint8_t *ptr;
+  uintptr_t len;
+} GoodsBytes;
+
+/**
+ * Asset uuid as 16 bytes in big-endian order.
+ */
+typedef struct GoodsUuid {
+  uint8_t bytes[16];
+} GoodsUuid;
+
+/**
+ * Native asset data fetched from treasury, owned by the caller.
+ * Must be released with [`goods_asset_data_free`].
+ */
+typedef struct GoodsAssetData {
+  struct GoodsBytes bytes;
+  /**
+   * Version of native files, incremented on each reimport.
+   */
+  uint64_t version;
+  /**
+   * Schema version of native files.
+   */
+  uint32_t schema;
+  /**
+   * Nul-terminated native format name.
+   */
+  char *native_format;
+} GoodsAssetData;
+
+/**
+ * List of asset uuids owned by the caller.
+ * Must be released with [`goods_uuid_list_free`].
+ */
+typedef struct GoodsUuidList {
+  struct GoodsUuid *ptr;
+  uintptr_t len;
+} GoodsUuidList;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Releases byte buffer and resets it to empty.
+ *
+ * # Safety
+ *
+ * `bytes` must be null or point to buffer written by this API.
+ */
+void goods_bytes_free(struct GoodsBytes *bytes);
+
+/**
+ * Returns message of last error occurred on calling thread, or null.
+ * Pointer is valid until next API call on the same thread.
+ */
+const char *goods_last_error(void);
+
+/**
+ * Opens loader with treasury at the root directory as source.
+ * Writes handle to `out`, which must be released with [`goods_loader_close`].
+ *
+ * # Safety
+ *
+ * `root` must be a nul-terminated string, `out` must be valid for writes.
+ */
+enum GoodsStatus goods_loader_open(const char *root, struct GoodsLoader **out);
+
+/**
+ * Closes loader handle.
+ *
+ * # Safety
+ *
+ * `loader` must be null or handle returned by [`goods_loader_open`] not closed yet.
+ */
+void goods_loader_close(struct GoodsLoader *loader);
+
+/**
+ * Reads raw asset data, blocking until done.
+ * Writes data to `out`, which must be released with `goods_bytes_free`.
+ *
+ * # Safety
+ *
+ * `loader` must be valid handle, `out` must be valid for writes.
+ */
+enum GoodsStatus goods_loader_read(const struct GoodsLoader *loader,
+                                   struct GoodsUuid uuid,
+                                   struct GoodsBytes *out);
+
+/**
+ * Opens treasury at the root directory.
+ * Writes handle to `out`, which must be released with [`goods_treasury_close`].
+ *
+ * # Safety
+ *
+ * `root` must be a nul-terminated string, `out` must be valid for writes.
+ */
+enum GoodsStatus goods_treasury_open(const char *root, struct GoodsTreasury **out);
+
+/**
+ * Closes treasury handle.
+ *
+ * # Safety
+ *
+ * `treasury` must be null or handle returned by [`goods_treasury_open`] not closed yet.
+ */
+void goods_treasury_close(struct GoodsTreasury *treasury);
+
+/**
+ * Loads importers from the directory.
+ *
+ * # Safety
+ *
+ * `treasury` must be valid handle, `dir` must be a nul-terminated string.
+ */
+enum GoodsStatus goods_treasury_load_importers_dir(struct GoodsTreasury *treasury, const char *dir);
+
+/**
+ * Imports asset from source file and writes its uuid to `out`.
+ *
+ * # Safety
+ *
+ * `treasury` must be valid handle, strings must be nul-terminated,
+ * `tags` must point to `tags_len` strings, `out` must be valid for writes.
+ */
+enum GoodsStatus goods_treasury_store(const struct GoodsTreasury *treasury,
+                                      const char *source,
+                                      const char *source_format,
+                                      const char *native_format,
+                                      const char *const *tags,
+                                      uintptr_t tags_len,
+                                      struct GoodsUuid *out);
+
+/**
+ * Fetches native data of the asset, reimporting it if outdated.
+ * Writes data to `out`, which must be released with [`goods_asset_data_free`].
+ *
+ * # Safety
+ *
+ * `treasury` must be valid handle, `out` must be valid for writes.
+ */
+enum GoodsStatus goods_treasury_fetch(struct GoodsTreasury *treasury,
+                                      struct GoodsUuid uuid,
+                                      struct GoodsAssetData *out);
+
+/**
+ * Releases asset data.
+ *
+ * # Safety
+ *
+ * `data` must be null or point to data written by [`goods_treasury_fetch`].
+ */
+void goods_asset_data_free(struct GoodsAssetData *data);
+
+/**
+ * Writes uuids of assets with all specified tags to `out`,
+ * which must be released with [`goods_uuid_list_free`].
+ *
+ * # Safety
+ *
+ * `treasury` must be valid handle, `tags` must point to `tags_len` nul-terminated strings,
+ * `out` must be valid for writes.
+ */
+enum GoodsStatus goods_treasury_list(const struct GoodsTreasury *treasury,
+                                     const char *const *tags,
+                                     uintptr_t tags_len,
+                                     struct GoodsUuidList *out);
+
+/**
+ * Releases list of uuids.
+ *
+ * # Safety
+ *
+ * `list` must be null or point to list written by this API.
+ */
+void goods_uuid_list_free(struct GoodsUuidList *list);
+
+/**
+ * Saves treasury manifest.
+ *
+ * # Safety
+ *
+ * `treasury` must be valid handle.
+ */
+enum GoodsStatus goods_treasury_save(const struct GoodsTreasury *treasury);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* GOODS_H */
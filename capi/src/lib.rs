@@ -0,0 +1,213 @@
+//!
+//! C API of goods for engines written in C and C++.
+//!
+//! Header `include/goods.h` is generated on build.
+//!
+//! Functions return [`GoodsStatus`] and write results through out-pointers.
+//! On failure, message is available from [`goods_last_error`].
+//!
+//! Buffers written to out-pointers are owned by the caller
+//! and must be released with matching `*_free` function exactly once.
+//! Handles returned by `*_open` functions must be released with matching `*_close` function.
+//!
+
+mod loader;
+mod treasury;
+
+pub use self::{loader::*, treasury::*};
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr::null,
+};
+
+/// Result of API call.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoodsStatus {
+    Ok = 0,
+
+    /// Requested asset does not exist.
+    NotFound = 1,
+
+    /// Null pointer or invalid UTF-8 string passed.
+    InvalidArgument = 2,
+
+    /// Operation failed, see [`goods_last_error`].
+    Error = 3,
+}
+
+/// Asset uuid as 16 bytes in big-endian order.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GoodsUuid {
+    pub bytes: [u8; 16],
+}
+
+impl From<uuid::Uuid> for GoodsUuid {
+    fn from(uuid: uuid::Uuid) -> Self {
+        GoodsUuid {
+            bytes: *uuid.as_bytes(),
+        }
+    }
+}
+
+impl From<GoodsUuid> for uuid::Uuid {
+    fn from(uuid: GoodsUuid) -> Self {
+        uuid::Uuid::from_bytes(uuid.bytes)
+    }
+}
+
+/// Byte buffer owned by the caller.
+/// Must be released with [`goods_bytes_free`].
+#[repr(C)]
+pub struct GoodsBytes {
+    pub ptr: *mut u8,
+    pub len: usize,
+}
+
+impl GoodsBytes {
+    fn new(bytes: Box<[u8]>) -> Self {
+        let len = bytes.len();
+        GoodsBytes {
+            ptr: Box::into_raw(bytes) as *mut u8,
+            len,
+        }
+    }
+}
+
+/// Releases byte buffer and resets it to empty.
+///
+/// # Safety
+///
+/// `bytes` must be null or point to buffer written by this API.
+#[no_mangle]
+pub unsafe extern "C" fn goods_bytes_free(bytes: *mut GoodsBytes) {
+    if let Some(bytes) = bytes.as_mut() {
+        if !bytes.ptr.is_null() {
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                bytes.ptr, bytes.len,
+            )));
+        }
+        bytes.ptr = std::ptr::null_mut();
+        bytes.len = 0;
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Returns message of last error occurred on calling thread, or null.
+/// Pointer is valid until next API call on the same thread.
+#[no_mangle]
+pub extern "C" fn goods_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| match &*last.borrow() {
+        None => null(),
+        Some(message) => message.as_ptr(),
+    })
+}
+
+/// Failure of API call.
+pub(crate) enum Failure {
+    NotFound,
+    InvalidArgument(&'static str),
+    Error(String),
+}
+
+impl Failure {
+    /// Error with its sources.
+    pub(crate) fn error(err: &dyn std::error::Error) -> Self {
+        let mut message = err.to_string();
+        let mut source = err.source();
+        while let Some(err) = source {
+            message.push_str(". ");
+            message.push_str(&err.to_string());
+            source = err.source();
+        }
+        Failure::Error(message)
+    }
+}
+
+fn set_last_error(message: Option<String>) {
+    let message = message
+        .map(|message| CString::new(message.replace('\0', " ")).expect("Nul bytes are replaced"));
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Runs API call, converting failures and panics into status.
+pub(crate) fn guard(f: impl FnOnce() -> Result<(), Failure>) -> GoodsStatus {
+    let result = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = match panic.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match panic.downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "Panicked".to_owned(),
+            },
+        };
+        Err(Failure::Error(message))
+    });
+
+    match result {
+        Ok(()) => {
+            set_last_error(None);
+            GoodsStatus::Ok
+        }
+        Err(Failure::NotFound) => {
+            set_last_error(Some("Asset not found".to_owned()));
+            GoodsStatus::NotFound
+        }
+        Err(Failure::InvalidArgument(name)) => {
+            set_last_error(Some(format!("Invalid argument '{}'", name)));
+            GoodsStatus::InvalidArgument
+        }
+        Err(Failure::Error(message)) => {
+            set_last_error(Some(message));
+            GoodsStatus::Error
+        }
+    }
+}
+
+/// Reads nul-terminated UTF-8 string argument.
+pub(crate) unsafe fn arg_str<'a>(
+    ptr: *const c_char,
+    name: &'static str,
+) -> Result<&'a str, Failure> {
+    if ptr.is_null() {
+        return Err(Failure::InvalidArgument(name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| Failure::InvalidArgument(name))
+}
+
+/// Reads reference argument.
+pub(crate) unsafe fn arg_ref<'a, T>(ptr: *const T, name: &'static str) -> Result<&'a T, Failure> {
+    ptr.as_ref().ok_or(Failure::InvalidArgument(name))
+}
+
+/// Reads mutable reference argument.
+pub(crate) unsafe fn arg_mut<'a, T>(ptr: *mut T, name: &'static str) -> Result<&'a mut T, Failure> {
+    ptr.as_mut().ok_or(Failure::InvalidArgument(name))
+}
+
+/// Reads array of strings argument.
+pub(crate) unsafe fn arg_strs<'a>(
+    ptr: *const *const c_char,
+    len: usize,
+    name: &'static str,
+) -> Result<Vec<&'a str>, Failure> {
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    if ptr.is_null() {
+        return Err(Failure::InvalidArgument(name));
+    }
+    std::slice::from_raw_parts(ptr, len)
+        .iter()
+        .map(|&s| arg_str(s, name))
+        .collect()
+}
@@ -0,0 +1,69 @@
+use {
+    crate::{arg_mut, arg_ref, arg_str, guard, Failure, GoodsBytes, GoodsStatus, GoodsUuid},
+    futures::executor::block_on,
+    goods::{source::treasury::TreasurySource, Loader},
+    std::os::raw::c_char,
+};
+
+/// Loader reading assets from treasury.
+pub struct GoodsLoader(Loader);
+
+/// Opens loader with treasury at the root directory as source.
+/// Writes handle to `out`, which must be released with [`goods_loader_close`].
+///
+/// # Safety
+///
+/// `root` must be a nul-terminated string, `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn goods_loader_open(
+    root: *const c_char,
+    out: *mut *mut GoodsLoader,
+) -> GoodsStatus {
+    guard(|| {
+        let root = arg_str(root, "root")?;
+        let out = arg_mut(out, "out")?;
+        let source = TreasurySource::open(root).map_err(|err| Failure::error(&err))?;
+        let loader = Loader::builder().with(source).build();
+        *out = Box::into_raw(Box::new(GoodsLoader(loader)));
+        Ok(())
+    })
+}
+
+/// Closes loader handle.
+///
+/// # Safety
+///
+/// `loader` must be null or handle returned by [`goods_loader_open`] not closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn goods_loader_close(loader: *mut GoodsLoader) {
+    if !loader.is_null() {
+        drop(Box::from_raw(loader));
+    }
+}
+
+/// Reads raw asset data, blocking until done.
+/// Writes data to `out`, which must be released with `goods_bytes_free`.
+///
+/// # Safety
+///
+/// `loader` must be valid handle, `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn goods_loader_read(
+    loader: *const GoodsLoader,
+    uuid: GoodsUuid,
+    out: *mut GoodsBytes,
+) -> GoodsStatus {
+    guard(|| {
+        let loader = arg_ref(loader, "loader")?;
+        let out = arg_mut(out, "out")?;
+
+        match block_on(loader.0.read(&uuid.into())) {
+            Ok(bytes) => {
+                *out = GoodsBytes::new(bytes.to_vec().into_boxed_slice());
+                Ok(())
+            }
+            Err(err) if err.is_not_found() => Err(Failure::NotFound),
+            Err(err) => Err(Failure::error(&err)),
+        }
+    })
+}
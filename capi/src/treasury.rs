@@ -0,0 +1,236 @@
+use {
+    crate::{
+        arg_mut, arg_ref, arg_str, arg_strs, guard, Failure, GoodsBytes, GoodsStatus, GoodsUuid,
+    },
+    goods_treasury::{FetchError, Treasury},
+    std::{ffi::CString, os::raw::c_char, ptr::null_mut},
+};
+
+/// Opened treasury.
+pub struct GoodsTreasury(Treasury);
+
+/// Native asset data fetched from treasury, owned by the caller.
+/// Must be released with [`goods_asset_data_free`].
+#[repr(C)]
+pub struct GoodsAssetData {
+    pub bytes: GoodsBytes,
+
+    /// Version of native files, incremented on each reimport.
+    pub version: u64,
+
+    /// Schema version of native files.
+    pub schema: u32,
+
+    /// Nul-terminated native format name.
+    pub native_format: *mut c_char,
+}
+
+/// List of asset uuids owned by the caller.
+/// Must be released with [`goods_uuid_list_free`].
+#[repr(C)]
+pub struct GoodsUuidList {
+    pub ptr: *mut GoodsUuid,
+    pub len: usize,
+}
+
+/// Opens treasury at the root directory.
+/// Writes handle to `out`, which must be released with [`goods_treasury_close`].
+///
+/// # Safety
+///
+/// `root` must be a nul-terminated string, `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn goods_treasury_open(
+    root: *const c_char,
+    out: *mut *mut GoodsTreasury,
+) -> GoodsStatus {
+    guard(|| {
+        let root = arg_str(root, "root")?;
+        let out = arg_mut(out, "out")?;
+        let treasury = Treasury::open(root).map_err(|err| Failure::error(&err))?;
+        *out = Box::into_raw(Box::new(GoodsTreasury(treasury)));
+        Ok(())
+    })
+}
+
+/// Closes treasury handle.
+///
+/// # Safety
+///
+/// `treasury` must be null or handle returned by [`goods_treasury_open`] not closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn goods_treasury_close(treasury: *mut GoodsTreasury) {
+    if !treasury.is_null() {
+        drop(Box::from_raw(treasury));
+    }
+}
+
+/// Loads importers from the directory.
+///
+/// # Safety
+///
+/// `treasury` must be valid handle, `dir` must be a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn goods_treasury_load_importers_dir(
+    treasury: *mut GoodsTreasury,
+    dir: *const c_char,
+) -> GoodsStatus {
+    guard(|| {
+        let treasury = arg_mut(treasury, "treasury")?;
+        let dir = arg_str(dir, "dir")?;
+        treasury
+            .0
+            .load_importers_dir(dir)
+            .map_err(|err| Failure::error(&err))
+    })
+}
+
+/// Imports asset from source file and writes its uuid to `out`.
+///
+/// # Safety
+///
+/// `treasury` must be valid handle, strings must be nul-terminated,
+/// `tags` must point to `tags_len` strings, `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn goods_treasury_store(
+    treasury: *const GoodsTreasury,
+    source: *const c_char,
+    source_format: *const c_char,
+    native_format: *const c_char,
+    tags: *const *const c_char,
+    tags_len: usize,
+    out: *mut GoodsUuid,
+) -> GoodsStatus {
+    guard(|| {
+        let treasury = arg_ref(treasury, "treasury")?;
+        let source = arg_str(source, "source")?;
+        let source_format = arg_str(source_format, "source_format")?;
+        let native_format = arg_str(native_format, "native_format")?;
+        let tags = arg_strs(tags, tags_len, "tags")?;
+        let out = arg_mut(out, "out")?;
+
+        let uuid = treasury
+            .0
+            .store(source, source_format, native_format, &tags)
+            .map_err(|err| Failure::error(&err))?;
+        *out = uuid.into();
+        Ok(())
+    })
+}
+
+/// Fetches native data of the asset, reimporting it if outdated.
+/// Writes data to `out`, which must be released with [`goods_asset_data_free`].
+///
+/// # Safety
+///
+/// `treasury` must be valid handle, `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn goods_treasury_fetch(
+    treasury: *mut GoodsTreasury,
+    uuid: GoodsUuid,
+    out: *mut GoodsAssetData,
+) -> GoodsStatus {
+    guard(|| {
+        let treasury = arg_mut(treasury, "treasury")?;
+        let out = arg_mut(out, "out")?;
+
+        let data = match treasury.0.fetch(&uuid.into(), None) {
+            Ok(data) => data,
+            Err(FetchError::NotFound) => return Err(Failure::NotFound),
+            Err(err) => return Err(Failure::error(&err)),
+        };
+
+        let native_format =
+            CString::new(data.native_format.into_string()).map_err(|err| Failure::error(&err))?;
+
+        *out = GoodsAssetData {
+            bytes: GoodsBytes::new(data.bytes),
+            version: data.version,
+            schema: data.schema,
+            native_format: native_format.into_raw(),
+        };
+        Ok(())
+    })
+}
+
+/// Releases asset data.
+///
+/// # Safety
+///
+/// `data` must be null or point to data written by [`goods_treasury_fetch`].
+#[no_mangle]
+pub unsafe extern "C" fn goods_asset_data_free(data: *mut GoodsAssetData) {
+    if let Some(data) = data.as_mut() {
+        crate::goods_bytes_free(&mut data.bytes);
+        if !data.native_format.is_null() {
+            drop(CString::from_raw(data.native_format));
+        }
+        data.native_format = null_mut();
+    }
+}
+
+/// Writes uuids of assets with all specified tags to `out`,
+/// which must be released with [`goods_uuid_list_free`].
+///
+/// # Safety
+///
+/// `treasury` must be valid handle, `tags` must point to `tags_len` nul-terminated strings,
+/// `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn goods_treasury_list(
+    treasury: *const GoodsTreasury,
+    tags: *const *const c_char,
+    tags_len: usize,
+    out: *mut GoodsUuidList,
+) -> GoodsStatus {
+    guard(|| {
+        let treasury = arg_ref(treasury, "treasury")?;
+        let tags = arg_strs(tags, tags_len, "tags")?;
+        let out = arg_mut(out, "out")?;
+
+        let uuids: Box<[GoodsUuid]> = treasury
+            .0
+            .list(&tags, None)
+            .iter()
+            .map(|asset| asset.uuid().into())
+            .collect();
+
+        let len = uuids.len();
+        *out = GoodsUuidList {
+            ptr: Box::into_raw(uuids) as *mut GoodsUuid,
+            len,
+        };
+        Ok(())
+    })
+}
+
+/// Releases list of uuids.
+///
+/// # Safety
+///
+/// `list` must be null or point to list written by this API.
+#[no_mangle]
+pub unsafe extern "C" fn goods_uuid_list_free(list: *mut GoodsUuidList) {
+    if let Some(list) = list.as_mut() {
+        if !list.ptr.is_null() {
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                list.ptr, list.len,
+            )));
+        }
+        list.ptr = null_mut();
+        list.len = 0;
+    }
+}
+
+/// Saves treasury manifest.
+///
+/// # Safety
+///
+/// `treasury` must be valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn goods_treasury_save(treasury: *const GoodsTreasury) -> GoodsStatus {
+    guard(|| {
+        let treasury = arg_ref(treasury, "treasury")?;
+        treasury.0.save().map_err(|err| Failure::error(&err))
+    })
+}
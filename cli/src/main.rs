@@ -1,4 +1,7 @@
-use {clap::Clap, goods_treasury::*, tracing_subscriber::layer::SubscriberExt as _, uuid::Uuid};
+use {
+    clap::Clap, eyre::WrapErr as _, goods_treasury::*,
+    tracing_subscriber::layer::SubscriberExt as _, uuid::Uuid,
+};
 
 #[derive(Clap)]
 #[clap(version = "0.1", author = "Zakarum <zakarumych@ya.ru>")]
@@ -11,6 +14,10 @@ struct Opts {
     #[clap(short, long, parse(from_occurrences))]
     verbose: i32,
 
+    /// Path to file with 32 bytes key native files are encrypted with at rest.
+    #[clap(long)]
+    native_key_file: Option<String>,
+
     #[clap(subcommand)]
     subcmd: SubCommand,
 }
@@ -21,8 +28,31 @@ enum SubCommand {
     Update(CreateUpdate),
     Store(Store),
     Fetch(Fetch),
+    Stat(Stat),
     List(List),
     Remove(Remove),
+    Duplicate(Duplicate),
+    Verify(Verify),
+    Convert(Convert),
+    Pack(Pack),
+    PackDiff(PackDiff),
+    ApplyPatch(ApplyPatch),
+    Collection(Collection),
+    Policy(Policies),
+    Visibility(SetVisibility),
+    Lifecycle(SetLifecycle),
+    Name(SetName),
+    Pin(Pin),
+    Namespace(Namespace),
+    Checksums(Checksums),
+    IndexedManifest(IndexedManifest),
+    Compact(Compact),
+    Diff(Diff),
+    ReimportChanged(ReimportChanged),
+    ExportSubset(ExportSubset),
+    ImportSubset(ImportSubset),
+    Importers(DescribeImporters),
+    Graph(Graph),
 }
 
 /// A subcommand for creating new treasury
@@ -50,6 +80,33 @@ struct Store {
 
     #[clap(short, long)]
     tags: Vec<String>,
+
+    /// Import settings as JSON document.
+    #[clap(short, long)]
+    settings: Option<String>,
+
+    /// Detect source identical to source of existing asset.
+    /// "reuse" returns existing asset, "alias" also records source path as its alias.
+    #[clap(short, long)]
+    dedup: Option<String>,
+
+    /// Copy source as native file without importer.
+    /// "fallback" copies with a warning if no importer is found, "never" always requires importer.
+    #[clap(long)]
+    copy_through: Option<String>,
+
+    /// Import twice and report if importer output is not deterministic.
+    #[clap(long)]
+    check_determinism: bool,
+
+    /// Identity of the user the stored assets are attributed to.
+    /// Defaults to `TREASURY_USER`, `USER` or `USERNAME` environment variable.
+    #[clap(long)]
+    user: Option<String>,
+
+    /// Directory of import cache shared with other machines.
+    #[clap(long)]
+    import_cache: Option<String>,
 }
 
 /// A subcommand for registering assets
@@ -59,11 +116,24 @@ struct Fetch {
     #[clap(short, long)]
     binary: bool,
 
+    /// Locale to fetch native file for.
+    /// Falls back to less specific locales and then to native file without locale.
+    #[clap(short, long)]
+    locale: Option<String>,
+
     /// Path to asset source file.
     #[clap()]
     uuid: Uuid,
 }
 
+/// A subcommand for printing asset metadata without fetching it
+#[derive(Clap)]
+struct Stat {
+    /// Uuid of the asset.
+    #[clap()]
+    uuid: Uuid,
+}
+
 /// A subcommand for registering assets
 #[derive(Clap)]
 struct List {
@@ -74,6 +144,14 @@ struct List {
     /// Filter by importer.
     #[clap(short, long)]
     tags: Vec<String>,
+
+    /// Filter by visibility: "public", "editor-only" or "debug-only".
+    #[clap(long)]
+    visibility: Vec<String>,
+
+    /// Filter by lifecycle: "draft", "approved" or "deprecated".
+    #[clap(long)]
+    lifecycle: Vec<String>,
 }
 
 /// A subcommand for registering assets
@@ -84,6 +162,427 @@ struct Remove {
     uuids: Vec<Uuid>,
 }
 
+/// A subcommand for registering copy of an asset under new uuid
+#[derive(Clap)]
+struct Duplicate {
+    /// Uuid of the asset to copy.
+    #[clap()]
+    uuid: Uuid,
+
+    /// Tags of the copy.
+    #[clap(short, long)]
+    tags: Vec<String>,
+}
+
+/// A subcommand for reporting uuids the treasury has no asset for
+#[derive(Clap)]
+struct Verify {
+    /// Uuids to check, e.g. reported missing by the loader.
+    #[clap(required = true)]
+    uuids: Vec<Uuid>,
+}
+
+/// A subcommand for reimporting assets into another native format
+#[derive(Clap)]
+struct Convert {
+    /// Native format to convert assets into.
+    #[clap()]
+    native_format: String,
+
+    /// Convert only assets of this native format.
+    #[clap(short, long)]
+    from: Option<String>,
+
+    /// Convert only assets with these tags.
+    #[clap(short, long)]
+    tags: Vec<String>,
+}
+
+/// A subcommand for packing native files for shipping
+#[derive(Clap)]
+struct Pack {
+    /// Path to pack file to write.
+    #[clap()]
+    output: String,
+
+    /// Pack only assets with all specified tags.
+    #[clap(short, long)]
+    tags: Vec<String>,
+
+    /// Include assets with specified visibility besides public ones.
+    #[clap(short, long)]
+    include: Vec<String>,
+
+    /// Include draft assets, e.g. in internal builds.
+    #[clap(long)]
+    drafts: bool,
+
+    /// Path to file with 32 bytes key to encrypt pack with.
+    #[clap(short, long)]
+    key_file: Option<String>,
+
+    /// Path to file with 32 bytes ed25519 secret key to sign pack with.
+    #[clap(short, long)]
+    signing_key_file: Option<String>,
+
+    /// Path to recorded load session to lay out the pack by.
+    #[clap(short, long)]
+    layout: Option<String>,
+}
+
+/// A subcommand for writing patch between two versions of a pack
+#[derive(Clap)]
+struct PackDiff {
+    /// Path to old version of the pack.
+    #[clap()]
+    old: String,
+
+    /// Path to new version of the pack.
+    #[clap()]
+    new: String,
+
+    /// Path to patch file to write.
+    #[clap()]
+    output: String,
+
+    /// Path to file with 32 bytes key packs are encrypted with.
+    #[clap(short, long)]
+    key_file: Option<String>,
+
+    /// Path to file with 32 bytes ed25519 secret key to sign patch with.
+    #[clap(short, long)]
+    signing_key_file: Option<String>,
+}
+
+/// A subcommand for applying patches to a pack
+#[derive(Clap)]
+struct ApplyPatch {
+    /// Path to the pack.
+    #[clap()]
+    pack: String,
+
+    /// Paths to patches in the order they were made.
+    #[clap(required = true)]
+    patches: Vec<String>,
+
+    /// Path to pack file to write. Defaults to the pack itself.
+    #[clap(short, long)]
+    output: Option<String>,
+
+    /// Path to file with 32 bytes key pack and patches are encrypted with.
+    #[clap(short, long)]
+    key_file: Option<String>,
+
+    /// Path to file with 32 bytes ed25519 secret key to sign pack with.
+    #[clap(short, long)]
+    signing_key_file: Option<String>,
+}
+
+/// A subcommand for setting lifecycle of assets
+#[derive(Clap)]
+struct SetLifecycle {
+    /// Lifecycle: "draft", "approved" or "deprecated".
+    #[clap()]
+    lifecycle: String,
+
+    /// Uuids of assets.
+    #[clap(required = true)]
+    uuids: Vec<Uuid>,
+}
+
+/// A subcommand for setting visibility of assets
+#[derive(Clap)]
+struct SetVisibility {
+    /// Visibility: "public", "editor-only" or "debug-only".
+    #[clap()]
+    visibility: String,
+
+    /// Uuids of assets.
+    #[clap(required = true)]
+    uuids: Vec<Uuid>,
+}
+
+/// A subcommand for setting human-readable name of an asset used in errors and logs
+#[derive(Clap)]
+struct SetName {
+    /// Uuid of the asset.
+    #[clap()]
+    uuid: Uuid,
+
+    /// Name of the asset. Name is reset to source file stem if omitted.
+    #[clap()]
+    name: Option<String>,
+}
+
+/// A subcommand for pinning assets as always resident
+#[derive(Clap)]
+struct Pin {
+    /// Unpin assets instead.
+    #[clap(short, long)]
+    unpin: bool,
+
+    /// Uuids of assets.
+    #[clap(required = true)]
+    uuids: Vec<Uuid>,
+}
+
+/// A subcommand for exporting assets to be imported into another treasury
+#[derive(Clap)]
+struct ExportSubset {
+    /// Path to bundle file to write.
+    #[clap()]
+    output: String,
+
+    /// Export only assets with all of these tags.
+    #[clap(short, long)]
+    tags: Vec<String>,
+}
+
+/// A subcommand for exporting graph of assets and assets they pull in
+#[derive(Clap)]
+struct Graph {
+    /// Path to file to write. Graph is printed when omitted.
+    #[clap(short, long)]
+    output: Option<String>,
+
+    /// Write JSON Graph Format document instead of Graphviz DOT.
+    #[clap(long)]
+    json: bool,
+
+    /// Color of assets with the native format, as `format=color`.
+    #[clap(long)]
+    format_color: Vec<String>,
+
+    /// Color of assets with the tag, as `tag=color`.
+    /// Overrides color of native format.
+    #[clap(long)]
+    tag_color: Vec<String>,
+}
+
+/// A subcommand for importing assets exported from another treasury
+#[derive(Clap)]
+struct ImportSubset {
+    /// Path to bundle file.
+    #[clap()]
+    bundle: String,
+
+    /// Handling of already registered assets. One of "skip", "overwrite" or "fail".
+    #[clap(short, long, default_value = "skip")]
+    conflict: String,
+}
+
+/// A subcommand for listing importers modules and why they failed to load
+#[derive(Clap)]
+struct DescribeImporters {
+    /// Lists importers used for imports with paths they are loaded from instead.
+    #[clap(short, long)]
+    active: bool,
+}
+
+/// A subcommand for printing or setting project namespace
+#[derive(Clap)]
+struct Namespace {
+    /// Namespace to set.
+    #[clap(conflicts_with_all = &["generate", "clear"])]
+    namespace: Option<Uuid>,
+
+    /// Sets newly generated namespace.
+    #[clap(short, long, conflicts_with = "clear")]
+    generate: bool,
+
+    /// Removes namespace.
+    #[clap(short, long)]
+    clear: bool,
+}
+
+/// A subcommand for enabling checksums file written on save
+#[derive(Clap)]
+struct Checksums {
+    /// Stops writing checksums file instead.
+    #[clap(short, long)]
+    disable: bool,
+}
+
+/// A subcommand for keeping asset records in indexed file loaded on demand
+#[derive(Clap)]
+struct IndexedManifest {
+    /// Moves asset records back into JSON manifest instead.
+    #[clap(short, long)]
+    disable: bool,
+}
+
+/// A subcommand for removing files no asset refers to from treasury directory
+#[derive(Clap)]
+struct Compact {}
+
+/// A subcommand for listing assets added, removed and changed in another treasury
+#[derive(Clap)]
+struct Diff {
+    /// Root of the other treasury, e.g. checkout of a content branch.
+    #[clap()]
+    other: String,
+}
+
+/// A subcommand for reimporting assets with sources changed in git since a commit
+#[derive(Clap)]
+struct ReimportChanged {
+    /// Commit to compare work tree with, e.g. `ORIG_HEAD` after pull.
+    #[clap()]
+    commit: String,
+}
+
+/// A subcommand for managing ordered asset collections
+#[derive(Clap)]
+struct Collection {
+    /// Name of the collection.
+    #[clap()]
+    name: String,
+
+    /// Lists members when omitted.
+    #[clap(subcommand)]
+    action: Option<CollectionAction>,
+}
+
+#[derive(Clap)]
+enum CollectionAction {
+    /// Appends assets to the collection.
+    Add { uuids: Vec<Uuid> },
+
+    /// Removes assets from the collection.
+    Remove { uuids: Vec<Uuid> },
+
+    /// Moves member of the collection to specified position.
+    Move { uuid: Uuid, index: usize },
+}
+
+/// A subcommand for managing size policies checked on store and reimport
+#[derive(Clap)]
+struct Policies {
+    /// Lists policies when omitted.
+    #[clap(subcommand)]
+    action: Option<PoliciesAction>,
+}
+
+#[derive(Clap)]
+enum PoliciesAction {
+    /// Limits size of native file of each asset with the native format.
+    Format {
+        format: String,
+        /// Maximum size in bytes.
+        max: u64,
+        /// Warn about violations instead of rejecting imports.
+        #[clap(long)]
+        warn: bool,
+    },
+
+    /// Limits total size of native files of assets with the tag.
+    Tag {
+        tag: String,
+        /// Maximum size in bytes.
+        max: u64,
+        /// Warn about violations instead of rejecting imports.
+        #[clap(long)]
+        warn: bool,
+    },
+
+    /// Removes all policies.
+    Clear,
+}
+
+/// Splits `key=value` argument.
+fn parse_assignment(arg: &str) -> eyre::Result<(&str, &str)> {
+    match arg.split_once('=') {
+        Some(pair) => Ok(pair),
+        None => eyre::bail!("Expected 'key=value', found '{}'", arg),
+    }
+}
+
+fn parse_visibility(visibility: &str) -> eyre::Result<Visibility> {
+    match visibility {
+        "public" => Ok(Visibility::Public),
+        "editor-only" => Ok(Visibility::EditorOnly),
+        "debug-only" => Ok(Visibility::DebugOnly),
+        _ => eyre::bail!("Unknown visibility '{}'", visibility),
+    }
+}
+
+fn parse_lifecycle(lifecycle: &str) -> eyre::Result<Lifecycle> {
+    match lifecycle {
+        "draft" => Ok(Lifecycle::Draft),
+        "approved" => Ok(Lifecycle::Approved),
+        "deprecated" => Ok(Lifecycle::Deprecated),
+        _ => eyre::bail!("Unknown lifecycle '{}'", lifecycle),
+    }
+}
+
+fn read_key(cd: &std::path::Path, key_file: &str) -> eyre::Result<PackKey> {
+    let bytes = std::fs::read(cd.join(key_file))
+        .wrap_err_with(|| format!("Failed to read key file '{}'", key_file))?;
+
+    match PackKey::from_slice(&bytes) {
+        Some(key) => Ok(key),
+        None => eyre::bail!("Key file '{}' must contain 32 bytes", key_file),
+    }
+}
+
+fn pack_options(
+    cd: &std::path::Path,
+    key_file: Option<&str>,
+    signing_key_file: Option<&str>,
+) -> eyre::Result<PackOptions> {
+    let mut options = PackOptions::new();
+
+    if let Some(key_file) = key_file {
+        options = options.with_key(read_key(cd, key_file)?);
+    }
+
+    if let Some(key_file) = signing_key_file {
+        let bytes = std::fs::read(cd.join(key_file))
+            .wrap_err_with(|| format!("Failed to read key file '{}'", key_file))?;
+
+        match std::convert::TryInto::<[u8; 32]>::try_into(&bytes[..]) {
+            Ok(key) => options = options.with_signing_key(SigningKey::from_bytes(&key)),
+            Err(_) => eyre::bail!("Key file '{}' must contain 32 bytes", key_file),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Returns seconds since Unix epoch.
+fn unix_time(time: Option<std::time::SystemTime>) -> Option<u64> {
+    time.and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since| since.as_secs())
+}
+
+/// Cancels the token on Ctrl-C.
+/// Second Ctrl-C terminates the process without waiting for cancellation.
+fn cancel_on_ctrl_c(cancel: CancelToken) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            tracing::warn!("Failed to listen for Ctrl-C: {}", err);
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        runtime.block_on(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                eprintln!("\nCancelling, press Ctrl-C again to abort");
+                cancel.cancel();
+
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    std::process::exit(130);
+                }
+            }
+        })
+    });
+}
+
 pub fn main() -> eyre::Result<()> {
     if let Err(err) = color_eyre::install() {
         tracing::error!("Failed to install eyre report handler: {}", err);
@@ -109,9 +608,20 @@ pub fn main() -> eyre::Result<()> {
         tracing::error!("Failed to install tracing subscriber: {}", err);
     }
 
+    let native_key = match &opts.native_key_file {
+        None => None,
+        Some(key_file) => Some(read_key(&cd, key_file)?),
+    };
+    let open = |root: std::path::PathBuf| -> eyre::Result<Treasury> {
+        let treasury = Treasury::open(root)?;
+        treasury.set_native_key(native_key.clone());
+        Ok(treasury)
+    };
+
     match opts.subcmd {
         SubCommand::Create(create) => {
             let mut treasury = Treasury::new(cd.join(&opts.root), false)?;
+            treasury.set_native_key(native_key.clone());
 
             for dir_path in create.importers {
                 treasury.load_importers_dir(cd.join(&dir_path))?;
@@ -122,7 +632,7 @@ pub fn main() -> eyre::Result<()> {
             println!("New goods created at '{}'", opts.root)
         }
         SubCommand::Update(create) => {
-            let mut treasury = Treasury::open(cd.join(&opts.root))?;
+            let mut treasury = open(cd.join(&opts.root))?;
 
             for dir_path in create.importers {
                 treasury.load_importers_dir(cd.join(&dir_path))?;
@@ -133,22 +643,73 @@ pub fn main() -> eyre::Result<()> {
             println!("New goods created at '{}'", opts.root)
         }
         SubCommand::Store(store) => {
-            let treasury = Treasury::open(cd.join(&opts.root))?;
+            let mut treasury = open(cd.join(&opts.root))?;
+
+            match store.dedup.as_deref() {
+                None => {}
+                Some("reuse") => treasury.set_dedup(Dedup::Reuse),
+                Some("alias") => treasury.set_dedup(Dedup::Alias),
+                Some(dedup) => eyre::bail!("Unknown dedup mode '{}'", dedup),
+            }
 
-            let uuid = treasury.store(
+            match store.copy_through.as_deref() {
+                None => {}
+                Some("fallback") => treasury.set_copy_through(CopyThrough::Fallback),
+                Some("never") => treasury.set_copy_through(CopyThrough::Never),
+                Some(copy_through) => eyre::bail!("Unknown copy-through mode '{}'", copy_through),
+            }
+
+            treasury.set_check_determinism(store.check_determinism);
+            if let Some(user) = &store.user {
+                treasury.set_identity(user);
+            }
+            if let Some(import_cache) = &store.import_cache {
+                treasury.set_import_cache(DirImportCache::new(import_cache));
+            }
+
+            let settings = match &store.settings {
+                None => serde_json::Value::Null,
+                Some(settings) => serde_json::from_str(settings)
+                    .wrap_err_with(|| format!("Invalid import settings '{}'", settings))?,
+            };
+
+            let cancel = CancelToken::new();
+            cancel_on_ctrl_c(cancel.clone());
+
+            let mut reported = false;
+            let uuid = treasury.store_cancellable(
                 store.source_path,
                 &store.source_format,
                 &store.native_format,
+                &settings,
                 &store.tags,
-            )?;
+                |done, total, stage: &str| {
+                    reported = true;
+                    eprint!("\r{}: {}/{}", stage, done, total);
+                },
+                &cancel,
+            );
+
+            if reported {
+                eprintln!();
+            }
+
+            let uuid = uuid?;
 
             treasury.save()?;
 
             println!("New asset registered as '{}'", uuid);
+
+            for import in treasury.determinism_report() {
+                eprintln!(
+                    "Importer '{}' is not deterministic for '{}'. {}",
+                    import.importer, import.uuid, import.reason
+                );
+            }
         }
         SubCommand::Fetch(fetch) => {
-            let mut treasury = Treasury::open(cd.join(&opts.root))?;
-            let data = treasury.fetch(&fetch.uuid)?;
+            let mut treasury = open(cd.join(&opts.root))?;
+            let data = treasury.fetch(&fetch.uuid, fetch.locale.as_deref())?;
             println!("Asset loaded. Size: {}", data.bytes.len());
 
             if fetch.binary {
@@ -167,9 +728,103 @@ pub fn main() -> eyre::Result<()> {
                 eprintln!("Data is too long");
             }
         }
+        SubCommand::Stat(stat) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let stat = treasury.stat(stat.uuid)?;
+            println!(
+                "{} '{}' as '{}'",
+                stat.uuid, stat.source_format, stat.native_format
+            );
+            println!("Tags: [{}]", stat.tags.join(", "));
+            println!("Version: {}", stat.version);
+            println!("Schema: {}", stat.schema);
+            match stat.native_size {
+                Some(size) => println!("Native size: {}", size),
+                None => println!("Native file is missing"),
+            }
+            println!("Up-to-date: {}", stat.up_to_date);
+            if let Some(stored_by) = &stat.stored_by {
+                println!("Stored by: {}", stored_by);
+            }
+            if let Some(reimported_by) = &stat.reimported_by {
+                println!("Reimported by: {}", reimported_by);
+            }
+            if let Some(stored_at) = unix_time(stat.stored_at) {
+                println!("Stored at: {} (Unix time)", stored_at);
+            }
+            if let Some(reimported_at) = unix_time(stat.reimported_at) {
+                println!("Reimported at: {} (Unix time)", reimported_at);
+            }
+        }
+        SubCommand::Importers(DescribeImporters { active: true }) => {
+            let treasury = open(cd.join(&opts.root))?;
+            for importer in treasury.importers() {
+                print!(
+                    "'{}' from '{}' to '{}'",
+                    importer.name, importer.source, importer.native
+                );
+                if let Some(version) = &importer.version {
+                    print!(" version {}", version);
+                }
+                match &importer.origin {
+                    Some(origin) => println!(" @ {}", origin.display()),
+                    None => println!(" @ <added by application>"),
+                }
+            }
+        }
+        SubCommand::Importers(DescribeImporters { active: false }) => {
+            let treasury = open(cd.join(&opts.root))?;
+            for module in treasury.describe_importers() {
+                match &module.path {
+                    Some(path) => print!("{}", path.display()),
+                    None => print!("<added by application>"),
+                }
+                if let (Some(name), Some(version)) = (&module.name, &module.version) {
+                    print!(" {} {}", name, version);
+                }
+                if let Some(ffi_version) = module.ffi_version {
+                    print!(" (FFI {})", ffi_version);
+                }
+                println!();
+
+                if !module.features.is_empty() {
+                    println!("  Requires features: {}", module.features.join(", "));
+                }
+                for importer in &module.importers {
+                    print!(
+                        "  '{}' from '{}' to '{}'",
+                        importer.name, importer.source, importer.native
+                    );
+                    match &importer.version {
+                        Some(version) => println!(" version {}", version),
+                        None => println!(),
+                    }
+                }
+                if let Some(error) = &module.error {
+                    println!("  Not loaded: {}", error);
+                }
+            }
+        }
         SubCommand::List(list) => {
-            let treasury = Treasury::open(cd.join(&opts.root))?;
-            let assets = treasury.list(&list.tags, list.native_format.as_deref());
+            let treasury = open(cd.join(&opts.root))?;
+            let mut assets = if list.visibility.is_empty() {
+                treasury.list(&list.tags, list.native_format.as_deref())
+            } else {
+                let visibilities = list
+                    .visibility
+                    .iter()
+                    .map(|v| parse_visibility(v))
+                    .collect::<eyre::Result<Vec<_>>>()?;
+                treasury.list_visible(&list.tags, list.native_format.as_deref(), &visibilities)
+            };
+            if !list.lifecycle.is_empty() {
+                let lifecycles = list
+                    .lifecycle
+                    .iter()
+                    .map(|l| parse_lifecycle(l))
+                    .collect::<eyre::Result<Vec<_>>>()?;
+                assets.retain(|a| lifecycles.contains(&a.lifecycle()));
+            }
             println!("{} assets found", assets.len());
             for asset in assets {
                 if opts.verbose > 0 {
@@ -180,11 +835,349 @@ pub fn main() -> eyre::Result<()> {
             }
         }
         SubCommand::Remove(remove) => {
-            let treasury = Treasury::open(cd.join(&opts.root))?;
+            let treasury = open(cd.join(&opts.root))?;
             for uuid in &remove.uuids {
                 treasury.remove(*uuid);
             }
         }
+        SubCommand::Duplicate(duplicate) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let uuid = treasury
+                .duplicate(duplicate.uuid, &duplicate.tags)
+                .wrap_err_with(|| format!("Failed to duplicate '{}'", duplicate.uuid))?;
+            treasury.save()?;
+            println!("{}", uuid);
+        }
+        SubCommand::Verify(verify) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let dangling = treasury.verify(&verify.uuids);
+            for uuid in &dangling {
+                println!("{}", uuid);
+            }
+            if !dangling.is_empty() {
+                eyre::bail!("{} uuids are not in the treasury", dangling.len());
+            }
+        }
+        SubCommand::Convert(convert) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let converted = treasury.convert(
+                |asset| {
+                    convert
+                        .from
+                        .as_deref()
+                        .is_none_or(|from| asset.native_format() == from)
+                        && convert
+                            .tags
+                            .iter()
+                            .all(|tag| asset.tags().iter().any(|t| **t == **tag))
+                },
+                &convert.native_format,
+            );
+            treasury.save()?;
+            println!("{} assets converted", converted.len());
+            for uuid in converted {
+                println!("{}", uuid);
+            }
+        }
+        SubCommand::Pack(pack) => {
+            let treasury = open(cd.join(&opts.root))?;
+
+            let mut options = pack_options(
+                &cd,
+                pack.key_file.as_deref(),
+                pack.signing_key_file.as_deref(),
+            )?;
+            for include in &pack.include {
+                options = options.with_visibility(parse_visibility(include)?);
+            }
+            if pack.drafts {
+                options = options.with_lifecycle(Lifecycle::Draft);
+            }
+
+            let count = match &pack.layout {
+                None => treasury.pack(cd.join(&pack.output), &pack.tags, &options)?,
+                Some(layout) => {
+                    let recording = Recording::read(cd.join(layout))?;
+                    treasury.pack_with_layout(
+                        cd.join(&pack.output),
+                        &pack.tags,
+                        &options,
+                        &recording,
+                    )?
+                }
+            };
+            println!("{} native files packed into '{}'", count, pack.output);
+        }
+        SubCommand::PackDiff(diff) => {
+            let options = pack_options(
+                &cd,
+                diff.key_file.as_deref(),
+                diff.signing_key_file.as_deref(),
+            )?;
+
+            let count = pack_diff(
+                cd.join(&diff.old),
+                cd.join(&diff.new),
+                cd.join(&diff.output),
+                &options,
+            )?;
+            println!("{} entries written into patch '{}'", count, diff.output);
+        }
+        SubCommand::ApplyPatch(apply) => {
+            let options = pack_options(
+                &cd,
+                apply.key_file.as_deref(),
+                apply.signing_key_file.as_deref(),
+            )?;
+
+            let output = apply.output.as_ref().unwrap_or(&apply.pack);
+            let patches: Vec<_> = apply.patches.iter().map(|patch| cd.join(patch)).collect();
+
+            let count = apply_patch(cd.join(&apply.pack), &patches, cd.join(output), &options)?;
+            println!("{} native files written into '{}'", count, output);
+        }
+        SubCommand::Visibility(set) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let visibility = parse_visibility(&set.visibility)?;
+            for uuid in set.uuids {
+                treasury
+                    .set_visibility(uuid, visibility)
+                    .wrap_err_with(|| format!("Failed to set visibility of '{}'", uuid))?;
+            }
+            treasury.save()?;
+        }
+        SubCommand::Lifecycle(set) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let lifecycle = parse_lifecycle(&set.lifecycle)?;
+            for uuid in set.uuids {
+                treasury
+                    .set_lifecycle(uuid, lifecycle)
+                    .wrap_err_with(|| format!("Failed to set lifecycle of '{}'", uuid))?;
+            }
+            treasury.save()?;
+        }
+        SubCommand::Name(set) => {
+            let treasury = open(cd.join(&opts.root))?;
+            treasury
+                .set_name(set.uuid, set.name.as_deref())
+                .wrap_err_with(|| format!("Failed to set name of '{}'", set.uuid))?;
+            treasury.save()?;
+        }
+        SubCommand::Pin(pin) => {
+            let treasury = open(cd.join(&opts.root))?;
+            for uuid in pin.uuids {
+                let result = if pin.unpin {
+                    treasury.unpin(uuid)
+                } else {
+                    treasury.pin(uuid)
+                };
+                result.wrap_err_with(|| format!("Failed to pin '{}'", uuid))?;
+            }
+            treasury.save()?;
+        }
+        SubCommand::ReimportChanged(reimport) => {
+            let mut treasury = open(cd.join(&opts.root))?;
+            let marked = treasury.mark_changed_since(&reimport.commit)?;
+
+            let cancel = CancelToken::new();
+            cancel_on_ctrl_c(cancel.clone());
+
+            let mut reimported = 0;
+            for uuid in &marked {
+                if cancel.is_cancelled() {
+                    eprintln!("Reimport cancelled");
+                    break;
+                }
+                treasury
+                    .fetch(uuid, None)
+                    .wrap_err_with(|| format!("Failed to reimport '{}'", uuid))?;
+                reimported += 1;
+            }
+            println!("{} assets reimported", reimported);
+        }
+        SubCommand::ExportSubset(export) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let bundle = treasury.export_subset(|asset| {
+                export
+                    .tags
+                    .iter()
+                    .all(|tag| asset.tags().iter().any(|t| **t == **tag))
+            })?;
+            bundle.write(cd.join(&export.output))?;
+            println!("{} assets exported into '{}'", bundle.len(), export.output);
+        }
+        SubCommand::Graph(graph) => {
+            let treasury = open(cd.join(&opts.root))?;
+
+            let mut style = GraphStyle::new();
+            for arg in &graph.format_color {
+                let (format, color) = parse_assignment(arg)?;
+                style = style.with_format(format, NodeStyle::color(color));
+            }
+            for arg in &graph.tag_color {
+                let (tag, color) = parse_assignment(arg)?;
+                style = style.with_tag(tag, NodeStyle::color(color));
+            }
+
+            let format = match graph.json {
+                false => GraphFormat::Dot,
+                true => GraphFormat::GraphJson,
+            };
+            let exported = treasury.export_graph_with_style(format, &style);
+
+            match &graph.output {
+                None => print!("{}", exported),
+                Some(output) => std::fs::write(cd.join(output), exported)
+                    .wrap_err_with(|| format!("Failed to write graph to '{}'", output))?,
+            }
+        }
+        SubCommand::ImportSubset(import) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let conflict = match &*import.conflict {
+                "skip" => SubsetConflict::Skip,
+                "overwrite" => SubsetConflict::Overwrite,
+                "fail" => SubsetConflict::Fail,
+                other => eyre::bail!("Unknown conflict handling '{}'", other),
+            };
+            let bundle = SubsetBundle::read(cd.join(&import.bundle))?;
+            let count = treasury.import_subset(bundle, conflict)?;
+            treasury.save()?;
+            println!("{} assets imported", count);
+        }
+        SubCommand::Checksums(checksums) => {
+            let treasury = open(cd.join(&opts.root))?;
+            treasury.set_checksums(!checksums.disable);
+            treasury.save()?;
+        }
+        SubCommand::IndexedManifest(indexed) => {
+            let treasury = open(cd.join(&opts.root))?;
+            treasury.set_indexed_manifest(!indexed.disable);
+            treasury.save()?;
+        }
+        SubCommand::Compact(_) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let report = treasury.compact(|_, _| {})?;
+            for path in &report.removed {
+                println!("{}", path.display());
+            }
+            println!(
+                "{} files removed, {} bytes freed",
+                report.removed.len(),
+                report.freed
+            );
+        }
+        SubCommand::Diff(diff) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let other = open(cd.join(&diff.other))?;
+            let diff = treasury.diff(&other);
+            for asset in &diff.added {
+                println!("+ {} {}", asset.uuid(), asset.name());
+            }
+            for asset in &diff.removed {
+                println!("- {} {}", asset.uuid(), asset.name());
+            }
+            for change in &diff.changed {
+                let what: Vec<&str> = [
+                    (change.native, "native"),
+                    (change.source, "source"),
+                    (change.settings, "settings"),
+                    (change.tags, "tags"),
+                ]
+                .iter()
+                .filter(|(changed, _)| *changed)
+                .map(|(_, what)| *what)
+                .collect();
+                println!("~ {} {} ({})", change.uuid, change.name, what.join(", "));
+            }
+        }
+        SubCommand::Namespace(namespace) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let new = if namespace.generate {
+                Some(Some(Uuid::new_v4()))
+            } else if namespace.clear {
+                Some(None)
+            } else {
+                namespace.namespace.map(Some)
+            };
+
+            match new {
+                None => match treasury.namespace() {
+                    None => println!("No namespace"),
+                    Some(namespace) => println!("{}", namespace),
+                },
+                Some(new) => {
+                    treasury.set_namespace(new);
+                    treasury.save()?;
+                    if let Some(new) = new {
+                        println!("{}", new);
+                    }
+                }
+            }
+        }
+        SubCommand::Collection(collection) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let name = &collection.name;
+
+            match collection.action {
+                None => match treasury.collection(name) {
+                    None => eyre::bail!("Collection '{}' not found", name),
+                    Some(members) => {
+                        for uuid in members {
+                            println!("{}", uuid);
+                        }
+                    }
+                },
+                Some(CollectionAction::Add { uuids }) => {
+                    for uuid in uuids {
+                        treasury
+                            .add_to_collection(name, uuid)
+                            .wrap_err_with(|| format!("Failed to add '{}'", uuid))?;
+                    }
+                    treasury.save()?;
+                }
+                Some(CollectionAction::Remove { uuids }) => {
+                    for uuid in uuids {
+                        treasury.remove_from_collection(name, uuid);
+                    }
+                    treasury.save()?;
+                }
+                Some(CollectionAction::Move { uuid, index }) => {
+                    if !treasury.move_in_collection(name, uuid, index) {
+                        eyre::bail!("'{}' is not a member of collection '{}'", uuid, name);
+                    }
+                    treasury.save()?;
+                }
+            }
+        }
+        SubCommand::Policy(policies) => {
+            let treasury = open(cd.join(&opts.root))?;
+            let action = |warn| match warn {
+                true => PolicyAction::Warn,
+                false => PolicyAction::Reject,
+            };
+
+            match policies.action {
+                None => {
+                    for policy in treasury.size_policies() {
+                        println!("{}", policy);
+                    }
+                }
+                Some(PoliciesAction::Format { format, max, warn }) => {
+                    treasury.add_size_policy(
+                        SizePolicy::native_format(&format, max).with_action(action(warn)),
+                    );
+                    treasury.save()?;
+                }
+                Some(PoliciesAction::Tag { tag, max, warn }) => {
+                    treasury.add_size_policy(SizePolicy::tag(&tag, max).with_action(action(warn)));
+                    treasury.save()?;
+                }
+                Some(PoliciesAction::Clear) => {
+                    treasury.clear_size_policies();
+                    treasury.save()?;
+                }
+            }
+        }
     }
 
     Ok(())
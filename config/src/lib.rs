@@ -0,0 +1,182 @@
+//! Config importer for treasury.
+//!
+//! Imports JSON documents into `config` native format,
+//! loadable as `goods::ConfigAsset`.
+//! Native file contains compact JSON with object keys sorted.
+//!
+//! Documents may be validated against JSON schema specified in import settings,
+//! see [`ConfigSettings`].
+//! Documents that fail validation are rejected with line and column of offending values.
+//!
+//! Localized string tables are imported from CSV documents, see [`strings`] module.
+
+mod locate;
+pub mod strings;
+
+use {
+    goods_treasury_import::{
+        eyre::{self, WrapErr},
+        ImportProgress, Importer, Registry,
+    },
+    jsonschema::JSONSchema,
+    serde_json::Value,
+    std::path::{Path, PathBuf},
+};
+
+#[cfg(target_os = "wasi")]
+pub use goods_treasury_import_ffi::{
+    treasury_importer_alloc, treasury_importer_dealloc, treasury_importer_ffi_version,
+    treasury_importer_import_trampoline, treasury_importer_name_source_native_trampoline,
+};
+
+/// Native format name of the imported configs.
+pub const CONFIG_NATIVE_FORMAT: &str = "config";
+
+/// Identifiers of formats imported by this crate.
+pub mod formats {
+    use goods_treasury_import::FormatId;
+
+    /// JSON document source format.
+    pub const JSON: FormatId = FormatId::new("json");
+
+    /// CSV string table source format.
+    pub const CSV: FormatId = FormatId::new("csv");
+
+    /// Config native format.
+    pub const CONFIG: FormatId = FormatId::new(super::CONFIG_NATIVE_FORMAT);
+}
+
+/// Import settings of config assets.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigSettings {
+    /// Schema to validate document against.
+    pub schema: Option<Schema>,
+}
+
+/// JSON schema of config documents.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Schema {
+    /// Path to schema file relative to the source directory.
+    /// Schema file is recorded as dependency of the asset.
+    Path(PathBuf),
+
+    /// Schema embedded into settings.
+    Inline(Value),
+}
+
+/// Importer of JSON configs.
+pub struct ConfigImporter;
+
+pub static JSON_IMPORTER: ConfigImporter = ConfigImporter;
+
+impl Importer for ConfigImporter {
+    fn name(&self) -> &str {
+        "json-config"
+    }
+
+    fn source(&self) -> &str {
+        formats::JSON.as_str()
+    }
+
+    fn native(&self) -> &str {
+        CONFIG_NATIVE_FORMAT
+    }
+
+    fn import(
+        &self,
+        source_path: &Path,
+        native_path: &Path,
+        settings: &str,
+        registry: &mut dyn Registry,
+        _progress: &mut dyn ImportProgress,
+    ) -> eyre::Result<()> {
+        let settings: ConfigSettings = if settings.is_empty() {
+            ConfigSettings::default()
+        } else {
+            serde_json::from_str(settings).wrap_err("Invalid config import settings")?
+        };
+
+        let source = std::fs::read_to_string(source_path)
+            .wrap_err_with(|| format!("Failed to read '{}'", source_path.display()))?;
+
+        let document: Value = serde_json::from_str(&source)
+            .wrap_err_with(|| format!("Failed to parse '{}'", source_path.display()))?;
+
+        if let Some(schema) = &settings.schema {
+            let schema = match schema {
+                Schema::Inline(schema) => schema.clone(),
+                Schema::Path(path) => {
+                    let path = source_path
+                        .parent()
+                        .unwrap_or_else(|| Path::new(""))
+                        .join(path);
+
+                    registry.add_dependency(&path)?;
+                    read_schema(&path)?
+                }
+            };
+
+            validate(&schema, &document, &source, source_path)?;
+        }
+
+        let native = serde_json::to_vec(&document)?;
+        std::fs::write(native_path, native)
+            .wrap_err_with(|| format!("Failed to write '{}'", native_path.display()))?;
+
+        Ok(())
+    }
+}
+
+fn read_schema(path: &Path) -> eyre::Result<Value> {
+    let schema = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read schema '{}'", path.display()))?;
+
+    serde_json::from_str(&schema)
+        .wrap_err_with(|| format!("Failed to parse schema '{}'", path.display()))
+}
+
+fn validate(
+    schema: &Value,
+    document: &Value,
+    source: &str,
+    source_path: &Path,
+) -> eyre::Result<()> {
+    let schema = JSONSchema::compile(schema)
+        .map_err(|err| eyre::eyre!("Invalid schema. {} at '{}'", err, err.schema_path))?;
+
+    let errors = match schema.validate(document) {
+        Ok(()) => return Ok(()),
+        Err(errors) => errors,
+    };
+
+    let messages: Vec<_> = errors
+        .map(|err| {
+            let path: Vec<_> = err.instance_path.iter().cloned().collect();
+
+            match locate::locate(source, &path) {
+                Some((line, column)) => {
+                    format!("'{}':{}:{}: {}", source_path.display(), line, column, err)
+                }
+                None => format!(
+                    "'{}' at '{}': {}",
+                    source_path.display(),
+                    err.instance_path,
+                    err
+                ),
+            }
+        })
+        .collect();
+
+    Err(eyre::eyre!(
+        "Config does not match schema\n{}",
+        messages.join("\n")
+    ))
+}
+
+#[cfg(target_os = "wasi")]
+goods_treasury_import_ffi::generate_imports_and_exports! {
+    &JSON_IMPORTER,
+    &strings::CSV_STRINGS_IMPORTER,
+}
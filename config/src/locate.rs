@@ -0,0 +1,161 @@
+//! Locates values of JSON document by their paths.
+//!
+//! `serde_json::Value` does not keep positions,
+//! so the document text is walked again to report validation errors
+//! with line and column.
+
+use jsonschema::paths::PathChunk;
+
+/// Returns 1-based line and column of the value at specified path.
+/// Document must be valid JSON.
+pub fn locate(source: &str, path: &[PathChunk]) -> Option<(usize, usize)> {
+    let mut walker = Walker { source, pos: 0 };
+    walker.skip_ws();
+
+    for chunk in path {
+        match chunk {
+            PathChunk::Property(key) => walker.enter_property(key)?,
+            PathChunk::Index(index) => walker.enter_index(*index)?,
+            PathChunk::Keyword(_) => return None,
+        }
+    }
+
+    let prefix = &source[..walker.pos];
+    let line = prefix.matches('\n').count() + 1;
+    let line_start = prefix.rfind('\n').map_or(0, |pos| pos + 1);
+    let column = prefix[line_start..].chars().count() + 1;
+    Some((line, column))
+}
+
+struct Walker<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl Walker<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips byte and following whitespace.
+    fn bump(&mut self) {
+        self.pos += 1;
+        self.skip_ws();
+    }
+
+    /// Moves to value of the property of the object at current position.
+    fn enter_property(&mut self, key: &str) -> Option<()> {
+        if self.peek()? != b'{' {
+            return None;
+        }
+        self.bump();
+
+        loop {
+            if self.peek()? == b'}' {
+                return None;
+            }
+
+            let name = self.string()?;
+            self.skip_ws();
+            debug_assert_eq!(self.peek(), Some(b':'));
+            self.bump();
+
+            if name == key {
+                return Some(());
+            }
+
+            self.skip_value()?;
+            if self.peek()? == b',' {
+                self.bump();
+            }
+        }
+    }
+
+    /// Moves to element of the array at current position.
+    fn enter_index(&mut self, index: usize) -> Option<()> {
+        if self.peek()? != b'[' {
+            return None;
+        }
+        self.bump();
+
+        for _ in 0..index {
+            if self.peek()? == b']' {
+                return None;
+            }
+
+            self.skip_value()?;
+            if self.peek()? == b',' {
+                self.bump();
+            }
+        }
+
+        if self.peek()? == b']' {
+            None
+        } else {
+            Some(())
+        }
+    }
+
+    /// Parses string at current position.
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.skip_string()?;
+        serde_json::from_str(&self.source[start..self.pos]).ok()
+    }
+
+    fn skip_string(&mut self) -> Option<()> {
+        debug_assert_eq!(self.peek(), Some(b'"'));
+        self.pos += 1;
+
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// Skips value at current position and following whitespace.
+    fn skip_value(&mut self) -> Option<()> {
+        let mut depth = 0usize;
+
+        loop {
+            match self.peek()? {
+                b'"' => self.skip_string()?,
+                b'{' | b'[' => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                b'}' | b']' => {
+                    depth -= 1;
+                    self.pos += 1;
+                }
+                _ if depth > 0 => self.pos += 1,
+                _ => {
+                    // Scalar at top level ends at delimiter.
+                    while !matches!(
+                        self.peek(),
+                        None | Some(b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r')
+                    ) {
+                        self.pos += 1;
+                    }
+                }
+            }
+
+            if depth == 0 {
+                self.skip_ws();
+                return Some(());
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Importer of localized string tables.
+//!
+//! Source is CSV document with header row `key,<locale>,<locale>,...`
+//! and a row per string.
+//! Each locale column is written as separate native file,
+//! a JSON object mapping keys to strings,
+//! loadable as `goods::ConfigAsset<HashMap<String, String>>`.
+
+use {
+    crate::CONFIG_NATIVE_FORMAT,
+    goods_treasury_import::{
+        eyre::{self, WrapErr},
+        localized_native_path, ImportProgress, Importer, Registry,
+    },
+    serde_json::{Map, Value},
+    std::path::Path,
+};
+
+/// Import settings of string tables.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StringsSettings {
+    /// Locale column written as native file without locale.
+    /// Defaults to the first locale column.
+    pub default_locale: Option<String>,
+}
+
+/// Importer of CSV string tables.
+pub struct StringsImporter;
+
+pub static CSV_STRINGS_IMPORTER: StringsImporter = StringsImporter;
+
+impl Importer for StringsImporter {
+    fn name(&self) -> &str {
+        "csv-strings"
+    }
+
+    fn source(&self) -> &str {
+        crate::formats::CSV.as_str()
+    }
+
+    fn native(&self) -> &str {
+        CONFIG_NATIVE_FORMAT
+    }
+
+    fn import(
+        &self,
+        source_path: &Path,
+        native_path: &Path,
+        settings: &str,
+        registry: &mut dyn Registry,
+        _progress: &mut dyn ImportProgress,
+    ) -> eyre::Result<()> {
+        let settings: StringsSettings = if settings.is_empty() {
+            StringsSettings::default()
+        } else {
+            serde_json::from_str(settings).wrap_err("Invalid strings import settings")?
+        };
+
+        let mut reader = csv::Reader::from_path(source_path)
+            .wrap_err_with(|| format!("Failed to read '{}'", source_path.display()))?;
+
+        let header = reader.headers()?.clone();
+        let locales: Vec<&str> = header.iter().skip(1).map(str::trim).collect();
+
+        if locales.is_empty() {
+            eyre::bail!("String table must have at least one locale column");
+        }
+
+        let default = match &settings.default_locale {
+            None => 0,
+            Some(default_locale) => locales
+                .iter()
+                .position(|locale| *locale == default_locale)
+                .ok_or_else(|| eyre::eyre!("No column for locale '{}'", default_locale))?,
+        };
+
+        let mut tables = vec![Map::new(); locales.len()];
+
+        for record in reader.records() {
+            let record =
+                record.wrap_err_with(|| format!("Failed to parse '{}'", source_path.display()))?;
+
+            let key = match record.get(0) {
+                Some(key) if !key.is_empty() => key,
+                _ => continue,
+            };
+
+            let default_string = record.get(default + 1).unwrap_or_default();
+
+            for (index, table) in tables.iter_mut().enumerate() {
+                // Missing translations fall back to the default locale.
+                let string = match record.get(index + 1) {
+                    Some(string) if !string.is_empty() => string,
+                    _ => default_string,
+                };
+                table.insert(key.to_owned(), Value::String(string.to_owned()));
+            }
+        }
+
+        for (index, (locale, table)) in locales.iter().zip(tables).enumerate() {
+            let native = serde_json::to_vec(&Value::Object(table))?;
+
+            let path = if index == default {
+                native_path.to_owned()
+            } else {
+                registry.add_locale(locale)?;
+                localized_native_path(native_path, locale)
+            };
+
+            std::fs::write(&path, native)
+                .wrap_err_with(|| format!("Failed to write '{}'", path.display()))?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use {
     goods::{
+        bytes::Bytes,
         source::{AssetData, Source},
         Asset, AssetField, Loader, Uuid,
     },
@@ -40,6 +41,7 @@ pub struct ComplexAsset {
 }
 
 #[derive(Clone, Asset)]
+#[asset(format = "wrapper")]
 pub struct WrapperAsset {
     wrapped: u32,
 }
@@ -74,26 +76,41 @@ pub struct AssetWithOption {
     foo: Option<SimpleAsset>,
 }
 
+#[derive(Clone, Asset)]
+pub struct AssetWithOptionalExternal {
+    #[optional_external]
+    present: Option<SimpleAsset>,
+
+    #[optional_external]
+    absent: Option<SimpleAsset>,
+}
+
 #[derive(Clone, serde::Deserialize)]
 struct SimpleFieldType {}
 
 /// Dummy source which just gives bytes from map.
-struct HashMapSource(HashMap<Uuid, Box<[u8]>>);
+struct HashMapSource(HashMap<Uuid, Bytes>);
 
 impl Source for HashMapSource {
     type Error = Infallible;
     type Fut = Ready<Result<Option<AssetData>, Infallible>>;
 
-    fn load(&self, uuid: &Uuid) -> Self::Fut {
+    fn load(&self, uuid: &Uuid, _locale: Option<&str>) -> Self::Fut {
         ready(Ok(match self.0.get(uuid) {
             Some(data) => Some(AssetData {
                 bytes: data.clone(),
                 version: 0,
+                format: None,
+                signature: None,
+                locale: None,
+                name: None,
+                schema: None,
+                deprecated: false,
             }),
             None => None,
         }))
     }
-    fn update(&self, _uuid: &Uuid, _version: u64) -> Self::Fut {
+    fn update(&self, _uuid: &Uuid, _locale: Option<&str>, _version: u64) -> Self::Fut {
         ready(Ok(None))
     }
 }
@@ -109,35 +126,36 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
     // Fill map.
     let source = HashMapSource(
         vec![
-            (Uuid::from_u128(2), b"null".to_vec().into_boxed_slice()),
+            (Uuid::from_u128(2), Bytes::from_static(b"null")),
             (
                 Uuid::from_u128(3),
-                b"{\"field\": {}}".to_vec().into_boxed_slice(),
+                Bytes::from_static(b"{\"field\": {}}"),
             ),
             (
                 Uuid::from_u128(4),
-                b"{\"a\":\"00000000-0000-0000-0000-000000000003\"}"
-                    .to_vec()
-                    .into_boxed_slice(),
+                Bytes::from_static(b"{\"a\":\"00000000-0000-0000-0000-000000000003\"}"),
             ),
             (
                 Uuid::from_u128(5),
-                b"{\"c\": {\"a\":\"00000000-0000-0000-0000-000000000002\"}, \"a\":\"00000000-0000-0000-0000-000000000003\"}"
-                    .to_vec()
-                    .into_boxed_slice(),
+                Bytes::from_static(b"{\"c\": {\"a\":\"00000000-0000-0000-0000-000000000002\"}, \"a\":\"00000000-0000-0000-0000-000000000003\"}"),
             ),
             (
                 Uuid::from_u128(6),
-                b"{\"wrapped\": 42}".to_vec().into_boxed_slice(),
+                Bytes::from_static(b"{\"wrapped\": 42}"),
             ),
             (
                 Uuid::from_u128(7),
-                b"{\"a\":\"00000000-0000-0000-0000-000000000006\"}".to_vec().into_boxed_slice(),
+                Bytes::from_static(b"{\"a\":\"00000000-0000-0000-0000-000000000006\"}"),
             ),
             (
                 Uuid::from_u128(8),
-                b"{}".to_vec().into_boxed_slice(),
+                Bytes::from_static(b"{}"),
             ),
+            (
+                Uuid::from_u128(9),
+                Bytes::from_static(b"{\"present\":\"00000000-0000-0000-0000-000000000003\", \"absent\":\"00000000-0000-0000-0000-000000000010\"}"),
+            ),
+            (Uuid::from_u128(10), Bytes::from_static(b"{}")),
         ]
         .into_iter()
         .collect(),
@@ -166,8 +184,13 @@ async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let _: &AssetWithSerdeAttribute = loader.load(&Uuid::from_u128(8)).await.get(&mut ())?;
     println!("AssetWithSerdeAttribute loaded");
 
-    let _: &AssetWithOption = loader.load(&Uuid::from_u128(8)).await.get(&mut ())?;
+    let _: &AssetWithOption = loader.load(&Uuid::from_u128(10)).await.get(&mut ())?;
     println!("AssetWithOption loaded");
 
+    let mut result = loader.load(&Uuid::from_u128(9)).await;
+    let asset: &AssetWithOptionalExternal = result.get(&mut ())?;
+    assert!(asset.present.is_some() && asset.absent.is_none());
+    println!("AssetWithOptionalExternal loaded");
+
     Ok(())
 }
@@ -0,0 +1,65 @@
+//! Native font format produced by font importers.
+//!
+//! Native file contains single [`FontAtlas`] serialized with `bincode`
+//! using default options.
+
+use serde::{Deserialize, Serialize};
+
+/// Native format name of the imported fonts.
+pub const FONT_NATIVE_FORMAT: &str = "sdf-font";
+
+/// Glyphs of a font rendered into single-channel signed distance field atlas.
+///
+/// Atlas value of 128 lies on glyph outline.
+/// Values grow inside glyphs and fall outside,
+/// changing by 127 over [`FontAtlas::spread`] pixels.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FontAtlas {
+    /// Size in pixels glyphs were rendered at.
+    /// All metrics are in pixels of this size.
+    pub size: f32,
+
+    /// Distance in pixels encoded by the field on each side of the outline.
+    pub spread: u32,
+
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+
+    pub width: u32,
+    pub height: u32,
+
+    /// Row-major atlas pixels, top row first.
+    pub pixels: Vec<u8>,
+
+    /// Glyphs sorted by codepoint.
+    pub glyphs: Vec<Glyph>,
+
+    /// Kerning pairs with non-zero adjustment, sorted by codepoints.
+    pub kerning: Vec<Kerning>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Glyph {
+    pub codepoint: char,
+
+    /// Horizontal advance to the next glyph.
+    pub advance: f32,
+
+    /// Offset of the bottom-left corner of the glyph rectangle from the pen position.
+    /// Y axis points up. Includes spread padding.
+    pub offset: [i32; 2],
+
+    /// Glyph rectangle in the atlas as `[x, y, width, height]`, including spread padding.
+    /// Empty for glyphs without outline, e.g. space.
+    pub rect: [u32; 4],
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Kerning {
+    pub left: char,
+    pub right: char,
+
+    /// Adjustment added to advance of the left glyph.
+    pub adjustment: f32,
+}
@@ -0,0 +1,301 @@
+//! Font importers for treasury.
+//!
+//! Imports TrueType and OpenType fonts into signed distance field atlases
+//! described in [`atlas`] module.
+//!
+//! Glyph ranges and rendering parameters are controlled by per-asset settings
+//! deserialized into [`FontSettings`].
+
+pub mod atlas;
+mod sdf;
+
+use {
+    crate::atlas::{FontAtlas, Glyph, Kerning, FONT_NATIVE_FORMAT},
+    goods_treasury_import::{
+        eyre::{self, WrapErr},
+        ImportProgress, Importer, Registry,
+    },
+    std::path::Path,
+};
+
+#[cfg(target_os = "wasi")]
+pub use goods_treasury_import_ffi::{
+    treasury_importer_alloc, treasury_importer_dealloc, treasury_importer_ffi_version,
+    treasury_importer_import_trampoline, treasury_importer_name_source_native_trampoline,
+};
+
+/// Gap between glyph rectangles in the atlas,
+/// so that filtering does not bleed neighbours.
+const GAP: u32 = 1;
+
+/// Import settings of font assets.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FontSettings {
+    /// Size in pixels to render glyphs at.
+    /// Defaults to 32.
+    pub size: f32,
+
+    /// Distance in pixels encoded on each side of glyph outline.
+    /// Defaults to 4.
+    pub spread: u32,
+
+    /// Inclusive ranges of codepoints to put into atlas.
+    /// Codepoints missing in the font are skipped.
+    /// Defaults to printable ASCII `[[32, 126]]`.
+    pub ranges: Vec<[u32; 2]>,
+
+    /// Width of the atlas. Height is chosen to fit all glyphs.
+    /// Defaults to 512.
+    pub atlas_width: u32,
+
+    /// Record kerning pairs of glyphs in the atlas.
+    /// Defaults to `true`.
+    pub kerning: bool,
+}
+
+impl Default for FontSettings {
+    fn default() -> Self {
+        FontSettings {
+            size: 32.0,
+            spread: 4,
+            ranges: vec![[32, 126]],
+            atlas_width: 512,
+            kerning: true,
+        }
+    }
+}
+
+/// Identifiers of formats imported by this crate.
+pub mod formats {
+    use goods_treasury_import::FormatId;
+
+    /// TrueType font source format.
+    pub const TTF: FormatId = FormatId::new("ttf");
+
+    /// OpenType font source format.
+    pub const OTF: FormatId = FormatId::new("otf");
+
+    /// [`FontAtlas`](crate::atlas::FontAtlas) native format.
+    pub const SDF_FONT: FormatId = FormatId::new(crate::atlas::FONT_NATIVE_FORMAT);
+}
+
+/// Importer of fonts in particular format.
+pub struct FontImporter {
+    source: &'static str,
+}
+
+/// Importer for TrueType fonts.
+pub static TTF_IMPORTER: FontImporter = FontImporter {
+    source: formats::TTF.as_str(),
+};
+
+/// Importer for OpenType fonts.
+pub static OTF_IMPORTER: FontImporter = FontImporter {
+    source: formats::OTF.as_str(),
+};
+
+/// Rendered glyph before packing.
+struct Rendered {
+    codepoint: char,
+    advance: f32,
+    offset: [i32; 2],
+    field: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl Importer for FontImporter {
+    fn name(&self) -> &str {
+        self.source
+    }
+
+    fn source(&self) -> &str {
+        self.source
+    }
+
+    fn native(&self) -> &str {
+        FONT_NATIVE_FORMAT
+    }
+
+    fn import(
+        &self,
+        source_path: &Path,
+        native_path: &Path,
+        settings: &str,
+        registry: &mut dyn Registry,
+        progress: &mut dyn ImportProgress,
+    ) -> eyre::Result<()> {
+        let settings: FontSettings = if settings.is_empty() {
+            FontSettings::default()
+        } else {
+            serde_json::from_str(settings).wrap_err("Invalid font import settings")?
+        };
+
+        if settings.size.is_nan() || settings.size <= 0.0 {
+            eyre::bail!("Font size must be positive");
+        }
+
+        let bytes = std::fs::read(source_path)
+            .wrap_err_with(|| format!("Failed to read '{}'", source_path.display()))?;
+
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())
+            .map_err(|err| eyre::eyre!("Failed to parse '{}'. {}", source_path.display(), err))?;
+
+        let mut codepoints: Vec<char> = settings
+            .ranges
+            .iter()
+            .flat_map(|&[first, last]| first..=last)
+            .filter_map(std::char::from_u32)
+            .filter(|&codepoint| font.has_glyph(codepoint))
+            .collect();
+
+        codepoints.sort_unstable();
+        codepoints.dedup();
+
+        let total = codepoints.len() as u64;
+        let mut rendered = Vec::with_capacity(codepoints.len());
+
+        for (index, &codepoint) in codepoints.iter().enumerate() {
+            progress.report(index as u64, total, "glyphs");
+
+            let (metrics, coverage) = font.rasterize(codepoint, settings.size);
+            let spread = settings.spread as i32;
+
+            let (field, width, height) = if metrics.width == 0 || metrics.height == 0 {
+                (Vec::new(), 0, 0)
+            } else {
+                sdf::sdf(&coverage, metrics.width, metrics.height, settings.spread)
+            };
+
+            rendered.push(Rendered {
+                codepoint,
+                advance: metrics.advance_width,
+                offset: [metrics.xmin - spread, metrics.ymin - spread],
+                field,
+                width: width as u32,
+                height: height as u32,
+            });
+        }
+
+        progress.report(total, total, "glyphs");
+
+        let atlas = pack(&font, &settings, rendered)?;
+
+        let native = std::fs::File::create(native_path)
+            .wrap_err_with(|| format!("Failed to create '{}'", native_path.display()))?;
+
+        bincode::serialize_into(std::io::BufWriter::new(native), &atlas)
+            .wrap_err("Failed to write font atlas")?;
+
+        let meta = serde_json::json!({
+            "size": atlas.size,
+            "glyphs": atlas.glyphs.len(),
+            "width": atlas.width,
+            "height": atlas.height,
+        });
+
+        registry.set_meta(&meta.to_string())
+    }
+}
+
+/// Packs glyphs into atlas rows, tallest first.
+fn pack(
+    font: &fontdue::Font,
+    settings: &FontSettings,
+    rendered: Vec<Rendered>,
+) -> eyre::Result<FontAtlas> {
+    let atlas_width = settings.atlas_width;
+
+    if let Some(glyph) = rendered.iter().find(|glyph| glyph.width > atlas_width) {
+        eyre::bail!(
+            "Glyph '{}' is wider than atlas. Increase atlas width or decrease size",
+            glyph.codepoint
+        );
+    }
+
+    let mut order: Vec<usize> = (0..rendered.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(rendered[index].height));
+
+    let mut rects = vec![[0; 4]; rendered.len()];
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+
+    for &index in &order {
+        let glyph = &rendered[index];
+        if glyph.width == 0 || glyph.height == 0 {
+            continue;
+        }
+
+        if x + glyph.width > atlas_width {
+            x = 0;
+            y += row_height + GAP;
+            row_height = 0;
+        }
+
+        rects[index] = [x, y, glyph.width, glyph.height];
+        x += glyph.width + GAP;
+        row_height = row_height.max(glyph.height);
+    }
+
+    let atlas_height = y + row_height;
+    let mut pixels = vec![0; atlas_width as usize * atlas_height as usize];
+
+    for (glyph, rect) in rendered.iter().zip(&rects) {
+        for row in 0..glyph.height as usize {
+            let src = &glyph.field[row * glyph.width as usize..][..glyph.width as usize];
+            let dst = (rect[1] as usize + row) * atlas_width as usize + rect[0] as usize;
+            pixels[dst..][..src.len()].copy_from_slice(src);
+        }
+    }
+
+    let mut kerning = Vec::new();
+    if settings.kerning {
+        for left in &rendered {
+            for right in &rendered {
+                if let Some(adjustment) =
+                    font.horizontal_kern(left.codepoint, right.codepoint, settings.size)
+                {
+                    if adjustment != 0.0 {
+                        kerning.push(Kerning {
+                            left: left.codepoint,
+                            right: right.codepoint,
+                            adjustment,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let line_metrics = font.horizontal_line_metrics(settings.size);
+
+    let glyphs = rendered
+        .iter()
+        .zip(rects)
+        .map(|(glyph, rect)| Glyph {
+            codepoint: glyph.codepoint,
+            advance: glyph.advance,
+            offset: glyph.offset,
+            rect,
+        })
+        .collect();
+
+    Ok(FontAtlas {
+        size: settings.size,
+        spread: settings.spread,
+        ascent: line_metrics.map_or(settings.size, |m| m.ascent),
+        descent: line_metrics.map_or(0.0, |m| m.descent),
+        line_gap: line_metrics.map_or(0.0, |m| m.line_gap),
+        width: atlas_width,
+        height: atlas_height,
+        pixels,
+        glyphs,
+        kerning,
+    })
+}
+
+#[cfg(target_os = "wasi")]
+goods_treasury_import_ffi::generate_imports_and_exports! {
+    &TTF_IMPORTER,
+    &OTF_IMPORTER,
+}
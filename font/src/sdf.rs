@@ -0,0 +1,121 @@
+//! Signed distance field generation from glyph coverage.
+//!
+//! Uses exact euclidean distance transform by Felzenszwalb and Huttenlocher
+//! on coverage thresholded at half.
+
+const INF: f32 = 1e20;
+
+/// Converts coverage bitmap into signed distance field
+/// padded by `spread` pixels on each side.
+/// Returns field with its width and height.
+pub fn sdf(coverage: &[u8], width: usize, height: usize, spread: u32) -> (Vec<u8>, usize, usize) {
+    let pad = spread as usize;
+    let padded_width = width + 2 * pad;
+    let padded_height = height + 2 * pad;
+
+    let inside = |x: usize, y: usize| {
+        x >= pad
+            && y >= pad
+            && x < width + pad
+            && y < height + pad
+            && coverage[(y - pad) * width + (x - pad)] >= 128
+    };
+
+    let mut outside_distance = vec![INF; padded_width * padded_height];
+    let mut inside_distance = vec![INF; padded_width * padded_height];
+
+    for y in 0..padded_height {
+        for x in 0..padded_width {
+            if inside(x, y) {
+                outside_distance[y * padded_width + x] = 0.0;
+            } else {
+                inside_distance[y * padded_width + x] = 0.0;
+            }
+        }
+    }
+
+    edt(&mut outside_distance, padded_width, padded_height);
+    edt(&mut inside_distance, padded_width, padded_height);
+
+    let field = outside_distance
+        .iter()
+        .zip(&inside_distance)
+        .map(|(&outside, &inside)| {
+            // Pixel centers are half pixel away from the outline.
+            let distance = if inside > 0.0 {
+                -(inside.sqrt() - 0.5)
+            } else {
+                outside.sqrt() - 0.5
+            };
+
+            let value = 128.0 - distance / spread.max(1) as f32 * 127.0;
+            value.round().clamp(0.0, 255.0) as u8
+        })
+        .collect();
+
+    (field, padded_width, padded_height)
+}
+
+/// Replaces values with squared distances to the nearest zero value.
+fn edt(grid: &mut [f32], width: usize, height: usize) {
+    let len = width.max(height);
+    let mut f = vec![0.0; len];
+    let mut d = vec![0.0; len];
+    let mut v = vec![0; len];
+    let mut z = vec![0.0; len + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = grid[y * width + x];
+        }
+        edt_1d(&f[..height], &mut d, &mut v, &mut z);
+        for y in 0..height {
+            grid[y * width + x] = d[y];
+        }
+    }
+
+    for y in 0..height {
+        f[..width].copy_from_slice(&grid[y * width..][..width]);
+        edt_1d(&f[..width], &mut d, &mut v, &mut z);
+        grid[y * width..][..width].copy_from_slice(&d[..width]);
+    }
+}
+
+fn edt_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    let n = f.len();
+    if n == 0 {
+        return;
+    }
+
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = -INF;
+    z[1] = INF;
+
+    for q in 1..n {
+        let mut s;
+        loop {
+            let r = v[k];
+            s = ((f[q] + (q * q) as f32) - (f[r] + (r * r) as f32)) / (2 * (q - r)) as f32;
+            if s <= z[k] {
+                k -= 1;
+            } else {
+                break;
+            }
+        }
+
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = INF;
+    }
+
+    k = 0;
+    for (q, d) in d.iter_mut().enumerate().take(n) {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let r = v[k];
+        *d = (q as f32 - r as f32).powi(2) + f[r];
+    }
+}
@@ -0,0 +1,301 @@
+//! glTF importers for treasury.
+//!
+//! Imports `.gltf` and `.glb` documents into [`Scene`] native format
+//! described in [`scene`] module.
+//!
+//! External images referenced by the document are registered as sub-assets
+//! and referenced from the scene by UUID.
+//! Meshes and materials are stored inline in the scene,
+//! as treasury registers sub-assets by source file.
+
+pub mod scene;
+
+use {
+    crate::scene::{
+        Image, Material, Mesh, Node, Primitive, Scene, IMAGE_NATIVE_FORMAT, SCENE_NATIVE_FORMAT,
+    },
+    gltf::{buffer, image, mesh::Mode, Document, Gltf},
+    goods_treasury_import::{
+        eyre::{self, WrapErr},
+        ImportProgress, Importer, Registry,
+    },
+    std::path::Path,
+};
+
+#[cfg(target_os = "wasi")]
+pub use goods_treasury_import_ffi::{
+    treasury_importer_alloc, treasury_importer_dealloc, treasury_importer_ffi_version,
+    treasury_importer_import_trampoline, treasury_importer_name_source_native_trampoline,
+};
+
+/// Identifiers of formats imported by this crate.
+pub mod formats {
+    use goods_treasury_import::FormatId;
+
+    /// Text glTF document source format.
+    pub const GLTF: FormatId = FormatId::new("gltf");
+
+    /// Binary glTF document source format.
+    pub const GLB: FormatId = FormatId::new("glb");
+
+    /// [`Scene`](crate::scene::Scene) native format.
+    pub const SCENE: FormatId = FormatId::new(crate::scene::SCENE_NATIVE_FORMAT);
+}
+
+/// Importer of glTF documents.
+pub struct GltfImporter {
+    source: &'static str,
+}
+
+/// Importer for text `.gltf` documents.
+pub static GLTF_IMPORTER: GltfImporter = GltfImporter {
+    source: formats::GLTF.as_str(),
+};
+
+/// Importer for binary `.glb` documents.
+pub static GLB_IMPORTER: GltfImporter = GltfImporter {
+    source: formats::GLB.as_str(),
+};
+
+impl Importer for GltfImporter {
+    fn name(&self) -> &str {
+        self.source
+    }
+
+    fn source(&self) -> &str {
+        self.source
+    }
+
+    fn native(&self) -> &str {
+        SCENE_NATIVE_FORMAT
+    }
+
+    fn import(
+        &self,
+        source_path: &Path,
+        native_path: &Path,
+        _settings: &str,
+        registry: &mut dyn Registry,
+        progress: &mut dyn ImportProgress,
+    ) -> eyre::Result<()> {
+        let Gltf { document, blob } = Gltf::open(source_path)
+            .wrap_err_with(|| format!("Failed to open '{}'", source_path.display()))?;
+
+        let base = source_path.parent();
+        let buffers =
+            gltf::import_buffers(&document, base, blob).wrap_err("Failed to load buffers")?;
+
+        let scene = Scene {
+            nodes: import_nodes(&document),
+            roots: document
+                .default_scene()
+                .or_else(|| document.scenes().next())
+                .map_or_else(Vec::new, |scene| {
+                    scene.nodes().map(|node| node.index()).collect()
+                }),
+            meshes: import_meshes(&document, &buffers, progress)?,
+            materials: document.materials().map(import_material).collect(),
+            images: import_images(&document, &buffers, base, registry, progress)?,
+        };
+
+        let native = std::fs::File::create(native_path)
+            .wrap_err_with(|| format!("Failed to create '{}'", native_path.display()))?;
+
+        bincode::serialize_into(std::io::BufWriter::new(native), &scene)
+            .wrap_err("Failed to write scene")?;
+
+        Ok(())
+    }
+}
+
+fn import_nodes(document: &Document) -> Vec<Node> {
+    document
+        .nodes()
+        .map(|node| Node {
+            name: node.name().map(String::from),
+            transform: node.transform().matrix(),
+            mesh: node.mesh().map(|mesh| mesh.index()),
+            children: node.children().map(|child| child.index()).collect(),
+        })
+        .collect()
+}
+
+fn import_meshes(
+    document: &Document,
+    buffers: &[buffer::Data],
+    progress: &mut dyn ImportProgress,
+) -> eyre::Result<Vec<Mesh>> {
+    let total = document.meshes().len() as u64;
+    let mut meshes = Vec::new();
+
+    for mesh in document.meshes() {
+        progress.report(mesh.index() as u64, total, "meshes");
+
+        let mut primitives = Vec::new();
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != Mode::Triangles {
+                eyre::bail!(
+                    "Primitive {} of mesh {} has unsupported mode {:?}",
+                    primitive.index(),
+                    mesh.index(),
+                    primitive.mode(),
+                );
+            }
+
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let positions: Vec<_> = match reader.read_positions() {
+                Some(positions) => positions.collect(),
+                None => eyre::bail!(
+                    "Primitive {} of mesh {} has no positions",
+                    primitive.index(),
+                    mesh.index(),
+                ),
+            };
+
+            let indices = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..positions.len() as u32).collect(),
+            };
+
+            primitives.push(Primitive {
+                normals: reader
+                    .read_normals()
+                    .map_or_else(Vec::new, |normals| normals.collect()),
+                tangents: reader
+                    .read_tangents()
+                    .map_or_else(Vec::new, |tangents| tangents.collect()),
+                uvs: reader
+                    .read_tex_coords(0)
+                    .map_or_else(Vec::new, |uvs| uvs.into_f32().collect()),
+                positions,
+                indices,
+                material: primitive.material().index(),
+            });
+        }
+
+        meshes.push(Mesh {
+            name: mesh.name().map(String::from),
+            primitives,
+        });
+    }
+
+    progress.report(total, total, "meshes");
+    Ok(meshes)
+}
+
+fn import_material(material: gltf::Material<'_>) -> Material {
+    let pbr = material.pbr_metallic_roughness();
+
+    Material {
+        name: material.name().map(String::from),
+        base_color_factor: pbr.base_color_factor(),
+        base_color_texture: pbr
+            .base_color_texture()
+            .map(|info| info.texture().source().index()),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr
+            .metallic_roughness_texture()
+            .map(|info| info.texture().source().index()),
+        normal_texture: material
+            .normal_texture()
+            .map(|normal| normal.texture().source().index()),
+        occlusion_texture: material
+            .occlusion_texture()
+            .map(|occlusion| occlusion.texture().source().index()),
+        emissive_factor: material.emissive_factor(),
+        emissive_texture: material
+            .emissive_texture()
+            .map(|info| info.texture().source().index()),
+    }
+}
+
+fn import_images(
+    document: &Document,
+    buffers: &[buffer::Data],
+    base: Option<&Path>,
+    registry: &mut dyn Registry,
+    progress: &mut dyn ImportProgress,
+) -> eyre::Result<Vec<Image>> {
+    let total = document.images().len() as u64;
+    let mut images = Vec::new();
+
+    for image in document.images() {
+        progress.report(image.index() as u64, total, "images");
+
+        let image = match image.source() {
+            image::Source::View { view, mime_type } => {
+                let bytes = &buffers[view.buffer().index()];
+                Image::Embedded {
+                    mime_type: mime_type.to_owned(),
+                    bytes: bytes[view.offset()..view.offset() + view.length()].to_vec(),
+                }
+            }
+            image::Source::Uri { uri, mime_type } if uri.starts_with("data:") => {
+                let (media, data) =
+                    uri["data:".len()..].split_once(";base64,").ok_or_else(|| {
+                        eyre::eyre!("Image {} has unsupported data URI", image.index())
+                    })?;
+
+                let bytes = base64::decode(data)
+                    .wrap_err_with(|| format!("Image {} has invalid data URI", image.index()))?;
+
+                Image::Embedded {
+                    mime_type: mime_type.unwrap_or(media).to_owned(),
+                    bytes,
+                }
+            }
+            image::Source::Uri { uri, mime_type } => {
+                let path = match base {
+                    Some(base) => base.join(uri),
+                    None => Path::new(uri).to_owned(),
+                };
+
+                let source_format = image_format(&path, mime_type);
+
+                match registry.store(&path, source_format, IMAGE_NATIVE_FORMAT, &[]) {
+                    Ok(uuid) => Image::Asset(uuid),
+                    Err(_) => {
+                        // Image cannot be imported as separate asset.
+                        // Keep it usable by embedding into the scene.
+                        let bytes = std::fs::read(&path).wrap_err_with(|| {
+                            format!("Failed to read image '{}'", path.display())
+                        })?;
+
+                        Image::Embedded {
+                            mime_type: mime_type
+                                .map_or_else(|| format!("image/{}", source_format), String::from),
+                            bytes,
+                        }
+                    }
+                }
+            }
+        };
+
+        images.push(image);
+    }
+
+    progress.report(total, total, "images");
+    Ok(images)
+}
+
+/// Returns source format of an image,
+/// preferring MIME type over file extension.
+fn image_format<'a>(path: &'a Path, mime_type: Option<&'a str>) -> &'a str {
+    match mime_type {
+        Some(mime_type) if mime_type.starts_with("image/") => &mime_type["image/".len()..],
+        _ => match path.extension().and_then(|ext| ext.to_str()) {
+            Some("jpg") | Some("JPG") => "jpeg",
+            Some(ext) => ext,
+            None => "",
+        },
+    }
+}
+
+#[cfg(target_os = "wasi")]
+goods_treasury_import_ffi::generate_imports_and_exports! {
+    &GLTF_IMPORTER,
+    &GLB_IMPORTER,
+}
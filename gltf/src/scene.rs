@@ -0,0 +1,96 @@
+//! Native scene format produced by glTF importers.
+//!
+//! Native file contains single [`Scene`] serialized with `bincode`
+//! using default options.
+//! All indices are indices into corresponding arrays of the [`Scene`].
+
+use {
+    serde::{Deserialize, Serialize},
+    uuid::Uuid,
+};
+
+/// Native format name of the imported scenes.
+pub const SCENE_NATIVE_FORMAT: &str = "scene";
+
+/// Native format requested for images referenced by imported scenes.
+pub const IMAGE_NATIVE_FORMAT: &str = "texture";
+
+/// Scene imported from glTF document.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Scene {
+    /// All nodes of the document.
+    pub nodes: Vec<Node>,
+
+    /// Root nodes of the default scene of the document.
+    /// If document has no default scene, first scene is used.
+    pub roots: Vec<usize>,
+
+    /// All meshes of the document.
+    pub meshes: Vec<Mesh>,
+
+    /// All materials of the document.
+    pub materials: Vec<Material>,
+
+    /// All images of the document.
+    pub images: Vec<Image>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Node {
+    pub name: Option<String>,
+
+    /// Local transformation as column-major 4x4 matrix.
+    pub transform: [[f32; 4]; 4],
+
+    /// Mesh attached to the node.
+    pub mesh: Option<usize>,
+
+    /// Child nodes.
+    pub children: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Mesh {
+    pub name: Option<String>,
+    pub primitives: Vec<Primitive>,
+}
+
+/// Indexed triangle list.
+/// Non-indexed primitives are imported with sequential indices.
+/// Missing attributes are left empty.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Primitive {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub tangents: Vec<[f32; 4]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+/// Metallic-roughness material.
+/// Textures are indices of images.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Material {
+    pub name: Option<String>,
+    pub base_color_factor: [f32; 4],
+    pub base_color_texture: Option<usize>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub occlusion_texture: Option<usize>,
+    pub emissive_factor: [f32; 3],
+    pub emissive_texture: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Image {
+    /// External image registered as separate asset
+    /// with [`IMAGE_NATIVE_FORMAT`] native format.
+    Asset(Uuid),
+
+    /// Image embedded into the document,
+    /// or external image that failed to be registered.
+    Embedded { mime_type: String, bytes: Vec<u8> },
+}
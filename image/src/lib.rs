@@ -0,0 +1,356 @@
+//! Image importers for treasury.
+//!
+//! Imports `png`, `jpeg`, `tga` and `hdr` images into [`Texture`] native format
+//! described in [`texture`] module.
+//!
+//! Import is controlled by per-asset settings deserialized into [`ImageSettings`].
+//!
+//! Textures may be block compressed into BC1-BC5 formats, see [`Compression`].
+//! ASTC compression is not supported, as there is no ASTC encoder among dependencies,
+//! so textures for mobile GPUs without BC support should be imported uncompressed.
+
+pub mod texture;
+
+use {
+    crate::texture::{Texture, TextureFormat, TEXTURE_NATIVE_FORMAT},
+    goods_treasury_import::{
+        eyre::{self, WrapErr},
+        ImportProgress, Importer, Registry,
+    },
+    image::{
+        imageops::{resize, FilterType},
+        ImageFormat, Rgba32FImage, RgbaImage,
+    },
+    std::path::Path,
+};
+
+#[cfg(target_os = "wasi")]
+pub use goods_treasury_import_ffi::{
+    treasury_importer_alloc, treasury_importer_dealloc, treasury_importer_ffi_version,
+    treasury_importer_import_trampoline, treasury_importer_name_source_native_trampoline,
+};
+
+/// Import settings of image assets.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImageSettings {
+    /// Generate full mip chain.
+    /// Defaults to `true`.
+    pub mipmaps: bool,
+
+    /// Treat color channels as sRGB encoded.
+    /// Mipmaps are generated in linear space.
+    /// Defaults to `true`. Ignored for HDR images.
+    pub srgb: bool,
+
+    /// Block compression of the texture.
+    /// Defaults to no compression.
+    pub compression: Compression,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        ImageSettings {
+            mipmaps: true,
+            srgb: true,
+            compression: Compression::None,
+        }
+    }
+}
+
+/// Block compression of imported texture.
+/// Only BC formats are supported, ASTC is not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Bc1,
+    Bc2,
+    Bc3,
+
+    /// Compresses red channel only.
+    Bc4,
+
+    /// Compresses red and green channels only.
+    Bc5,
+}
+
+/// Identifiers of formats imported by this crate.
+pub mod formats {
+    use goods_treasury_import::FormatId;
+
+    /// PNG image source format.
+    pub const PNG: FormatId = FormatId::new("png");
+
+    /// JPEG image source format.
+    pub const JPEG: FormatId = FormatId::new("jpeg");
+
+    /// TGA image source format.
+    pub const TGA: FormatId = FormatId::new("tga");
+
+    /// Radiance HDR image source format.
+    pub const HDR: FormatId = FormatId::new("hdr");
+
+    /// [`Texture`](crate::texture::Texture) native format.
+    pub const TEXTURE: FormatId = FormatId::new(crate::texture::TEXTURE_NATIVE_FORMAT);
+}
+
+/// Importer of images in particular format.
+pub struct ImageImporter {
+    source: &'static str,
+    format: ImageFormat,
+}
+
+pub static PNG_IMPORTER: ImageImporter = ImageImporter {
+    source: formats::PNG.as_str(),
+    format: ImageFormat::Png,
+};
+
+pub static JPEG_IMPORTER: ImageImporter = ImageImporter {
+    source: formats::JPEG.as_str(),
+    format: ImageFormat::Jpeg,
+};
+
+pub static TGA_IMPORTER: ImageImporter = ImageImporter {
+    source: formats::TGA.as_str(),
+    format: ImageFormat::Tga,
+};
+
+pub static HDR_IMPORTER: ImageImporter = ImageImporter {
+    source: formats::HDR.as_str(),
+    format: ImageFormat::Hdr,
+};
+
+impl Importer for ImageImporter {
+    fn name(&self) -> &str {
+        self.source
+    }
+
+    fn source(&self) -> &str {
+        self.source
+    }
+
+    fn native(&self) -> &str {
+        TEXTURE_NATIVE_FORMAT
+    }
+
+    fn import(
+        &self,
+        source_path: &Path,
+        native_path: &Path,
+        settings: &str,
+        _registry: &mut dyn Registry,
+        progress: &mut dyn ImportProgress,
+    ) -> eyre::Result<()> {
+        let settings: ImageSettings = if settings.is_empty() {
+            ImageSettings::default()
+        } else {
+            serde_json::from_str(settings).wrap_err("Invalid image import settings")?
+        };
+
+        let bytes = std::fs::read(source_path)
+            .wrap_err_with(|| format!("Failed to read '{}'", source_path.display()))?;
+
+        let image = image::load_from_memory_with_format(&bytes, self.format)
+            .wrap_err_with(|| format!("Failed to decode '{}'", source_path.display()))?;
+
+        let texture = if self.format == ImageFormat::Hdr {
+            hdr_texture(image.into_rgba32f(), &settings, progress)?
+        } else {
+            ldr_texture(image.into_rgba8(), &settings, progress)
+        };
+
+        let native = std::fs::File::create(native_path)
+            .wrap_err_with(|| format!("Failed to create '{}'", native_path.display()))?;
+
+        bincode::serialize_into(std::io::BufWriter::new(native), &texture)
+            .wrap_err("Failed to write texture")?;
+
+        Ok(())
+    }
+}
+
+fn mip_count(width: u32, height: u32, settings: &ImageSettings) -> u32 {
+    if settings.mipmaps {
+        32 - width.max(height).max(1).leading_zeros()
+    } else {
+        1
+    }
+}
+
+fn mip_size(size: u32, level: u32) -> u32 {
+    (size >> level).max(1)
+}
+
+fn ldr_texture(
+    base: RgbaImage,
+    settings: &ImageSettings,
+    progress: &mut dyn ImportProgress,
+) -> Texture {
+    let (width, height) = base.dimensions();
+    let count = mip_count(width, height, settings);
+
+    let mut levels = vec![base];
+
+    if settings.srgb && count > 1 {
+        let mut linear = srgb_to_linear_image(&levels[0]);
+
+        for level in 1..count {
+            progress.report(level as u64, count as u64, "mipmaps");
+            linear = resize(
+                &linear,
+                mip_size(width, level),
+                mip_size(height, level),
+                FilterType::Triangle,
+            );
+            levels.push(linear_to_srgb_image(&linear));
+        }
+    } else {
+        for level in 1..count {
+            progress.report(level as u64, count as u64, "mipmaps");
+            let next = resize(
+                &levels[level as usize - 1],
+                mip_size(width, level),
+                mip_size(height, level),
+                FilterType::Triangle,
+            );
+            levels.push(next);
+        }
+    }
+
+    let (format, block_format) = match (settings.compression, settings.srgb) {
+        (Compression::None, false) => (TextureFormat::Rgba8Unorm, None),
+        (Compression::None, true) => (TextureFormat::Rgba8Srgb, None),
+        (Compression::Bc1, false) => (TextureFormat::Bc1RgbaUnorm, Some(texpresso::Format::Bc1)),
+        (Compression::Bc1, true) => (TextureFormat::Bc1RgbaSrgb, Some(texpresso::Format::Bc1)),
+        (Compression::Bc2, false) => (TextureFormat::Bc2RgbaUnorm, Some(texpresso::Format::Bc2)),
+        (Compression::Bc2, true) => (TextureFormat::Bc2RgbaSrgb, Some(texpresso::Format::Bc2)),
+        (Compression::Bc3, false) => (TextureFormat::Bc3RgbaUnorm, Some(texpresso::Format::Bc3)),
+        (Compression::Bc3, true) => (TextureFormat::Bc3RgbaSrgb, Some(texpresso::Format::Bc3)),
+        (Compression::Bc4, _) => (TextureFormat::Bc4RUnorm, Some(texpresso::Format::Bc4)),
+        (Compression::Bc5, _) => (TextureFormat::Bc5RgUnorm, Some(texpresso::Format::Bc5)),
+    };
+
+    let levels = match block_format {
+        None => levels.into_iter().map(RgbaImage::into_raw).collect(),
+        Some(block_format) => levels
+            .iter()
+            .enumerate()
+            .map(|(level, image)| {
+                progress.report(level as u64, count as u64, "compression");
+
+                let (width, height) = image.dimensions();
+                let mut output =
+                    vec![0; block_format.compressed_size(width as usize, height as usize)];
+
+                block_format.compress(
+                    image.as_raw(),
+                    width as usize,
+                    height as usize,
+                    texpresso::Params::default(),
+                    &mut output,
+                );
+                output
+            })
+            .collect(),
+    };
+
+    Texture {
+        width,
+        height,
+        format,
+        levels,
+    }
+}
+
+fn hdr_texture(
+    base: Rgba32FImage,
+    settings: &ImageSettings,
+    progress: &mut dyn ImportProgress,
+) -> eyre::Result<Texture> {
+    if settings.compression != Compression::None {
+        eyre::bail!("Block compression of HDR images is not supported");
+    }
+
+    let (width, height) = base.dimensions();
+    let count = mip_count(width, height, settings);
+
+    let mut levels = vec![base];
+    for level in 1..count {
+        progress.report(level as u64, count as u64, "mipmaps");
+        let next = resize(
+            &levels[level as usize - 1],
+            mip_size(width, level),
+            mip_size(height, level),
+            FilterType::Triangle,
+        );
+        levels.push(next);
+    }
+
+    Ok(Texture {
+        width,
+        height,
+        format: TextureFormat::Rgba32Float,
+        levels: levels
+            .iter()
+            .map(|image| {
+                image
+                    .as_raw()
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect()
+            })
+            .collect(),
+    })
+}
+
+fn srgb_to_linear_image(image: &RgbaImage) -> Rgba32FImage {
+    Rgba32FImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        image::Rgba([
+            srgb_to_linear(r),
+            srgb_to_linear(g),
+            srgb_to_linear(b),
+            a as f32 / 255.0,
+        ])
+    })
+}
+
+fn linear_to_srgb_image(image: &Rgba32FImage) -> RgbaImage {
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        image::Rgba([
+            linear_to_srgb(r),
+            linear_to_srgb(g),
+            linear_to_srgb(b),
+            (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+        ])
+    })
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let value = if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (value * 255.0).round() as u8
+}
+
+#[cfg(target_os = "wasi")]
+goods_treasury_import_ffi::generate_imports_and_exports! {
+    &PNG_IMPORTER,
+    &JPEG_IMPORTER,
+    &TGA_IMPORTER,
+    &HDR_IMPORTER,
+}
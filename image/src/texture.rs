@@ -0,0 +1,56 @@
+//! Native texture format produced by image importers.
+//!
+//! Native file contains single [`Texture`] serialized with `bincode`
+//! using default options.
+
+use serde::{Deserialize, Serialize};
+
+/// Native format name of the imported textures.
+pub const TEXTURE_NATIVE_FORMAT: &str = "texture";
+
+/// Texture with optional mip chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Texture {
+    /// Width of the first level in pixels.
+    pub width: u32,
+
+    /// Height of the first level in pixels.
+    pub height: u32,
+
+    /// Format of texel data of all levels.
+    pub format: TextureFormat,
+
+    /// Texel data of every mip level, starting from full-size image.
+    /// Level `n` has size `max(1, width >> n)` by `max(1, height >> n)`.
+    /// Rows are tightly packed, block-compressed levels are stored in rows of 4x4 blocks.
+    pub levels: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextureFormat {
+    Rgba8Unorm,
+    Rgba8Srgb,
+    /// Little-endian 32-bit floats.
+    Rgba32Float,
+    Bc1RgbaUnorm,
+    Bc1RgbaSrgb,
+    Bc2RgbaUnorm,
+    Bc2RgbaSrgb,
+    Bc3RgbaUnorm,
+    Bc3RgbaSrgb,
+    Bc4RUnorm,
+    Bc5RgUnorm,
+}
+
+impl TextureFormat {
+    /// Returns `true` if color channels are sRGB encoded.
+    pub fn is_srgb(&self) -> bool {
+        matches!(
+            self,
+            TextureFormat::Rgba8Srgb
+                | TextureFormat::Bc1RgbaSrgb
+                | TextureFormat::Bc2RgbaSrgb
+                | TextureFormat::Bc3RgbaSrgb
+        )
+    }
+}
@@ -1,5 +1,7 @@
+pub use goods_treasury_import::Importer;
+
 use {
-    goods_treasury_import::{Importer, Registry},
+    goods_treasury_import::{ImportProgress, Registry, IMPORTER_FFI_VERSION},
     std::path::Path,
     uuid::Uuid,
 };
@@ -12,31 +14,38 @@ pub struct ImporterOpaque {
     _byte: u8,
 }
 
+/// FFI-safe importer vtable.
+///
+/// Layout of this structure is part of [`IMPORTER_FFI_VERSION`].
 #[repr(C)]
 pub struct ImporterFFI {
     data: *const ImporterOpaque,
-    name: unsafe fn(*const ImporterOpaque, *mut u8, usize) -> usize,
-    source: unsafe fn(*const ImporterOpaque, *mut u8, usize) -> usize,
-    native: unsafe fn(*const ImporterOpaque, *mut u8, usize) -> usize,
+    name: unsafe extern "C" fn(*const ImporterOpaque, *mut u8, usize) -> usize,
+    source: unsafe extern "C" fn(*const ImporterOpaque, *mut u8, usize) -> usize,
+    native: unsafe extern "C" fn(*const ImporterOpaque, *mut u8, usize) -> usize,
 
     #[cfg(any(unix, target_os = "wasi"))]
-    import: unsafe fn(
+    import: unsafe extern "C" fn(
         *const ImporterOpaque,
         *const u8,
         usize,
         *const u8,
         usize,
+        *const u8,
+        usize,
         *mut u8,
         usize,
     ) -> isize,
 
     #[cfg(windows)]
-    import: unsafe fn(
+    import: unsafe extern "C" fn(
         *const ImporterOpaque,
         *const u16,
         usize,
         *const u16,
         usize,
+        *const u8,
+        usize,
         *mut u8,
         usize,
     ) -> isize,
@@ -49,72 +58,187 @@ impl ImporterFFI {
     {
         ImporterFFI {
             data: importer as *const I as *const ImporterOpaque,
-            name: |data, buf, len| unsafe {
-                let name = (*(data as *mut I)).name();
-                let buf = std::slice::from_raw_parts_mut(buf, len);
-                buf[..len.min(name.len())].copy_from_slice(&name.as_bytes());
-                name.len()
-            },
-            source: |data, buf, len| unsafe {
-                let source = (*(data as *mut I)).source();
-                let buf = std::slice::from_raw_parts_mut(buf, len);
-                buf[..len.min(source.len())].copy_from_slice(&source.as_bytes());
-                source.len()
-            },
-            native: |data, buf, len| unsafe {
-                let native = (*(data as *mut I)).native();
-                let buf = std::slice::from_raw_parts_mut(buf, len);
-                buf[..len.min(native.len())].copy_from_slice(&native.as_bytes());
-                native.len()
-            },
-            import: |data, source_ptr, source_len, native_ptr, native_len, error_ptr, error_len| unsafe {
-                #[cfg(any(unix, target_os = "wasi"))]
-                let (source, native) = {
-                    use std::ffi::OsStr;
-
-                    #[cfg(unix)]
-                    use std::os::unix::ffi::OsStrExt;
-                    #[cfg(target_os = "wasi")]
-                    use std::os::wasi::ffi::OsStrExt;
-
-                    let source =
-                        OsStr::from_bytes(std::slice::from_raw_parts(source_ptr, source_len));
-
-                    let native =
-                        OsStr::from_bytes(std::slice::from_raw_parts(native_ptr, native_len));
-
-                    (source, native)
-                };
-
-                #[cfg(windows)]
-                let (source, native) = {
-                    use std::{ffi::OsString, os::windows::ffi::OsStringExt};
-
-                    let source =
-                        OsString::from_wide(std::slice::from_raw_parts(source_ptr, source_len));
-
-                    let native =
-                        OsString::from_wide(std::slice::from_raw_parts(native_ptr, native_len));
-
-                    (source, native)
-                };
-
-                match (*(data as *const I)).import(
-                    source.as_ref(),
-                    native.as_ref(),
-                    &mut RegistryFFI,
-                ) {
-                    Ok(()) => 0,
-                    Err(err) => {
-                        use std::io::{Cursor, Write as _};
-                        let error_slice = std::slice::from_raw_parts_mut(error_ptr, error_len);
-                        let mut error_write = Cursor::new(error_slice);
-
-                        let _ = write!(error_write, "{:#}", err);
-                        -(error_write.position() as isize)
-                    }
-                }
-            },
+            name: importer_name::<I>,
+            source: importer_source::<I>,
+            native: importer_native::<I>,
+            import: importer_import::<I>,
+        }
+    }
+}
+
+/// Writes metadata of importers module into buffer as JSON document.
+/// Returns length of the document, which may exceed buffer length.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `len` bytes.
+#[doc(hidden)]
+pub unsafe fn write_metadata(
+    name: &str,
+    version: &str,
+    features: &[&str],
+    importers: &[&dyn Importer],
+    buf: *mut u8,
+    len: usize,
+) -> usize {
+    let mut json = String::new();
+    json.push_str("{\"name\":");
+    push_json_str(&mut json, name);
+    json.push_str(",\"version\":");
+    push_json_str(&mut json, version);
+    json.push_str(",\"ffi_version\":");
+    json.push_str(&IMPORTER_FFI_VERSION.to_string());
+    json.push_str(",\"features\":[");
+    for (index, feature) in features.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        push_json_str(&mut json, feature);
+    }
+    json.push_str("],\"importers\":[");
+    for (index, importer) in importers.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        json.push_str("{\"name\":");
+        push_json_str(&mut json, importer.name());
+        json.push_str(",\"source\":");
+        push_json_str(&mut json, importer.source());
+        json.push_str(",\"native\":");
+        push_json_str(&mut json, importer.native());
+        json.push('}');
+    }
+    json.push_str("]}");
+
+    write_str(&json, buf, len)
+}
+
+fn push_json_str(json: &mut String, s: &str) {
+    use std::fmt::Write as _;
+
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+unsafe fn write_str(s: &str, buf: *mut u8, len: usize) -> usize {
+    let buf = std::slice::from_raw_parts_mut(buf, len);
+    buf[..len.min(s.len())].copy_from_slice(&s.as_bytes()[..len.min(s.len())]);
+    s.len()
+}
+
+unsafe extern "C" fn importer_name<I: Importer>(
+    data: *const ImporterOpaque,
+    buf: *mut u8,
+    len: usize,
+) -> usize {
+    write_str((*(data as *const I)).name(), buf, len)
+}
+
+unsafe extern "C" fn importer_source<I: Importer>(
+    data: *const ImporterOpaque,
+    buf: *mut u8,
+    len: usize,
+) -> usize {
+    write_str((*(data as *const I)).source(), buf, len)
+}
+
+unsafe extern "C" fn importer_native<I: Importer>(
+    data: *const ImporterOpaque,
+    buf: *mut u8,
+    len: usize,
+) -> usize {
+    write_str((*(data as *const I)).native(), buf, len)
+}
+
+#[cfg(any(unix, target_os = "wasi"))]
+unsafe extern "C" fn importer_import<I: Importer>(
+    data: *const ImporterOpaque,
+    source_ptr: *const u8,
+    source_len: usize,
+    native_ptr: *const u8,
+    native_len: usize,
+    settings_ptr: *const u8,
+    settings_len: usize,
+    error_ptr: *mut u8,
+    error_len: usize,
+) -> isize {
+    use std::ffi::OsStr;
+
+    #[cfg(unix)]
+    use std::os::unix::ffi::OsStrExt;
+    #[cfg(target_os = "wasi")]
+    use std::os::wasi::ffi::OsStrExt;
+
+    let source = OsStr::from_bytes(std::slice::from_raw_parts(source_ptr, source_len));
+    let native = OsStr::from_bytes(std::slice::from_raw_parts(native_ptr, native_len));
+    let settings = String::from_utf8_lossy(std::slice::from_raw_parts(settings_ptr, settings_len));
+
+    import_with::<I>(
+        data,
+        source.as_ref(),
+        native.as_ref(),
+        &settings,
+        error_ptr,
+        error_len,
+    )
+}
+
+#[cfg(windows)]
+unsafe extern "C" fn importer_import<I: Importer>(
+    data: *const ImporterOpaque,
+    source_ptr: *const u16,
+    source_len: usize,
+    native_ptr: *const u16,
+    native_len: usize,
+    settings_ptr: *const u8,
+    settings_len: usize,
+    error_ptr: *mut u8,
+    error_len: usize,
+) -> isize {
+    use std::{ffi::OsString, os::windows::ffi::OsStringExt};
+
+    let source = OsString::from_wide(std::slice::from_raw_parts(source_ptr, source_len));
+    let native = OsString::from_wide(std::slice::from_raw_parts(native_ptr, native_len));
+    let settings = String::from_utf8_lossy(std::slice::from_raw_parts(settings_ptr, settings_len));
+
+    import_with::<I>(
+        data,
+        source.as_ref(),
+        native.as_ref(),
+        &settings,
+        error_ptr,
+        error_len,
+    )
+}
+
+unsafe fn import_with<I: Importer>(
+    data: *const ImporterOpaque,
+    source: &Path,
+    native: &Path,
+    settings: &str,
+    error_ptr: *mut u8,
+    error_len: usize,
+) -> isize {
+    match (*(data as *const I)).import(source, native, settings, &mut RegistryFFI, &mut ProgressFFI)
+    {
+        Ok(()) => 0,
+        Err(err) => {
+            use std::io::{Cursor, Write as _};
+            let error_slice = std::slice::from_raw_parts_mut(error_ptr, error_len);
+            let mut error_write = Cursor::new(error_slice);
+
+            let _ = write!(error_write, "{:#}", err);
+            -(error_write.position() as isize)
         }
     }
 }
@@ -173,7 +297,7 @@ impl Registry for RegistryFFI {
 
         if result < 0 {
             let len = result.abs() as usize;
-            let error = std::str::from_utf8(&result_array[..len.min(BUFFER_LEN)]).unwrap();
+            let error = String::from_utf8_lossy(&result_array[..len.min(BUFFER_LEN)]);
             Err(eyre::eyre!("{}", error))
         } else {
             // success
@@ -201,7 +325,7 @@ impl Registry for RegistryFFI {
 
         if result < 0 {
             let len = result.abs() as usize;
-            let error = std::str::from_utf8(&error_array[..len.min(BUFFER_LEN)]).unwrap();
+            let error = String::from_utf8_lossy(&error_array[..len.min(BUFFER_LEN)]);
             Err(eyre::eyre!("{}", error))
         } else {
             let len = result as usize;
@@ -229,9 +353,98 @@ impl Registry for RegistryFFI {
             }
         }
     }
+
+    fn set_meta(&mut self, meta: &str) -> eyre::Result<()> {
+        let mut error_array = [0; BUFFER_LEN];
+
+        let result = unsafe {
+            treasury_import_meta(
+                meta.as_ptr(),
+                meta.len(),
+                error_array.as_mut_ptr(),
+                BUFFER_LEN,
+            )
+        };
+
+        if result < 0 {
+            let len = result.unsigned_abs();
+            let error = String::from_utf8_lossy(&error_array[..len.min(BUFFER_LEN)]);
+            Err(eyre::eyre!("{}", error))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_dependency(&mut self, path: &Path) -> eyre::Result<()> {
+        let path = match path.to_str() {
+            Some(path) => path,
+            None => eyre::bail!("Dependency path '{}' is not UTF-8", path.display()),
+        };
+
+        let mut error_array = [0; BUFFER_LEN];
+
+        let result = unsafe {
+            treasury_import_dependency(
+                path.as_ptr(),
+                path.len(),
+                error_array.as_mut_ptr(),
+                BUFFER_LEN,
+            )
+        };
+
+        if result < 0 {
+            let len = result.unsigned_abs();
+            let error = String::from_utf8_lossy(&error_array[..len.min(BUFFER_LEN)]);
+            Err(eyre::eyre!("{}", error))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_locale(&mut self, locale: &str) -> eyre::Result<()> {
+        let mut error_array = [0; BUFFER_LEN];
+
+        let result = unsafe {
+            treasury_import_locale(
+                locale.as_ptr(),
+                locale.len(),
+                error_array.as_mut_ptr(),
+                BUFFER_LEN,
+            )
+        };
+
+        if result < 0 {
+            let len = result.unsigned_abs();
+            let error = String::from_utf8_lossy(&error_array[..len.min(BUFFER_LEN)]);
+            Err(eyre::eyre!("{}", error))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn set_schema(&mut self, schema: u32) -> eyre::Result<()> {
+        unsafe { treasury_import_schema(schema) }
+        Ok(())
+    }
+}
+
+struct ProgressFFI;
+
+impl ImportProgress for ProgressFFI {
+    fn report(&mut self, done: u64, total: u64, stage: &str) {
+        unsafe { treasury_import_progress(done, total, stage.as_ptr(), stage.len()) }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        unsafe { treasury_import_cancelled() != 0 }
+    }
 }
 
 extern "C" {
+    fn treasury_import_progress(done: u64, total: u64, stage_ptr: *const u8, stage_len: usize);
+
+    fn treasury_import_cancelled() -> u32;
+
     fn treasury_registry_store(
         source_ptr: *const u8,
         source_len: usize,
@@ -253,6 +466,29 @@ extern "C" {
         error_ptr: *mut u8,
         error_len: usize,
     ) -> isize;
+
+    fn treasury_import_meta(
+        meta_ptr: *const u8,
+        meta_len: usize,
+        error_ptr: *mut u8,
+        error_len: usize,
+    ) -> isize;
+
+    fn treasury_import_dependency(
+        path_ptr: *const u8,
+        path_len: usize,
+        error_ptr: *mut u8,
+        error_len: usize,
+    ) -> isize;
+
+    fn treasury_import_locale(
+        locale_ptr: *const u8,
+        locale_len: usize,
+        error_ptr: *mut u8,
+        error_len: usize,
+    ) -> isize;
+
+    fn treasury_import_schema(schema: u32);
 }
 
 #[doc(hidden)]
@@ -263,12 +499,29 @@ macro_rules! count_tt {
 }
 
 /// Generates FFI-safe function to enumerate importers
+/// and function describing the module.
+///
+/// Features of treasury the importers require may be listed first,
+/// e.g. `features: ["http"];`.
+/// Treasury built without any of them refuses to load the module.
 #[macro_export]
 macro_rules! generate_imports_and_exports {
-    ($($importer:expr),* $(,)?) => {
+    (features: [$($feature:literal),* $(,)?]; $($importer:expr),* $(,)?) => {
+
+        #[no_mangle]
+        pub unsafe extern "C" fn treasury_importer_metadata(buf: *mut u8, len: usize) -> usize {
+            $crate::write_metadata(
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION"),
+                &[$($feature),*],
+                &[$($importer as &dyn $crate::Importer),*],
+                buf,
+                len,
+            )
+        }
 
         #[no_mangle]
-        pub unsafe fn treasury_importer_enumerate_importers(importers: *mut $crate::ImporterFFI, count: usize) -> usize {
+        pub unsafe extern "C" fn treasury_importer_enumerate_importers(importers: *mut $crate::ImporterFFI, count: usize) -> usize {
             const COUNT: usize = $crate::count_tt!($(($importer)),*);
 
             if count < COUNT {
@@ -285,12 +538,19 @@ macro_rules! generate_imports_and_exports {
             }
         }
     };
+
+    ($($importer:expr),* $(,)?) => {
+        $crate::generate_imports_and_exports! {
+            features: [];
+            $($importer),*
+        }
+    };
 }
 
 #[cfg(target_os = "wasi")]
 #[no_mangle]
-pub unsafe fn treasury_importer_name_source_native_trampoline(
-    function: unsafe fn(*const ImporterOpaque, *mut u8, u32) -> u32,
+pub unsafe extern "C" fn treasury_importer_name_source_native_trampoline(
+    function: unsafe extern "C" fn(*const ImporterOpaque, *mut u8, u32) -> u32,
     ptr: *const ImporterOpaque,
     result_ptr: *mut u8,
     result_len: u32,
@@ -300,13 +560,25 @@ pub unsafe fn treasury_importer_name_source_native_trampoline(
 
 #[cfg(target_os = "wasi")]
 #[no_mangle]
-pub unsafe fn treasury_importer_import_trampoline(
-    function: unsafe fn(*const ImporterOpaque, *const u8, u32, *const u8, u32, *mut u8, u32) -> i32,
+pub unsafe extern "C" fn treasury_importer_import_trampoline(
+    function: unsafe extern "C" fn(
+        *const ImporterOpaque,
+        *const u8,
+        u32,
+        *const u8,
+        u32,
+        *const u8,
+        u32,
+        *mut u8,
+        u32,
+    ) -> i32,
     ptr: *const ImporterOpaque,
     source_path_ptr: *const u8,
     source_path_len: u32,
     native_path_ptr: *const u8,
     native_path_len: u32,
+    settings_ptr: *const u8,
+    settings_len: u32,
     error_ptr: *mut u8,
     error_len: u32,
 ) -> i32 {
@@ -316,17 +588,26 @@ pub unsafe fn treasury_importer_import_trampoline(
         source_path_len,
         native_path_ptr,
         native_path_len,
+        settings_ptr,
+        settings_len,
         error_ptr,
         error_len,
     )
 }
 
+/// Returns version of FFI this module was built with.
+/// Treasury refuses to load importers with mismatching version.
+#[no_mangle]
+pub extern "C" fn treasury_importer_ffi_version() -> u32 {
+    IMPORTER_FFI_VERSION
+}
+
 /// # Safety
 ///
 /// This function is export of standard function `alloc::alloc::alloc`.
 /// Same safety principles applies.
 #[no_mangle]
-pub unsafe fn treasury_importer_alloc(size: usize, align: usize) -> *mut u8 {
+pub unsafe extern "C" fn treasury_importer_alloc(size: usize, align: usize) -> *mut u8 {
     let layout = std::alloc::Layout::from_size_align(size, align).unwrap();
     std::alloc::alloc(layout)
 }
@@ -336,7 +617,7 @@ pub unsafe fn treasury_importer_alloc(size: usize, align: usize) -> *mut u8 {
 /// This function is export of standard function `alloc::alloc::dealloc`.
 /// Same safety principles applies.
 #[no_mangle]
-pub unsafe fn treasury_importer_dealloc(ptr: *mut u8, size: usize, align: usize) {
+pub unsafe extern "C" fn treasury_importer_dealloc(ptr: *mut u8, size: usize, align: usize) {
     let layout = std::alloc::Layout::from_size_align(size, align).unwrap();
     std::alloc::dealloc(ptr, layout);
 }
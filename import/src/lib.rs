@@ -1,11 +1,118 @@
-use {std::path::Path, uuid::Uuid};
+use {
+    std::{
+        collections::HashSet,
+        fmt::{self, Debug, Display},
+        future::Future,
+        ops::Deref,
+        path::{Path, PathBuf},
+        pin::Pin,
+        sync::{Mutex, OnceLock},
+    },
+    uuid::Uuid,
+};
 
 pub use eyre;
 
+/// Version of FFI between treasury and importers modules.
+/// Must be bumped on any change of exported functions or their signatures.
+pub const IMPORTER_FFI_VERSION: u32 = 8;
+
+/// Identifier of source or native asset format.
+///
+/// Importers should export constants for formats they use,
+/// so that misspelled format names fail to compile
+/// instead of failing at runtime.
+/// Format names from user input are interned with [`FormatId::intern`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FormatId(&'static str);
+
+impl FormatId {
+    /// Returns format identifier with specified name.
+    pub const fn new(name: &'static str) -> Self {
+        FormatId(name)
+    }
+
+    /// Returns format identifier with specified name,
+    /// allocating the name once per process.
+    pub fn intern(name: &str) -> Self {
+        static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+        let mut interned = INTERNED.get_or_init(Default::default).lock().unwrap();
+        match interned.get(name) {
+            Some(name) => FormatId(name),
+            None => {
+                let name: &'static str = Box::leak(name.into());
+                interned.insert(name);
+                FormatId(name)
+            }
+        }
+    }
+
+    /// Returns name of the format.
+    pub const fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl Deref for FormatId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl AsRef<str> for FormatId {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl Debug for FormatId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.0, f)
+    }
+}
+
+impl Display for FormatId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Interns format name.
+/// Allows passing strings where [`FormatId`] is expected.
+impl<T> From<&T> for FormatId
+where
+    T: AsRef<str> + ?Sized,
+{
+    fn from(name: &T) -> Self {
+        FormatId::intern(name.as_ref())
+    }
+}
+
 /// Object to register sub-assets when importing super-asset.
+///
+/// Importers receive registry in [`Importer::import`] and may use it
+/// to store sources referenced by the asset being imported
+/// (e.g. textures referenced by a material)
+/// and embed returned UUIDs into native output.
+///
+/// # Re-entrancy
+///
+/// Treasury does not hold any locks while importer runs,
+/// so calls to the registry never block on the import that performs them.
+///
+/// * `store` of a source that is already registered returns its UUID without importing.
+/// * `store` of a source that is being imported by another thread
+///   waits for that import to finish.
+/// * `store` and `fetch` that would wait for an import performed by the caller,
+///   directly or through a chain of nested imports, fail with an error instead of deadlocking.
+///   Importers must handle such errors, e.g. by leaving reference unresolved.
 pub trait Registry {
     /// Register asset at source path, assigning specified importer.
     /// Source path must be absolute.
+    /// Returns UUID of the registered asset.
     fn store(
         &mut self,
         source: &Path,
@@ -15,7 +122,79 @@ pub trait Registry {
     ) -> eyre::Result<Uuid>;
 
     /// Returns native path to asset with specified uuid.
+    /// Reimports asset if native file is out of date.
     fn fetch(&mut self, asset: &Uuid) -> eyre::Result<Box<Path>>;
+
+    /// Records metadata of the asset being imported.
+    /// `meta` must be JSON document.
+    /// It is stored in the asset record, replacing metadata of previous import.
+    fn set_meta(&mut self, meta: &str) -> eyre::Result<()>;
+
+    /// Records file the asset being imported depends on, besides its source.
+    /// E.g. file included by the source.
+    /// Asset is reimported when any of its dependencies is modified.
+    fn add_dependency(&mut self, path: &Path) -> eyre::Result<()>;
+
+    /// Records that native file for specified locale is written
+    /// to [`localized_native_path`] of the native path.
+    /// Native file at the native path is used for locales without own file.
+    ///
+    /// Locale must be non-empty and consist of ASCII alphanumerics, `-` and `_`,
+    /// e.g. `pt-BR`.
+    fn add_locale(&mut self, locale: &str) -> eyre::Result<()>;
+
+    /// Records schema version of the native file written by the import.
+    /// Loader decodes native files of older schema with migrations
+    /// registered for the asset type, and `Treasury::mark_schema_outdated`
+    /// marks them for reimport.
+    /// Native files are of schema `0` unless recorded otherwise.
+    fn set_schema(&mut self, schema: u32) -> eyre::Result<()>;
+}
+
+/// Returns path to native file for specified locale
+/// next to the native file at `native_path`.
+pub fn localized_native_path(native_path: &Path, locale: &str) -> PathBuf {
+    let mut file_name = native_path.file_name().unwrap_or_default().to_owned();
+    file_name.push("@");
+    file_name.push(locale);
+    native_path.with_file_name(file_name)
+}
+
+/// Checks that locale is valid to be recorded with [`Registry::add_locale`].
+pub fn is_valid_locale(locale: &str) -> bool {
+    !locale.is_empty()
+        && locale
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Receives progress of long-running imports.
+pub trait ImportProgress {
+    /// Reports that `done` out of `total` units of work are finished
+    /// at specified stage of import.
+    /// Units are chosen by importer and may differ between stages.
+    fn report(&mut self, done: u64, total: u64, stage: &str);
+
+    /// Returns `true` once import is cancelled.
+    /// Long-running importers should check it between stages
+    /// and fail early, as result of cancelled import is discarded.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl<F> ImportProgress for F
+where
+    F: FnMut(u64, u64, &str),
+{
+    fn report(&mut self, done: u64, total: u64, stage: &str) {
+        self(done, total, stage)
+    }
+}
+
+/// Ignores progress reports.
+impl ImportProgress for () {
+    fn report(&mut self, _done: u64, _total: u64, _stage: &str) {}
 }
 
 pub trait Importer: Send + Sync {
@@ -30,10 +209,57 @@ pub trait Importer: Send + Sync {
 
     /// Imports asset from source file, saving result to native file.
     /// Register sub-assets if necessary.
+    /// Long-running importers should report progress
+    /// and check [`ImportProgress::is_cancelled`].
+    ///
+    /// `settings` is JSON document with import settings of the asset,
+    /// or empty string if asset has no settings.
     fn import(
         &self,
         source_path: &Path,
         native_path: &Path,
+        settings: &str,
         registry: &mut dyn Registry,
+        progress: &mut dyn ImportProgress,
     ) -> eyre::Result<()>;
 }
+
+/// Boxed future returned by [`AsyncImporter::import`].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Importer that awaits external tools or services.
+///
+/// Async importers are not loaded from WASM modules,
+/// but registered on the treasury by the host application.
+/// They run on the treasury's async runtime, so importer may spawn
+/// and await subprocesses or network requests using `tokio`.
+///
+/// Calls to the registry may perform nested imports and block until they finish.
+pub trait AsyncImporter: Send + Sync + 'static {
+    /// Returns name of the importer
+    fn name(&self) -> &str;
+
+    /// Returns name of the source format
+    fn source(&self) -> &str;
+
+    /// Returns name of the native format
+    fn native(&self) -> &str;
+
+    /// Imports asset from source file, saving result to native file.
+    /// Register sub-assets if necessary.
+    /// Long-running importers should report progress.
+    ///
+    /// `settings` is JSON document with import settings of the asset,
+    /// or empty string if asset has no settings.
+    ///
+    /// Future is dropped if import times out or is cancelled.
+    /// Subprocesses should be spawned with `kill_on_drop` to be stopped then.
+    fn import<'a>(
+        &'a self,
+        source_path: &'a Path,
+        native_path: &'a Path,
+        settings: &'a str,
+        registry: &'a mut (dyn Registry + Send),
+        progress: &'a mut (dyn ImportProgress + Send),
+    ) -> BoxFuture<'a, eyre::Result<()>>;
+}
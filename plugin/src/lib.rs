@@ -4,10 +4,10 @@ pub use {
     goods_treasury_import::{
         eyre,
         ffi::{
-            treasury_importer_alloc, treasury_importer_dealloc,
+            treasury_importer_alloc, treasury_importer_dealloc, treasury_importer_ffi_version,
             treasury_importer_import_trampoline, treasury_importer_name_source_native_trampoline,
         },
-        generate_imports_and_exports, Importer, Registry,
+        generate_imports_and_exports, ImportProgress, Importer, Registry,
     },
     std::path::Path,
 };
@@ -31,7 +31,9 @@ impl Importer for PluginImporter {
         &self,
         source_path: &Path,
         native_path: &Path,
+        _settings: &str,
         _registry: &mut dyn Registry,
+        _progress: &mut dyn ImportProgress,
     ) -> eyre::Result<()> {
         std::fs::copy(source_path, native_path)?;
 
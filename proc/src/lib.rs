@@ -26,16 +26,24 @@
 ///
 /// /// Asset structure. Implements Asset trait using
 /// /// two generated structures are intermediate phases.
+/// /// Optional `asset` attribute sets native format of the asset type.
 /// #[asset]
+/// #[asset(format = "asset-struct")]
 /// struct AssetStruct {
 ///     foo: Foo,
 ///     bar: Bar,
 ///     #[external]
 ///     baz: Baz,
+///     /// `None` if uuid is null or asset is not found in any source.
+///     #[optional_external]
+///     qux: Option<Baz>,
 /// }
 /// ```
 ///
-#[proc_macro_derive(Asset, attributes(external, container, serde))]
+#[proc_macro_derive(
+    Asset,
+    attributes(asset, external, optional_external, container, serde)
+)]
 pub fn asset(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     match parse(item) {
         Ok(parsed) => asset_impl(parsed),
@@ -44,7 +52,7 @@ pub fn asset(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     .into()
 }
 
-#[proc_macro_derive(AssetField, attributes(external, container, serde))]
+#[proc_macro_derive(AssetField, attributes(external, optional_external, container, serde))]
 pub fn asset_field(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     match parse(item).and_then(asset_field_impl) {
         Ok(tokens) => tokens,
@@ -71,6 +79,7 @@ struct Parsed {
     decoded_fields: proc_macro2::TokenStream,
     decoded_to_asset_fields: proc_macro2::TokenStream,
     serde_attributes: Vec<syn::Attribute>,
+    format: Option<syn::LitStr>,
 }
 
 fn parse(item: proc_macro::TokenStream) -> syn::Result<Parsed> {
@@ -89,6 +98,23 @@ fn parse(item: proc_macro::TokenStream) -> syn::Result<Parsed> {
         .cloned()
         .collect();
 
+    let mut format = None;
+    for attr in &derive_input.attrs {
+        if attr.path.is_ident("asset") {
+            format = Some(attr.parse_args_with(|stream: syn::parse::ParseStream| {
+                let name = stream.parse::<syn::Ident>()?;
+                if name != "format" {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        "Only 'format' argument is supported by 'asset' attribute",
+                    ));
+                }
+                let _eq = stream.parse::<syn::Token![=]>()?;
+                stream.parse::<syn::LitStr>()
+            })?);
+        }
+    }
+
     let mut decode_field_errors = proc_macro2::TokenStream::new();
     let mut build_field_errors = proc_macro2::TokenStream::new();
     let mut builder_bounds = proc_macro2::TokenStream::new();
@@ -132,11 +158,9 @@ fn parse(item: proc_macro::TokenStream) -> syn::Result<Parsed> {
             .iter()
             .enumerate()
             .filter_map(|(index, attr)| {
-                if attr
-                    .path
-                    .get_ident()
-                    .map_or(false, |ident| ident == "external" || ident == "container")
-                {
+                if attr.path.get_ident().map_or(false, |ident| {
+                    ident == "external" || ident == "optional_external" || ident == "container"
+                }) {
                     Some(index)
                 } else {
                     None
@@ -182,14 +206,25 @@ fn parse(item: proc_macro::TokenStream) -> syn::Result<Parsed> {
 
                 let attribute = &field.attrs[asset_attributes[0]];
 
+                let optional = attribute.path.is_ident("optional_external");
+
                 let kind = match attribute.path.get_ident().unwrap() {
-                    i if i == "external" => quote::quote!(::goods::External),
+                    i if i == "external" || i == "optional_external" => {
+                        quote::quote!(::goods::External)
+                    }
                     i if i == "container" => quote::quote!(::goods::Container),
                     _ => unreachable!(),
                 };
 
                 let as_type_arg = match attribute.tokens.is_empty() {
+                    true if optional => Some(optional_external_type(ty)?),
                     true => None,
+                    false if optional => {
+                        return Err(syn::Error::new_spanned(
+                            attribute,
+                            "'optional_external' attribute accepts no arguments",
+                        ))
+                    }
                     false => Some(attribute.parse_args_with(
                         |stream: syn::parse::ParseStream| {
                             let _as = stream.parse::<syn::Token![as]>()?;
@@ -282,7 +317,7 @@ fn parse(item: proc_macro::TokenStream) -> syn::Result<Parsed> {
             _ => {
                 return Err(syn::Error::new_spanned(
                     &field.attrs[asset_attributes[1]],
-                    "Only one of two attributes 'external' or 'container' can be specified",
+                    "Only one of attributes 'external', 'optional_external' or 'container' can be specified",
                 ));
             }
         }
@@ -306,6 +341,7 @@ fn parse(item: proc_macro::TokenStream) -> syn::Result<Parsed> {
         decoded_fields,
         decoded_to_asset_fields,
         serde_attributes,
+        format,
     })
 }
 
@@ -328,8 +364,11 @@ fn asset_impl(parsed: Parsed) -> proc_macro2::TokenStream {
         decoded_fields,
         decoded_to_asset_fields,
         serde_attributes,
+        format,
     } = parsed;
 
+    let format = format.map(|format| quote::quote!(const FORMAT: &'static str = #format;));
+
     let data_struct = match &derive_input.data {
         syn::Data::Struct(data) => data,
         _ => unreachable!(),
@@ -355,12 +394,14 @@ fn asset_impl(parsed: Parsed) -> proc_macro2::TokenStream {
             pub type #build_error = ::std::convert::Infallible;
 
             impl ::goods::Asset for #ty {
+                #format
+
                 type BuildError = #build_error;
                 type DecodeError = #decode_error;
                 type Decoded = #info;
                 type Fut = ::std::future::Ready<::std::result::Result<#info, #decode_error>>;
 
-                fn decode(bytes: ::std::boxed::Box<[u8]>, _loader: &::goods::Loader) -> Self::Fut {
+                fn decode(bytes: ::goods::bytes::Bytes, _loader: &::goods::Loader) -> Self::Fut {
                     use {::std::result::Result::{Ok, Err}, ::goods::serde_json::error::Category};
 
                     /// Zero-length is definitely bincode. For unit structs we may skip deserialization.
@@ -421,12 +462,14 @@ fn asset_impl(parsed: Parsed) -> proc_macro2::TokenStream {
             }
 
             impl ::goods::Asset for #ty {
+                #format
+
                 type BuildError = #build_error;
                 type DecodeError = #decode_error;
                 type Decoded = #decoded;
                 type Fut = ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = Result<#decoded, #decode_error>> + Send>>;
 
-                fn decode(bytes: ::std::boxed::Box<[u8]>, loader: &::goods::Loader) -> Self::Fut {
+                fn decode(bytes: ::goods::bytes::Bytes, loader: &::goods::Loader) -> Self::Fut {
                     use {::std::{boxed::Box, result::Result::{self, Ok, Err}}, ::goods::serde_json::error::Category};
 
                     // Zero-length is definitely bincode.
@@ -495,12 +538,14 @@ fn asset_impl(parsed: Parsed) -> proc_macro2::TokenStream {
             pub type #build_error = ::std::convert::Infallible;
 
             impl ::goods::Asset for #ty {
+                #format
+
                 type BuildError = #build_error;
                 type DecodeError = #decode_error;
                 type Decoded = #info;
                 type Fut = ::std::future::Ready<Result<#info, #decode_error>>;
 
-                fn decode(bytes: ::std::boxed::Box<[u8]>, _loader: &::goods::Loader) -> Self::Fut {
+                fn decode(bytes: ::goods::bytes::Bytes, _loader: &::goods::Loader) -> Self::Fut {
                     use {::std::result::Result::{Ok, Err}, ::goods::serde_json::error::Category};
 
                     /// Zero-length is definitely bincode.
@@ -563,6 +608,7 @@ fn asset_field_impl(parsed: Parsed) -> syn::Result<proc_macro2::TokenStream> {
         decoded_fields,
         decoded_to_asset_fields,
         serde_attributes,
+        format: _,
     } = parsed;
 
     if !complex {
@@ -660,3 +706,23 @@ fn snake_to_pascal(input: &syn::Ident) -> syn::Ident {
     }
     syn::Ident::new(&result, input.span())
 }
+
+/// Returns `OptionalExternal<A>` type for `Option<A>` field type.
+fn optional_external_type(ty: &syn::Type) -> syn::Result<syn::Type> {
+    if let syn::Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Option" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(asset)) = args.args.first() {
+                        return Ok(syn::parse_quote!(::goods::OptionalExternal<#asset>));
+                    }
+                }
+            }
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        ty,
+        "Field with 'optional_external' attribute must be of type 'Option<A>'",
+    ))
+}
@@ -0,0 +1,206 @@
+//!
+//! Python bindings of goods treasury for pipeline scripts,
+//! e.g. batch re-tagging and audits, using real registry logic
+//! instead of parsing manifest file.
+//!
+//! Built with `maturin build --release` into `goods_py` Python module.
+//!
+//! ```python
+//! import goods_py
+//!
+//! treasury = goods_py.Treasury.open("assets")
+//! for asset in treasury.list(tags=["character"]):
+//!     treasury.set_tags(asset.uuid, asset.tags + ["npc"])
+//! treasury.save()
+//! ```
+//!
+
+use {
+    goods_treasury::Treasury,
+    pyo3::{create_exception, exceptions::PyException, exceptions::PyValueError, prelude::*},
+    uuid::Uuid,
+};
+
+create_exception!(
+    goods_py,
+    TreasuryError,
+    PyException,
+    "Failed treasury operation."
+);
+
+/// Converts error with its sources into [`TreasuryError`].
+fn treasury_error(err: &dyn std::error::Error) -> PyErr {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message.push_str(". ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+    TreasuryError::new_err(message)
+}
+
+fn parse_uuid(uuid: &str) -> PyResult<Uuid> {
+    uuid.parse()
+        .map_err(|err| PyValueError::new_err(format!("Invalid uuid '{}'. {}", uuid, err)))
+}
+
+/// Asset record in treasury.
+#[pyclass(name = "Asset", frozen, module = "goods_py")]
+struct PyAsset {
+    asset: goods_treasury::Asset,
+}
+
+#[pymethods]
+impl PyAsset {
+    #[getter]
+    fn uuid(&self) -> String {
+        self.asset.uuid().to_string()
+    }
+
+    #[getter]
+    fn source(&self) -> &str {
+        self.asset.source()
+    }
+
+    #[getter]
+    fn source_format(&self) -> &str {
+        self.asset.source_format()
+    }
+
+    #[getter]
+    fn native_format(&self) -> &str {
+        self.asset.native_format()
+    }
+
+    #[getter]
+    fn tags(&self) -> Vec<&str> {
+        self.asset.tags().iter().map(|tag| &**tag).collect()
+    }
+
+    #[getter]
+    fn version(&self) -> u64 {
+        self.asset.version()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Asset(uuid='{}', source='{}', {} -> {})",
+            self.asset.uuid(),
+            self.asset.source(),
+            self.asset.source_format(),
+            self.asset.native_format(),
+        )
+    }
+}
+
+/// Opened treasury.
+#[pyclass(name = "Treasury", module = "goods_py")]
+struct PyTreasury {
+    treasury: Treasury,
+}
+
+#[pymethods]
+impl PyTreasury {
+    /// Creates new treasury at the root directory.
+    #[staticmethod]
+    #[pyo3(signature = (root, overwrite = false))]
+    fn new(root: &str, overwrite: bool) -> PyResult<Self> {
+        let treasury = Treasury::new(root, overwrite).map_err(|err| treasury_error(&err))?;
+        Ok(PyTreasury { treasury })
+    }
+
+    /// Opens existing treasury at the root directory.
+    #[staticmethod]
+    fn open(root: &str) -> PyResult<Self> {
+        let treasury = Treasury::open(root).map_err(|err| treasury_error(&err))?;
+        Ok(PyTreasury { treasury })
+    }
+
+    fn load_importers_dir(&mut self, dir: &str) -> PyResult<()> {
+        self.treasury
+            .load_importers_dir(dir)
+            .map_err(|err| treasury_error(&err))
+    }
+
+    /// Imports asset and returns its uuid.
+    #[pyo3(signature = (source, source_format, native_format, tags = Vec::new()))]
+    fn store(
+        &self,
+        py: Python<'_>,
+        source: &str,
+        source_format: &str,
+        native_format: &str,
+        tags: Vec<String>,
+    ) -> PyResult<String> {
+        let treasury = &self.treasury;
+        let uuid = py
+            .allow_threads(|| treasury.store(source, source_format, native_format, &tags))
+            .map_err(|err| treasury_error(&err))?;
+        Ok(uuid.to_string())
+    }
+
+    /// Returns assets with all specified tags and native format.
+    #[pyo3(signature = (tags = Vec::new(), native_format = None))]
+    fn list(&self, tags: Vec<String>, native_format: Option<&str>) -> Vec<PyAsset> {
+        self.treasury
+            .list(&tags, native_format)
+            .into_iter()
+            .map(|asset| PyAsset { asset })
+            .collect()
+    }
+
+    /// Replaces tags of the asset.
+    fn set_tags(&self, uuid: &str, tags: Vec<String>) -> PyResult<()> {
+        self.treasury
+            .set_tags(parse_uuid(uuid)?, &tags)
+            .map_err(|err| treasury_error(&err))
+    }
+
+    fn remove(&self, uuid: &str) -> PyResult<()> {
+        self.treasury.remove(parse_uuid(uuid)?);
+        Ok(())
+    }
+
+    /// Reimports asset and returns new version of native file.
+    fn reimport(&mut self, py: Python<'_>, uuid: &str) -> PyResult<u64> {
+        let uuid = parse_uuid(uuid)?;
+        self.treasury
+            .mark_outdated(uuid)
+            .map_err(|err| treasury_error(&err))?;
+
+        let treasury = &mut self.treasury;
+        let data = py
+            .allow_threads(|| treasury.fetch(&uuid, None))
+            .map_err(|err| treasury_error(&err))?;
+        Ok(data.version)
+    }
+
+    /// Returns uuids of assets with native files missing or out of date.
+    fn verify(&self) -> PyResult<Vec<String>> {
+        let mut outdated = Vec::new();
+        for asset in self.treasury.list(&[] as &[&str], None) {
+            let stat = self
+                .treasury
+                .stat(asset.uuid())
+                .map_err(|err| treasury_error(&err))?;
+
+            if stat.native_size.is_none() || !stat.up_to_date {
+                outdated.push(stat.uuid.to_string());
+            }
+        }
+        Ok(outdated)
+    }
+
+    fn save(&self) -> PyResult<()> {
+        self.treasury.save().map_err(|err| treasury_error(&err))
+    }
+}
+
+#[pymodule]
+fn goods_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyTreasury>()?;
+    m.add_class::<PyAsset>()?;
+    m.add("TreasuryError", m.py().get_type::<TreasuryError>())?;
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! Resolution of `#include` directives.
+//!
+//! Both `#include "path"` and `#include <path>` forms are supported.
+//! Quoted paths are searched relative to the including file first,
+//! then in include directories. Angle-bracketed paths are searched in include directories only.
+//!
+//! Every file is included at most once, so include guards are not required
+//! and recursive includes do not loop.
+
+use {
+    goods_treasury_import::eyre::{self, WrapErr},
+    std::path::{Path, PathBuf},
+};
+
+/// Source with all includes expanded.
+pub struct Expanded {
+    pub source: String,
+
+    /// Included files, not including the root one.
+    pub includes: Vec<PathBuf>,
+
+    /// File index and line number of every line of expanded source.
+    /// File index 0 is the root file, others are `includes[index - 1]`.
+    lines: Vec<(usize, u32)>,
+
+    root: PathBuf,
+}
+
+impl Expanded {
+    /// Maps 1-based line of expanded source to file and line in that file.
+    pub fn origin(&self, line: u32) -> (&Path, u32) {
+        match self.lines.get(line as usize - 1) {
+            None => (&self.root, line),
+            Some(&(0, line)) => (&self.root, line),
+            Some(&(index, line)) => (&self.includes[index - 1], line),
+        }
+    }
+}
+
+pub fn expand(root: &Path, include_dirs: &[PathBuf]) -> eyre::Result<Expanded> {
+    let mut expanded = Expanded {
+        source: String::new(),
+        includes: Vec::new(),
+        lines: Vec::new(),
+        root: root.to_owned(),
+    };
+
+    expand_file(root, 0, include_dirs, &mut expanded)?;
+    Ok(expanded)
+}
+
+fn expand_file(
+    path: &Path,
+    index: usize,
+    include_dirs: &[PathBuf],
+    expanded: &mut Expanded,
+) -> eyre::Result<()> {
+    let source = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read '{}'", path.display()))?;
+
+    for (number, line) in source.lines().enumerate() {
+        let number = number as u32 + 1;
+
+        let include = match parse_include(line) {
+            None => {
+                expanded.source.push_str(line);
+                expanded.source.push('\n');
+                expanded.lines.push((index, number));
+                continue;
+            }
+            Some(include) => include,
+        };
+
+        let include = include
+            .and_then(|include| resolve(path, include, include_dirs))
+            .wrap_err_with(|| format!("Bad include at '{}':{}", path.display(), number))?;
+
+        // Keep line numbers of the including file intact.
+        expanded.source.push('\n');
+        expanded.lines.push((index, number));
+
+        if include != expanded.root && !expanded.includes.contains(&include) {
+            expanded.includes.push(include.clone());
+            let index = expanded.includes.len();
+            expand_file(&include, index, include_dirs, expanded)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `None` if line is not an include directive.
+fn parse_include(line: &str) -> Option<eyre::Result<Include<'_>>> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start();
+    let rest = rest.strip_prefix("include")?.trim();
+
+    let include = if let Some(path) = rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+        Ok(Include::Quoted(path))
+    } else if let Some(path) = rest.strip_prefix('<').and_then(|r| r.strip_suffix('>')) {
+        Ok(Include::Angled(path))
+    } else {
+        Err(eyre::eyre!(
+            "Include path must be in quotes or angle brackets"
+        ))
+    };
+
+    Some(include)
+}
+
+enum Include<'a> {
+    Quoted(&'a str),
+    Angled(&'a str),
+}
+
+fn resolve(from: &Path, include: Include<'_>, include_dirs: &[PathBuf]) -> eyre::Result<PathBuf> {
+    let (path, relative) = match include {
+        Include::Quoted(path) => (path, from.parent()),
+        Include::Angled(path) => (path, None),
+    };
+
+    relative
+        .into_iter()
+        .chain(include_dirs.iter().map(PathBuf::as_path))
+        .map(|dir| dir.join(path))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| eyre::eyre!("Included file '{}' not found", path))
+}
@@ -0,0 +1,276 @@
+//! Shader importers for treasury.
+//!
+//! Imports GLSL and WGSL shaders into `spirv` or validated `wgsl` native formats.
+//! `spirv` native file contains SPIR-V module words in little-endian order,
+//! `wgsl` native file contains WGSL source.
+//!
+//! `#include` directives are resolved before compilation, see [`include`] module.
+//! Included files are recorded as dependencies of the asset,
+//! so it is reimported when any of them is modified.
+//!
+//! [`Reflection`] of the shader is recorded as metadata of the asset.
+
+mod include;
+pub mod reflect;
+
+use {
+    crate::{include::Expanded, reflect::Reflection},
+    goods_treasury_import::{
+        eyre::{self, WrapErr},
+        ImportProgress, Importer, Registry,
+    },
+    naga::{
+        valid::{Capabilities, ModuleInfo, ValidationFlags, Validator},
+        Module, ShaderStage,
+    },
+    std::{collections::BTreeMap, path::Path, path::PathBuf},
+};
+
+#[cfg(target_os = "wasi")]
+pub use goods_treasury_import_ffi::{
+    treasury_importer_alloc, treasury_importer_dealloc, treasury_importer_ffi_version,
+    treasury_importer_import_trampoline, treasury_importer_name_source_native_trampoline,
+};
+
+/// Native format name of SPIR-V shaders.
+pub const SPIRV_NATIVE_FORMAT: &str = "spirv";
+
+/// Native format name of WGSL shaders.
+pub const WGSL_NATIVE_FORMAT: &str = "wgsl";
+
+/// Identifiers of formats imported by this crate.
+pub mod formats {
+    use goods_treasury_import::FormatId;
+
+    /// GLSL shader source format.
+    pub const GLSL: FormatId = FormatId::new("glsl");
+
+    /// WGSL shader source and native format.
+    pub const WGSL: FormatId = FormatId::new(super::WGSL_NATIVE_FORMAT);
+
+    /// SPIR-V native format.
+    pub const SPIRV: FormatId = FormatId::new(super::SPIRV_NATIVE_FORMAT);
+}
+
+/// Import settings of shader assets.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShaderSettings {
+    /// Stage of GLSL shader.
+    /// If not set, it is inferred from `.vert`, `.frag` or `.comp` extension of the source.
+    /// Ignored for WGSL shaders which declare stages of entry points.
+    pub stage: Option<reflect::Stage>,
+
+    /// Preprocessor definitions, as if `#define key value` was written
+    /// at the beginning of the shader.
+    /// Supported for GLSL shaders only.
+    pub defines: BTreeMap<String, String>,
+
+    /// Directories to search included files in.
+    /// Relative to the source directory.
+    pub include_dirs: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy)]
+enum Language {
+    Glsl,
+    Wgsl,
+}
+
+/// Importer of shaders from particular language into particular native format.
+pub struct ShaderImporter {
+    name: &'static str,
+    source: Language,
+    native: &'static str,
+}
+
+pub static GLSL_SPIRV_IMPORTER: ShaderImporter = ShaderImporter {
+    name: "glsl-spirv",
+    source: Language::Glsl,
+    native: SPIRV_NATIVE_FORMAT,
+};
+
+pub static GLSL_WGSL_IMPORTER: ShaderImporter = ShaderImporter {
+    name: "glsl-wgsl",
+    source: Language::Glsl,
+    native: WGSL_NATIVE_FORMAT,
+};
+
+pub static WGSL_SPIRV_IMPORTER: ShaderImporter = ShaderImporter {
+    name: "wgsl-spirv",
+    source: Language::Wgsl,
+    native: SPIRV_NATIVE_FORMAT,
+};
+
+pub static WGSL_IMPORTER: ShaderImporter = ShaderImporter {
+    name: "wgsl",
+    source: Language::Wgsl,
+    native: WGSL_NATIVE_FORMAT,
+};
+
+impl Importer for ShaderImporter {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn source(&self) -> &str {
+        match self.source {
+            Language::Glsl => formats::GLSL.as_str(),
+            Language::Wgsl => formats::WGSL.as_str(),
+        }
+    }
+
+    fn native(&self) -> &str {
+        self.native
+    }
+
+    fn import(
+        &self,
+        source_path: &Path,
+        native_path: &Path,
+        settings: &str,
+        registry: &mut dyn Registry,
+        _progress: &mut dyn ImportProgress,
+    ) -> eyre::Result<()> {
+        let settings: ShaderSettings = if settings.is_empty() {
+            ShaderSettings::default()
+        } else {
+            serde_json::from_str(settings).wrap_err("Invalid shader import settings")?
+        };
+
+        let source_dir = source_path.parent().unwrap_or_else(|| Path::new(""));
+        let include_dirs: Vec<_> = settings
+            .include_dirs
+            .iter()
+            .map(|dir| source_dir.join(dir))
+            .collect();
+
+        let expanded = include::expand(source_path, &include_dirs)?;
+
+        // Record dependencies before compilation,
+        // so that fixing an included file triggers reimport.
+        for include in &expanded.includes {
+            registry.add_dependency(include)?;
+        }
+
+        let module = match self.source {
+            Language::Glsl => parse_glsl(source_path, &settings, &expanded)?,
+            Language::Wgsl => {
+                if !settings.defines.is_empty() {
+                    eyre::bail!("Defines are supported for GLSL shaders only");
+                }
+                parse_wgsl(&expanded)?
+            }
+        };
+
+        let info = validate(&module, &expanded)?;
+
+        let native = match self.native {
+            SPIRV_NATIVE_FORMAT => {
+                let words = naga::back::spv::write_vec(
+                    &module,
+                    &info,
+                    &naga::back::spv::Options::default(),
+                    None,
+                )
+                .wrap_err("Failed to generate SPIR-V")?;
+
+                words.iter().flat_map(|word| word.to_le_bytes()).collect()
+            }
+            _ => naga::back::wgsl::write_string(
+                &module,
+                &info,
+                naga::back::wgsl::WriterFlags::empty(),
+            )
+            .wrap_err("Failed to generate WGSL")?
+            .into_bytes(),
+        };
+
+        std::fs::write(native_path, native)
+            .wrap_err_with(|| format!("Failed to write '{}'", native_path.display()))?;
+
+        let reflection: Reflection = reflect::reflect(&module);
+        registry.set_meta(&serde_json::to_string(&reflection)?)
+    }
+}
+
+fn parse_glsl(
+    source_path: &Path,
+    settings: &ShaderSettings,
+    expanded: &Expanded,
+) -> eyre::Result<Module> {
+    let stage = match settings.stage {
+        Some(stage) => stage,
+        None => match source_path.extension().and_then(|ext| ext.to_str()) {
+            Some("vert") => reflect::Stage::Vertex,
+            Some("frag") => reflect::Stage::Fragment,
+            Some("comp") => reflect::Stage::Compute,
+            _ => eyre::bail!(
+                "Cannot infer shader stage of '{}', specify it in import settings",
+                source_path.display()
+            ),
+        },
+    };
+
+    let mut options = naga::front::glsl::Options::from(match stage {
+        reflect::Stage::Vertex => ShaderStage::Vertex,
+        reflect::Stage::Fragment => ShaderStage::Fragment,
+        reflect::Stage::Compute => ShaderStage::Compute,
+    });
+
+    options.defines.extend(
+        settings
+            .defines
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone())),
+    );
+
+    naga::front::glsl::Frontend::default()
+        .parse(&options, &expanded.source)
+        .map_err(|errors| {
+            let messages: Vec<_> = errors
+                .iter()
+                .map(|error| {
+                    let line = error.meta.location(&expanded.source).line_number;
+                    located(expanded, line, &error.kind)
+                })
+                .collect();
+
+            eyre::eyre!("{}", messages.join("\n"))
+        })
+}
+
+fn parse_wgsl(expanded: &Expanded) -> eyre::Result<Module> {
+    naga::front::wgsl::parse_str(&expanded.source).map_err(|error| {
+        match error.location(&expanded.source) {
+            Some(location) => eyre::eyre!("{}", located(expanded, location.line_number, &error)),
+            None => eyre::eyre!("{}", error),
+        }
+    })
+}
+
+fn validate(module: &Module, expanded: &Expanded) -> eyre::Result<ModuleInfo> {
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(module)
+        .map_err(|error| match error.location(&expanded.source) {
+            Some(location) => eyre::eyre!(
+                "Validation failed. {}",
+                located(expanded, location.line_number, error.as_inner())
+            ),
+            None => eyre::eyre!("Validation failed. {}", error.as_inner()),
+        })
+}
+
+/// Formats error with location in original file.
+fn located(expanded: &Expanded, line: u32, error: &dyn std::fmt::Display) -> String {
+    let (path, line) = expanded.origin(line);
+    format!("'{}':{}: {}", path.display(), line, error)
+}
+
+#[cfg(target_os = "wasi")]
+goods_treasury_import_ffi::generate_imports_and_exports! {
+    &GLSL_SPIRV_IMPORTER,
+    &GLSL_WGSL_IMPORTER,
+    &WGSL_SPIRV_IMPORTER,
+    &WGSL_IMPORTER,
+}
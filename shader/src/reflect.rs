@@ -0,0 +1,158 @@
+//! Reflection metadata recorded for imported shaders.
+
+use {
+    naga::{AddressSpace, Binding, ImageClass, Module, ShaderStage, StorageAccess, TypeInner},
+    serde::{Deserialize, Serialize},
+};
+
+/// Metadata of the imported shader asset.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Reflection {
+    pub entry_points: Vec<EntryPoint>,
+    pub bindings: Vec<ResourceBinding>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntryPoint {
+    pub name: String,
+    pub stage: Stage,
+
+    /// Workgroup size of compute entry points.
+    /// `[0, 0, 0]` for other stages.
+    pub workgroup_size: [u32; 3],
+
+    /// User-defined inputs with their locations.
+    pub inputs: Vec<Input>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Input {
+    pub name: Option<String>,
+    pub location: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResourceBinding {
+    pub name: Option<String>,
+    pub group: u32,
+    pub binding: u32,
+    pub kind: ResourceKind,
+
+    /// Number of elements of binding arrays.
+    /// `None` for single resources and runtime-sized arrays.
+    pub count: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    UniformBuffer,
+    StorageBuffer { read_only: bool },
+    Texture,
+    DepthTexture,
+    StorageTexture,
+    Sampler,
+    ComparisonSampler,
+}
+
+pub fn reflect(module: &Module) -> Reflection {
+    Reflection {
+        entry_points: module
+            .entry_points
+            .iter()
+            .map(|entry| EntryPoint {
+                name: entry.name.clone(),
+                stage: match entry.stage {
+                    ShaderStage::Vertex => Stage::Vertex,
+                    ShaderStage::Fragment => Stage::Fragment,
+                    ShaderStage::Compute => Stage::Compute,
+                },
+                workgroup_size: entry.workgroup_size,
+                inputs: entry_inputs(module, &entry.function),
+            })
+            .collect(),
+        bindings: module
+            .global_variables
+            .iter()
+            .filter_map(|(_, var)| {
+                let binding = var.binding.as_ref()?;
+
+                let (ty, count) = match module.types[var.ty].inner {
+                    TypeInner::BindingArray { base, size } => (
+                        base,
+                        match size {
+                            naga::ArraySize::Constant(size) => Some(size.get()),
+                            naga::ArraySize::Dynamic => None,
+                        },
+                    ),
+                    _ => (var.ty, None),
+                };
+
+                Some(ResourceBinding {
+                    name: var.name.clone(),
+                    group: binding.group,
+                    binding: binding.binding,
+                    kind: resource_kind(var.space, &module.types[ty].inner)?,
+                    count,
+                })
+            })
+            .collect(),
+    }
+}
+
+fn resource_kind(space: AddressSpace, ty: &TypeInner) -> Option<ResourceKind> {
+    match space {
+        AddressSpace::Uniform => Some(ResourceKind::UniformBuffer),
+        AddressSpace::Storage { access } => Some(ResourceKind::StorageBuffer {
+            read_only: !access.contains(StorageAccess::STORE),
+        }),
+        AddressSpace::Handle => match *ty {
+            TypeInner::Image { class, .. } => Some(match class {
+                ImageClass::Sampled { .. } => ResourceKind::Texture,
+                ImageClass::Depth { .. } => ResourceKind::DepthTexture,
+                ImageClass::Storage { .. } => ResourceKind::StorageTexture,
+            }),
+            TypeInner::Sampler { comparison: false } => Some(ResourceKind::Sampler),
+            TypeInner::Sampler { comparison: true } => Some(ResourceKind::ComparisonSampler),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn entry_inputs(module: &Module, function: &naga::Function) -> Vec<Input> {
+    let mut inputs = Vec::new();
+
+    for argument in &function.arguments {
+        match &argument.binding {
+            Some(Binding::Location { location, .. }) => inputs.push(Input {
+                name: argument.name.clone(),
+                location: *location,
+            }),
+            Some(Binding::BuiltIn(_)) => {}
+            None => {
+                if let TypeInner::Struct { members, .. } = &module.types[argument.ty].inner {
+                    for member in members {
+                        if let Some(Binding::Location { location, .. }) = member.binding {
+                            inputs.push(Input {
+                                name: member.name.clone(),
+                                location,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    inputs.sort_by_key(|input| input.location);
+    inputs
+}
@@ -1,10 +1,25 @@
 use {
     crate::loader::Loader,
+    bytes::Bytes,
     std::{error::Error, future::Future},
 };
 
 /// An asset type that can be built from decoded representation.
 pub trait Asset: Clone + Sized + Send + Sync + 'static {
+    /// Native format this asset type is decoded from.
+    /// Used to resolve asset type in [`Loader::load_untyped`].
+    ///
+    /// Empty string means that asset type is not associated with any native format.
+    const FORMAT: &'static str = "";
+
+    /// Version of native format schema decoded by [`Asset::decode`].
+    /// Asset data of other schema versions is decoded with
+    /// [`Migration`] registered with [`LoaderBuilder::add_migration`].
+    ///
+    /// [`Migration`]: crate::Migration
+    /// [`LoaderBuilder::add_migration`]: crate::LoaderBuilder::add_migration
+    const SCHEMA_VERSION: u32 = 0;
+
     /// Decoded representation of this asset.
     type Decoded: Send + Sync;
 
@@ -18,7 +33,7 @@ pub trait Asset: Clone + Sized + Send + Sync + 'static {
     type Fut: Future<Output = Result<Self::Decoded, Self::DecodeError>> + Send;
 
     /// Decode asset from bytes loaded from asset source.
-    fn decode(bytes: Box<[u8]>, loader: &Loader) -> Self::Fut;
+    fn decode(bytes: Bytes, loader: &Loader) -> Self::Fut;
 }
 
 pub trait AssetBuild<B>: Asset {
@@ -0,0 +1,102 @@
+use {
+    crate::{
+        asset::{Asset, AssetBuild},
+        loader::Loader,
+    },
+    bytes::Bytes,
+    serde::de::DeserializeOwned,
+    std::{
+        convert::Infallible,
+        fmt::{self, Debug},
+        future::{ready, Ready},
+        ops::Deref,
+        sync::Arc,
+    },
+};
+
+/// Native format name of config assets.
+pub const CONFIG_FORMAT: &str = "config";
+
+/// Asset that is a value deserialized from config document.
+///
+/// Native format is compact JSON, as produced by `goods-config` importer.
+/// The importer validates source documents against JSON schema,
+/// so malformed configs are rejected at import time.
+///
+/// All `ConfigAsset` types share [`CONFIG_FORMAT`] native format,
+/// so only one of them can be registered with [`LoaderBuilder::add_format`].
+///
+/// [`LoaderBuilder::add_format`]: crate::LoaderBuilder::add_format
+pub struct ConfigAsset<T> {
+    value: Arc<T>,
+}
+
+impl<T> ConfigAsset<T> {
+    pub fn new(value: T) -> Self {
+        ConfigAsset {
+            value: Arc::new(value),
+        }
+    }
+
+    /// Returns shared config value.
+    pub fn as_arc(&self) -> &Arc<T> {
+        &self.value
+    }
+}
+
+impl<T> Clone for ConfigAsset<T> {
+    fn clone(&self) -> Self {
+        ConfigAsset {
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T> Deref for ConfigAsset<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Debug for ConfigAsset<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.value, f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to deserialize config")]
+pub struct ConfigDecodeError {
+    #[from]
+    source: serde_json::Error,
+}
+
+impl<T> Asset for ConfigAsset<T>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    const FORMAT: &'static str = CONFIG_FORMAT;
+
+    type Decoded = T;
+    type DecodeError = ConfigDecodeError;
+    type BuildError = Infallible;
+    type Fut = Ready<Result<T, ConfigDecodeError>>;
+
+    fn decode(bytes: Bytes, _loader: &Loader) -> Self::Fut {
+        ready(serde_json::from_slice(&bytes).map_err(ConfigDecodeError::from))
+    }
+}
+
+impl<T, B> AssetBuild<B> for ConfigAsset<T>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    fn build(decoded: T, _builder: &mut B) -> Result<Self, Infallible> {
+        Ok(ConfigAsset::new(decoded))
+    }
+}
@@ -0,0 +1,199 @@
+use {
+    crate::{asset::Asset, loader::Loader},
+    bytes::Bytes,
+    futures::{
+        channel::{mpsc, oneshot},
+        future::{BoxFuture, FutureExt as _},
+        stream::{FuturesUnordered, StreamExt as _},
+    },
+    hashbrown::HashMap,
+    std::{
+        any::{Any, TypeId},
+        future::Future,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    },
+    tokio::sync::Semaphore,
+};
+
+/// Scheduling of decoding assets of a type.
+/// Configured with [`LoaderBuilder::set_decode_policy_for`].
+///
+/// [`LoaderBuilder::set_decode_policy_for`]: crate::LoaderBuilder::set_decode_policy_for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodePolicy {
+    /// Decode in loading task, on the thread running it.
+    /// Suits small assets, e.g. configs, that are decoded quickly.
+    #[default]
+    Inline,
+
+    /// Decode on threads dedicated to the asset type,
+    /// so that long decodes, e.g. of meshes, do not occupy threads of the spawner.
+    Dedicated { threads: usize },
+
+    /// Decode in loading task, but only as many assets of the type at once.
+    /// Other decodes of the type wait for their turn.
+    ///
+    /// Decodes keep their turn while waiting for assets they reference,
+    /// so types referencing assets of the same type must not use it.
+    Limited { max_parallel: usize },
+}
+
+/// Decoding of asset data written with older schema version of the asset type.
+/// Registered with [`LoaderBuilder::add_migration`].
+///
+/// Asset data reports its schema version with [`AssetData::schema`].
+/// Data of [`Asset::SCHEMA_VERSION`] is decoded with [`Asset::decode`],
+/// and data of other schema versions with migration registered for that version.
+///
+/// [`LoaderBuilder::add_migration`]: crate::LoaderBuilder::add_migration
+/// [`AssetData::schema`]: crate::source::AssetData::schema
+pub trait Migration: 'static {
+    /// Asset type this migration decodes.
+    type Asset: Asset;
+
+    /// Schema version of asset data this migration decodes.
+    const SCHEMA_VERSION: u32;
+
+    /// Future that will resolve into decoded asset when ready.
+    type Fut: Future<
+            Output = Result<<Self::Asset as Asset>::Decoded, <Self::Asset as Asset>::DecodeError>,
+        > + Send
+        + 'static;
+
+    /// Decode asset of current schema from bytes of older schema.
+    fn migrate(bytes: Bytes, loader: &Loader) -> Self::Fut;
+}
+
+/// Type-erased [`Migration::migrate`].
+pub(crate) type MigrateFn<A> =
+    fn(
+        Bytes,
+        &Loader,
+    ) -> BoxFuture<'static, Result<<A as Asset>::Decoded, <A as Asset>::DecodeError>>;
+
+pub(crate) fn migrate_fn<M: Migration>() -> Box<dyn Any + Send + Sync> {
+    let migrate: MigrateFn<M::Asset> = |bytes, loader| M::migrate(bytes, loader).boxed();
+    Box::new(migrate)
+}
+
+pub(crate) struct DecodePolicies {
+    pub default: DecodePolicy,
+    pub types: HashMap<TypeId, (DecodePolicy, &'static str)>,
+
+    /// [`MigrateFn`] of asset types by schema version they decode.
+    pub migrations: HashMap<(TypeId, u32), Box<dyn Any + Send + Sync>>,
+}
+
+/// Decode schedulers of asset types, created by the loader from [`DecodePolicies`].
+pub(crate) struct Decoders {
+    default: Arc<Decoder>,
+    types: HashMap<TypeId, Arc<Decoder>>,
+    migrations: HashMap<(TypeId, u32), Box<dyn Any + Send + Sync>>,
+}
+
+impl Decoders {
+    pub fn new(policies: DecodePolicies) -> Self {
+        Decoders {
+            default: Arc::new(Decoder::new(policies.default, "default")),
+            types: policies
+                .types
+                .into_iter()
+                .map(|(type_id, (policy, name))| (type_id, Arc::new(Decoder::new(policy, name))))
+                .collect(),
+            migrations: policies.migrations,
+        }
+    }
+
+    pub fn get(&self, type_id: TypeId) -> Arc<Decoder> {
+        self.types.get(&type_id).unwrap_or(&self.default).clone()
+    }
+
+    /// Returns migration of asset type from specified schema version.
+    pub fn migration<A: Asset>(&self, schema: u32) -> Option<MigrateFn<A>> {
+        let migrate = self.migrations.get(&(TypeId::of::<A>(), schema))?;
+        Some(*migrate.downcast_ref::<MigrateFn<A>>().unwrap())
+    }
+}
+
+pub(crate) enum Decoder {
+    Inline,
+    Dedicated {
+        threads: Vec<mpsc::UnboundedSender<BoxFuture<'static, ()>>>,
+        next: AtomicUsize,
+    },
+    Limited {
+        semaphore: Semaphore,
+    },
+}
+
+impl Decoder {
+    fn new(policy: DecodePolicy, name: &str) -> Self {
+        match policy {
+            DecodePolicy::Inline => Decoder::Inline,
+            DecodePolicy::Dedicated { threads } => Decoder::Dedicated {
+                threads: (0..threads.max(1))
+                    .map(|index| {
+                        let (tx, rx) = mpsc::unbounded();
+                        thread::Builder::new()
+                            .name(format!("goods-decode {} #{}", name, index))
+                            .spawn(move || futures::executor::block_on(run_decodes(rx)))
+                            .expect("Failed to spawn decode thread");
+                        tx
+                    })
+                    .collect(),
+                next: AtomicUsize::new(0),
+            },
+            DecodePolicy::Limited { max_parallel } => Decoder::Limited {
+                semaphore: Semaphore::new(max_parallel.max(1)),
+            },
+        }
+    }
+
+    /// Runs decoding future according to the policy.
+    pub async fn run<F>(&self, decode: F) -> F::Output
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        match self {
+            Decoder::Inline => decode.await,
+            Decoder::Dedicated { threads, next } => {
+                let (tx, rx) = oneshot::channel();
+                let task = async move {
+                    let _ = tx.send(decode.await);
+                };
+
+                let index = next.fetch_add(1, Ordering::Relaxed) % threads.len();
+                threads[index]
+                    .unbounded_send(task.boxed())
+                    .expect("Decode thread is stopped");
+                rx.await.expect("Decode task is dropped")
+            }
+            Decoder::Limited { semaphore } => {
+                let _permit = semaphore.acquire().await;
+                decode.await
+            }
+        }
+    }
+}
+
+/// Runs decodes sent to dedicated thread.
+/// Decodes waiting for referenced assets do not block others.
+/// Returns once loader is dropped and all decodes are finished.
+async fn run_decodes(mut rx: mpsc::UnboundedReceiver<BoxFuture<'static, ()>>) {
+    let mut tasks = FuturesUnordered::new();
+    loop {
+        futures::select! {
+            task = rx.next() => match task {
+                Some(task) => tasks.push(task),
+                None => break,
+            },
+            () = tasks.select_next_some() => {}
+        }
+    }
+    while tasks.next().await.is_some() {}
+}
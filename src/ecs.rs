@@ -0,0 +1,156 @@
+//! Helpers resolving asset handles stored in ECS components.
+//!
+//! Entities are spawned with components holding [`AssetHandle`]s.
+//! Calling `resolve` once per frame polls the handles,
+//! builds loaded assets and replaces handle components with the built assets,
+//! or with [`LoadFailed`] if asset failed to load or build.
+
+use {
+    crate::{
+        asset::AssetBuild,
+        loader::{AssetHandle, Error},
+        Asset,
+    },
+    futures::task::noop_waker_ref,
+    std::{
+        future::Future,
+        marker::PhantomData,
+        pin::Pin,
+        task::{Context, Poll},
+    },
+    uuid::Uuid,
+};
+
+/// Component holding handle of the asset to be replaced with the asset once built.
+pub trait HandleComponent: Send + Sync + 'static {
+    type Asset: Asset;
+
+    fn handle_mut(&mut self) -> &mut AssetHandle<Self::Asset>;
+}
+
+impl<A> HandleComponent for AssetHandle<A>
+where
+    A: Asset,
+{
+    type Asset = A;
+
+    fn handle_mut(&mut self) -> &mut AssetHandle<A> {
+        self
+    }
+}
+
+/// Component replacing handle component if asset failed to load or build.
+pub struct LoadFailed<A> {
+    pub uuid: Uuid,
+    pub error: Error,
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<A> LoadFailed<A> {
+    pub fn new(uuid: Uuid, error: Error) -> Self {
+        LoadFailed {
+            uuid,
+            error,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Polls handle of the component without blocking.
+/// Returns built asset or error once asset is loaded.
+///
+/// Building block for integrations with other ECS.
+pub fn poll_component<C, B>(component: &mut C, builder: &mut B) -> Option<Result<C::Asset, Error>>
+where
+    C: HandleComponent,
+    C::Asset: AssetBuild<B>,
+{
+    let mut ctx = Context::from_waker(noop_waker_ref());
+    match Pin::new(component.handle_mut()).poll(&mut ctx) {
+        Poll::Pending => None,
+        Poll::Ready(mut result) => Some(result.get(builder).cloned()),
+    }
+}
+
+#[cfg(feature = "hecs")]
+pub mod hecs {
+    use {
+        super::{poll_component, HandleComponent, LoadFailed},
+        crate::asset::AssetBuild,
+    };
+
+    /// Resolves handles of `C` components in the world.
+    /// Loaded assets are built with `builder` and replace handle components,
+    /// failures replace them with [`LoadFailed`].
+    ///
+    /// Returns number of resolved handles.
+    pub fn resolve<C, B>(world: &mut ::hecs::World, builder: &mut B) -> usize
+    where
+        C: HandleComponent,
+        C::Asset: AssetBuild<B>,
+    {
+        let mut resolved = Vec::new();
+        for (entity, component) in world.query_mut::<&mut C>() {
+            let uuid = *component.handle_mut().uuid();
+            if let Some(result) = poll_component(component, builder) {
+                resolved.push((entity, uuid, result));
+            }
+        }
+
+        let count = resolved.len();
+        for (entity, uuid, result) in resolved {
+            let _ = world.remove_one::<C>(entity);
+            let _ = match result {
+                Ok(asset) => world.insert_one(entity, asset),
+                Err(error) => world.insert_one(entity, LoadFailed::<C::Asset>::new(uuid, error)),
+            };
+        }
+        count
+    }
+}
+
+#[cfg(feature = "edict")]
+pub mod edict {
+    use {
+        super::{poll_component, HandleComponent, LoadFailed},
+        crate::asset::AssetBuild,
+        ::edict::{query::Entities, world::World},
+    };
+
+    /// Resolves handles of `C` components in the world.
+    /// Loaded assets are built with `builder` and replace handle components,
+    /// failures replace them with [`LoadFailed`].
+    /// Assets and failures are inserted as external components.
+    ///
+    /// Returns number of resolved handles.
+    pub fn resolve<C, B>(world: &mut World, builder: &mut B) -> usize
+    where
+        C: HandleComponent,
+        C::Asset: AssetBuild<B>,
+    {
+        let mut resolved = Vec::new();
+        for (entity, component) in world.query_mut::<(Entities, &mut C)>().iter_mut() {
+            let uuid = *component.handle_mut().uuid();
+            if let Some(result) = poll_component(component, builder) {
+                resolved.push((entity, uuid, result));
+            }
+        }
+
+        if !resolved.is_empty() {
+            world.ensure_external_registered::<C::Asset>();
+            world.ensure_external_registered::<LoadFailed<C::Asset>>();
+        }
+
+        let count = resolved.len();
+        for (entity, uuid, result) in resolved {
+            let _ = world.drop::<C>(entity);
+            let _ = match result {
+                Ok(asset) => world.insert_external(entity, asset),
+                Err(error) => {
+                    world.insert_external(entity, LoadFailed::<C::Asset>::new(uuid, error))
+                }
+            };
+        }
+        count
+    }
+}
@@ -71,6 +71,78 @@ where
 
 pub struct ExternAssetFut<A>(AssetHandle<A>);
 
+/// External asset that may be absent in all sources,
+/// e.g. optional high-resolution texture or platform-specific variant.
+/// Built into `None` instead of failing container if asset is not found.
+///
+/// Used for `#[optional_external]` fields of `Option<A>` type.
+/// Asset that is found but fails to load still fails the container.
+#[derive(Clone)]
+pub struct OptionalExternal<A>(pub Option<A>);
+
+impl<A> From<OptionalExternal<A>> for Option<A> {
+    fn from(optional: OptionalExternal<A>) -> Self {
+        optional.0
+    }
+}
+
+impl<A> AssetField<External> for OptionalExternal<A>
+where
+    A: Asset,
+{
+    type Info = Option<Uuid>;
+    type DecodeError = Infallible;
+    type BuildError = loader::Error;
+    type Decoded = Option<(Uuid, AssetResult<A>)>;
+    type Fut = OptionalExternAssetFut<A>;
+
+    fn decode(uuid: Option<Uuid>, loader: &Loader) -> Self::Fut {
+        OptionalExternAssetFut(uuid.map(|uuid| loader.load(&uuid)))
+    }
+}
+
+impl<A, B> AssetFieldBuild<External, B> for OptionalExternal<A>
+where
+    A: Asset + AssetBuild<B>,
+{
+    fn build(
+        decoded: Option<(Uuid, AssetResult<A>)>,
+        builder: &mut B,
+    ) -> Result<Self, loader::Error> {
+        match decoded {
+            None => Ok(OptionalExternal(None)),
+            Some((uuid, mut result)) => match result.get_optional(builder)? {
+                Some(asset) => Ok(OptionalExternal(Some(asset.clone()))),
+                None => {
+                    tracing::warn!("Optional asset `{}` is not found", uuid);
+                    Ok(OptionalExternal(None))
+                }
+            },
+        }
+    }
+}
+
+pub struct OptionalExternAssetFut<A>(Option<AssetHandle<A>>);
+
+impl<A> Future for OptionalExternAssetFut<A>
+where
+    A: Asset,
+{
+    type Output = Result<Option<(Uuid, AssetResult<A>)>, Infallible>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &mut self.get_mut().0 {
+            None => Poll::Ready(Ok(None)),
+            Some(handle) => {
+                let uuid = *handle.uuid();
+                Pin::new(handle)
+                    .poll(cx)
+                    .map(|result| Ok(Some((uuid, result))))
+            }
+        }
+    }
+}
+
 impl<A> Future for ExternAssetFut<A>
 where
     A: Asset,
@@ -0,0 +1,111 @@
+use {
+    crate::{
+        asset::Asset,
+        loader::{AssetHandle, Loader},
+    },
+    serde::{
+        de::{DeserializeSeed, Deserializer, Error as _},
+        Deserialize, Serialize, Serializer,
+    },
+    std::{cell::RefCell, marker::PhantomData},
+    uuid::Uuid,
+};
+
+thread_local! {
+    static CURRENT_LOADER: RefCell<Option<Loader>> = const { RefCell::new(None) };
+}
+
+/// Restores previous current loader on drop.
+struct Restore(Option<Loader>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT_LOADER.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+impl Loader {
+    /// Runs provided function with this loader used to deserialize [`AssetHandle`]s
+    /// with their `Deserialize` implementation.
+    ///
+    /// Calls may be nested, innermost loader is used.
+    pub fn deserialize_with<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = CURRENT_LOADER.with(|current| current.borrow_mut().replace(self.clone()));
+        let _restore = Restore(previous);
+        f()
+    }
+}
+
+/// Handle is serialized as uuid of the asset.
+impl<A> Serialize for AssetHandle<A> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.uuid().serialize(serializer)
+    }
+}
+
+/// Handle is deserialized from uuid of the asset
+/// and loaded with loader provided by [`Loader::deserialize_with`].
+/// Deserialization fails if there is no such loader.
+///
+/// Use [`LoaderSeed`] to provide loader explicitly.
+impl<'de, A> Deserialize<'de> for AssetHandle<A>
+where
+    A: Asset,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let uuid = Uuid::deserialize(deserializer)?;
+
+        CURRENT_LOADER.with(|current| match &*current.borrow() {
+            Some(loader) => Ok(loader.load(&uuid)),
+            None => Err(D::Error::custom(
+                "No `Loader` provided to deserialize `AssetHandle`",
+            )),
+        })
+    }
+}
+
+/// Deserializes [`AssetHandle`] from uuid of the asset,
+/// loading it with specified [`Loader`].
+pub struct LoaderSeed<'a, A> {
+    loader: &'a Loader,
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<'a, A> LoaderSeed<'a, A> {
+    pub fn new(loader: &'a Loader) -> Self {
+        LoaderSeed {
+            loader,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<A> Clone for LoaderSeed<'_, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for LoaderSeed<'_, A> {}
+
+impl<'de, A> DeserializeSeed<'de> for LoaderSeed<'_, A>
+where
+    A: Asset,
+{
+    type Value = AssetHandle<A>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<AssetHandle<A>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let uuid = Uuid::deserialize(deserializer)?;
+        Ok(self.loader.load(&uuid))
+    }
+}
@@ -0,0 +1,205 @@
+use {
+    crate::{
+        loader::{BuildBudget, Error},
+        stats::{LoadStage, Watchdog},
+    },
+    hashbrown::HashMap,
+    parking_lot::Mutex,
+    std::{collections::HashSet, sync::Arc, time::Duration},
+    uuid::Uuid,
+};
+
+/// Instrumentation hook registered with [`LoaderBuilder::add_hook`].
+/// Allows engines to collect telemetry or log asset loading.
+///
+/// Methods are called from loading tasks and from the thread that builds assets,
+/// and so should return quickly.
+/// `asset` is the type name of the asset being loaded.
+///
+/// [`LoaderBuilder::add_hook`]: crate::LoaderBuilder::add_hook
+pub trait LoaderHook: Send + Sync + 'static {
+    /// Called when asset is requested and not found in the cache.
+    fn on_load_start(&self, uuid: &Uuid, asset: &'static str) {
+        let _ = (uuid, asset);
+    }
+
+    /// Called when asset data is fetched from a source.
+    fn on_bytes_fetched(&self, uuid: &Uuid, asset: &'static str, len: usize) {
+        let _ = (uuid, asset, len);
+    }
+
+    /// Called when asset is decoded.
+    fn on_decoded(&self, uuid: &Uuid, asset: &'static str) {
+        let _ = (uuid, asset);
+    }
+
+    /// Called when asset is built.
+    fn on_built(&self, uuid: &Uuid, asset: &'static str) {
+        let _ = (uuid, asset);
+    }
+
+    /// Called when asset fails to load, decode or build,
+    /// or when its data is not found.
+    fn on_failed(&self, uuid: &Uuid, asset: &'static str, error: &Error) {
+        let _ = (uuid, asset, error);
+    }
+}
+
+/// Hooks registered with the loader, along with watchdog of slow executions.
+#[derive(Clone)]
+pub(crate) struct Hooks {
+    hooks: Arc<[Box<dyn LoaderHook>]>,
+    watchdog: Arc<Watchdog>,
+}
+
+impl Hooks {
+    pub(crate) fn new(hooks: Vec<Box<dyn LoaderHook>>, watchdog: Watchdog) -> Self {
+        Hooks {
+            hooks: hooks.into(),
+            watchdog: Arc::new(watchdog),
+        }
+    }
+
+    pub(crate) fn watchdog(&self) -> &Watchdog {
+        &self.watchdog
+    }
+
+    /// Reports execution of loading stage to the watchdog.
+    pub(crate) fn on_executed(
+        &self,
+        uuid: &Uuid,
+        asset: &'static str,
+        stage: LoadStage,
+        elapsed: Duration,
+    ) {
+        self.watchdog.watch(uuid, asset, stage, elapsed);
+    }
+}
+
+impl LoaderHook for Hooks {
+    fn on_load_start(&self, uuid: &Uuid, asset: &'static str) {
+        for hook in self.hooks.iter() {
+            hook.on_load_start(uuid, asset);
+        }
+    }
+
+    fn on_bytes_fetched(&self, uuid: &Uuid, asset: &'static str, len: usize) {
+        for hook in self.hooks.iter() {
+            hook.on_bytes_fetched(uuid, asset, len);
+        }
+    }
+
+    fn on_decoded(&self, uuid: &Uuid, asset: &'static str) {
+        for hook in self.hooks.iter() {
+            hook.on_decoded(uuid, asset);
+        }
+    }
+
+    fn on_built(&self, uuid: &Uuid, asset: &'static str) {
+        for hook in self.hooks.iter() {
+            hook.on_built(uuid, asset);
+        }
+    }
+
+    fn on_failed(&self, uuid: &Uuid, asset: &'static str, error: &Error) {
+        for hook in self.hooks.iter() {
+            hook.on_failed(uuid, asset, error);
+        }
+    }
+}
+
+/// Loads finished since previous [`Loader::maintain`] call.
+///
+/// [`Loader::maintain`]: crate::Loader::maintain
+#[derive(Default)]
+pub(crate) struct FinishedLoads {
+    pub(crate) completed: Vec<Uuid>,
+    pub(crate) failed: Vec<(Uuid, Error)>,
+}
+
+/// Hook recording finished loads for [`Loader::maintain`] report
+/// and assets not found for [`Loader::missing_assets`].
+///
+/// Decoded assets wait in `pending` until [`BuildBudget`] of their type allows reporting them.
+///
+/// [`Loader::maintain`]: crate::Loader::maintain
+/// [`Loader::missing_assets`]: crate::Loader::missing_assets
+#[derive(Clone, Default)]
+pub(crate) struct LoadRecorder {
+    budgets: Arc<HashMap<&'static str, BuildBudget>>,
+    pending: Arc<Mutex<Vec<(Uuid, &'static str)>>>,
+    failed: Arc<Mutex<Vec<(Uuid, Error)>>>,
+    missing: Arc<Mutex<HashSet<Uuid>>>,
+}
+
+impl LoadRecorder {
+    pub(crate) fn new(budgets: HashMap<&'static str, BuildBudget>) -> Self {
+        LoadRecorder {
+            budgets: Arc::new(budgets),
+            ..LoadRecorder::default()
+        }
+    }
+
+    /// Takes failed loads and decoded assets within budgets, higher class first.
+    /// Assets over budget stay pending in order of decoding.
+    pub(crate) fn take(&self) -> FinishedLoads {
+        let failed = std::mem::take(&mut *self.failed.lock());
+
+        let mut pending = self.pending.lock();
+        let mut order: Vec<_> = (0..pending.len()).collect();
+        order.sort_by_key(|&index| self.budget(pending[index].1).class());
+
+        let mut reported = HashMap::new();
+        let mut taken = vec![false; pending.len()];
+        for index in order {
+            let asset = pending[index].1;
+            let count = reported.entry(asset).or_insert(0);
+            if self
+                .budget(asset)
+                .max_per_frame()
+                .is_none_or(|max| *count < max)
+            {
+                *count += 1;
+                taken[index] = true;
+            }
+        }
+
+        let mut completed = Vec::new();
+        let mut taken = taken.into_iter();
+        pending.retain(|&(uuid, asset)| {
+            if taken.next() == Some(true) {
+                completed.push((uuid, self.budget(asset).class()));
+                false
+            } else {
+                true
+            }
+        });
+        completed.sort_by_key(|&(_, class)| class);
+
+        FinishedLoads {
+            completed: completed.into_iter().map(|(uuid, _)| uuid).collect(),
+            failed,
+        }
+    }
+
+    pub(crate) fn missing(&self) -> Vec<Uuid> {
+        self.missing.lock().iter().copied().collect()
+    }
+
+    fn budget(&self, asset: &'static str) -> BuildBudget {
+        self.budgets.get(asset).copied().unwrap_or_default()
+    }
+}
+
+impl LoaderHook for LoadRecorder {
+    fn on_decoded(&self, uuid: &Uuid, asset: &'static str) {
+        self.pending.lock().push((*uuid, asset));
+    }
+
+    fn on_failed(&self, uuid: &Uuid, _asset: &'static str, error: &Error) {
+        if error.is_not_found() {
+            self.missing.lock().insert(*uuid);
+        }
+        self.failed.lock().push((*uuid, error.clone()));
+    }
+}
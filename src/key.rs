@@ -1,36 +1,39 @@
 use {
-    super::asset::Asset,
     std::{
-        any::TypeId,
         hash::{Hash, Hasher},
+        sync::Arc,
     },
     uuid::Uuid,
 };
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Key {
-    type_id: TypeId,
     uuid: Uuid,
+    locale: Option<Arc<str>>,
 }
 
 impl Key {
-    pub fn new<A: Asset>(uuid: Uuid) -> Self {
-        Key {
-            type_id: TypeId::of::<A>(),
-            uuid,
-        }
+    pub fn new(uuid: Uuid, locale: Option<Arc<str>>) -> Self {
+        Key { uuid, locale }
     }
 
-    pub fn eq_key<A: Asset>(&self, uuid: &Uuid) -> bool {
-        self.type_id == TypeId::of::<A>() && self.uuid == *uuid
+    pub fn uuid(&self) -> &Uuid {
+        &self.uuid
+    }
+
+    pub fn locale(&self) -> Option<&Arc<str>> {
+        self.locale.as_ref()
+    }
+
+    pub fn eq_key(&self, uuid: &Uuid, locale: Option<&str>) -> bool {
+        self.uuid == *uuid && self.locale.as_deref() == locale
     }
 }
 
-pub fn hash_key<A, H>(uuid: &Uuid, state: &mut H)
+pub fn hash_key<H>(uuid: &Uuid, locale: Option<&str>, state: &mut H)
 where
-    A: Asset,
     H: Hasher,
 {
-    TypeId::of::<A>().hash(state);
     uuid.hash(state);
+    locale.hash(state);
 }
@@ -0,0 +1,119 @@
+use {
+    hashbrown::HashMap,
+    parking_lot::Mutex,
+    std::{
+        backtrace::Backtrace,
+        fmt::{self, Display},
+        panic::Location,
+        sync::Arc,
+    },
+    uuid::Uuid,
+};
+
+/// Place where asset handle was created.
+#[derive(Clone, Debug)]
+pub struct HandleSite {
+    /// Location of the `Loader::load*` call.
+    pub location: &'static Location<'static>,
+
+    /// Backtrace of the call.
+    /// Captured only if enabled with `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`.
+    pub backtrace: Arc<Backtrace>,
+}
+
+impl Display for HandleSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.location, f)
+    }
+}
+
+/// Asset kept alive by handles, as reported by [`Loader::report_leaks`].
+///
+/// [`Loader::report_leaks`]: crate::Loader::report_leaks
+#[derive(Clone, Debug)]
+pub struct LeakedAsset {
+    pub uuid: Uuid,
+    pub locale: Option<Arc<str>>,
+    pub type_name: &'static str,
+
+    /// Creation sites of handles still referencing the asset.
+    pub sites: Vec<HandleSite>,
+}
+
+impl Display for LeakedAsset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Asset `{}` ({})", self.uuid, self.type_name)?;
+        if let Some(locale) = &self.locale {
+            write!(f, " for locale `{}`", locale)?;
+        }
+        f.write_str(" is referenced by handles created at:")?;
+        for site in &self.sites {
+            write!(f, "\n  {}", site)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct SitesInner {
+    next: u64,
+    live: HashMap<u64, HandleSite>,
+}
+
+/// Creation sites of live handles to one cached asset.
+#[derive(Clone, Default)]
+pub(crate) struct Sites {
+    inner: Arc<Mutex<SitesInner>>,
+}
+
+impl Sites {
+    /// Registers handle created at caller location.
+    #[track_caller]
+    pub fn register(&self) -> SiteGuard {
+        let site = HandleSite {
+            location: Location::caller(),
+            backtrace: Arc::new(Backtrace::capture()),
+        };
+        self.insert(site)
+    }
+
+    /// Returns sites of live handles, oldest first.
+    pub fn collect(&self) -> Vec<HandleSite> {
+        let inner = self.inner.lock();
+        let mut sites: Vec<_> = inner.live.iter().collect();
+        sites.sort_by_key(|(id, _)| **id);
+        sites.into_iter().map(|(_, site)| site.clone()).collect()
+    }
+
+    fn insert(&self, site: HandleSite) -> SiteGuard {
+        let mut inner = self.inner.lock();
+        let id = inner.next;
+        inner.next += 1;
+        inner.live.insert(id, site);
+
+        SiteGuard {
+            sites: self.clone(),
+            id,
+        }
+    }
+}
+
+/// Keeps creation site of a handle registered while handle is alive.
+/// Clones share creation site of the original.
+pub(crate) struct SiteGuard {
+    sites: Sites,
+    id: u64,
+}
+
+impl Clone for SiteGuard {
+    fn clone(&self) -> Self {
+        let site = self.sites.inner.lock().live[&self.id].clone();
+        self.sites.insert(site)
+    }
+}
+
+impl Drop for SiteGuard {
+    fn drop(&mut self) {
+        self.sites.inner.lock().live.remove(&self.id);
+    }
+}
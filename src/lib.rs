@@ -1,25 +1,176 @@
 //! Asset loader.
 
 mod asset;
+mod config;
+mod decode;
+pub mod ecs;
 mod field;
+mod handle_serde;
+mod hook;
 mod key;
+#[cfg(feature = "leak-detector")]
+mod leak;
 mod loader;
 pub mod source;
+mod spawn;
+mod stats;
+mod streaming;
 
 pub use {
     self::{
         asset::{Asset, AssetBuild},
-        field::{AssetField, AssetFieldBuild, Container, External},
-        loader::{AssetHandle, AssetResult, AssetResultPoisoned, Error, Loader, LoaderBuilder},
+        config::{ConfigAsset, ConfigDecodeError, CONFIG_FORMAT},
+        decode::{DecodePolicy, Migration},
+        field::{AssetField, AssetFieldBuild, Container, External, OptionalExternal},
+        handle_serde::LoaderSeed,
+        hook::LoaderHook,
+        loader::{
+            AnyAssetHandle, AnyAssetResult, AssetHandle, AssetResult, AssetResultPoisoned,
+            BuildBudget, BuildClass, CollectionHandle, Error, FetchLimits, Loader, LoaderBuilder,
+            MaintainReport, SourceId, UnloadPolicy,
+        },
+        spawn::{PollingSpawner, Spawner, TokioSpawner},
+        stats::{LoadStage, LoaderStats, SlowExecution},
+        streaming::StreamingHint,
     },
     goods_proc::{Asset, AssetField},
+    goods_treasury::{Clock, ManualClock, SystemClock, VerifyingKey},
     uuid::Uuid,
 };
 
+#[cfg(feature = "async-std")]
+pub use self::spawn::AsyncStdSpawner;
+
+#[cfg(feature = "leak-detector")]
+pub use self::leak::{HandleSite, LeakedAsset};
+
 // Used by generated code.
 #[doc(hidden)]
-pub use {bincode, serde, serde_json, std::convert::Infallible, thiserror};
+pub use {bincode, bytes, serde, serde_json, std::convert::Infallible, thiserror};
 
 #[derive(Debug, thiserror::Error)]
 #[error("Not found")]
 struct NotFound;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Asset is being loaded again after failure")]
+struct Retrying;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Source did not report native format of the asset")]
+struct UnknownNativeFormat;
+
+#[derive(Debug, thiserror::Error)]
+#[error("No asset type registered for native format '{format}'")]
+struct UnknownFormat {
+    format: Box<str>,
+}
+
+/// Error of assets from sources added with [`LoaderBuilder::add_signed`]
+/// that are not signed or whose signature does not match.
+/// Check with [`Error::is_signature_invalid`].
+#[derive(Debug, thiserror::Error)]
+#[error("Signature of asset `{uuid}` is missing or invalid")]
+pub struct SignatureInvalid {
+    pub uuid: Uuid,
+}
+
+/// Error of loading asset as a type different from the type
+/// it is already cached as.
+/// Check with [`Error::is_type_mismatch`].
+#[derive(Debug, thiserror::Error)]
+#[error("Asset `{uuid}` is requested as `{requested}` but cached as `{cached}`")]
+pub struct TypeMismatch {
+    pub uuid: Uuid,
+    pub requested: &'static str,
+    pub cached: &'static str,
+}
+
+/// Error of assets from sources of a project different from the one
+/// set with [`LoaderBuilder::set_namespace`].
+/// Check with [`Error::is_namespace_mismatch`].
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Asset `{uuid}` belongs to project `{found}`, but loader accepts only project `{expected}`"
+)]
+pub struct NamespaceMismatch {
+    pub uuid: Uuid,
+    pub expected: Uuid,
+    pub found: Uuid,
+}
+
+/// Error of assets with data of schema version that asset type
+/// neither decodes nor has migration registered for.
+/// Check with [`Error::is_schema_mismatch`].
+#[derive(Debug, thiserror::Error)]
+#[error("Asset `{uuid}` has data of schema {found}, but `{type_name}` decodes schema {expected}")]
+pub struct SchemaMismatch {
+    pub uuid: Uuid,
+    pub type_name: &'static str,
+    pub expected: u32,
+    pub found: u32,
+}
+
+/// Error of assets that depend on themselves through assets they reference,
+/// e.g. container `A` referencing `B` which references `A`.
+/// Check with [`Error::is_cyclic_dependency`].
+#[derive(Debug, thiserror::Error)]
+#[error("Asset `{}` depends on itself through {}", path[0], display_path(path))]
+pub struct CyclicDependency {
+    /// Uuids of assets in the cycle, starting and ending with the same asset.
+    pub path: Vec<Uuid>,
+}
+
+fn display_path(path: &[Uuid]) -> String {
+    let path: Vec<_> = path.iter().map(Uuid::to_string).collect();
+    path.join(" -> ")
+}
+
+/// Error of assets whose decoding or building panicked.
+/// Handles of such assets resolve to this error instead of staying pending.
+/// Check with [`Error::is_build_panicked`].
+#[derive(Debug, thiserror::Error)]
+#[error("Building asset `{uuid}` panicked: {message}")]
+pub struct BuildPanicked {
+    pub uuid: Uuid,
+    pub message: Box<str>,
+}
+
+impl BuildPanicked {
+    fn new(uuid: Uuid, payload: Box<dyn std::any::Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => (*message).into(),
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => (*message).into(),
+                Err(_) => "Box<dyn Any>".into(),
+            },
+        };
+
+        tracing::error!("Building asset `{}` panicked: {}", uuid, message);
+        BuildPanicked { uuid, message }
+    }
+}
+
+/// Error of assets with human-readable name reported by their source,
+/// wrapping the error asset failed with.
+/// Check with [`Error::asset_name`].
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to load '{name}' (uuid {uuid})")]
+pub struct LoadFailed {
+    pub uuid: Uuid,
+    pub name: Box<str>,
+    pub source: Error,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Asset is not of type `{expected}`")]
+struct DowncastMismatch {
+    expected: &'static str,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Asset of native format '{format}' must be built with `{expected}`")]
+struct BuilderMismatch {
+    format: &'static str,
+    expected: &'static str,
+}
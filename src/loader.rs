@@ -1,38 +1,80 @@
 use {
     crate::{
         asset::{Asset, AssetBuild},
+        decode::{migrate_fn, DecodePolicies, DecodePolicy, Decoders, Migration},
+        hook::{Hooks, LoadRecorder, LoaderHook},
         key::{hash_key, Key},
         source::{AssetData, Source},
-        NotFound,
+        spawn::{block_on, spawn, Spawner, TokioSpawner},
+        stats::{LoadStage, LoaderStats, Watchdog},
+        streaming::{FetchQueue, Priority, StreamingHint},
+        BuildPanicked, BuilderMismatch, CyclicDependency, DowncastMismatch, LoadFailed,
+        NamespaceMismatch, NotFound, Retrying, SchemaMismatch, SignatureInvalid, TypeMismatch,
+        UnknownFormat, UnknownNativeFormat,
     },
     ahash::RandomState,
-    futures::future::{BoxFuture, TryFutureExt as _},
-    hashbrown::hash_map::{HashMap, RawEntryMut},
+    bytes::Bytes,
+    futures::{
+        channel::oneshot,
+        future::{BoxFuture, FutureExt as _, TryFutureExt as _},
+    },
+    futures_timer::Delay,
+    goods_treasury::Recording,
+    goods_treasury::{verify_signature, Clock, SystemClock, VerifyingKey},
+    hashbrown::{
+        hash_map::{HashMap, RawEntryMut},
+        HashSet,
+    },
     parking_lot::Mutex,
     std::{
-        any::Any,
+        any::{type_name, Any, TypeId},
         fmt::{self, Debug, Display},
         future::Future,
         hash::{BuildHasher, Hasher},
+        marker::PhantomData,
+        panic::{catch_unwind, AssertUnwindSafe},
+        path::Path,
         pin::Pin,
         sync::Arc,
         task::{Context, Poll, Waker},
+        time::{Duration, Instant, SystemTime},
     },
     tracing::Instrument,
     uuid::Uuid,
 };
 
 macro_rules! assets_inner {
-    ($sources:ident, $random_state:ident, $count:tt) => {{
+    ($sources:ident, $overrides:ident, $session:ident, $formats:ident, $unload:ident, $decoders:ident, $hooks:ident, $loads:ident, $retry_interval:ident, $spawner:ident, $random_state:ident, $count:tt) => {{
         {
             let sources = $sources;
+            let overrides = $overrides;
+            let session = $session;
+            let formats = $formats;
+            let unload = $unload;
+            let decoders = $decoders;
+            let hooks = $hooks;
+            let loads = $loads;
+            let retry_interval = $retry_interval;
+            let spawner = $spawner;
             let random_state = $random_state;
+            // Shards must hash keys as `Loader::shard` does,
+            // otherwise precomputed hashes miss entries once shard is resized.
             let shards: Vec<_> = (0..$count * 4)
-                .map(|_| Arc::new(Mutex::new(HashMap::new())))
+                .map(|_| Arc::new(Mutex::new(HashMap::with_hasher(random_state.clone()))))
                 .collect();
 
             let shards: Arc<Inner<[Shard]>> = Arc::new(Inner {
                 sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                pinned: Mutex::new(HashSet::new()),
                 random_state,
                 cache: std::convert::TryInto::<[Shard; $count * 4]>::try_into(shards)
                     .unwrap_or_else(|_| panic!()),
@@ -45,6 +87,13 @@ macro_rules! assets_inner {
 /// This is default number of shards per CPU for shared hash map of asset states.
 const DEFAULT_SHARDS_PER_CPU: usize = 8;
 
+/// This is default duration of decode or build execution reported as slow.
+/// Half of a frame at 60 FPS.
+const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_millis(8);
+
+/// This is default number of [`Loader::maintain`] calls between checks for newer data of failed assets.
+const DEFAULT_RETRY_INTERVAL: u32 = 60;
+
 #[derive(Clone)]
 #[repr(transparent)]
 pub struct Error(Arc<dyn std::error::Error + Send + Sync>);
@@ -56,6 +105,82 @@ impl Error {
     {
         Error(Arc::new(error))
     }
+
+    /// Wraps error of the asset with its name, if known.
+    fn named(self, uuid: Uuid, name: Option<&str>) -> Self {
+        match name {
+            None => self,
+            Some(name) => Error::new(LoadFailed {
+                uuid,
+                name: name.into(),
+                source: self,
+            }),
+        }
+    }
+
+    /// Returns error the asset failed with, looking through its name.
+    fn kind(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        match self.0.downcast_ref::<LoadFailed>() {
+            Some(failed) => failed.source.kind(),
+            None => &*self.0,
+        }
+    }
+
+    /// Returns human-readable name of the asset reported by its source, if any.
+    pub fn asset_name(&self) -> Option<&str> {
+        self.0
+            .downcast_ref::<LoadFailed>()
+            .map(|failed| &*failed.name)
+    }
+
+    /// Returns `true` if no source has the asset.
+    pub fn is_not_found(&self) -> bool {
+        self.kind().is::<NotFound>()
+    }
+
+    /// Returns `true` if asset was rejected because its signature is missing or invalid.
+    pub fn is_signature_invalid(&self) -> bool {
+        self.kind().is::<SignatureInvalid>()
+    }
+
+    /// Returns `true` if asset was requested as a type
+    /// different from the type it is cached as.
+    pub fn is_type_mismatch(&self) -> bool {
+        self.kind().is::<TypeMismatch>()
+    }
+
+    /// Returns `true` if asset belongs to a project different from
+    /// the one set with [`LoaderBuilder::set_namespace`].
+    pub fn is_namespace_mismatch(&self) -> bool {
+        self.kind().is::<NamespaceMismatch>()
+    }
+
+    /// Returns `true` if asset data is of schema version
+    /// asset type neither decodes nor has [`Migration`] registered for.
+    pub fn is_schema_mismatch(&self) -> bool {
+        self.kind().is::<SchemaMismatch>()
+    }
+
+    /// Returns `true` if asset depends on itself through assets it references.
+    /// Looks through errors of dependencies the cycle is reported by.
+    pub fn is_cyclic_dependency(&self) -> bool {
+        let mut error: Option<&(dyn std::error::Error + 'static)> = Some(self.kind());
+        while let Some(err) = error {
+            if err.is::<CyclicDependency>() {
+                return true;
+            }
+            error = match err.downcast_ref::<Error>() {
+                Some(nested) => Some(&*nested.0),
+                None => err.source(),
+            };
+        }
+        false
+    }
+
+    /// Returns `true` if decoding or building the asset panicked.
+    pub fn is_build_panicked(&self) -> bool {
+        self.kind().is::<BuildPanicked>()
+    }
 }
 
 impl Debug for Error {
@@ -77,336 +202,1875 @@ impl std::error::Error for Error {
 }
 
 trait AnySource: Send + Sync + 'static {
-    fn load(&self, uuid: &Uuid) -> BoxFuture<Result<Option<AssetData>, Error>>;
-    fn update(&self, uuid: &Uuid, version: u64) -> BoxFuture<Result<Option<AssetData>, Error>>;
+    fn load(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        priority: &Priority,
+    ) -> BoxFuture<Result<Option<AssetData>, Error>>;
+
+    fn update(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        version: u64,
+        priority: &Priority,
+    ) -> BoxFuture<Result<Option<AssetData>, Error>>;
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Error>>;
+
+    fn namespace(&self) -> Option<Uuid>;
 }
 
 impl<S> AnySource for S
 where
     S: Source,
 {
-    fn load(&self, uuid: &Uuid) -> BoxFuture<Result<Option<AssetData>, Error>> {
-        let fut = Source::load(self, uuid);
+    fn load(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        priority: &Priority,
+    ) -> BoxFuture<Result<Option<AssetData>, Error>> {
+        let _ = priority;
+        let fut = Source::load(self, uuid, locale);
         Box::pin(fut.map_err(Error::new))
     }
 
-    fn update(&self, uuid: &Uuid, version: u64) -> BoxFuture<Result<Option<AssetData>, Error>> {
-        let fut = Source::update(self, uuid, version);
+    fn update(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        version: u64,
+        priority: &Priority,
+    ) -> BoxFuture<Result<Option<AssetData>, Error>> {
+        let _ = priority;
+        let fut = Source::update(self, uuid, locale, version);
         Box::pin(fut.map_err(Error::new))
     }
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Error>> {
+        Box::pin(Source::collection(self, name).map_err(Error::new))
+    }
+
+    fn namespace(&self) -> Option<Uuid> {
+        Source::namespace(self)
+    }
 }
 
-struct Data {
-    bytes: Box<[u8]>,
-    version: u64,
-    source: usize,
+/// Source which asset data must be signed with key.
+struct Signed<S> {
+    source: S,
+    key: VerifyingKey,
 }
 
-async fn load_asset(sources: &[Box<dyn AnySource>], uuid: &Uuid) -> Result<Option<Data>, Error> {
-    for (index, source) in sources.iter().enumerate() {
-        if let Some(asset) = source.load(uuid).await? {
-            return Ok(Some(Data {
-                bytes: asset.bytes,
-                version: asset.version,
-                source: index,
-            }));
+impl<S> Signed<S> {
+    fn verify(
+        key: &VerifyingKey,
+        uuid: &Uuid,
+        data: Option<AssetData>,
+    ) -> Result<Option<AssetData>, Error> {
+        match data {
+            None => Ok(None),
+            Some(data) => {
+                let valid = data.signature.as_ref().is_some_and(|signature| {
+                    let format = data.format.as_deref().unwrap_or("");
+                    let locale = data.locale.as_deref();
+                    verify_signature(key, uuid, locale, format, &data.bytes, signature)
+                });
+
+                if valid {
+                    Ok(Some(data))
+                } else {
+                    tracing::error!("Signature of asset `{}` is missing or invalid", uuid);
+                    Err(Error::new(SignatureInvalid { uuid: *uuid }))
+                }
+            }
         }
     }
-    Ok(None)
 }
 
-/// Builder for [`Loader`].
-/// Allows configure asset loader with required [`Source`]s.
-pub struct LoaderBuilder {
-    num_shards: usize,
-    sources: Vec<Box<dyn AnySource>>,
-}
+impl<S> AnySource for Signed<S>
+where
+    S: Source,
+{
+    fn load(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        let _ = priority;
+        let fut = Source::load(&self.source, uuid, locale);
+        let key = self.key;
+        let uuid = *uuid;
+        Box::pin(async move { Self::verify(&key, &uuid, fut.await.map_err(Error::new)?) })
+    }
 
-impl Default for LoaderBuilder {
-    fn default() -> Self {
-        Self::new()
+    fn update(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        version: u64,
+        priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        let _ = priority;
+        let fut = Source::update(&self.source, uuid, locale, version);
+        let key = self.key;
+        let uuid = *uuid;
+        Box::pin(async move { Self::verify(&key, &uuid, fut.await.map_err(Error::new)?) })
     }
-}
 
-impl LoaderBuilder {
-    /// Returns new [`LoaderBuilder`] without asset sources.
-    pub fn new() -> Self {
-        let num_cpus = num_cpus::get();
-        let num_shards = DEFAULT_SHARDS_PER_CPU * num_cpus;
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Error>> {
+        // Members are verified when loaded.
+        Box::pin(Source::collection(&self.source, name).map_err(Error::new))
+    }
 
-        LoaderBuilder {
-            num_shards,
-            sources: Vec::new(),
-        }
+    fn namespace(&self) -> Option<Uuid> {
+        Source::namespace(&self.source)
     }
+}
 
-    /// Adds provided source to the loader.
-    pub fn add(&mut self, source: impl Source) -> &mut Self {
-        self.sources.push(Box::new(source));
-        self
+/// Limits of fetching asset data from a source
+/// added with [`LoaderBuilder::add_limited`].
+///
+/// Fetches exceeding the limits wait for their turn,
+/// so that background streaming doesn't saturate network or disk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FetchLimits {
+    max_concurrent: Option<usize>,
+    max_bytes_per_sec: Option<u64>,
+}
+
+impl FetchLimits {
+    /// Returns limits that do not limit anything.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Adds provided source to the loader.
-    pub fn with(mut self, source: impl Source) -> Self {
-        self.sources.push(Box::new(source));
+    /// Limits number of fetches running concurrently.
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = Some(max_concurrent.max(1));
         self
     }
 
-    /// Sets number of shards for the loader.
-    ///
-    /// Actual number of shards will be bumped to the next power of two
-    /// and limited to 512.
+    /// Limits average number of bytes fetched per second.
+    /// Budget is shared by all fetches from the source:
+    /// next fetch starts only after time required to fetch all previous data at this rate.
     ///
-    /// This is low-level optimization tweaking function.
-    /// Default value should be sufficient most use cases.
-    pub fn set_num_shards(&mut self, num_shards: usize) -> &mut Self {
-        self.num_shards = num_shards;
+    /// Size of data is known only once fetched, so the limit is approximate.
+    /// Fetches in flight together are charged when they finish
+    /// and may exceed the rate for a moment, by as much data as they fetch.
+    /// Combine with [`FetchLimits::with_max_concurrent`] to bound such bursts.
+    pub fn with_max_bytes_per_sec(mut self, max_bytes_per_sec: u64) -> Self {
+        self.max_bytes_per_sec = Some(max_bytes_per_sec.max(1));
         self
     }
+}
 
-    /// Sets number of shards for the loader.
-    ///
-    /// Actual number of shards will be bumped to the next power of two
-    /// and limited to 512.
-    ///
-    /// This is low-level optimization tweaking function.
-    /// Default value should be sufficient most use cases.
-    pub fn with_num_shards(mut self, num_shards: usize) -> Self {
-        self.num_shards = num_shards;
-        self
+/// Source which fetches are limited with [`FetchLimits`].
+struct Limited {
+    source: Box<dyn AnySource>,
+    concurrent: Option<FetchQueue>,
+    max_bytes_per_sec: Option<u64>,
+
+    /// Instant when next fetch may start.
+    next_fetch: Arc<Mutex<Instant>>,
+}
+
+impl Limited {
+    fn new(source: Box<dyn AnySource>, limits: FetchLimits) -> Self {
+        Limited {
+            source,
+            concurrent: limits.max_concurrent.map(FetchQueue::new),
+            max_bytes_per_sec: limits.max_bytes_per_sec,
+            next_fetch: Arc::new(Mutex::new(Instant::now())),
+        }
     }
 
-    /// Builds and returns new [`Loader`] instance.
-    pub fn build(self) -> Loader {
-        let random_state = RandomState::new();
-        let sources: Arc<[_]> = self.sources.into();
+    fn limit<'a>(
+        &self,
+        priority: &Priority,
+        fetch: impl FnOnce() -> BoxFuture<'a, Result<Option<AssetData>, Error>> + Send + 'a,
+    ) -> BoxFuture<'a, Result<Option<AssetData>, Error>> {
+        let acquire = self
+            .concurrent
+            .as_ref()
+            .map(|queue| queue.acquire(priority));
+        let max_bytes_per_sec = self.max_bytes_per_sec;
+        let next_fetch = self.next_fetch.clone();
 
-        let inner = match self.num_shards {
-            0..=1 => assets_inner!(sources, random_state, 1),
-            2..=2 => assets_inner!(sources, random_state, 2),
-            3..=4 => assets_inner!(sources, random_state, 4),
-            5..=8 => assets_inner!(sources, random_state, 8),
-            9..=16 => assets_inner!(sources, random_state, 16),
-            17..=32 => assets_inner!(sources, random_state, 32),
-            33..=64 => assets_inner!(sources, random_state, 64),
-            65..=128 => assets_inner!(sources, random_state, 128),
-            129..=256 => assets_inner!(sources, random_state, 256),
-            _ => assets_inner!(sources, random_state, 512),
-        };
+        Box::pin(async move {
+            let _permit = match acquire {
+                None => None,
+                Some(acquire) => Some(acquire.await),
+            };
+
+            let max_bytes_per_sec = match max_bytes_per_sec {
+                None => return fetch().await,
+                Some(max_bytes_per_sec) => max_bytes_per_sec,
+            };
+
+            // Fetches finished while waiting push next fetch further.
+            loop {
+                let start = *next_fetch.lock();
+                let now = Instant::now();
+                if start <= now {
+                    break;
+                }
+                Delay::new(start - now).await;
+            }
 
-        Loader { inner }
+            let data = fetch().await?;
+
+            if let Some(data) = &data {
+                let duration =
+                    Duration::from_secs_f64(data.bytes.len() as f64 / max_bytes_per_sec as f64);
+                let mut next_fetch = next_fetch.lock();
+                *next_fetch = (*next_fetch).max(Instant::now()) + duration;
+            }
+
+            Ok(data)
+        })
     }
 }
 
-type Shard = Arc<Mutex<HashMap<Key, AssetEntry, RandomState>>>;
+impl AnySource for Limited {
+    fn namespace(&self) -> Option<Uuid> {
+        self.source.namespace()
+    }
 
-/// Virtual storage for all available assets.
-#[derive(Clone)]
-pub struct Loader {
-    inner: Arc<Inner<[Shard]>>,
-}
+    fn load(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        let uuid = *uuid;
+        let locale = locale.map(Box::<str>::from);
+        let inner_priority = priority.clone();
+        self.limit(priority, move || {
+            self.source.load(&uuid, locale.as_deref(), &inner_priority)
+        })
+    }
 
-enum StateTyped<A: Asset> {
-    #[allow(dead_code)]
-    Asset {
-        asset: A,
+    fn update(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
         version: u64,
-        source: usize,
-    },
-    Decoded {
-        decoded: Option<A::Decoded>,
-        version: u64,
-        source: usize,
-    },
-}
+        priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        let uuid = *uuid;
+        let locale = locale.map(Box::<str>::from);
+        let inner_priority = priority.clone();
+        self.limit(priority, move || {
+            self.source
+                .update(&uuid, locale.as_deref(), version, &inner_priority)
+        })
+    }
 
-enum StateErased {
-    Unloaded,
-    Typed(Box<dyn Any + Send + Sync>),
-    Missing,
-    Error(Error),
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Error>> {
+        self.source.collection(name)
+    }
 }
 
-struct AssetEntry {
-    state: StateErased,
-    wakers: Vec<Waker>,
+/// Source of a project different from the loader's one.
+/// Assets found in it fail to load.
+struct Foreign {
+    source: Box<dyn AnySource>,
+    expected: Uuid,
+    found: Uuid,
 }
 
-enum AssetResultInner<A: Asset> {
-    Asset(A),
-    Error(Error),
-    Missing,
-    Decoded {
+impl Foreign {
+    fn reject<'a>(
+        &self,
         uuid: Uuid,
-        key_hash: u64,
-        shard: Arc<Mutex<HashMap<Key, AssetEntry>>>,
-    },
+        fetch: BoxFuture<'a, Result<Option<AssetData>, Error>>,
+    ) -> BoxFuture<'a, Result<Option<AssetData>, Error>> {
+        let expected = self.expected;
+        let found = self.found;
+        Box::pin(async move {
+            match fetch.await? {
+                None => Ok(None),
+                Some(_) => {
+                    tracing::error!("Asset `{}` belongs to project `{}`", uuid, found);
+                    Err(Error::new(NamespaceMismatch {
+                        uuid,
+                        expected,
+                        found,
+                    }))
+                }
+            }
+        })
+    }
 }
 
-#[derive(Debug)]
-pub struct AssetResultPoisoned;
+impl AnySource for Foreign {
+    fn load(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        self.reject(*uuid, self.source.load(uuid, locale, priority))
+    }
 
-impl Display for AssetResultPoisoned {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("`AssetResult` poisoned by panic")
+    fn update(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        version: u64,
+        priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        self.reject(*uuid, self.source.update(uuid, locale, version, priority))
     }
-}
 
-impl std::error::Error for AssetResultPoisoned {}
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Error>> {
+        // Members are rejected when loaded.
+        self.source.collection(name)
+    }
 
-#[repr(transparent)]
-pub struct AssetResult<A: Asset>(AssetResultInner<A>);
+    fn namespace(&self) -> Option<Uuid> {
+        Some(self.found)
+    }
+}
 
-impl<A> AssetResult<A>
-where
-    A: Asset,
-{
-    pub fn get_optional<B>(&mut self, builder: &mut B) -> Result<Option<&A>, Error>
-    where
-        A: AssetBuild<B>,
-    {
-        if let AssetResultInner::Decoded {
-            uuid,
-            key_hash,
-            shard,
-        } = &self.0
-        {
-            let mut locked_shard = shard.lock();
-            let entry = locked_shard
-                .raw_entry_mut()
-                .from_hash(*key_hash, |k| k.eq_key::<A>(uuid));
+/// Recording of data served by sources,
+/// `Some` between [`Loader::start_recording`] and [`Loader::stop_recording`].
+type SessionLog = Arc<Mutex<Option<Recording>>>;
 
-            match entry {
-                RawEntryMut::Vacant(_) => unreachable!(),
-                RawEntryMut::Occupied(mut entry) => match &mut entry.get_mut().state {
-                    StateErased::Typed(typed) => {
-                        let typed: &mut StateTyped<A> = typed.downcast_mut().unwrap();
+/// Source wrapper adding data it serves to the session recording.
+struct Recorded {
+    source: Box<dyn AnySource>,
+    session: SessionLog,
+}
 
-                        match typed {
-                            StateTyped::Decoded {
-                                decoded,
-                                version,
-                                source,
-                            } => match decoded.take() {
-                                Some(decoded) => match A::build(decoded, builder) {
-                                    Ok(asset) => {
-                                        *typed = StateTyped::Asset {
-                                            asset: asset.clone(),
-                                            version: *version,
-                                            source: *source,
-                                        };
-                                        drop(locked_shard);
-                                        self.0 = AssetResultInner::Asset(asset);
-                                    }
-                                    Err(err) => {
-                                        let err = Error::new(err);
-                                        entry.get_mut().state = StateErased::Error(err.clone());
-                                        drop(locked_shard);
-                                        self.0 = AssetResultInner::Error(err);
-                                    }
-                                },
-                                None => {
-                                    let err = Error::new(AssetResultPoisoned);
-                                    entry.get_mut().state = StateErased::Error(err.clone());
-                                    drop(locked_shard);
-                                    self.0 = AssetResultInner::Error(err);
-                                }
-                            },
-                            StateTyped::Asset { asset, .. } => {
-                                let asset = asset.clone();
-                                drop(locked_shard);
-                                self.0 = AssetResultInner::Asset(asset);
-                            }
-                        }
-                    }
-                    StateErased::Error(err) => {
-                        let err = err.clone();
-                        drop(locked_shard);
-                        self.0 = AssetResultInner::Error(err);
-                    }
-                    StateErased::Unloaded => unreachable!(),
-                    StateErased::Missing => unreachable!(),
-                },
+impl Recorded {
+    fn record<'a>(
+        &'a self,
+        uuid: Uuid,
+        locale: Option<Box<str>>,
+        fetch: BoxFuture<'a, Result<Option<AssetData>, Error>>,
+    ) -> BoxFuture<'a, Result<Option<AssetData>, Error>> {
+        Box::pin(async move {
+            let data = fetch.await?;
+            if let Some(data) = &data {
+                if let Some(recording) = &mut *self.session.lock() {
+                    recording.push(
+                        uuid,
+                        locale.as_deref(),
+                        data.version,
+                        data.format.as_deref(),
+                        data.schema,
+                        &data.bytes,
+                    );
+                }
             }
-        }
+            Ok(data)
+        })
+    }
+}
 
-        match &self.0 {
-            AssetResultInner::Missing => Ok(None),
-            AssetResultInner::Asset(asset) => Ok(Some(asset)),
-            AssetResultInner::Error(err) => Err(err.clone()),
-            AssetResultInner::Decoded { .. } => unreachable!(),
-        }
+impl AnySource for Recorded {
+    fn load(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        let fetch = self.source.load(uuid, locale, priority);
+        self.record(*uuid, locale.map(Into::into), fetch)
     }
 
-    pub fn get<B>(&mut self, builder: &mut B) -> Result<&A, Error>
-    where
-        A: AssetBuild<B>,
-    {
-        self.get_optional(builder)?
-            .ok_or_else(|| Error::new(NotFound))
+    fn update(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        version: u64,
+        priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        let fetch = self.source.update(uuid, locale, version, priority);
+        self.record(*uuid, locale.map(Into::into), fetch)
+    }
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Error>> {
+        self.source.collection(name)
+    }
+
+    fn namespace(&self) -> Option<Uuid> {
+        self.source.namespace()
     }
 }
 
-enum AssetHandleInner<A> {
-    Asset(A),
-    Error(Error),
-    Missing,
-    Pending {
-        uuid: Uuid,
-        key_hash: u64,
-        shard: Arc<Mutex<HashMap<Key, AssetEntry>>>,
-    },
+/// Identifier of a source of [`Loader`].
+///
+/// Sources added to [`LoaderBuilder`] are listed by [`Loader::sources`]
+/// in the order they were added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SourceId(u64);
+
+/// Id of [`Overrides`] in loader sources.
+const OVERRIDES_SOURCE: SourceId = SourceId(0);
+
+/// Namespace of uuids that assets of prefixed sources are qualified with.
+const PREFIX_NAMESPACE: Uuid = Uuid::from_u128(0x7c1e_3f5a_92d4_4b8e_a6c0_5d21_e9f8_3b47);
+
+/// Returns uuid mask of the source prefix.
+fn prefix_mask(prefix: &str) -> Uuid {
+    Uuid::new_v5(&PREFIX_NAMESPACE, prefix.as_bytes())
 }
 
-#[repr(transparent)]
-pub struct AssetHandle<A>(AssetHandleInner<A>);
+/// Masks or unmasks uuid.
+fn apply_mask(uuid: &Uuid, mask: &Uuid) -> Uuid {
+    let mut bytes = *uuid.as_bytes();
+    for (byte, mask) in bytes.iter_mut().zip(mask.as_bytes()) {
+        *byte ^= mask;
+    }
+    Uuid::from_bytes(bytes)
+}
 
-impl<A> Unpin for AssetHandle<A> {}
+#[derive(Clone)]
+struct SourceEntry {
+    id: SourceId,
+    source: Arc<dyn AnySource>,
 
-impl<A> Future for AssetHandle<A>
-where
-    A: Asset,
-{
-    type Output = AssetResult<A>;
+    /// Mask of qualified uuids of prefixed source.
+    mask: Option<Uuid>,
+}
 
-    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
-        let me = self.get_mut();
+/// Sources of the loader in priority order.
+///
+/// Loads iterate over a snapshot of the sources,
+/// so loads in flight complete against sources removed meanwhile.
+struct Sources {
+    entries: Mutex<(Arc<[SourceEntry]>, u64)>,
+    namespace: Option<Uuid>,
+    session: SessionLog,
+}
 
-        match &me.0 {
-            AssetHandleInner::Asset(asset) => {
-                Poll::Ready(AssetResult(AssetResultInner::Asset(asset.clone())))
-            }
-            AssetHandleInner::Error(err) => {
-                Poll::Ready(AssetResult(AssetResultInner::Error(err.clone())))
+impl Sources {
+    fn new(
+        overrides: Overrides,
+        added: Vec<(Box<dyn AnySource>, Option<Uuid>)>,
+        namespace: Option<Uuid>,
+        session: SessionLog,
+    ) -> Self {
+        let sources = Sources {
+            entries: Mutex::new((Arc::new([]), 0)),
+            namespace,
+            session,
+        };
+
+        // Overrides are added first to get `OVERRIDES_SOURCE` id.
+        sources.add(Box::new(overrides), None);
+        for (source, mask) in added {
+            sources.add(source, mask);
+        }
+        sources
+    }
+
+    /// Wraps source to reject assets of other projects and record served data.
+    fn wrap(&self, source: Box<dyn AnySource>) -> Arc<dyn AnySource> {
+        let source = match (self.namespace, source.namespace()) {
+            (Some(expected), Some(found)) if expected != found => Box::new(Foreign {
+                source,
+                expected,
+                found,
+            })
+                as Box<dyn AnySource>,
+            _ => source,
+        };
+
+        Arc::new(Recorded {
+            source,
+            session: self.session.clone(),
+        })
+    }
+
+    fn snapshot(&self) -> Arc<[SourceEntry]> {
+        self.entries.lock().0.clone()
+    }
+
+    fn ids(&self) -> Vec<SourceId> {
+        let entries = self.snapshot();
+        entries
+            .iter()
+            .map(|entry| entry.id)
+            .filter(|id| *id != OVERRIDES_SOURCE)
+            .collect()
+    }
+
+    fn add(&self, source: Box<dyn AnySource>, mask: Option<Uuid>) -> SourceId {
+        let source = self.wrap(source);
+        let mut entries = self.entries.lock();
+        let id = SourceId(entries.1);
+        entries.1 += 1;
+        entries.0 = entries
+            .0
+            .iter()
+            .cloned()
+            .chain(Some(SourceEntry { id, source, mask }))
+            .collect();
+        id
+    }
+
+    fn remove(&self, id: SourceId) -> bool {
+        if id == OVERRIDES_SOURCE {
+            return false;
+        }
+
+        let mut entries = self.entries.lock();
+        if !entries.0.iter().any(|entry| entry.id == id) {
+            return false;
+        }
+        entries.0 = entries
+            .0
+            .iter()
+            .filter(|entry| entry.id != id)
+            .cloned()
+            .collect();
+        true
+    }
+}
+
+/// Data of assets overridden with [`Loader::override_asset`].
+/// Checked before other sources.
+#[derive(Clone, Default)]
+struct Overrides(Arc<Mutex<OverridesData>>);
+
+#[derive(Default)]
+struct OverridesData {
+    assets: HashMap<Uuid, (Bytes, u64)>,
+    next_version: u64,
+}
+
+impl Overrides {
+    fn get(&self, uuid: &Uuid) -> Option<AssetData> {
+        let data = self.0.lock();
+        let (bytes, version) = data.assets.get(uuid)?;
+        Some(AssetData {
+            bytes: bytes.clone(),
+            version: *version,
+            format: None,
+            signature: None,
+            locale: None,
+            name: None,
+            schema: None,
+            deprecated: false,
+        })
+    }
+
+    fn insert(&self, uuid: Uuid, bytes: Bytes) {
+        let mut data = self.0.lock();
+        data.next_version += 1;
+        let version = data.next_version;
+        data.assets.insert(uuid, (bytes, version));
+    }
+
+    fn remove(&self, uuid: &Uuid) -> bool {
+        self.0.lock().assets.remove(uuid).is_some()
+    }
+}
+
+impl AnySource for Overrides {
+    fn load(
+        &self,
+        uuid: &Uuid,
+        _locale: Option<&str>,
+        _priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        Box::pin(futures::future::ready(Ok(self.get(uuid))))
+    }
+
+    fn update(
+        &self,
+        uuid: &Uuid,
+        _locale: Option<&str>,
+        version: u64,
+        _priority: &Priority,
+    ) -> BoxFuture<'_, Result<Option<AssetData>, Error>> {
+        let data = self.get(uuid).filter(|data| data.version != version);
+        Box::pin(futures::future::ready(Ok(data)))
+    }
+
+    fn collection(&self, _name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Error>> {
+        Box::pin(futures::future::ready(Ok(None)))
+    }
+
+    fn namespace(&self) -> Option<Uuid> {
+        None
+    }
+}
+
+struct Data {
+    bytes: Bytes,
+    version: u64,
+    format: Option<Box<str>>,
+    name: Option<Box<str>>,
+    schema: Option<u32>,
+    source: SourceId,
+}
+
+impl Data {
+    fn new(uuid: &Uuid, asset: AssetData, source: SourceId) -> Self {
+        if asset.deprecated {
+            match &asset.name {
+                None => tracing::warn!("Loading deprecated asset '{}'", uuid),
+                Some(name) => tracing::warn!("Loading deprecated asset '{}' ({})", name, uuid),
+            }
+        }
+
+        Data {
+            bytes: asset.bytes,
+            version: asset.version,
+            format: asset.format,
+            name: asset.name,
+            schema: asset.schema,
+            source,
+        }
+    }
+}
+
+async fn load_asset(
+    sources: &[SourceEntry],
+    uuid: &Uuid,
+    locale: Option<&str>,
+    priority: &Priority,
+) -> Result<Option<Data>, Error> {
+    for entry in sources.iter() {
+        if let Some(asset) = entry.source.load(uuid, locale, priority).await? {
+            return Ok(Some(Data::new(uuid, asset, entry.id)));
+        }
+    }
+
+    // Qualified uuid is served only by the source with matching prefix.
+    for entry in sources.iter() {
+        if let Some(mask) = &entry.mask {
+            let unqualified = apply_mask(uuid, mask);
+            if let Some(asset) = entry.source.load(&unqualified, locale, priority).await? {
+                return Ok(Some(Data::new(uuid, asset, entry.id)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Fetches data newer than specified version from the source asset was loaded from.
+/// If that source is removed, asset is loaded from remaining sources.
+async fn update_asset(
+    sources: &[SourceEntry],
+    source: SourceId,
+    uuid: &Uuid,
+    locale: Option<&str>,
+    version: u64,
+    priority: &Priority,
+) -> Result<Option<Data>, Error> {
+    let entry = match sources.iter().find(|entry| entry.id == source) {
+        None => return load_asset(sources, uuid, locale, priority).await,
+        Some(entry) => entry,
+    };
+
+    let mut data = entry.source.update(uuid, locale, version, priority).await?;
+    if data.is_none() {
+        if let Some(mask) = &entry.mask {
+            let unqualified = apply_mask(uuid, mask);
+            data = entry
+                .source
+                .update(&unqualified, locale, version, priority)
+                .await?;
+        }
+    }
+    Ok(data.map(|data| Data::new(uuid, data, source)))
+}
+
+/// Decodes asset data, migrating data of older schema version.
+async fn decode_asset<A: Asset>(
+    loader: Loader,
+    uuid: Uuid,
+    bytes: Bytes,
+    schema: Option<u32>,
+) -> Result<A::Decoded, Error> {
+    // Assets loaded by decoding one are decoded as part of the same chain.
+    let loader = Loader {
+        chain: Some(Arc::new(DecodeChain {
+            uuid,
+            parent: loader.chain,
+        })),
+        inner: loader.inner,
+    };
+
+    let decode = async {
+        match schema {
+            Some(schema) if schema != A::SCHEMA_VERSION => {
+                match loader.inner.decoders.migration::<A>(schema) {
+                    Some(migrate) => migrate(bytes, &loader).await.map_err(Error::new),
+                    None => Err(Error::new(SchemaMismatch {
+                        uuid,
+                        type_name: type_name::<A>(),
+                        expected: A::SCHEMA_VERSION,
+                        found: schema,
+                    })),
+                }
+            }
+            _ => A::decode(bytes, &loader).await.map_err(Error::new),
+        }
+    };
+
+    // Only time spent polling is counted, not waiting for referenced assets.
+    let mut decode = std::pin::pin!(decode);
+    let mut elapsed = Duration::ZERO;
+    let decoded = futures::future::poll_fn(|ctx| {
+        let start = Instant::now();
+        let poll = decode.as_mut().poll(ctx);
+        elapsed += start.elapsed();
+        poll
+    })
+    .await;
+
+    let hooks = &loader.inner.hooks;
+    hooks.on_executed(&uuid, type_name::<A>(), LoadStage::Decode, elapsed);
+    decoded
+}
+
+async fn load_collection(sources: &[SourceEntry], name: &str) -> Result<Option<Vec<Uuid>>, Error> {
+    for entry in sources.iter() {
+        if let Some(members) = entry.source.collection(name).await? {
+            return Ok(Some(members));
+        }
+    }
+    Ok(None)
+}
+
+/// Type-erased loading function of an asset type registered for a native format.
+struct FormatEntry {
+    load: fn(&Loader, &Uuid, Option<Data>) -> BoxFuture<'static, AnyAssetResult>,
+}
+
+fn load_format<A, B>(
+    loader: &Loader,
+    uuid: &Uuid,
+    data: Option<Data>,
+) -> BoxFuture<'static, AnyAssetResult>
+where
+    A: AssetBuild<B>,
+    B: 'static,
+{
+    let handle = loader.load_with_data::<A>(uuid, None, data, None);
+    Box::pin(async move {
+        AnyAssetResult(AnyAssetResultInner::Typed(Box::new(TypedResult::<A, B> {
+            result: handle.await,
+            marker: PhantomData,
+        })))
+    })
+}
+
+/// Policy for dropping cached assets no longer referenced by any handle.
+/// Applied in [`Loader::maintain`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnloadPolicy {
+    /// Drop unreferenced asset on next [`Loader::maintain`] call.
+    Immediate,
+
+    /// Drop asset after it stays unreferenced for specified number of [`Loader::maintain`] calls.
+    Delayed { frames: u32 },
+
+    /// Drop asset after it stays unreferenced for specified time,
+    /// e.g. for UI previews and one-shot sounds.
+    /// Asset is dropped by first [`Loader::maintain`] call after time passes.
+    Expire { ttl: Duration },
+
+    /// Keep asset cached until loader is dropped.
+    #[default]
+    Never,
+}
+
+/// Priority class of asset type, see [`BuildBudget`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BuildClass {
+    /// Assets needed to render the frame, e.g. shaders and UI.
+    Critical,
+
+    #[default]
+    Normal,
+
+    /// Assets that may show up late, e.g. high-resolution textures.
+    Background,
+}
+
+/// Limits how many assets of a type [`Loader::maintain`] reports as ready to be built per call.
+/// Assets over the limit are reported by following calls,
+/// so building reported assets spreads heavy builds over frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BuildBudget {
+    max_per_frame: Option<usize>,
+    class: BuildClass,
+}
+
+impl BuildBudget {
+    /// Returns budget without limit in [`BuildClass::Normal`].
+    pub fn new() -> Self {
+        BuildBudget::default()
+    }
+
+    /// Reports at most `max` assets of the type per [`Loader::maintain`] call.
+    pub fn with_max_per_frame(mut self, max: usize) -> Self {
+        self.max_per_frame = Some(max);
+        self
+    }
+
+    /// Reports assets of the type before assets of lower classes.
+    pub fn with_class(mut self, class: BuildClass) -> Self {
+        self.class = class;
+        self
+    }
+
+    pub(crate) fn max_per_frame(&self) -> Option<usize> {
+        self.max_per_frame
+    }
+
+    pub(crate) fn class(&self) -> BuildClass {
+        self.class
+    }
+}
+
+/// Report of [`Loader::maintain`] call.
+#[derive(Clone, Debug, Default)]
+pub struct MaintainReport {
+    /// Assets loaded and decoded since previous call, ready to be built.
+    /// Assets over [`BuildBudget`] of their type are carried over to following calls.
+    pub completed: Vec<Uuid>,
+
+    /// Assets failed to load, decode or build, or not found in any source, since previous call.
+    pub failed: Vec<(Uuid, Error)>,
+
+    /// Assets dropped from the cache by this call.
+    pub evicted: Vec<Uuid>,
+}
+
+struct UnloadPolicies {
+    default: UnloadPolicy,
+    types: HashMap<TypeId, UnloadPolicy>,
+    clock: Arc<dyn Clock>,
+}
+
+impl UnloadPolicies {
+    fn get(&self, type_id: TypeId) -> UnloadPolicy {
+        self.types.get(&type_id).copied().unwrap_or(self.default)
+    }
+}
+
+/// Builder for [`Loader`].
+/// Allows configure asset loader with required [`Source`]s.
+pub struct LoaderBuilder {
+    num_shards: usize,
+    sources: Vec<(Box<dyn AnySource>, Option<Uuid>)>,
+    formats: HashMap<Box<str>, FormatEntry>,
+    unload: UnloadPolicies,
+    decode: DecodePolicies,
+    hooks: Vec<Box<dyn LoaderHook>>,
+    builds: HashMap<&'static str, BuildBudget>,
+    slow_threshold: Duration,
+    retry_interval: u32,
+    spawner: Option<Arc<dyn Spawner>>,
+    namespace: Option<Uuid>,
+}
+
+impl Default for LoaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoaderBuilder {
+    /// Returns new [`LoaderBuilder`] without asset sources.
+    pub fn new() -> Self {
+        let num_cpus = num_cpus::get();
+        let num_shards = DEFAULT_SHARDS_PER_CPU * num_cpus;
+
+        LoaderBuilder {
+            num_shards,
+            sources: Vec::new(),
+            formats: HashMap::new(),
+            unload: UnloadPolicies {
+                default: UnloadPolicy::Never,
+                types: HashMap::new(),
+                clock: Arc::new(SystemClock),
+            },
+            decode: DecodePolicies {
+                default: DecodePolicy::Inline,
+                types: HashMap::new(),
+                migrations: HashMap::new(),
+            },
+            hooks: Vec::new(),
+            builds: HashMap::new(),
+            slow_threshold: DEFAULT_SLOW_THRESHOLD,
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+            spawner: None,
+            namespace: None,
+        }
+    }
+
+    /// Adds provided source to the loader.
+    pub fn add(&mut self, source: impl Source) -> &mut Self {
+        self.sources.push((Box::new(source), None));
+        self
+    }
+
+    /// Adds provided source to the loader.
+    pub fn with(mut self, source: impl Source) -> Self {
+        self.add(source);
+        self
+    }
+
+    /// Adds provided source to the loader with uuid prefix,
+    /// so that its assets are not shadowed by assets with same uuid in other sources,
+    /// e.g. to load base game and mod packs side by side.
+    ///
+    /// Uuids are resolved by sources in the order they were added.
+    /// Uuid qualified with [`Loader::qualify`] is resolved by the source with the prefix only.
+    pub fn add_prefixed(&mut self, prefix: &str, source: impl Source) -> &mut Self {
+        self.sources
+            .push((Box::new(source), Some(prefix_mask(prefix))));
+        self
+    }
+
+    /// Adds provided source to the loader with uuid prefix,
+    /// so that its assets are not shadowed by assets with same uuid in other sources,
+    /// e.g. to load base game and mod packs side by side.
+    ///
+    /// Uuids are resolved by sources in the order they were added.
+    /// Uuid qualified with [`Loader::qualify`] is resolved by the source with the prefix only.
+    pub fn with_prefixed(mut self, prefix: &str, source: impl Source) -> Self {
+        self.add_prefixed(prefix, source);
+        self
+    }
+
+    /// Adds provided source to the loader.
+    /// Asset data from this source must be signed with the key,
+    /// otherwise assets fail to load with error for which
+    /// [`Error::is_signature_invalid`] returns `true`.
+    /// Signature is verified before asset is decoded.
+    ///
+    /// Use for sources that deliver assets over untrusted channels.
+    pub fn add_signed(&mut self, source: impl Source, key: VerifyingKey) -> &mut Self {
+        self.sources.push((Box::new(Signed { source, key }), None));
+        self
+    }
+
+    /// Adds provided source to the loader.
+    /// Asset data from this source must be signed with the key,
+    /// otherwise assets fail to load with error for which
+    /// [`Error::is_signature_invalid`] returns `true`.
+    /// Signature is verified before asset is decoded.
+    ///
+    /// Use for sources that deliver assets over untrusted channels.
+    pub fn with_signed(mut self, source: impl Source, key: VerifyingKey) -> Self {
+        self.add_signed(source, key);
+        self
+    }
+
+    /// Adds provided source to the loader.
+    /// Fetches from this source are limited as configured by [`FetchLimits`].
+    pub fn add_limited(&mut self, source: impl Source, limits: FetchLimits) -> &mut Self {
+        self.sources
+            .push((Box::new(Limited::new(Box::new(source), limits)), None));
+        self
+    }
+
+    /// Adds provided source to the loader.
+    /// Fetches from this source are limited as configured by [`FetchLimits`].
+    pub fn with_limited(mut self, source: impl Source, limits: FetchLimits) -> Self {
+        self.add_limited(source, limits);
+        self
+    }
+
+    /// Registers asset type for its native format.
+    /// Assets loaded with [`Loader::load_untyped`] in that format
+    /// are decoded as `A` and built with builder of type `B`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` has no native format.
+    pub fn add_format<A, B>(&mut self) -> &mut Self
+    where
+        A: AssetBuild<B>,
+        B: 'static,
+    {
+        assert!(
+            !A::FORMAT.is_empty(),
+            "Asset type `{}` has no native format",
+            type_name::<A>()
+        );
+
+        self.formats.insert(
+            A::FORMAT.into(),
+            FormatEntry {
+                load: load_format::<A, B>,
+            },
+        );
+        self
+    }
+
+    /// Registers asset type for its native format.
+    /// Assets loaded with [`Loader::load_untyped`] in that format
+    /// are decoded as `A` and built with builder of type `B`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` has no native format.
+    pub fn with_format<A, B>(mut self) -> Self
+    where
+        A: AssetBuild<B>,
+        B: 'static,
+    {
+        self.add_format::<A, B>();
+        self
+    }
+
+    /// Sets number of shards for the loader.
+    ///
+    /// Actual number of shards will be bumped to the next power of two
+    /// and limited to 512.
+    ///
+    /// This is low-level optimization tweaking function.
+    /// Default value should be sufficient most use cases.
+    pub fn set_num_shards(&mut self, num_shards: usize) -> &mut Self {
+        self.num_shards = num_shards;
+        self
+    }
+
+    /// Sets number of shards for the loader.
+    ///
+    /// Actual number of shards will be bumped to the next power of two
+    /// and limited to 512.
+    ///
+    /// This is low-level optimization tweaking function.
+    /// Default value should be sufficient most use cases.
+    pub fn with_num_shards(mut self, num_shards: usize) -> Self {
+        self.num_shards = num_shards;
+        self
+    }
+
+    /// Sets policy for unloading assets that are no longer referenced.
+    /// Defaults to [`UnloadPolicy::Never`].
+    pub fn set_unload_policy(&mut self, policy: UnloadPolicy) -> &mut Self {
+        self.unload.default = policy;
+        self
+    }
+
+    /// Sets policy for unloading assets that are no longer referenced.
+    /// Defaults to [`UnloadPolicy::Never`].
+    pub fn with_unload_policy(mut self, policy: UnloadPolicy) -> Self {
+        self.unload.default = policy;
+        self
+    }
+
+    /// Overrides unload policy for assets of type `A`.
+    pub fn set_unload_policy_for<A>(&mut self, policy: UnloadPolicy) -> &mut Self
+    where
+        A: Asset,
+    {
+        self.unload.types.insert(TypeId::of::<A>(), policy);
+        self
+    }
+
+    /// Overrides unload policy for assets of type `A`.
+    pub fn with_unload_policy_for<A>(mut self, policy: UnloadPolicy) -> Self
+    where
+        A: Asset,
+    {
+        self.unload.types.insert(TypeId::of::<A>(), policy);
+        self
+    }
+
+    /// Sets build budget for assets of type `A`.
+    /// Types without budget are reported without limit in [`BuildClass::Normal`].
+    pub fn set_build_budget_for<A>(&mut self, budget: BuildBudget) -> &mut Self
+    where
+        A: Asset,
+    {
+        self.builds.insert(type_name::<A>(), budget);
+        self
+    }
+
+    /// Sets build budget for assets of type `A`.
+    /// See [`LoaderBuilder::set_build_budget_for`].
+    pub fn with_build_budget_for<A>(mut self, budget: BuildBudget) -> Self
+    where
+        A: Asset,
+    {
+        self.builds.insert(type_name::<A>(), budget);
+        self
+    }
+
+    /// Sets clock [`UnloadPolicy::Expire`] measures time with.
+    /// Defaults to [`SystemClock`], [`ManualClock`] makes expiration deterministic in tests.
+    ///
+    /// [`ManualClock`]: crate::ManualClock
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) -> &mut Self {
+        self.unload.clock = Arc::new(clock);
+        self
+    }
+
+    /// Sets clock [`UnloadPolicy::Expire`] measures time with.
+    /// See [`LoaderBuilder::set_clock`].
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.unload.clock = Arc::new(clock);
+        self
+    }
+
+    /// Sets policy for scheduling decodes of assets.
+    /// Defaults to [`DecodePolicy::Inline`].
+    pub fn set_decode_policy(&mut self, policy: DecodePolicy) -> &mut Self {
+        self.decode.default = policy;
+        self
+    }
+
+    /// Sets policy for scheduling decodes of assets.
+    /// Defaults to [`DecodePolicy::Inline`].
+    pub fn with_decode_policy(mut self, policy: DecodePolicy) -> Self {
+        self.decode.default = policy;
+        self
+    }
+
+    /// Overrides decode policy for assets of type `A`.
+    pub fn set_decode_policy_for<A>(&mut self, policy: DecodePolicy) -> &mut Self
+    where
+        A: Asset,
+    {
+        self.decode
+            .types
+            .insert(TypeId::of::<A>(), (policy, type_name::<A>()));
+        self
+    }
+
+    /// Overrides decode policy for assets of type `A`.
+    pub fn with_decode_policy_for<A>(mut self, policy: DecodePolicy) -> Self
+    where
+        A: Asset,
+    {
+        self.decode
+            .types
+            .insert(TypeId::of::<A>(), (policy, type_name::<A>()));
+        self
+    }
+
+    /// Registers migration decoding asset data of older schema version.
+    /// Replaces migration of the same asset type and schema version.
+    pub fn add_migration<M>(&mut self) -> &mut Self
+    where
+        M: Migration,
+    {
+        self.decode.migrations.insert(
+            (TypeId::of::<M::Asset>(), M::SCHEMA_VERSION),
+            migrate_fn::<M>(),
+        );
+        self
+    }
+
+    /// Registers migration decoding asset data of older schema version.
+    /// Replaces migration of the same asset type and schema version.
+    pub fn with_migration<M>(mut self) -> Self
+    where
+        M: Migration,
+    {
+        self.add_migration::<M>();
+        self
+    }
+
+    /// Adds instrumentation hook to the loader.
+    /// Hooks are called in the order they were added.
+    pub fn add_hook(&mut self, hook: impl LoaderHook) -> &mut Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Adds instrumentation hook to the loader.
+    /// Hooks are called in the order they were added.
+    pub fn with_hook(mut self, hook: impl LoaderHook) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Sets spawner that runs loading tasks.
+    /// Defaults to tokio runtime [`LoaderBuilder::build`] is called within,
+    /// or to [`TokioSpawner`] if called outside of any runtime.
+    pub fn set_spawner(&mut self, spawner: impl Spawner) -> &mut Self {
+        self.spawner = Some(Arc::new(spawner));
+        self
+    }
+
+    /// Sets spawner that runs loading tasks.
+    /// Defaults to tokio runtime [`LoaderBuilder::build`] is called within,
+    /// or to [`TokioSpawner`] if called outside of any runtime.
+    pub fn with_spawner(mut self, spawner: impl Spawner) -> Self {
+        self.spawner = Some(Arc::new(spawner));
+        self
+    }
+
+    /// Sets namespace of the project the loader loads assets of.
+    /// Assets from sources of other projects fail to load
+    /// with error for which [`Error::is_namespace_mismatch`] returns `true`.
+    /// Sources that do not know their project are not checked.
+    pub fn set_namespace(&mut self, namespace: Uuid) -> &mut Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    /// Sets namespace of the project the loader loads assets of.
+    /// Assets from sources of other projects fail to load
+    /// with error for which [`Error::is_namespace_mismatch`] returns `true`.
+    /// Sources that do not know their project are not checked.
+    pub fn with_namespace(mut self, namespace: Uuid) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    /// Sets duration of decode or build execution reported as slow.
+    /// Slow executions are logged as warnings and reported by [`Loader::stats`].
+    /// Decoding time does not include time spent waiting for referenced assets.
+    pub fn set_slow_threshold(&mut self, threshold: Duration) -> &mut Self {
+        self.slow_threshold = threshold;
+        self
+    }
+
+    /// Sets duration of decode or build execution reported as slow.
+    /// Slow executions are logged as warnings and reported by [`Loader::stats`].
+    /// Decoding time does not include time spent waiting for referenced assets.
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = threshold;
+        self
+    }
+
+    /// Sets number of [`Loader::maintain`] calls between checks of sources
+    /// for newer data of assets that failed to decode or build.
+    /// Asset is loaded again once its source reports newer data,
+    /// e.g. after broken file is fixed.
+    /// Assets not found or failed to be fetched from sources are not checked
+    /// and are loaded again only by [`Loader::retry`].
+    /// Zero disables checks, leaving only [`Loader::retry`].
+    pub fn set_retry_interval(&mut self, frames: u32) -> &mut Self {
+        self.retry_interval = frames;
+        self
+    }
+
+    /// Sets number of [`Loader::maintain`] calls between checks of sources
+    /// for newer data of failed assets.
+    /// See [`LoaderBuilder::set_retry_interval`].
+    pub fn with_retry_interval(mut self, frames: u32) -> Self {
+        self.retry_interval = frames;
+        self
+    }
+
+    /// Builds and returns new [`Loader`] instance.
+    pub fn build(self) -> Loader {
+        let random_state = RandomState::new();
+        let namespace = self.namespace;
+        let overrides = Overrides::default();
+        let session = SessionLog::default();
+        let sources = Sources::new(overrides.clone(), self.sources, namespace, session.clone());
+        let formats = self.formats;
+        let unload = self.unload;
+        let decoders = Decoders::new(self.decode);
+        let loads = LoadRecorder::new(self.builds);
+        let mut hooks = self.hooks;
+        hooks.push(Box::new(loads.clone()));
+        let hooks = Hooks::new(hooks, Watchdog::new(self.slow_threshold));
+        let retry_interval = self.retry_interval;
+        let spawner = self.spawner.unwrap_or_else(|| {
+            // Runtime handle accepts tasks from threads outside of the runtime.
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle) => Arc::new(handle),
+                Err(_) => Arc::new(TokioSpawner),
+            }
+        });
+
+        let inner = match self.num_shards {
+            0..=1 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                1
+            ),
+            2..=2 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                2
+            ),
+            3..=4 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                4
+            ),
+            5..=8 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                8
+            ),
+            9..=16 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                16
+            ),
+            17..=32 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                32
+            ),
+            33..=64 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                64
+            ),
+            65..=128 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                128
+            ),
+            129..=256 => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                256
+            ),
+            _ => assets_inner!(
+                sources,
+                overrides,
+                session,
+                formats,
+                unload,
+                decoders,
+                hooks,
+                loads,
+                retry_interval,
+                spawner,
+                random_state,
+                512
+            ),
+        };
+
+        Loader { inner, chain: None }
+    }
+}
+
+type Shard = Arc<Mutex<HashMap<Key, AssetEntry, RandomState>>>;
+
+type RetryFn = fn(&Loader, &Uuid, Option<Arc<str>>, Priority, Option<Data>);
+
+type ReloadFn = fn(&Loader, &Uuid);
+
+// Loader must stay shareable between threads.
+const _: fn() = || {
+    fn assert<T: Clone + Send + Sync + 'static>() {}
+    assert::<Loader>();
+};
+
+/// Virtual storage for all available assets.
+///
+/// Loader is `Send + Sync` and clones share the same cache, so cloning is cheap.
+/// All methods may be called from any thread concurrently,
+/// including threads outside of the runtime loading tasks are spawned onto,
+/// unless explicitly configured [`Spawner`] requires otherwise.
+#[derive(Clone)]
+pub struct Loader {
+    inner: Arc<Inner<[Shard]>>,
+
+    /// Assets being decoded by the load request this loader is passed to.
+    chain: Option<Arc<DecodeChain>>,
+}
+
+/// Asset being decoded, linked to the asset whose decoding requested it.
+struct DecodeChain {
+    uuid: Uuid,
+    parent: Option<Arc<DecodeChain>>,
+}
+
+impl Loader {
+    /// Returns cycle of assets if asset with specified uuid is being decoded
+    /// by the same load request.
+    fn find_cycle(&self, uuid: &Uuid) -> Option<Vec<Uuid>> {
+        let mut path = vec![*uuid];
+        let mut link = self.chain.as_deref();
+        while let Some(chain) = link {
+            path.push(chain.uuid);
+            if chain.uuid == *uuid {
+                path.reverse();
+                return Some(path);
+            }
+            link = chain.parent.as_deref();
+        }
+        None
+    }
+}
+
+enum StateTyped<A: Asset> {
+    #[allow(dead_code)]
+    Asset {
+        asset: A,
+        version: u64,
+        source: SourceId,
+    },
+    Decoded {
+        decoded: Option<A::Decoded>,
+        version: u64,
+        source: SourceId,
+    },
+}
+
+enum StateErased {
+    Unloaded,
+    Typed(Box<dyn Any + Send + Sync>),
+    Missing,
+    Error(Error),
+}
+
+struct AssetEntry {
+    state: StateErased,
+    wakers: Vec<Waker>,
+
+    /// Type asset is loaded as.
+    type_id: TypeId,
+    type_name: &'static str,
+
+    /// Shared with handles to track whether asset is still referenced.
+    refs: Arc<()>,
+
+    /// Number of [`Loader::maintain`] calls asset stayed unreferenced.
+    unused: u32,
+
+    /// Time [`Loader::maintain`] found asset unreferenced first.
+    unused_since: Option<SystemTime>,
+
+    /// Policy set for the asset with [`Loader::load_with_unload_policy`].
+    unload: Option<UnloadPolicy>,
+
+    /// Priority of fetching asset data.
+    priority: Priority,
+
+    /// Number of times asset was reloaded or loaded again after failure.
+    generation: u64,
+
+    /// Starts loading asset again after failure.
+    retry: RetryFn,
+
+    /// Spawns reload of loaded asset.
+    reload: ReloadFn,
+
+    /// Source and version of data asset failed to decode or build from.
+    /// `None` while the source is checked for newer data.
+    failed_data: Option<(SourceId, u64)>,
+
+    /// Number of [`Loader::maintain`] calls since failed asset was checked for newer data.
+    failed_frames: u32,
+
+    /// Source that served data of loaded asset.
+    source: Option<SourceId>,
+
+    /// Creation sites of handles referencing the asset.
+    #[cfg(feature = "leak-detector")]
+    sites: crate::leak::Sites,
+}
+
+impl AssetEntry {
+    /// Resets asset that failed to load to be loaded again.
+    /// Returns function spawning the load.
+    fn reset_failed(&mut self) -> Option<RetryFn> {
+        match self.state {
+            StateErased::Error(_) | StateErased::Missing => {
+                self.state = StateErased::Unloaded;
+                self.failed_data = None;
+                self.failed_frames = 0;
+                self.generation += 1;
+                Some(self.retry)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Reference to cached asset held by handles.
+/// Keeps asset from being unloaded.
+#[derive(Clone)]
+struct HandleRefs {
+    _refs: Arc<()>,
+    #[cfg(feature = "leak-detector")]
+    _site: Option<crate::leak::SiteGuard>,
+}
+
+impl HandleRefs {
+    #[track_caller]
+    fn new(entry: &AssetEntry) -> Self {
+        HandleRefs {
+            _refs: entry.refs.clone(),
+            #[cfg(feature = "leak-detector")]
+            _site: Some(entry.sites.register()),
+        }
+    }
+
+    /// References nothing, for handles not bound to cached asset.
+    fn detached() -> Self {
+        HandleRefs {
+            _refs: Arc::new(()),
+            #[cfg(feature = "leak-detector")]
+            _site: None,
+        }
+    }
+}
+
+enum AssetResultInner<A: Asset> {
+    Asset(A),
+    Error(Error),
+    Missing,
+    Decoded {
+        uuid: Uuid,
+        locale: Option<Arc<str>>,
+        key_hash: u64,
+        shard: Arc<Mutex<HashMap<Key, AssetEntry>>>,
+        hooks: Hooks,
+        /// Keeps asset referenced until it is built.
+        _refs: HandleRefs,
+    },
+}
+
+#[derive(Debug)]
+pub struct AssetResultPoisoned;
+
+impl Display for AssetResultPoisoned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("`AssetResult` poisoned by panic")
+    }
+}
+
+impl std::error::Error for AssetResultPoisoned {}
+
+pub struct AssetResult<A: Asset> {
+    inner: AssetResultInner<A>,
+    generation: u64,
+}
+
+impl<A> AssetResult<A>
+where
+    A: Asset,
+{
+    pub fn get_optional<B>(&mut self, builder: &mut B) -> Result<Option<&A>, Error>
+    where
+        A: AssetBuild<B>,
+    {
+        if let AssetResultInner::Decoded {
+            uuid,
+            locale,
+            key_hash,
+            shard,
+            hooks,
+            ..
+        } = &self.inner
+        {
+            let mut locked_shard = shard.lock();
+            let entry = locked_shard
+                .raw_entry_mut()
+                .from_hash(*key_hash, |k| k.eq_key(uuid, locale.as_deref()));
+
+            match entry {
+                RawEntryMut::Vacant(_) => unreachable!(),
+                RawEntryMut::Occupied(mut entry) => {
+                    // Asset may be reloaded since handle resolved.
+                    self.generation = entry.get().generation;
+
+                    match &mut entry.get_mut().state {
+                        StateErased::Typed(typed) => {
+                            let typed: &mut StateTyped<A> = typed.downcast_mut().unwrap();
+
+                            match typed {
+                                StateTyped::Decoded {
+                                    decoded,
+                                    version,
+                                    source,
+                                } => match decoded.take() {
+                                    Some(decoded) => {
+                                        let start = Instant::now();
+                                        let built = catch_unwind(AssertUnwindSafe(|| {
+                                            A::build(decoded, builder).map_err(Error::new)
+                                        }));
+                                        hooks.on_executed(
+                                            uuid,
+                                            type_name::<A>(),
+                                            LoadStage::Build,
+                                            start.elapsed(),
+                                        );
+                                        let built = built.unwrap_or_else(|payload| {
+                                            Err(Error::new(BuildPanicked::new(*uuid, payload)))
+                                        });
+
+                                        match built {
+                                            Ok(asset) => {
+                                                hooks.on_built(uuid, type_name::<A>());
+                                                *typed = StateTyped::Asset {
+                                                    asset: asset.clone(),
+                                                    version: *version,
+                                                    source: *source,
+                                                };
+                                                drop(locked_shard);
+                                                self.inner = AssetResultInner::Asset(asset);
+                                            }
+                                            Err(err) => {
+                                                hooks.on_failed(uuid, type_name::<A>(), &err);
+                                                let failed_data = Some((*source, *version));
+                                                entry.get_mut().state =
+                                                    StateErased::Error(err.clone());
+                                                entry.get_mut().failed_data = failed_data;
+                                                drop(locked_shard);
+                                                self.inner = AssetResultInner::Error(err);
+                                            }
+                                        }
+                                    }
+                                    None => {
+                                        let err = Error::new(AssetResultPoisoned);
+                                        entry.get_mut().state = StateErased::Error(err.clone());
+                                        drop(locked_shard);
+                                        self.inner = AssetResultInner::Error(err);
+                                    }
+                                },
+                                StateTyped::Asset { asset, .. } => {
+                                    let asset = asset.clone();
+                                    drop(locked_shard);
+                                    self.inner = AssetResultInner::Asset(asset);
+                                }
+                            }
+                        }
+                        StateErased::Error(err) => {
+                            let err = err.clone();
+                            drop(locked_shard);
+                            self.inner = AssetResultInner::Error(err);
+                        }
+                        // Failed asset is loaded again since this result resolved.
+                        StateErased::Unloaded => {
+                            drop(locked_shard);
+                            self.inner = AssetResultInner::Error(Error::new(Retrying));
+                        }
+                        StateErased::Missing => {
+                            drop(locked_shard);
+                            self.inner = AssetResultInner::Missing;
+                        }
+                    }
+                }
+            }
+        }
+
+        match &self.inner {
+            AssetResultInner::Missing => Ok(None),
+            AssetResultInner::Asset(asset) => Ok(Some(asset)),
+            AssetResultInner::Error(err) => Err(err.clone()),
+            AssetResultInner::Decoded { .. } => unreachable!(),
+        }
+    }
+
+    pub fn get<B>(&mut self, builder: &mut B) -> Result<&A, Error>
+    where
+        A: AssetBuild<B>,
+    {
+        self.get_optional(builder)?
+            .ok_or_else(|| Error::new(NotFound))
+    }
+
+    /// Returns number of times asset was reloaded with [`Loader::reload`]
+    /// before this result was obtained or built.
+    ///
+    /// Allows cheap detection of changed asset
+    /// by systems caching data derived from it.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+enum AssetHandleInner<A> {
+    Asset(A),
+    Error(Error),
+    Missing,
+    Pending {
+        uuid: Uuid,
+        locale: Option<Arc<str>>,
+        key_hash: u64,
+        shard: Arc<Mutex<HashMap<Key, AssetEntry>>>,
+        hooks: Hooks,
+        priority: Priority,
+    },
+}
+
+pub struct AssetHandle<A> {
+    uuid: Uuid,
+    generation: u64,
+    inner: AssetHandleInner<A>,
+    refs: HandleRefs,
+}
+
+impl<A> AssetHandle<A> {
+    fn new(uuid: Uuid, generation: u64, refs: HandleRefs, inner: AssetHandleInner<A>) -> Self {
+        AssetHandle {
+            uuid,
+            generation,
+            inner,
+            refs,
+        }
+    }
+
+    /// Returns uuid of the asset.
+    pub fn uuid(&self) -> &Uuid {
+        &self.uuid
+    }
+
+    fn result(&self, inner: AssetResultInner<A>) -> AssetResult<A>
+    where
+        A: Asset,
+    {
+        AssetResult {
+            inner,
+            generation: self.generation,
+        }
+    }
+
+    /// Replaces streaming hint of the asset if its data is not yet fetched.
+    /// Affects all handles to the same asset.
+    pub fn set_priority(&self, hint: StreamingHint) {
+        if let AssetHandleInner::Pending { priority, .. } = &self.inner {
+            priority.set(hint);
+        }
+    }
+}
+
+impl<A> Unpin for AssetHandle<A> {}
+
+impl<A> Future for AssetHandle<A>
+where
+    A: Asset,
+{
+    type Output = AssetResult<A>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+
+        match &me.inner {
+            AssetHandleInner::Asset(asset) => {
+                Poll::Ready(me.result(AssetResultInner::Asset(asset.clone())))
+            }
+            AssetHandleInner::Error(err) => {
+                Poll::Ready(me.result(AssetResultInner::Error(err.clone())))
             }
-            AssetHandleInner::Missing => Poll::Ready(AssetResult(AssetResultInner::Missing)),
+            AssetHandleInner::Missing => Poll::Ready(me.result(AssetResultInner::Missing)),
             AssetHandleInner::Pending {
                 uuid,
+                locale,
                 key_hash,
                 shard,
+                hooks,
+                ..
             } => {
                 let mut locked_shard = shard.lock();
                 let asset_entry = locked_shard
                     .raw_entry_mut()
-                    .from_hash(*key_hash, |k| k.eq_key::<A>(uuid));
+                    .from_hash(*key_hash, |k| k.eq_key(uuid, locale.as_deref()));
 
                 match asset_entry {
                     RawEntryMut::Occupied(mut entry) => {
                         let entry = entry.get_mut();
+                        me.generation = entry.generation;
                         match &entry.state {
                             StateErased::Error(err) => {
                                 let err = err.clone();
                                 drop(locked_shard);
-                                me.0 = AssetHandleInner::Error(err.clone());
-                                Poll::Ready(AssetResult(AssetResultInner::Error(err)))
+                                me.inner = AssetHandleInner::Error(err.clone());
+                                Poll::Ready(me.result(AssetResultInner::Error(err)))
                             }
                             StateErased::Missing => {
                                 drop(locked_shard);
-                                me.0 = AssetHandleInner::Missing;
-                                Poll::Ready(AssetResult(AssetResultInner::Missing))
+                                me.inner = AssetHandleInner::Missing;
+                                Poll::Ready(me.result(AssetResultInner::Missing))
                             }
                             StateErased::Unloaded => {
-                                entry.wakers.push(ctx.waker().clone());
+                                // Handles polled every frame register the same waker repeatedly.
+                                if !entry.wakers.iter().any(|w| w.will_wake(ctx.waker())) {
+                                    entry.wakers.push(ctx.waker().clone());
+                                }
                                 Poll::Pending
                             }
                             StateErased::Typed(typed) => {
@@ -415,15 +2079,18 @@ where
                                     StateTyped::Asset { asset, .. } => {
                                         let asset = asset.clone();
                                         drop(locked_shard);
-                                        me.0 = AssetHandleInner::Asset(asset.clone());
-                                        Poll::Ready(AssetResult(AssetResultInner::Asset(asset)))
+                                        me.inner = AssetHandleInner::Asset(asset.clone());
+                                        Poll::Ready(me.result(AssetResultInner::Asset(asset)))
                                     }
                                     StateTyped::Decoded { .. } => {
                                         drop(locked_shard);
-                                        Poll::Ready(AssetResult(AssetResultInner::Decoded {
+                                        Poll::Ready(me.result(AssetResultInner::Decoded {
                                             uuid: *uuid,
+                                            locale: locale.clone(),
                                             key_hash: *key_hash,
                                             shard: shard.clone(),
+                                            hooks: hooks.clone(),
+                                            _refs: me.refs.clone(),
                                         }))
                                     }
                                 }
@@ -437,29 +2104,525 @@ where
             }
         }
     }
-}
+}
+
+/// Type-erased [`AssetResult`] of asset type registered for a native format.
+trait ErasedResult: Send + Sync {
+    fn get_optional(
+        &mut self,
+        builder: &mut dyn Any,
+    ) -> Result<Option<Arc<dyn Any + Send + Sync>>, Error>;
+}
+
+struct TypedResult<A: Asset, B> {
+    result: AssetResult<A>,
+    marker: PhantomData<fn(&mut B)>,
+}
+
+impl<A, B> ErasedResult for TypedResult<A, B>
+where
+    A: AssetBuild<B>,
+    B: 'static,
+{
+    fn get_optional(
+        &mut self,
+        builder: &mut dyn Any,
+    ) -> Result<Option<Arc<dyn Any + Send + Sync>>, Error> {
+        let builder = builder.downcast_mut::<B>().ok_or_else(|| {
+            Error::new(BuilderMismatch {
+                format: A::FORMAT,
+                expected: type_name::<B>(),
+            })
+        })?;
+
+        let asset = self.result.get_optional(builder)?;
+        Ok(asset.map(|asset| Arc::new(asset.clone()) as Arc<dyn Any + Send + Sync>))
+    }
+}
+
+enum AnyAssetResultInner {
+    Asset(Arc<dyn Any + Send + Sync>),
+    Typed(Box<dyn ErasedResult>),
+    Error(Error),
+    Missing,
+}
+
+/// Type-erased counterpart of [`AssetResult`].
+/// Built asset is shared as `Arc<dyn Any + Send + Sync>`
+/// and can be downcast to the asset type registered for its native format.
+pub struct AnyAssetResult(AnyAssetResultInner);
+
+impl AnyAssetResult {
+    /// Builds asset if not yet built.
+    /// Returns `Ok(None)` if asset is not found.
+    ///
+    /// `builder` must be of the type asset type was registered with.
+    pub fn get_optional(
+        &mut self,
+        builder: &mut dyn Any,
+    ) -> Result<Option<&Arc<dyn Any + Send + Sync>>, Error> {
+        if let AnyAssetResultInner::Typed(typed) = &mut self.0 {
+            self.0 = match typed.get_optional(builder) {
+                Ok(Some(asset)) => AnyAssetResultInner::Asset(asset),
+                Ok(None) => AnyAssetResultInner::Missing,
+                Err(err) => AnyAssetResultInner::Error(err),
+            };
+        }
+
+        match &self.0 {
+            AnyAssetResultInner::Asset(asset) => Ok(Some(asset)),
+            AnyAssetResultInner::Error(err) => Err(err.clone()),
+            AnyAssetResultInner::Missing => Ok(None),
+            AnyAssetResultInner::Typed(_) => unreachable!(),
+        }
+    }
+
+    /// Builds asset if not yet built.
+    ///
+    /// `builder` must be of the type asset type was registered with.
+    pub fn get(&mut self, builder: &mut dyn Any) -> Result<&Arc<dyn Any + Send + Sync>, Error> {
+        self.get_optional(builder)?
+            .ok_or_else(|| Error::new(NotFound))
+    }
+
+    /// Builds asset if not yet built and downcasts it to `A`.
+    /// Returns `Ok(None)` if asset is not found.
+    ///
+    /// `builder` must be of the type asset type was registered with.
+    pub fn downcast_optional<A>(&mut self, builder: &mut dyn Any) -> Result<Option<&A>, Error>
+    where
+        A: Asset,
+    {
+        match self.get_optional(builder)? {
+            None => Ok(None),
+            Some(asset) => match asset.downcast_ref() {
+                Some(asset) => Ok(Some(asset)),
+                None => Err(Error::new(DowncastMismatch {
+                    expected: type_name::<A>(),
+                })),
+            },
+        }
+    }
+
+    /// Builds asset if not yet built and downcasts it to `A`.
+    ///
+    /// `builder` must be of the type asset type was registered with.
+    pub fn downcast<A>(&mut self, builder: &mut dyn Any) -> Result<&A, Error>
+    where
+        A: Asset,
+    {
+        self.downcast_optional(builder)?
+            .ok_or_else(|| Error::new(NotFound))
+    }
+}
+
+/// Handle to assets of a collection loaded with [`Loader::load_collection`].
+/// Resolves into results of all members in collection order
+/// once all of them are loaded and decoded.
+pub struct CollectionHandle(oneshot::Receiver<Result<Vec<(Uuid, AnyAssetResult)>, Error>>);
+
+impl Future for CollectionHandle {
+    type Output = Result<Vec<(Uuid, AnyAssetResult)>, Error>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.get_mut().0).poll(ctx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(Error::new(err))),
+        }
+    }
+}
+
+/// Type-erased counterpart of [`AssetHandle`].
+/// Resolves into [`AnyAssetResult`] once asset is loaded and decoded.
+pub struct AnyAssetHandle(oneshot::Receiver<AnyAssetResult>);
+
+impl Future for AnyAssetHandle {
+    type Output = AnyAssetResult;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<AnyAssetResult> {
+        match Pin::new(&mut self.get_mut().0).poll(ctx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(err)) => {
+                Poll::Ready(AnyAssetResult(AnyAssetResultInner::Error(Error::new(err))))
+            }
+        }
+    }
+}
+
+struct Inner<T: ?Sized> {
+    sources: Sources,
+    overrides: Overrides,
+    session: SessionLog,
+    formats: HashMap<Box<str>, FormatEntry>,
+    unload: UnloadPolicies,
+    decoders: Decoders,
+    hooks: Hooks,
+    loads: LoadRecorder,
+    retry_interval: u32,
+    spawner: Arc<dyn Spawner>,
+    pinned: Mutex<HashSet<Uuid>>,
+    random_state: RandomState,
+    cache: T,
+}
+
+impl Loader {
+    /// Returns [`LoaderBuilder`] instance
+    pub fn builder() -> LoaderBuilder {
+        LoaderBuilder::new()
+    }
+
+    /// Drops cached assets that are no longer referenced by any [`AssetHandle`]
+    /// according to [`UnloadPolicy`] configured for their types.
+    /// Assets still being loaded are never dropped.
+    ///
+    /// Intended to be called once per frame.
+    /// Loading dropped asset again fetches and decodes it anew.
+    ///
+    /// Sources of assets that failed to decode or build are checked for newer data
+    /// every [`LoaderBuilder::set_retry_interval`] calls.
+    /// Assets not found or failed to be fetched are not checked, use [`Loader::retry`] for them.
+    ///
+    /// Assets ready to be built are reported within [`LoaderBuilder::set_build_budget_for`] budgets,
+    /// higher [`BuildClass`] first. Assets over budget are reported by following calls.
+    ///
+    /// Returns loads finished since previous call and assets dropped by this call,
+    /// e.g. to spawn entities once scene is loaded or show load errors.
+    pub fn maintain(&self) -> MaintainReport {
+        let loads = self.inner.loads.take();
+        let mut evicted = Vec::new();
+        let mut checks = Vec::new();
+        let now = self.inner.unload.clock.now();
+        let pinned = self.inner.pinned.lock();
+
+        for shard in self.inner.cache.iter() {
+            shard.lock().retain(|key, entry| {
+                if self.inner.retry_interval > 0 && entry.failed_data.is_some() {
+                    entry.failed_frames += 1;
+                    if entry.failed_frames >= self.inner.retry_interval {
+                        entry.failed_frames = 0;
+                        let (source, version) = entry.failed_data.take().unwrap();
+                        checks.push((key.clone(), source, version, entry.generation));
+                    }
+                }
+
+                if Arc::strong_count(&entry.refs) > 1
+                    || matches!(entry.state, StateErased::Unloaded)
+                    || pinned.contains(key.uuid())
+                {
+                    entry.unused = 0;
+                    entry.unused_since = None;
+                    return true;
+                }
+
+                let policy = entry
+                    .unload
+                    .unwrap_or_else(|| self.inner.unload.get(entry.type_id));
+
+                let keep = match policy {
+                    UnloadPolicy::Never => true,
+                    UnloadPolicy::Immediate => false,
+                    UnloadPolicy::Delayed { frames } => {
+                        entry.unused += 1;
+                        entry.unused <= frames
+                    }
+                    UnloadPolicy::Expire { ttl } => {
+                        let since = *entry.unused_since.get_or_insert(now);
+                        now.duration_since(since).unwrap_or_default() < ttl
+                    }
+                };
+
+                if !keep {
+                    evicted.push(*key.uuid());
+                }
+                keep
+            });
+        }
+
+        drop(pinned);
+        for (key, source, version, generation) in checks {
+            let loader = self.clone();
+            spawn(
+                &*self.inner.spawner,
+                async move { loader.check_failed(key, source, version, generation).await }
+                    .in_current_span()
+                    .boxed(),
+            );
+        }
+
+        MaintainReport {
+            completed: loads.completed,
+            failed: loads.failed,
+            evicted,
+        }
+    }
+
+    /// Loads failed asset again if its source has data newer than the failed one.
+    async fn check_failed(self, key: Key, source: SourceId, version: u64, generation: u64) {
+        let uuid = *key.uuid();
+        let locale = key.locale().cloned();
+        let priority = Priority::default();
+        let sources = self.inner.sources.snapshot();
+        let result = update_asset(
+            &sources,
+            source,
+            &uuid,
+            locale.as_deref(),
+            version,
+            &priority,
+        )
+        .await;
+
+        let (key_hash, shard) = self.shard(&uuid, locale.as_deref());
+        let mut locked_shard = shard.lock();
+        let entry = match locked_shard
+            .raw_entry_mut()
+            .from_hash(key_hash, |k| k.eq_key(&uuid, locale.as_deref()))
+        {
+            RawEntryMut::Occupied(entry) => entry.into_mut(),
+            RawEntryMut::Vacant(_) => return,
+        };
+
+        // Asset may be retried since check started.
+        if entry.generation != generation {
+            return;
+        }
+
+        match result {
+            Ok(Some(data)) => {
+                let retry = match entry.reset_failed() {
+                    Some(retry) => retry,
+                    None => return,
+                };
+                let priority = entry.priority.clone();
+                drop(locked_shard);
+
+                tracing::debug!("Failed asset `{}` has newer data, loading again", uuid);
+                retry(&self, &uuid, locale, priority, Some(data));
+            }
+            Ok(None) => entry.failed_data = Some((source, version)),
+            Err(err) => {
+                tracing::debug!("Failed to check newer data of asset `{}`: {}", uuid, err);
+                entry.failed_data = Some((source, version));
+            }
+        }
+    }
+
+    /// Loads again asset with specified uuid that failed to load or was not found,
+    /// e.g. after file is fixed or network connection is recovered.
+    /// Asset is loaded again for every locale it failed for.
+    ///
+    /// This is the only way to recover assets not found or failed to be fetched from sources,
+    /// as [`Loader::maintain`] only checks sources of assets that failed to decode or build.
+    ///
+    /// Handles resolved before keep the failure,
+    /// while following loads wait for new attempt and report bumped [`AssetResult::generation`].
+    /// Returns `false` if no failed asset with the uuid is cached.
+    pub fn retry(&self, uuid: &Uuid) -> bool {
+        let mut retries = Vec::new();
+        for shard in self.inner.cache.iter() {
+            for (key, entry) in shard.lock().iter_mut() {
+                if key.uuid() == uuid {
+                    if let Some(retry) = entry.reset_failed() {
+                        retries.push((retry, key.locale().cloned(), entry.priority.clone()));
+                    }
+                }
+            }
+        }
+
+        let retried = !retries.is_empty();
+        for (retry, locale, priority) in retries {
+            tracing::debug!("Loading failed asset `{}` again", uuid);
+            retry(self, uuid, locale, priority, None);
+        }
+        retried
+    }
+
+    /// Forces following loads of the asset to use provided data instead of querying sources,
+    /// e.g. to try asset tweaks in game without touching the treasury.
+    /// Data is used for all locales and is decoded as of current [`Asset::SCHEMA_VERSION`].
+    ///
+    /// Cached asset without locale is reloaded with provided data as with [`Loader::reload`],
+    /// and failed one is loaded again, bumping [`AssetResult::generation`].
+    pub fn override_asset(&self, uuid: &Uuid, bytes: impl Into<Bytes>) {
+        self.inner.overrides.insert(*uuid, bytes.into());
+        self.refresh(uuid);
+    }
+
+    /// Overrides asset with content of the file as with [`Loader::override_asset`].
+    pub fn override_asset_file(&self, uuid: &Uuid, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let bytes = std::fs::read(path)?;
+        self.override_asset(uuid, bytes);
+        Ok(())
+    }
+
+    /// Removes override set with [`Loader::override_asset`].
+    /// Cached asset is reloaded from sources.
+    /// Returns `false` if the asset is not overridden.
+    pub fn remove_override(&self, uuid: &Uuid) -> bool {
+        let removed = self.inner.overrides.remove(uuid);
+        if removed {
+            self.refresh(uuid);
+        }
+        removed
+    }
+
+    /// Reloads cached asset without locale and loads failed asset again.
+    fn refresh(&self, uuid: &Uuid) {
+        let mut reloads = Vec::new();
+        let mut retries = Vec::new();
+        for shard in self.inner.cache.iter() {
+            for (key, entry) in shard.lock().iter_mut() {
+                if key.uuid() == uuid {
+                    if let Some(retry) = entry.reset_failed() {
+                        retries.push((retry, key.locale().cloned(), entry.priority.clone()));
+                    } else if key.locale().is_none() && matches!(entry.state, StateErased::Typed(_))
+                    {
+                        reloads.push(entry.reload);
+                    }
+                }
+            }
+        }
+
+        for reload in reloads {
+            reload(self, uuid);
+        }
+        for (retry, locale, priority) in retries {
+            retry(self, uuid, locale, priority, None);
+        }
+    }
+
+    /// Adds source to the live loader with lowest priority.
+    ///
+    /// Assets cached or being loaded are not affected,
+    /// following loads query the source after existing ones.
+    /// Use to switch sources at runtime, e.g. fall back to local pack
+    /// when connection to asset server drops.
+    pub fn add_source(&self, source: impl Source) -> SourceId {
+        self.inner.sources.add(Box::new(source), None)
+    }
+
+    /// Adds source to the live loader with lowest priority and uuid prefix.
+    /// See [`LoaderBuilder::add_prefixed`].
+    pub fn add_prefixed_source(&self, prefix: &str, source: impl Source) -> SourceId {
+        self.inner
+            .sources
+            .add(Box::new(source), Some(prefix_mask(prefix)))
+    }
+
+    /// Returns uuid that addresses asset of the source added with the prefix,
+    /// even if sources with higher priority have asset with the same uuid.
+    /// See [`LoaderBuilder::add_prefixed`].
+    pub fn qualify(prefix: &str, uuid: &Uuid) -> Uuid {
+        apply_mask(uuid, &prefix_mask(prefix))
+    }
+
+    /// Returns source that served data of loaded asset.
+    /// Returns `None` if asset is not loaded.
+    pub fn source_of(&self, uuid: &Uuid) -> Option<SourceId> {
+        let (key_hash, shard) = self.shard(uuid, None);
+        let locked_shard = shard.lock();
+        let (_, entry) = locked_shard
+            .raw_entry()
+            .from_hash(key_hash, |k| k.eq_key(uuid, None))?;
+        entry.source
+    }
+
+    /// Removes source from the live loader.
+    ///
+    /// Loads in flight complete against removed source.
+    /// Assets loaded from it stay cached and are reloaded from remaining sources.
+    /// Returns `false` if there is no such source.
+    pub fn remove_source(&self, id: SourceId) -> bool {
+        self.inner.sources.remove(id)
+    }
+
+    /// Returns ids of loader sources in priority order.
+    pub fn sources(&self) -> Vec<SourceId> {
+        self.inner.sources.ids()
+    }
+
+    /// Starts recording asset data served by sources, in order,
+    /// replacing recording in progress.
+    /// Recorded session is replayed with [`ReplaySource`],
+    /// e.g. to reproduce load-order dependent bugs.
+    ///
+    /// [`ReplaySource`]: crate::source::replay::ReplaySource
+    pub fn start_recording(&self) {
+        *self.inner.session.lock() = Some(Recording::new());
+    }
 
-struct Inner<T: ?Sized> {
-    sources: Arc<[Box<dyn AnySource>]>,
-    random_state: RandomState,
-    cache: T,
-}
+    /// Stops recording started with [`Loader::start_recording`] and returns recorded session.
+    /// Returns `None` if not recording.
+    pub fn stop_recording(&self) -> Option<Recording> {
+        self.inner.session.lock().take()
+    }
 
-impl Loader {
-    /// Returns [`LoaderBuilder`] instance
-    pub fn builder() -> LoaderBuilder {
-        LoaderBuilder::new()
+    /// Returns statistics of the loader,
+    /// including slowest decode and build executions
+    /// longer than threshold set with [`LoaderBuilder::set_slow_threshold`].
+    pub fn stats(&self) -> LoaderStats {
+        self.inner.hooks.watchdog().stats()
+    }
+
+    /// Returns uuids of all assets requested since the loader was built
+    /// that no source has, in no particular order.
+    ///
+    /// Useful to find dangling references, e.g. by checking returned uuids
+    /// with `Treasury::verify`.
+    /// Assets found later by [`Loader::retry`] are still reported.
+    pub fn missing_assets(&self) -> Vec<Uuid> {
+        self.inner.loads.missing()
+    }
+
+    /// Pins asset with specified uuid.
+    /// Pinned assets of any type and locale are never dropped by [`Loader::maintain`],
+    /// regardless of [`UnloadPolicy`].
+    pub fn pin(&self, uuid: &Uuid) {
+        self.inner.pinned.lock().insert(*uuid);
+    }
+
+    /// Unpins asset with specified uuid.
+    pub fn unpin(&self, uuid: &Uuid) {
+        self.inner.pinned.lock().remove(uuid);
+    }
+
+    /// Returns cached assets still referenced by handles,
+    /// along with places where those handles were created.
+    ///
+    /// Intended to be called after level is unloaded
+    /// and all its handles are expected to be dropped.
+    #[cfg(feature = "leak-detector")]
+    pub fn report_leaks(&self) -> Vec<crate::leak::LeakedAsset> {
+        let mut leaks = Vec::new();
+        for shard in self.inner.cache.iter() {
+            for (key, entry) in shard.lock().iter() {
+                let sites = entry.sites.collect();
+                if !sites.is_empty() {
+                    leaks.push(crate::leak::LeakedAsset {
+                        uuid: *key.uuid(),
+                        locale: key.locale().cloned(),
+                        type_name: entry.type_name,
+                        sites,
+                    });
+                }
+            }
+        }
+        leaks
     }
 
     /// Reads raw bytes with provided key
-    pub fn read(&self, uuid: &Uuid) -> impl Future<Output = Result<Box<[u8]>, Error>> {
+    pub fn read(&self, uuid: &Uuid) -> impl Future<Output = Result<Bytes, Error>> {
         let inner = Arc::clone(&self.inner);
         let uuid = *uuid;
         async move {
-            Ok(load_asset(&inner.sources, &uuid)
-                .await?
-                .ok_or_else(|| Error::new(NotFound))?
-                .bytes)
+            Ok(
+                load_asset(&inner.sources.snapshot(), &uuid, None, &Priority::default())
+                    .await?
+                    .ok_or_else(|| Error::new(NotFound))?
+                    .bytes,
+            )
         }
     }
 
@@ -468,150 +2631,459 @@ impl Loader {
     ///
     /// It asset was previously requested it will not be re-loaded,
     /// but handle to shared state will be returned instead,
-    /// even if first load was not successful or different format was used.
+    /// even if first load was not successful.
+    /// Handle resolves to error for which [`Error::is_type_mismatch`] returns `true`
+    /// if asset was previously requested as different type.
     #[tracing::instrument(skip(self))]
+    #[track_caller]
     pub fn load<A>(&self, uuid: &Uuid) -> AssetHandle<A>
     where
         A: Asset,
     {
+        self.load_with_data(uuid, None, None, None)
+    }
+
+    /// Load asset with specified uuid as [`Loader::load`] does,
+    /// attaching streaming hint that orders its fetch
+    /// among fetches waiting for a source with limited concurrency.
+    ///
+    /// If asset is already being loaded, hint replaces its current priority.
+    #[tracing::instrument(skip(self))]
+    #[track_caller]
+    pub fn load_with_hint<A>(&self, uuid: &Uuid, hint: StreamingHint) -> AssetHandle<A>
+    where
+        A: Asset,
+    {
+        self.load_with_data(uuid, None, None, Some(hint))
+    }
+
+    /// Load asset with specified uuid as [`Loader::load`] does,
+    /// setting policy for dropping it from the cache once unreferenced,
+    /// e.g. [`UnloadPolicy::Expire`] for assets loaded for transient purposes.
+    ///
+    /// Policy overrides one configured for asset type
+    /// and replaces policy set by previous loads of the asset.
+    #[tracing::instrument(skip(self))]
+    #[track_caller]
+    pub fn load_with_unload_policy<A>(&self, uuid: &Uuid, policy: UnloadPolicy) -> AssetHandle<A>
+    where
+        A: Asset,
+    {
+        let handle = self.load_with_data(uuid, None, None, None);
+
+        let (key_hash, shard) = self.shard(uuid, None);
+        if let RawEntryMut::Occupied(entry) = shard
+            .lock()
+            .raw_entry_mut()
+            .from_hash(key_hash, |k| k.eq_key(uuid, None))
+        {
+            let entry = entry.into_mut();
+            if entry.type_id == TypeId::of::<A>() {
+                entry.unload = Some(policy);
+            }
+        }
+        handle
+    }
+
+    /// Load asset with specified uuid for specified locale and returns handle
+    /// that can be used to access assets once it is loaded.
+    ///
+    /// Sources resolve locale with fallback to less specific locales
+    /// and finally to asset data without locale.
+    /// E.g. `pt-BR` falls back to `pt` and then to default data.
+    /// `None` locale is equivalent to [`Loader::load`].
+    ///
+    /// Asset loaded for different locales is cached separately.
+    #[tracing::instrument(skip(self))]
+    #[track_caller]
+    pub fn load_localized<A>(&self, uuid: &Uuid, locale: Option<&str>) -> AssetHandle<A>
+    where
+        A: Asset,
+    {
+        let locale = locale.filter(|locale| !locale.is_empty());
+        self.load_with_data(uuid, locale, None, None)
+    }
+
+    /// Starts fetching and decoding asset with specified uuid without returning handle,
+    /// so that following [`Loader::load`] resolves without waiting.
+    /// Asset is built on first [`AssetResult::get`] as usual.
+    ///
+    /// Preloaded asset is not referenced by any handle,
+    /// so it may be dropped by [`Loader::maintain`] according to [`UnloadPolicy`] of its type
+    /// once decoded.
+    #[tracing::instrument(skip(self))]
+    pub fn preload<A>(&self, uuid: &Uuid)
+    where
+        A: Asset,
+    {
+        drop(self.load_with_data::<A>(uuid, None, None, None));
+    }
+
+    /// Load asset with specified uuid without knowing its type.
+    /// Asset type is resolved from native format reported by the source,
+    /// and must be registered with [`LoaderBuilder::add_format`].
+    ///
+    /// Loaded asset shares state with assets loaded with [`Loader::load`]
+    /// using the registered type.
+    #[tracing::instrument(skip(self))]
+    pub fn load_untyped(&self, uuid: &Uuid) -> AnyAssetHandle {
+        let loader = self.clone();
+        let uuid = *uuid;
+
+        AnyAssetHandle(self.spawn_with_result(async move {
+            let data = load_asset(
+                &loader.inner.sources.snapshot(),
+                &uuid,
+                None,
+                &Priority::default(),
+            )
+            .await;
+            loader.load_untyped_data(&uuid, data).await
+        }))
+    }
+
+    /// Loads all assets of named collection without knowing their types,
+    /// as [`Loader::load_untyped`] does.
+    ///
+    /// Order of members is their load priority.
+    /// Data of members is fetched one by one in collection order,
+    /// while decoding runs concurrently.
+    /// Resolves into error if collection is not found in any source.
+    #[tracing::instrument(skip(self))]
+    pub fn load_collection(&self, name: &str) -> CollectionHandle {
+        let loader = self.clone();
+        let name = Box::<str>::from(name);
+
+        CollectionHandle(self.spawn_with_result(async move {
+            let members = load_collection(&loader.inner.sources.snapshot(), &name)
+                .await?
+                .ok_or_else(|| Error::new(NotFound))?;
+
+            let mut results = Vec::with_capacity(members.len());
+            for uuid in members {
+                let data = load_asset(
+                    &loader.inner.sources.snapshot(),
+                    &uuid,
+                    None,
+                    &Priority::default(),
+                )
+                .await;
+                results.push(
+                    loader
+                        .load_untyped_data(&uuid, data)
+                        .map(move |r| (uuid, r)),
+                );
+            }
+
+            Ok(futures::future::join_all(results).await)
+        }))
+    }
+
+    /// Spawns future with configured [`Spawner`] and returns receiver of its output.
+    fn spawn_with_result<T>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> oneshot::Receiver<T>
+    where
+        T: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        spawn(
+            &*self.inner.spawner,
+            async move {
+                let _ = sender.send(future.await);
+            }
+            .in_current_span()
+            .boxed(),
+        );
+        receiver
+    }
+
+    /// Loads and builds asset on the current thread, blocking until done.
+    ///
+    /// Loading tasks started meanwhile from this thread run on it
+    /// instead of configured [`Spawner`], so no async runtime is required.
+    /// Intended for tools and tests.
+    pub fn load_blocking<A, B>(&self, uuid: &Uuid, builder: &mut B) -> Result<A, Error>
+    where
+        A: AssetBuild<B>,
+    {
+        // Loading must start within `block_on` to spawn onto its executor.
+        let mut result = block_on(async { self.load::<A>(uuid).await });
+        result.get(builder).cloned()
+    }
+
+    /// Loads asset as type registered for native format of fetched data.
+    fn load_untyped_data(
+        &self,
+        uuid: &Uuid,
+        data: Result<Option<Data>, Error>,
+    ) -> BoxFuture<'static, AnyAssetResult> {
+        let data = match data {
+            Ok(Some(data)) => data,
+            Ok(None) => {
+                tracing::warn!("Asset data for `{}` is not found", uuid);
+                return futures::future::ready(AnyAssetResult(AnyAssetResultInner::Missing))
+                    .boxed();
+            }
+            Err(err) => {
+                return futures::future::ready(AnyAssetResult(AnyAssetResultInner::Error(err)))
+                    .boxed()
+            }
+        };
+
+        let entry = match &data.format {
+            None => Err(Error::new(UnknownNativeFormat)),
+            Some(format) => self.inner.formats.get(format).ok_or_else(|| {
+                Error::new(UnknownFormat {
+                    format: format.clone(),
+                })
+            }),
+        };
+
+        match entry {
+            Ok(entry) => (entry.load)(self, uuid, Some(data)),
+            Err(err) => {
+                futures::future::ready(AnyAssetResult(AnyAssetResultInner::Error(err))).boxed()
+            }
+        }
+    }
+
+    /// Load asset with specified uuid as type registered for specified native format
+    /// and returns type-erased handle.
+    ///
+    /// Unlike [`Loader::load_untyped`] this does not require source to report
+    /// native format of the asset.
+    #[tracing::instrument(skip(self))]
+    pub fn load_any(&self, uuid: &Uuid, format: &str) -> AnyAssetHandle {
+        let result = match self.inner.formats.get(format) {
+            None => futures::future::ready(AnyAssetResult(AnyAssetResultInner::Error(Error::new(
+                UnknownFormat {
+                    format: format.into(),
+                },
+            ))))
+            .boxed(),
+            Some(entry) => (entry.load)(self, uuid, None),
+        };
+
+        AnyAssetHandle(self.spawn_with_result(result))
+    }
+
+    /// Returns hash of asset key and shard the asset is cached in.
+    fn shard(&self, uuid: &Uuid, locale: Option<&str>) -> (u64, &Shard) {
         // Hash asset key.
         let mut hasher = self.inner.random_state.build_hasher();
-        hash_key::<A, _>(uuid, &mut hasher);
+        hash_key(uuid, locale, &mut hasher);
         let key_hash = hasher.finish();
 
         // Use asset key hash to pick a shard.
         // It will always pick same shard for same key.
         let shards_len = self.inner.cache.len();
-        let shard = &self.inner.cache[(key_hash as usize % shards_len)];
+        (
+            key_hash,
+            &self.inner.cache[(key_hash as usize % shards_len)],
+        )
+    }
 
-        // Lock picked shard.
-        let mut locked_shard = shard.lock();
+    /// Fetches newer version of cached asset data from the source it was loaded from
+    /// and replaces cached asset with one decoded from it.
+    ///
+    /// Results resolved before keep previous asset,
+    /// while following loads build new one and report bumped [`AssetResult::generation`].
+    /// Resolves to `false` if asset is not loaded or source has no newer data.
+    /// Previous asset stays cached if new data fails to decode.
+    #[tracing::instrument(skip(self))]
+    pub fn reload<A>(&self, uuid: &Uuid) -> impl Future<Output = Result<bool, Error>>
+    where
+        A: Asset,
+    {
+        let loader = self.clone();
+        let uuid = *uuid;
 
-        // Find an entry into sharded hashmap.
-        let asset_entry = locked_shard
-            .raw_entry_mut()
-            .from_hash(key_hash, |k| k.eq_key::<A>(uuid));
+        async move {
+            let (key_hash, shard) = loader.shard(&uuid, None);
 
-        match asset_entry {
-            RawEntryMut::Occupied(entry) => match &entry.get().state {
-                // Already queried. See status.
-                StateErased::Error(err) => AssetHandle(AssetHandleInner::Error(err.clone())),
-                StateErased::Missing => AssetHandle(AssetHandleInner::Missing),
-                StateErased::Unloaded => AssetHandle(AssetHandleInner::Pending {
-                    uuid: *uuid,
-                    key_hash,
-                    shard: shard.clone(),
-                }),
-                StateErased::Typed(typed) => {
-                    let typed: &StateTyped<A> = <dyn Any>::downcast_ref(&**typed).unwrap();
-                    match typed {
-                        StateTyped::Asset { asset, .. } => {
-                            AssetHandle(AssetHandleInner::Asset(asset.clone()))
+            let (version, source, priority, generation) = {
+                let mut locked_shard = shard.lock();
+                let entry = match locked_shard
+                    .raw_entry_mut()
+                    .from_hash(key_hash, |k| k.eq_key(&uuid, None))
+                {
+                    RawEntryMut::Vacant(_) => return Ok(false),
+                    RawEntryMut::Occupied(entry) => entry.into_mut(),
+                };
+
+                if entry.type_id != TypeId::of::<A>() {
+                    return Err(Error::new(TypeMismatch {
+                        uuid,
+                        requested: type_name::<A>(),
+                        cached: entry.type_name,
+                    }));
+                }
+
+                match &entry.state {
+                    StateErased::Typed(typed) => match typed.downcast_ref().unwrap() {
+                        StateTyped::<A>::Asset {
+                            version, source, ..
                         }
-                        StateTyped::Decoded { .. } => AssetHandle(AssetHandleInner::Pending {
-                            uuid: *uuid,
-                            key_hash,
-                            shard: shard.clone(),
-                        }),
+                        | StateTyped::<A>::Decoded {
+                            version, source, ..
+                        } => (*version, *source, entry.priority.clone(), entry.generation),
+                    },
+                    _ => return Ok(false),
+                }
+            };
+
+            let data = match loader.inner.overrides.get(&uuid) {
+                Some(data) if source == OVERRIDES_SOURCE && data.version == version => None,
+                Some(data) => Some(Data::new(&uuid, data, OVERRIDES_SOURCE)),
+                // Asset is no longer overridden and is loaded from sources anew.
+                None if source == OVERRIDES_SOURCE => {
+                    load_asset(&loader.inner.sources.snapshot(), &uuid, None, &priority).await?
+                }
+                None => {
+                    let sources = loader.inner.sources.snapshot();
+                    update_asset(&sources, source, &uuid, None, version, &priority).await?
+                }
+            };
+
+            let data = match data {
+                None => return Ok(false),
+                Some(data) => data,
+            };
+
+            let hooks = &loader.inner.hooks;
+            hooks.on_bytes_fetched(&uuid, type_name::<A>(), data.bytes.len());
+
+            // Decoding may panic before returning future.
+            let decoder = loader.inner.decoders.get(TypeId::of::<A>());
+            let decode = decode_asset::<A>(loader.clone(), uuid, data.bytes, data.schema);
+            let decoded = match decoder.run(AssertUnwindSafe(decode).catch_unwind()).await {
+                Ok(decoded) => decoded,
+                Err(payload) => Err(Error::new(BuildPanicked::new(uuid, payload))),
+            };
+
+            let decoded = match decoded {
+                Ok(decoded) => decoded,
+                Err(err) => {
+                    let err = err.named(uuid, data.name.as_deref());
+                    hooks.on_failed(&uuid, type_name::<A>(), &err);
+                    return Err(err);
+                }
+            };
+            hooks.on_decoded(&uuid, type_name::<A>());
+
+            let mut locked_shard = shard.lock();
+            match locked_shard
+                .raw_entry_mut()
+                .from_hash(key_hash, |k| k.eq_key(&uuid, None))
+            {
+                RawEntryMut::Occupied(mut entry) => {
+                    let entry = entry.get_mut();
+
+                    // Asset may be dropped and loaded anew, possibly as other type, meanwhile.
+                    if !matches!(entry.state, StateErased::Typed(_))
+                        || entry.type_id != TypeId::of::<A>()
+                        || entry.generation != generation
+                    {
+                        return Ok(false);
                     }
+
+                    entry.state = StateErased::Typed(Box::new(StateTyped::<A>::Decoded {
+                        decoded: Some(decoded),
+                        version: data.version,
+                        source: data.source,
+                    }));
+                    entry.source = Some(data.source);
+                    entry.generation += 1;
+                    tracing::debug!("Asset `{}` reloaded", uuid);
+                    Ok(true)
                 }
-            },
-            RawEntryMut::Vacant(entry) => {
-                let asset_key = Key::new::<A>(*uuid);
-                // Register query
-                let _ = entry.insert_hashed_nocheck(
-                    key_hash,
-                    asset_key,
-                    AssetEntry {
-                        state: StateErased::Unloaded,
-                        wakers: Vec::new(),
-                    },
-                );
-                drop(locked_shard);
+                RawEntryMut::Vacant(_) => Ok(false),
+            }
+        }
+    }
 
-                tokio::spawn({
-                    let uuid = *uuid;
-                    let inner = self.inner.clone();
-                    let shard = shard.clone();
-
-                    async move {
-                        match load_asset(&inner.sources, &uuid).await {
-                            Ok(Some(data)) => {
-                                tracing::debug!("Asset data for `{}` loaded", uuid);
-
-                                match A::decode(data.bytes, &Loader { inner }).await {
-                                    Ok(decoded) => {
-                                        let mut locked_shard = shard.lock();
-                                        let asset_entry = locked_shard
-                                            .raw_entry_mut()
-                                            .from_hash(key_hash, |k| k.eq_key::<A>(&uuid));
-
-                                        match asset_entry {
-                                            RawEntryMut::Vacant(_) => {
-                                                tracing::trace!("Asset already removed");
-                                            }
-                                            RawEntryMut::Occupied(mut entry) => {
-                                                match &mut entry.get_mut().state {
-                                                    StateErased::Unloaded => {
-                                                        entry.get_mut().state = StateErased::Typed(
-                                                            Box::new(StateTyped::<A>::Decoded {
-                                                                decoded: Some(decoded),
-                                                                version: data.version,
-                                                                source: data.source,
-                                                            }),
-                                                        );
-                                                        let wakers = std::mem::replace(
-                                                            &mut entry.get_mut().wakers,
-                                                            Vec::new(),
-                                                        );
-                                                        for waker in wakers {
-                                                            waker.wake();
-                                                        }
-                                                        drop(locked_shard);
-                                                    }
-                                                    _ => panic!("Unexpected asset state"),
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Err(err) => {
-                                        let mut locked_shard = shard.lock();
-                                        let asset_entry = locked_shard
-                                            .raw_entry_mut()
-                                            .from_hash(key_hash, |k| k.eq_key::<A>(&uuid));
-
-                                        match asset_entry {
-                                            RawEntryMut::Vacant(_) => {
-                                                tracing::trace!("Asset already removed");
-                                            }
-                                            RawEntryMut::Occupied(mut entry) => {
-                                                match &mut entry.get_mut().state {
-                                                    StateErased::Unloaded => {
-                                                        entry.get_mut().state =
-                                                            StateErased::Error(Error::new(err));
-                                                        let wakers = std::mem::replace(
-                                                            &mut entry.get_mut().wakers,
-                                                            Vec::new(),
-                                                        );
-                                                        for waker in wakers {
-                                                            waker.wake();
-                                                        }
-                                                        drop(locked_shard);
-                                                    }
-                                                    _ => panic!("Unexpected asset state"),
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
+    /// Spawns reload of the asset, logging failure.
+    fn spawn_reload<A>(&self, uuid: &Uuid)
+    where
+        A: Asset,
+    {
+        let uuid = *uuid;
+        let reload = self.reload::<A>(&uuid);
+        spawn(
+            &*self.inner.spawner,
+            Box::pin(async move {
+                if let Err(err) = reload.await {
+                    tracing::warn!("Failed to reload asset `{}`: {:#}", uuid, err);
+                }
+            }),
+        );
+    }
+
+    /// Spawns task fetching and decoding asset into cache entry in `Unloaded` state,
+    /// using already fetched data if provided.
+    fn spawn_load<A>(
+        &self,
+        uuid: &Uuid,
+        locale: Option<Arc<str>>,
+        priority: Priority,
+        data: Option<Data>,
+    ) where
+        A: Asset,
+    {
+        let (key_hash, shard) = self.shard(uuid, locale.as_deref());
+        self.inner.hooks.on_load_start(uuid, type_name::<A>());
+
+        spawn(&*self.inner.spawner, {
+            let uuid = *uuid;
+            let locale = locale.clone();
+            let loader = self.clone();
+            let hooks = self.inner.hooks.clone();
+            let shard = shard.clone();
+            let priority = priority.clone();
+
+            async move {
+                let result = match data {
+                    Some(data) => Ok(Some(data)),
+                    None => {
+                        load_asset(
+                            &loader.inner.sources.snapshot(),
+                            &uuid,
+                            locale.as_deref(),
+                            &priority,
+                        )
+                        .await
+                    }
+                };
+
+                match result {
+                    Ok(Some(data)) => {
+                        match &data.name {
+                            None => tracing::debug!("Asset data for `{}` loaded", uuid),
+                            Some(name) => {
+                                tracing::debug!("Asset data for '{}' (`{}`) loaded", name, uuid)
                             }
-                            Ok(None) => {
-                                tracing::warn!("Asset data for `{}` is not found", uuid);
+                        }
+                        hooks.on_bytes_fetched(&uuid, type_name::<A>(), data.bytes.len());
+
+                        // Decoding may panic before returning future.
+                        let decoder = loader.inner.decoders.get(TypeId::of::<A>());
+                        let decode = decode_asset::<A>(loader, uuid, data.bytes, data.schema);
+                        let decoded =
+                            match decoder.run(AssertUnwindSafe(decode).catch_unwind()).await {
+                                Ok(decoded) => decoded,
+                                Err(payload) => Err(Error::new(BuildPanicked::new(uuid, payload))),
+                            };
+
+                        match decoded {
+                            Ok(decoded) => {
+                                hooks.on_decoded(&uuid, type_name::<A>());
 
                                 let mut locked_shard = shard.lock();
                                 let asset_entry = locked_shard
                                     .raw_entry_mut()
-                                    .from_hash(key_hash, |k| k.eq_key::<A>(&uuid));
+                                    .from_hash(key_hash, |k| k.eq_key(&uuid, locale.as_deref()));
 
                                 match asset_entry {
                                     RawEntryMut::Vacant(_) => {
@@ -620,7 +3092,14 @@ impl Loader {
                                     RawEntryMut::Occupied(mut entry) => {
                                         match &mut entry.get_mut().state {
                                             StateErased::Unloaded => {
-                                                entry.get_mut().state = StateErased::Missing;
+                                                entry.get_mut().state = StateErased::Typed(
+                                                    Box::new(StateTyped::<A>::Decoded {
+                                                        decoded: Some(decoded),
+                                                        version: data.version,
+                                                        source: data.source,
+                                                    }),
+                                                );
+                                                entry.get_mut().source = Some(data.source);
                                                 let wakers = std::mem::replace(
                                                     &mut entry.get_mut().wakers,
                                                     Vec::new(),
@@ -636,10 +3115,19 @@ impl Loader {
                                 }
                             }
                             Err(err) => {
+                                let err = err.named(uuid, data.name.as_deref());
+                                match err.asset_name() {
+                                    None => {
+                                        tracing::warn!("Failed to load asset `{}`: {:#}", uuid, err)
+                                    }
+                                    Some(_) => tracing::warn!("{}: {:#}", err, err.kind()),
+                                }
+                                hooks.on_failed(&uuid, type_name::<A>(), &err);
+
                                 let mut locked_shard = shard.lock();
                                 let asset_entry = locked_shard
                                     .raw_entry_mut()
-                                    .from_hash(key_hash, |k| k.eq_key::<A>(&uuid));
+                                    .from_hash(key_hash, |k| k.eq_key(&uuid, locale.as_deref()));
 
                                 match asset_entry {
                                     RawEntryMut::Vacant(_) => {
@@ -649,6 +3137,8 @@ impl Loader {
                                         match &mut entry.get_mut().state {
                                             StateErased::Unloaded => {
                                                 entry.get_mut().state = StateErased::Error(err);
+                                                entry.get_mut().failed_data =
+                                                    Some((data.source, data.version));
                                                 let wakers = std::mem::replace(
                                                     &mut entry.get_mut().wakers,
                                                     Vec::new(),
@@ -665,14 +3155,232 @@ impl Loader {
                             }
                         }
                     }
-                    .in_current_span()
-                });
+                    Ok(None) => {
+                        tracing::warn!("Asset data for `{}` is not found", uuid);
+                        hooks.on_failed(&uuid, type_name::<A>(), &Error::new(NotFound));
 
-                AssetHandle(AssetHandleInner::Pending {
-                    uuid: *uuid,
+                        let mut locked_shard = shard.lock();
+                        let asset_entry = locked_shard
+                            .raw_entry_mut()
+                            .from_hash(key_hash, |k| k.eq_key(&uuid, locale.as_deref()));
+
+                        match asset_entry {
+                            RawEntryMut::Vacant(_) => {
+                                tracing::trace!("Asset already removed");
+                            }
+                            RawEntryMut::Occupied(mut entry) => match &mut entry.get_mut().state {
+                                StateErased::Unloaded => {
+                                    entry.get_mut().state = StateErased::Missing;
+                                    let wakers =
+                                        std::mem::replace(&mut entry.get_mut().wakers, Vec::new());
+                                    for waker in wakers {
+                                        waker.wake();
+                                    }
+                                    drop(locked_shard);
+                                }
+                                _ => panic!("Unexpected asset state"),
+                            },
+                        }
+                    }
+                    Err(err) => {
+                        hooks.on_failed(&uuid, type_name::<A>(), &err);
+
+                        let mut locked_shard = shard.lock();
+                        let asset_entry = locked_shard
+                            .raw_entry_mut()
+                            .from_hash(key_hash, |k| k.eq_key(&uuid, locale.as_deref()));
+
+                        match asset_entry {
+                            RawEntryMut::Vacant(_) => {
+                                tracing::trace!("Asset already removed");
+                            }
+                            RawEntryMut::Occupied(mut entry) => match &mut entry.get_mut().state {
+                                StateErased::Unloaded => {
+                                    entry.get_mut().state = StateErased::Error(err);
+                                    let wakers =
+                                        std::mem::replace(&mut entry.get_mut().wakers, Vec::new());
+                                    for waker in wakers {
+                                        waker.wake();
+                                    }
+                                    drop(locked_shard);
+                                }
+                                _ => panic!("Unexpected asset state"),
+                            },
+                        }
+                    }
+                }
+            }
+            .in_current_span()
+            .boxed()
+        });
+    }
+
+    /// Loads typed asset, using already fetched data if provided.
+    #[track_caller]
+    fn load_with_data<A>(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        data: Option<Data>,
+        hint: Option<StreamingHint>,
+    ) -> AssetHandle<A>
+    where
+        A: Asset,
+    {
+        let (key_hash, shard) = self.shard(uuid, locale);
+
+        // Lock picked shard.
+        let mut locked_shard = shard.lock();
+
+        // Find an entry into sharded hashmap.
+        let asset_entry = locked_shard
+            .raw_entry_mut()
+            .from_hash(key_hash, |k| k.eq_key(uuid, locale));
+
+        match asset_entry {
+            RawEntryMut::Occupied(entry) => {
+                let entry = entry.into_mut();
+                if entry.type_id != TypeId::of::<A>() {
+                    let err = Error::new(TypeMismatch {
+                        uuid: *uuid,
+                        requested: type_name::<A>(),
+                        cached: entry.type_name,
+                    });
+                    return AssetHandle::new(
+                        *uuid,
+                        0,
+                        HandleRefs::detached(),
+                        AssetHandleInner::Error(err),
+                    );
+                }
+
+                if let Some(hint) = hint {
+                    entry.priority.set(hint);
+                }
+
+                let refs = HandleRefs::new(entry);
+                let generation = entry.generation;
+                match &entry.state {
+                    // Already queried. See status.
+                    StateErased::Error(err) => AssetHandle::new(
+                        *uuid,
+                        generation,
+                        refs,
+                        AssetHandleInner::Error(err.clone()),
+                    ),
+                    StateErased::Missing => {
+                        AssetHandle::new(*uuid, generation, refs, AssetHandleInner::Missing)
+                    }
+                    // Waiting for asset that waits for this one would never finish.
+                    StateErased::Unloaded if self.chain.is_some() => match self.find_cycle(uuid) {
+                        Some(path) => {
+                            tracing::error!("Asset `{}` depends on itself", uuid);
+                            AssetHandle::new(
+                                *uuid,
+                                generation,
+                                refs,
+                                AssetHandleInner::Error(Error::new(CyclicDependency { path })),
+                            )
+                        }
+                        None => AssetHandle::new(
+                            *uuid,
+                            generation,
+                            refs,
+                            AssetHandleInner::Pending {
+                                uuid: *uuid,
+                                locale: locale.map(Arc::from),
+                                key_hash,
+                                shard: shard.clone(),
+                                hooks: self.inner.hooks.clone(),
+                                priority: entry.priority.clone(),
+                            },
+                        ),
+                    },
+                    StateErased::Unloaded => AssetHandle::new(
+                        *uuid,
+                        generation,
+                        refs,
+                        AssetHandleInner::Pending {
+                            uuid: *uuid,
+                            locale: locale.map(Arc::from),
+                            key_hash,
+                            shard: shard.clone(),
+                            hooks: self.inner.hooks.clone(),
+                            priority: entry.priority.clone(),
+                        },
+                    ),
+                    StateErased::Typed(typed) => {
+                        let typed: &StateTyped<A> = <dyn Any>::downcast_ref(&**typed).unwrap();
+                        match typed {
+                            StateTyped::Asset { asset, .. } => AssetHandle::new(
+                                *uuid,
+                                generation,
+                                refs,
+                                AssetHandleInner::Asset(asset.clone()),
+                            ),
+                            StateTyped::Decoded { .. } => AssetHandle::new(
+                                *uuid,
+                                generation,
+                                refs,
+                                AssetHandleInner::Pending {
+                                    uuid: *uuid,
+                                    locale: locale.map(Arc::from),
+                                    key_hash,
+                                    shard: shard.clone(),
+                                    hooks: self.inner.hooks.clone(),
+                                    priority: entry.priority.clone(),
+                                },
+                            ),
+                        }
+                    }
+                }
+            }
+            RawEntryMut::Vacant(entry) => {
+                let priority = hint.map(Priority::new).unwrap_or_default();
+                let locale: Option<Arc<str>> = locale.map(Arc::from);
+                let asset_key = Key::new(*uuid, locale.clone());
+                // Register query
+                let (_, entry) = entry.insert_hashed_nocheck(
                     key_hash,
-                    shard: shard.clone(),
-                })
+                    asset_key,
+                    AssetEntry {
+                        state: StateErased::Unloaded,
+                        wakers: Vec::new(),
+                        type_id: TypeId::of::<A>(),
+                        type_name: type_name::<A>(),
+                        refs: Arc::new(()),
+                        unused: 0,
+                        unused_since: None,
+                        unload: None,
+                        priority: priority.clone(),
+                        generation: 0,
+                        retry: Loader::spawn_load::<A>,
+                        reload: Loader::spawn_reload::<A>,
+                        failed_data: None,
+                        failed_frames: 0,
+                        source: None,
+                        #[cfg(feature = "leak-detector")]
+                        sites: Default::default(),
+                    },
+                );
+                let refs = HandleRefs::new(entry);
+                drop(locked_shard);
+
+                self.spawn_load::<A>(uuid, locale.clone(), priority.clone(), data);
+
+                AssetHandle::new(
+                    *uuid,
+                    0,
+                    refs,
+                    AssetHandleInner::Pending {
+                        uuid: *uuid,
+                        locale,
+                        key_hash,
+                        shard: shard.clone(),
+                        hooks: self.inner.hooks.clone(),
+                        priority,
+                    },
+                )
             }
         }
     }
@@ -1,6 +1,18 @@
+pub mod caching;
+pub mod embedded;
+pub mod pack;
+pub mod replay;
+pub mod throttled;
 pub mod treasury;
 
+mod staging;
+
+#[cfg(feature = "zip")]
+pub mod zip;
+
 use {
+    bytes::Bytes,
+    futures::future::BoxFuture,
     std::{error::Error, future::Future},
     uuid::Uuid,
 };
@@ -8,12 +20,45 @@ use {
 /// Asset data loaded from [`Source`].
 pub struct AssetData {
     /// Serialized asset data.
-    pub bytes: Box<[u8]>,
+    /// Reference-counted, so decoders can keep or slice it without copying.
+    pub bytes: Bytes,
 
     /// Opaque version for asset.
     /// It can only by interpreted by [`Source`]
     /// that returned this [`AssetData`] instance.
+    /// Source must report different version whenever asset data changes.
     pub version: u64,
+
+    /// Native format of the asset data, if known to the [`Source`].
+    /// Required to load asset with [`Loader::load_untyped`].
+    ///
+    /// [`Loader::load_untyped`]: crate::Loader::load_untyped
+    pub format: Option<Box<str>>,
+
+    /// Signature of the asset data, if known to the [`Source`].
+    /// Required for sources added with [`LoaderBuilder::add_signed`].
+    ///
+    /// [`LoaderBuilder::add_signed`]: crate::LoaderBuilder::add_signed
+    pub signature: Option<Box<[u8]>>,
+
+    /// Locale of the asset data, if localized.
+    /// May be less specific than requested locale it falls back from.
+    /// Signature covers the locale, so it must be reported by signed sources.
+    pub locale: Option<Box<str>>,
+
+    /// Human-readable name of the asset, if known to the [`Source`].
+    /// Errors and logs refer to the asset by this name besides uuid.
+    pub name: Option<Box<str>>,
+
+    /// Schema version of the asset data, if known to the [`Source`].
+    /// Data of unknown schema is decoded as of [`Asset::SCHEMA_VERSION`].
+    ///
+    /// [`Asset::SCHEMA_VERSION`]: crate::Asset::SCHEMA_VERSION
+    pub schema: Option<u32>,
+
+    /// Whether the asset is deprecated and should not be referenced anymore.
+    /// Loader warns when deprecated asset is loaded.
+    pub deprecated: bool,
 }
 
 /// Abstract source for asset raw data.
@@ -27,8 +72,33 @@ pub trait Source: Send + Sync + 'static {
     /// Load asset data from this source.
     /// Returns `Ok(Some(asset_data))` if asset is loaded successfully.
     /// Returns `Ok(None)` if asset is not found, allowing checking other sources.
-    fn load(&self, uuid: &Uuid) -> Self::Fut;
+    ///
+    /// If `locale` is specified, source should return data for that locale,
+    /// falling back to less specific locales (e.g. `pt-BR` to `pt`)
+    /// and then to data without locale.
+    /// Sources without localized assets may ignore it.
+    fn load(&self, uuid: &Uuid, locale: Option<&str>) -> Self::Fut;
 
     /// Update asset data if newer is available.
-    fn update(&self, uuid: &Uuid, version: u64) -> Self::Fut;
+    /// `version` is the one reported with previously loaded data.
+    /// Returns `Ok(None)` if data is unchanged.
+    fn update(&self, uuid: &Uuid, locale: Option<&str>, version: u64) -> Self::Fut;
+
+    /// Returns members of named collection in order.
+    /// Returns `Ok(None)` if collection is not found, allowing checking other sources.
+    ///
+    /// Sources without collections may leave default implementation.
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Self::Error>> {
+        let _ = name;
+        Box::pin(futures::future::ready(Ok(None)))
+    }
+
+    /// Returns namespace of the project assets of this source belong to, if known.
+    /// Loaders with namespace set by [`LoaderBuilder::set_namespace`]
+    /// reject assets from sources of other projects.
+    ///
+    /// [`LoaderBuilder::set_namespace`]: crate::LoaderBuilder::set_namespace
+    fn namespace(&self) -> Option<Uuid> {
+        None
+    }
 }
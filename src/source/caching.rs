@@ -0,0 +1,290 @@
+use {
+    crate::source::{AssetData, Source},
+    futures::future::{BoxFuture, FutureExt as _},
+    hashbrown::HashMap,
+    parking_lot::Mutex,
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::SystemTime,
+    },
+    uuid::Uuid,
+};
+
+type CacheKey = (Uuid, Option<Box<str>>);
+
+struct CacheEntry {
+    version: u64,
+    len: u64,
+
+    /// Value of [`Cache::clock`] when entry was last used.
+    used: u64,
+}
+
+struct Cache {
+    root: Box<Path>,
+    max_bytes: u64,
+    total_bytes: u64,
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+}
+
+/// Source decorator that persists asset data fetched from wrapped source
+/// in a local directory, so each asset version is fetched only once across runs.
+///
+/// Cached data is served without asking wrapped source,
+/// and [`Source::update`] asks wrapped source for newer versions.
+/// Least recently used data is evicted when total size exceeds configured limit.
+///
+/// Failures to access the cache are logged and fall back to wrapped source.
+pub struct CachingSource<S> {
+    source: S,
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl<S> CachingSource<S>
+where
+    S: Source,
+{
+    /// Wraps source with cache in specified directory.
+    /// Directory is created if missing, and data cached by previous runs is reused.
+    /// `max_bytes` bounds total size of cached data.
+    pub fn new(source: S, root: impl AsRef<Path>, max_bytes: u64) -> io::Result<Self> {
+        let root = root.as_ref();
+        fs::create_dir_all(root)?;
+
+        let mut found = Vec::new();
+        for entry in fs::read_dir(root)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let key = match file_name.to_str().and_then(parse_file_name) {
+                None => continue,
+                Some(key) => key,
+            };
+            let meta = entry.metadata()?;
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            found.push((key, meta.len(), modified));
+        }
+
+        // Recently modified files are considered recently used.
+        found.sort_by_key(|(_, _, modified)| *modified);
+
+        let mut cache = Cache {
+            root: root.into(),
+            max_bytes,
+            total_bytes: 0,
+            entries: HashMap::new(),
+            clock: 0,
+        };
+
+        for ((uuid, locale, version), len, _) in found {
+            cache.clock += 1;
+            let old = cache.entries.insert(
+                (uuid, locale.clone()),
+                CacheEntry {
+                    version,
+                    len,
+                    used: cache.clock,
+                },
+            );
+            cache.total_bytes += len;
+
+            // Stale version left by interrupted run.
+            if let Some(old) = old {
+                cache.remove_file(&uuid, locale.as_deref(), old.version, old.len);
+            }
+        }
+
+        cache.evict();
+
+        Ok(CachingSource {
+            source,
+            cache: Arc::new(Mutex::new(cache)),
+        })
+    }
+
+    /// Stores data fetched from wrapped source into the cache.
+    fn cache_fetched(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        fetch: S::Fut,
+    ) -> BoxFuture<'static, Result<Option<AssetData>, S::Error>> {
+        let cache = self.cache.clone();
+        let uuid = *uuid;
+        let locale = locale.map(Box::<str>::from);
+
+        async move {
+            let data = fetch.await?;
+            if let Some(data) = &data {
+                cache.lock().put(&uuid, locale.as_deref(), data);
+            }
+            Ok(data)
+        }
+        .boxed()
+    }
+}
+
+impl Cache {
+    fn path(&self, uuid: &Uuid, locale: Option<&str>, version: u64) -> PathBuf {
+        match locale {
+            None => self.root.join(format!("{}.{}", uuid, version)),
+            Some(locale) => self.root.join(format!("{}.{}.{}", uuid, locale, version)),
+        }
+    }
+
+    fn get(&mut self, uuid: &Uuid, locale: Option<&str>) -> Option<AssetData> {
+        let key = (*uuid, locale.map(Box::from));
+        let entry = self.entries.get_mut(&key)?;
+        self.clock += 1;
+        entry.used = self.clock;
+        let version = entry.version;
+
+        let path = self.path(uuid, locale, version);
+        let result = fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|data| {
+                bincode::deserialize::<(
+                    Option<Box<str>>,
+                    Option<Box<[u8]>>,
+                    Option<Box<str>>,
+                    Option<Box<str>>,
+                    Option<u32>,
+                    bool,
+                    Box<[u8]>,
+                )>(&data)
+                .map_err(|err| err.to_string())
+            });
+
+        match result {
+            Ok((format, signature, locale, name, schema, deprecated, bytes)) => Some(AssetData {
+                bytes: bytes.into(),
+                version,
+                format,
+                signature,
+                locale,
+                name,
+                schema,
+                deprecated,
+            }),
+            Err(err) => {
+                tracing::warn!("Failed to read cached data '{}': {}", path.display(), err);
+                let entry = self.entries.remove(&key).unwrap();
+                self.remove_file(uuid, locale, entry.version, entry.len);
+                None
+            }
+        }
+    }
+
+    fn put(&mut self, uuid: &Uuid, locale: Option<&str>, data: &AssetData) {
+        let path = self.path(uuid, locale, data.version);
+        let result = bincode::serialize(&(
+            &data.format,
+            &data.signature,
+            &data.locale,
+            &data.name,
+            &data.schema,
+            data.deprecated,
+            &*data.bytes,
+        ))
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| {
+            fs::write(&path, &bytes).map_err(|err| err.to_string())?;
+            Ok(bytes.len() as u64)
+        });
+
+        let len = match result {
+            Ok(len) => len,
+            Err(err) => {
+                tracing::warn!("Failed to write cached data '{}': {}", path.display(), err);
+                let _ = fs::remove_file(&path);
+                return;
+            }
+        };
+
+        self.clock += 1;
+        let old = self.entries.insert(
+            (*uuid, locale.map(Box::from)),
+            CacheEntry {
+                version: data.version,
+                len,
+                used: self.clock,
+            },
+        );
+        self.total_bytes += len;
+
+        if let Some(old) = old {
+            if old.version == data.version {
+                self.total_bytes -= old.len;
+            } else {
+                self.remove_file(uuid, locale, old.version, old.len);
+            }
+        }
+
+        self.evict();
+    }
+
+    fn remove_file(&mut self, uuid: &Uuid, locale: Option<&str>, version: u64, len: u64) {
+        self.total_bytes -= len;
+        let path = self.path(uuid, locale, version);
+        if let Err(err) = fs::remove_file(&path) {
+            tracing::warn!("Failed to remove cached data '{}': {}", path.display(), err);
+        }
+    }
+
+    /// Removes least recently used data until total size fits the limit.
+    fn evict(&mut self) {
+        while self.total_bytes > self.max_bytes {
+            let key = match self.entries.iter().min_by_key(|(_, entry)| entry.used) {
+                None => break,
+                Some((key, _)) => key.clone(),
+            };
+            let entry = self.entries.remove(&key).unwrap();
+            self.remove_file(&key.0, key.1.as_deref(), entry.version, entry.len);
+        }
+    }
+}
+
+/// Parses `<uuid>.<version>` or `<uuid>.<locale>.<version>` file name.
+fn parse_file_name(file_name: &str) -> Option<(Uuid, Option<Box<str>>, u64)> {
+    let (rest, version) = file_name.rsplit_once('.')?;
+    let version = version.parse().ok()?;
+
+    let (uuid, locale) = match rest.split_once('.') {
+        None => (rest, None),
+        Some((uuid, locale)) => (uuid, Some(locale.into())),
+    };
+
+    Some((uuid.parse().ok()?, locale, version))
+}
+
+impl<S> Source for CachingSource<S>
+where
+    S: Source,
+{
+    type Error = S::Error;
+    type Fut = BoxFuture<'static, Result<Option<AssetData>, S::Error>>;
+
+    fn load(&self, uuid: &Uuid, locale: Option<&str>) -> Self::Fut {
+        if let Some(data) = self.cache.lock().get(uuid, locale) {
+            return futures::future::ready(Ok(Some(data))).boxed();
+        }
+
+        let fetch = self.source.load(uuid, locale);
+        self.cache_fetched(uuid, locale, fetch)
+    }
+
+    fn update(&self, uuid: &Uuid, locale: Option<&str>, version: u64) -> Self::Fut {
+        let fetch = self.source.update(uuid, locale, version);
+        self.cache_fetched(uuid, locale, fetch)
+    }
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, S::Error>> {
+        self.source.collection(name)
+    }
+
+    fn namespace(&self) -> Option<Uuid> {
+        self.source.namespace()
+    }
+}
@@ -0,0 +1,74 @@
+use {
+    crate::source::{pack::PackSource, AssetData, Source},
+    futures::future::BoxFuture,
+    goods_treasury::{PackError, PackKey, PackReader},
+    std::future::Ready,
+    uuid::Uuid,
+};
+
+/// Source that serves assets from pack embedded into the binary.
+/// Available before any IO, which makes it suitable for examples,
+/// loading screens and fallback assets.
+///
+/// Usually created with [`embed_assets!`].
+///
+/// [`embed_assets!`]: crate::embed_assets
+pub struct EmbeddedSource {
+    pack: PackSource,
+}
+
+impl EmbeddedSource {
+    /// Returns source serving assets from pack bytes.
+    pub fn new(bytes: &'static [u8]) -> Result<Self, PackError> {
+        let reader = PackReader::from_static(bytes, None)?;
+        Ok(EmbeddedSource {
+            pack: PackSource::new(reader),
+        })
+    }
+
+    /// Returns source serving assets from encrypted pack bytes.
+    /// Unencrypted packs are accepted as well.
+    pub fn new_encrypted(bytes: &'static [u8], key: &PackKey) -> Result<Self, PackError> {
+        let reader = PackReader::from_static(bytes, Some(key))?;
+        Ok(EmbeddedSource {
+            pack: PackSource::new(reader),
+        })
+    }
+}
+
+impl Source for EmbeddedSource {
+    type Error = PackError;
+    type Fut = Ready<Result<Option<AssetData>, PackError>>;
+
+    fn load(&self, uuid: &Uuid, locale: Option<&str>) -> Self::Fut {
+        self.pack.load(uuid, locale)
+    }
+
+    fn update(&self, uuid: &Uuid, locale: Option<&str>, version: u64) -> Self::Fut {
+        self.pack.update(uuid, locale, version)
+    }
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, PackError>> {
+        self.pack.collection(name)
+    }
+
+    fn namespace(&self) -> Option<Uuid> {
+        self.pack.namespace()
+    }
+}
+
+/// Embeds pack file into the binary at compile time
+/// and evaluates to `Result<EmbeddedSource, PackError>`.
+///
+/// Path is resolved relative to the file the macro is invoked in,
+/// as with `include_bytes!`.
+///
+/// ```ignore
+/// let source = goods::embed_assets!("../assets/fallback.pak")?;
+/// ```
+#[macro_export]
+macro_rules! embed_assets {
+    ($path:expr) => {
+        $crate::source::embedded::EmbeddedSource::new(::core::include_bytes!($path))
+    };
+}
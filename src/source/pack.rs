@@ -0,0 +1,170 @@
+use {
+    crate::source::{staging::Staging, AssetData, Source},
+    futures::future::BoxFuture,
+    goods_treasury::{PackAsset, PackReader},
+    parking_lot::Mutex,
+    std::{
+        future::{ready, Ready},
+        path::Path,
+        sync::Arc,
+    },
+    uuid::Uuid,
+};
+
+pub use goods_treasury::{PackError, PackKey};
+
+/// Source that reads assets from pack written by `Treasury::pack`.
+///
+/// Encrypted packs require the key they were written with,
+/// provided with [`PackSource::open_encrypted`].
+///
+/// Patches written by `pack_diff` can be layered over the pack
+/// with [`PackSource::add_patch`] to load updated assets without rewriting the pack.
+///
+/// Packs laid out by recorded session with `Treasury::pack_with_layout`
+/// provide read-ahead hints with [`PackSource::read_ahead`].
+/// With [`PackSource::set_read_ahead`] the source reads hinted assets
+/// into in-memory staging buffer on each load, or assets stored after loaded one
+/// if the pack is not laid out, speeding up sequential level loads.
+///
+/// Checksums of pack entries are verified when they are first read,
+/// whole pack is verified with [`PackSource::verify_all`].
+///
+/// Signatures of signed packs are verified if source is added
+/// with [`LoaderBuilder::add_signed`].
+///
+/// [`LoaderBuilder::add_signed`]: crate::LoaderBuilder::add_signed
+pub struct PackSource {
+    reader: Arc<Mutex<PackReader>>,
+    staging: Arc<Mutex<Staging<PackAsset>>>,
+}
+
+impl PackSource {
+    pub fn new(reader: PackReader) -> Self {
+        PackSource {
+            reader: Arc::new(Mutex::new(reader)),
+            staging: Arc::new(Mutex::new(Staging::new(0))),
+        }
+    }
+
+    /// Opens unencrypted pack.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PackError> {
+        let reader = PackReader::open(path, None)?;
+        Ok(PackSource::new(reader))
+    }
+
+    /// Opens pack encrypted with specified key.
+    /// Unencrypted packs are opened as well.
+    pub fn open_encrypted(path: impl AsRef<Path>, key: &PackKey) -> Result<Self, PackError> {
+        let reader = PackReader::open(path, Some(key))?;
+        Ok(PackSource::new(reader))
+    }
+
+    /// Layers patch over the pack.
+    /// Patches must be added in the order they were made.
+    /// Encrypted patches are decrypted with the key the pack was opened with.
+    pub fn add_patch(&mut self, path: impl AsRef<Path>) -> Result<&mut Self, PackError> {
+        self.reader.lock().add_patch(path)?;
+
+        // Staged data may be outdated by the patch.
+        self.staging.lock().clear();
+        Ok(self)
+    }
+
+    /// Layers patch over the pack.
+    /// Patches must be added in the order they were made.
+    /// Encrypted patches are decrypted with the key the pack was opened with.
+    pub fn with_patch(mut self, path: impl AsRef<Path>) -> Result<Self, PackError> {
+        self.add_patch(path)?;
+        Ok(self)
+    }
+
+    /// Returns up to `count` assets that were accessed after specified asset
+    /// in the session the pack is laid out by.
+    /// Native files of these assets follow the asset in the pack.
+    pub fn read_ahead(&self, uuid: &Uuid, count: usize) -> Vec<Uuid> {
+        self.reader.lock().read_ahead(uuid, count).to_vec()
+    }
+
+    /// Verifies checksums of the pack and its patches,
+    /// e.g. on startup or when asset fails to load with corrupted data.
+    pub fn verify_all(&self) -> Result<(), PackError> {
+        self.reader.lock().verify_all()
+    }
+
+    /// Sets number of assets read ahead into staging buffer when an asset is loaded.
+    /// Zero disables read-ahead, which is the default.
+    pub fn set_read_ahead(&mut self, count: usize) -> &mut Self {
+        self.staging.lock().reset(count);
+        self
+    }
+
+    /// Sets number of assets read ahead into staging buffer when an asset is loaded.
+    /// Zero disables read-ahead, which is the default.
+    pub fn with_read_ahead(mut self, count: usize) -> Self {
+        self.set_read_ahead(count);
+        self
+    }
+}
+
+impl Source for PackSource {
+    type Error = PackError;
+    type Fut = Ready<Result<Option<AssetData>, PackError>>;
+
+    fn load(&self, uuid: &Uuid, locale: Option<&str>) -> Self::Fut {
+        let mut reader = self.reader.lock();
+        let mut staging = self.staging.lock();
+
+        let result = match staging.take(uuid, locale) {
+            Some(asset) => Ok(Some(asset)),
+            None => reader.read(uuid, locale),
+        };
+
+        if let Ok(Some(_)) = &result {
+            let next = reader.read_ahead(uuid, staging.capacity()).to_vec();
+            for next in next {
+                if staging.contains(&next, locale) {
+                    continue;
+                }
+                match reader.read(&next, locale) {
+                    Ok(None) => {}
+                    Ok(Some(asset)) => staging.insert(next, locale, asset),
+                    Err(err) => {
+                        // Error is reported when the asset is requested.
+                        tracing::debug!("Failed to read ahead asset '{}'. {:#}", next, err);
+                        break;
+                    }
+                }
+            }
+        }
+
+        let deprecated = reader.is_deprecated(uuid);
+        let result = result.map(|asset| {
+            asset.map(|asset| AssetData {
+                bytes: asset.bytes.into(),
+                version: 0,
+                format: Some(asset.native_format),
+                signature: asset.signature,
+                locale: asset.locale,
+                name: None,
+                schema: None,
+                deprecated,
+            })
+        });
+        ready(result)
+    }
+
+    fn update(&self, _uuid: &Uuid, _locale: Option<&str>, _version: u64) -> Self::Fut {
+        // Packs are immutable.
+        ready(Ok(None))
+    }
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, PackError>> {
+        let members = self.reader.lock().collection(name).map(<[_]>::to_vec);
+        Box::pin(ready(Ok(members)))
+    }
+
+    fn namespace(&self) -> Option<Uuid> {
+        self.reader.lock().namespace()
+    }
+}
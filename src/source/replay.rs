@@ -0,0 +1,101 @@
+use {
+    crate::source::{AssetData, Source},
+    bytes::Bytes,
+    hashbrown::HashMap,
+    std::{
+        convert::Infallible,
+        future::{ready, Ready},
+    },
+    uuid::Uuid,
+};
+
+pub use goods_treasury::{RecordedLoad, Recording, RecordingError};
+
+struct ReplayedData {
+    bytes: Bytes,
+    version: u64,
+    hash: u64,
+    format: Option<Box<str>>,
+    schema: Option<u32>,
+}
+
+impl ReplayedData {
+    fn asset_data(&self) -> AssetData {
+        AssetData {
+            bytes: self.bytes.clone(),
+            version: self.version,
+            format: self.format.clone(),
+            signature: None,
+            locale: None,
+            name: None,
+            schema: self.schema,
+            deprecated: false,
+        }
+    }
+}
+
+/// Source that serves data recorded with [`Loader::start_recording`],
+/// so that a session can be replayed deterministically regardless of sources changed since.
+///
+/// Asset is loaded with data served first during recorded session,
+/// and updated with data served after it.
+///
+/// [`Loader::start_recording`]: crate::Loader::start_recording
+pub struct ReplaySource {
+    assets: HashMap<(Uuid, Option<Box<str>>), Vec<ReplayedData>>,
+}
+
+impl ReplaySource {
+    pub fn new(recording: &Recording) -> Self {
+        let mut assets: HashMap<_, Vec<ReplayedData>> = HashMap::new();
+        for load in recording.loads() {
+            let served = assets.entry((load.uuid, load.locale.clone())).or_default();
+
+            // Same data is served again when asset is loaded after unloading.
+            if served
+                .iter()
+                .any(|data| data.version == load.version && data.hash == load.hash)
+            {
+                continue;
+            }
+
+            served.push(ReplayedData {
+                bytes: Bytes::from(load.bytes.to_vec()),
+                version: load.version,
+                hash: load.hash,
+                format: load.format.clone(),
+                schema: load.schema,
+            });
+        }
+        ReplaySource { assets }
+    }
+
+    fn served(&self, uuid: &Uuid, locale: Option<&str>) -> Option<&[ReplayedData]> {
+        let served = match self.assets.get(&(*uuid, locale.map(Into::into))) {
+            Some(served) => served,
+            None => self.assets.get(&(*uuid, None))?,
+        };
+        Some(served)
+    }
+}
+
+impl Source for ReplaySource {
+    type Error = Infallible;
+    type Fut = Ready<Result<Option<AssetData>, Infallible>>;
+
+    fn load(&self, uuid: &Uuid, locale: Option<&str>) -> Self::Fut {
+        let data = self
+            .served(uuid, locale)
+            .and_then(|served| served.first())
+            .map(ReplayedData::asset_data);
+        ready(Ok(data))
+    }
+
+    fn update(&self, uuid: &Uuid, locale: Option<&str>, version: u64) -> Self::Fut {
+        let data = self.served(uuid, locale).and_then(|served| {
+            let index = served.iter().position(|data| data.version == version)?;
+            served.get(index + 1).map(ReplayedData::asset_data)
+        });
+        ready(Ok(data))
+    }
+}
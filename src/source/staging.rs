@@ -0,0 +1,56 @@
+use {std::collections::VecDeque, uuid::Uuid};
+
+/// In-memory buffer of asset data read ahead of requests.
+/// Holds up to configured number of entries, oldest are dropped first.
+pub(crate) struct Staging<T> {
+    capacity: usize,
+    entries: VecDeque<(Uuid, Option<Box<str>>, T)>,
+}
+
+impl<T> Staging<T> {
+    pub fn new(capacity: usize) -> Self {
+        Staging {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Drops all staged data and sets new capacity.
+    pub fn reset(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.entries.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn contains(&self, uuid: &Uuid, locale: Option<&str>) -> bool {
+        self.entries
+            .iter()
+            .any(|(u, l, _)| u == uuid && l.as_deref() == locale)
+    }
+
+    /// Removes and returns data staged for the asset.
+    pub fn take(&mut self, uuid: &Uuid, locale: Option<&str>) -> Option<T> {
+        let position = self
+            .entries
+            .iter()
+            .position(|(u, l, _)| u == uuid && l.as_deref() == locale)?;
+        self.entries.remove(position).map(|(_, _, data)| data)
+    }
+
+    pub fn insert(&mut self, uuid: Uuid, locale: Option<&str>, data: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((uuid, locale.map(Into::into), data));
+    }
+}
@@ -0,0 +1,202 @@
+use {
+    crate::source::{AssetData, Source},
+    futures::future::{BoxFuture, FutureExt as _},
+    futures_timer::Delay,
+    parking_lot::Mutex,
+    std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+    uuid::Uuid,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThrottledError<E> {
+    #[error("Simulated IO failure")]
+    Injected,
+
+    #[error(transparent)]
+    Source(E),
+}
+
+#[derive(Clone, Copy)]
+struct Throttle {
+    latency: Duration,
+    jitter: Duration,
+    bandwidth: Option<u64>,
+    failure_rate: f64,
+}
+
+/// Source decorator that simulates slow and unreliable IO,
+/// to test loading screens and streaming without slow hardware.
+///
+/// Each load and update waits for latency plus random jitter,
+/// fails with [`ThrottledError::Injected`] with configured probability,
+/// and then transfers data over bandwidth shared by all requests,
+/// as a single slow drive would.
+///
+/// Random numbers are generated from configurable seed,
+/// so simulated conditions are reproducible.
+pub struct ThrottledSource<S> {
+    source: S,
+    throttle: Throttle,
+    rng: Arc<Mutex<u64>>,
+
+    /// Instant when all transfers in progress are done.
+    busy_until: Arc<Mutex<Instant>>,
+}
+
+impl<S> ThrottledSource<S>
+where
+    S: Source,
+{
+    /// Wraps source without any throttling.
+    pub fn new(source: S) -> Self {
+        ThrottledSource {
+            source,
+            throttle: Throttle {
+                latency: Duration::from_secs(0),
+                jitter: Duration::from_secs(0),
+                bandwidth: None,
+                failure_rate: 0.0,
+            },
+            rng: Arc::new(Mutex::new(0)),
+            busy_until: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Sets delay before each request is served.
+    pub fn set_latency(&mut self, latency: Duration) -> &mut Self {
+        self.throttle.latency = latency;
+        self
+    }
+
+    /// Sets delay before each request is served.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.set_latency(latency);
+        self
+    }
+
+    /// Sets upper bound of random delay added to latency.
+    pub fn set_jitter(&mut self, jitter: Duration) -> &mut Self {
+        self.throttle.jitter = jitter;
+        self
+    }
+
+    /// Sets upper bound of random delay added to latency.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.set_jitter(jitter);
+        self
+    }
+
+    /// Sets bandwidth in bytes per second shared by all requests.
+    pub fn set_bandwidth(&mut self, bytes_per_second: u64) -> &mut Self {
+        self.throttle.bandwidth = Some(bytes_per_second.max(1));
+        self
+    }
+
+    /// Sets bandwidth in bytes per second shared by all requests.
+    pub fn with_bandwidth(mut self, bytes_per_second: u64) -> Self {
+        self.set_bandwidth(bytes_per_second);
+        self
+    }
+
+    /// Sets probability of request failure, from 0 to 1.
+    pub fn set_failure_rate(&mut self, rate: f64) -> &mut Self {
+        self.throttle.failure_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets probability of request failure, from 0 to 1.
+    pub fn with_failure_rate(mut self, rate: f64) -> Self {
+        self.set_failure_rate(rate);
+        self
+    }
+
+    /// Sets seed of random jitter and failures.
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        *self.rng.lock() = seed;
+        self
+    }
+
+    /// Sets seed of random jitter and failures.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.set_seed(seed);
+        self
+    }
+
+    /// Returns random number in `[0, 1)`.
+    fn random(&self) -> f64 {
+        // splitmix64
+        let mut state = self.rng.lock();
+        *state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn throttle(
+        &self,
+        fetch: S::Fut,
+    ) -> BoxFuture<'static, Result<Option<AssetData>, ThrottledError<S::Error>>> {
+        let throttle = self.throttle;
+        let delay = throttle.latency + throttle.jitter.mul_f64(self.random());
+        let fail = self.random() < throttle.failure_rate;
+        let busy_until = self.busy_until.clone();
+
+        async move {
+            if delay > Duration::from_secs(0) {
+                Delay::new(delay).await;
+            }
+            if fail {
+                return Err(ThrottledError::Injected);
+            }
+
+            let data = fetch.await.map_err(ThrottledError::Source)?;
+
+            if let (Some(bandwidth), Some(data)) = (throttle.bandwidth, &data) {
+                let transfer = Duration::from_secs_f64(data.bytes.len() as f64 / bandwidth as f64);
+                let done = {
+                    let mut busy_until = busy_until.lock();
+                    *busy_until = (*busy_until).max(Instant::now()) + transfer;
+                    *busy_until
+                };
+                Delay::new(done.saturating_duration_since(Instant::now())).await;
+            }
+
+            Ok(data)
+        }
+        .boxed()
+    }
+}
+
+impl<S> Source for ThrottledSource<S>
+where
+    S: Source,
+{
+    type Error = ThrottledError<S::Error>;
+    type Fut = BoxFuture<'static, Result<Option<AssetData>, ThrottledError<S::Error>>>;
+
+    fn load(&self, uuid: &Uuid, locale: Option<&str>) -> Self::Fut {
+        let fetch = self.source.load(uuid, locale);
+        self.throttle(fetch)
+    }
+
+    fn update(&self, uuid: &Uuid, locale: Option<&str>, version: u64) -> Self::Fut {
+        let fetch = self.source.update(uuid, locale, version);
+        self.throttle(fetch)
+    }
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Self::Error>> {
+        self.source
+            .collection(name)
+            .map(|result| result.map_err(ThrottledError::Source))
+            .boxed()
+    }
+
+    fn namespace(&self) -> Option<Uuid> {
+        self.source.namespace()
+    }
+}
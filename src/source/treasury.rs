@@ -1,6 +1,7 @@
 use {
     crate::source::{AssetData, Source},
-    goods_treasury::Treasury,
+    futures::future::BoxFuture,
+    goods_treasury::{PackKey, Treasury},
     std::{future::Future, path::Path, pin::Pin, sync::Arc},
     tokio::sync::Mutex,
     uuid::Uuid,
@@ -9,20 +10,54 @@ use {
 pub use goods_treasury::OpenError;
 
 #[derive(Debug, thiserror::Error)]
-#[error("Failed to access native file '{path}'")]
-pub struct TreasuryFetchError {
-    path: Box<Path>,
-    source: std::io::Error,
+pub enum TreasuryFetchError {
+    #[error("Failed to access native file '{path}' of asset '{name}'")]
+    NativeIoError {
+        name: Box<str>,
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+
+    #[error("Asset is being imported by the caller")]
+    ImportCycle,
+
+    #[cfg(feature = "fetch-reimport")]
+    #[error("Failed to store unimported source of the asset")]
+    AutoStoreError {
+        #[from]
+        source: goods_treasury::StoreError,
+    },
+}
+
+/// Converts treasury error into optional asset data.
+/// Missing asset is not an error for a source.
+fn fetch_error(err: goods_treasury::FetchError) -> Result<Option<AssetData>, TreasuryFetchError> {
+    match err {
+        goods_treasury::FetchError::NotFound => Ok(None),
+        goods_treasury::FetchError::NativeIoError { name, path, source } => {
+            Err(TreasuryFetchError::NativeIoError { name, path, source })
+        }
+        goods_treasury::FetchError::ImportCycle => Err(TreasuryFetchError::ImportCycle),
+    }
 }
 
+/// Source of assets stored in [`Treasury`].
+/// Reported versions are counters incremented on each reimport of the asset,
+/// so they are unaffected by clock changes and file timestamp granularity.
 pub struct TreasurySource {
     treasury: Arc<Mutex<Treasury>>,
+    namespace: Option<Uuid>,
+    #[cfg(feature = "fetch-reimport")]
+    auto_store: bool,
 }
 
 impl TreasurySource {
     pub fn new(treasury: Treasury) -> Self {
         TreasurySource {
+            namespace: treasury.namespace(),
             treasury: Arc::new(Mutex::new(treasury)),
+            #[cfg(feature = "fetch-reimport")]
+            auto_store: false,
         }
     }
 
@@ -30,46 +65,108 @@ impl TreasurySource {
         let treasury = Treasury::open(root)?;
         Ok(TreasurySource::new(treasury))
     }
+
+    /// Opens treasury with native files encrypted at rest with specified key.
+    /// See [`Treasury::set_native_key`].
+    pub fn open_encrypted(root: impl AsRef<Path>, key: &PackKey) -> Result<Self, OpenError> {
+        let treasury = Treasury::open(root)?;
+        treasury.set_native_key(Some(key.clone()));
+        Ok(TreasurySource::new(treasury))
+    }
+
+    /// Enables storing sources that are not imported yet when missing asset is requested.
+    /// See [`Treasury::store_unimported`].
+    #[cfg(feature = "fetch-reimport")]
+    pub fn set_auto_store(&mut self, auto_store: bool) -> &mut Self {
+        self.auto_store = auto_store;
+        self
+    }
+
+    /// Enables storing sources that are not imported yet when missing asset is requested.
+    /// See [`Treasury::store_unimported`].
+    #[cfg(feature = "fetch-reimport")]
+    pub fn with_auto_store(mut self, auto_store: bool) -> Self {
+        self.auto_store = auto_store;
+        self
+    }
 }
 
 impl Source for TreasurySource {
     type Error = TreasuryFetchError;
     type Fut = Pin<Box<dyn Future<Output = Result<Option<AssetData>, TreasuryFetchError>> + Send>>;
 
-    fn load(&self, uuid: &Uuid) -> Self::Fut {
+    fn load(&self, uuid: &Uuid, locale: Option<&str>) -> Self::Fut {
         let treasury = self.treasury.clone();
         let uuid = *uuid;
+        let locale = locale.map(Box::<str>::from);
+        #[cfg(feature = "fetch-reimport")]
+        let auto_store = self.auto_store;
         Box::pin(async move {
-            let result = match treasury.lock().await.fetch(&uuid) {
+            let mut treasury = treasury.lock().await;
+            let fetched = treasury.fetch(&uuid, locale.as_deref());
+
+            #[cfg(feature = "fetch-reimport")]
+            let fetched = match fetched {
+                Err(goods_treasury::FetchError::NotFound) if auto_store => {
+                    if !treasury.store_unimported(uuid)? {
+                        return Ok(None);
+                    }
+                    treasury.fetch(&uuid, locale.as_deref())
+                }
+                fetched => fetched,
+            };
+
+            match fetched {
                 Ok(asset_data) => Ok(Some(AssetData {
-                    bytes: asset_data.bytes,
+                    bytes: asset_data.bytes.into(),
                     version: asset_data.version,
+                    format: Some(asset_data.native_format),
+                    signature: None,
+                    locale: None,
+                    name: Some(asset_data.name),
+                    schema: Some(asset_data.schema),
+                    deprecated: asset_data.lifecycle == goods_treasury::Lifecycle::Deprecated,
                 })),
-                Err(goods_treasury::FetchError::NotFound) => Ok(None),
-                Err(goods_treasury::FetchError::NativeIoError { source, path }) => {
-                    Err(TreasuryFetchError { path, source })
-                }
-            };
-            result
+                Err(err) => fetch_error(err),
+            }
         })
     }
 
-    fn update(&self, uuid: &Uuid, version: u64) -> Self::Fut {
+    fn update(&self, uuid: &Uuid, locale: Option<&str>, version: u64) -> Self::Fut {
         let treasury = self.treasury.clone();
         let uuid = *uuid;
+        let locale = locale.map(Box::<str>::from);
         Box::pin(async move {
-            let result = match treasury.lock().await.fetch_updated(&uuid, version) {
-                Ok(None) => Ok(None),
-                Ok(Some(asset_data)) => Ok(Some(AssetData {
-                    bytes: asset_data.bytes,
-                    version: asset_data.version,
-                })),
-                Err(goods_treasury::FetchError::NotFound) => Ok(None),
-                Err(goods_treasury::FetchError::NativeIoError { source, path }) => {
-                    Err(TreasuryFetchError { path, source })
-                }
-            };
+            let result =
+                match treasury
+                    .lock()
+                    .await
+                    .fetch_updated(&uuid, locale.as_deref(), version)
+                {
+                    Ok(None) => Ok(None),
+                    Ok(Some(asset_data)) => Ok(Some(AssetData {
+                        bytes: asset_data.bytes.into(),
+                        version: asset_data.version,
+                        format: Some(asset_data.native_format),
+                        signature: None,
+                        locale: None,
+                        name: Some(asset_data.name),
+                        schema: Some(asset_data.schema),
+                        deprecated: asset_data.lifecycle == goods_treasury::Lifecycle::Deprecated,
+                    })),
+                    Err(err) => fetch_error(err),
+                };
             result
         })
     }
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, Self::Error>> {
+        let treasury = self.treasury.clone();
+        let name = Box::<str>::from(name);
+        Box::pin(async move { Ok(treasury.lock().await.collection(&name)) })
+    }
+
+    fn namespace(&self) -> Option<Uuid> {
+        self.namespace
+    }
 }
@@ -0,0 +1,231 @@
+use {
+    crate::source::{staging::Staging, AssetData, Source},
+    ::zip::ZipArchive,
+    bytes::Bytes,
+    futures::future::BoxFuture,
+    goods_treasury::locale_fallbacks,
+    hashbrown::HashMap,
+    parking_lot::Mutex,
+    std::{
+        fs::File,
+        future::{ready, Ready},
+        io::{BufReader, Read, Seek},
+        path::Path,
+        sync::Arc,
+    },
+    uuid::Uuid,
+};
+
+pub use ::zip::result::ZipError;
+
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T> ReadSeek for T where T: Read + Seek + Send {}
+
+/// Entry of the archive found when central directory was read.
+struct Entry {
+    locale: Option<Box<str>>,
+    format: Option<Box<str>>,
+    index: usize,
+}
+
+/// Source that reads assets from zip archive.
+///
+/// Assets are stored as `<uuid>.<format>` entries,
+/// and localized data as `<locale>/<uuid>.<format>`.
+/// Format extension is optional.
+/// Collections are stored as `collections/<name>` entries
+/// listing member uuids one per line.
+///
+/// Entries may be stored or deflated, each independently.
+/// Central directory is read once on open,
+/// and only requested entries are read afterwards.
+/// With [`ZipSource::set_read_ahead`] assets stored after requested one
+/// are read into in-memory staging buffer as well.
+pub struct ZipSource {
+    archive: Arc<Mutex<ZipArchive<Box<dyn ReadSeek>>>>,
+    entries: HashMap<Uuid, Vec<Entry>>,
+    collections: HashMap<Box<str>, usize>,
+
+    /// Assets in the order they are stored in the archive.
+    order: Vec<Uuid>,
+    staging: Mutex<Staging<AssetData>>,
+}
+
+impl ZipSource {
+    pub fn new(reader: impl Read + Seek + Send + 'static) -> Result<Self, ZipError> {
+        let mut archive = ZipArchive::new(Box::new(reader) as Box<dyn ReadSeek>)?;
+
+        let mut entries = HashMap::<_, Vec<_>>::new();
+        let mut collections = HashMap::new();
+        let mut order = Vec::new();
+
+        for index in 0..archive.len() {
+            let file = archive.by_index_raw(index)?;
+            let name = file.name();
+
+            if let Some(collection) = name.strip_prefix("collections/") {
+                collections.insert(collection.into(), index);
+                continue;
+            }
+
+            let (locale, file_name) = match name.rsplit_once('/') {
+                None => (None, name),
+                Some((locale, file_name)) => (Some(locale.into()), file_name),
+            };
+
+            let (stem, format) = match file_name.split_once('.') {
+                None => (file_name, None),
+                Some((stem, format)) => (stem, Some(format.into())),
+            };
+
+            if let Ok(uuid) = stem.parse() {
+                if !entries.contains_key(&uuid) {
+                    order.push(uuid);
+                }
+                entries.entry(uuid).or_default().push(Entry {
+                    locale,
+                    format,
+                    index,
+                });
+            }
+        }
+
+        Ok(ZipSource {
+            archive: Arc::new(Mutex::new(archive)),
+            entries,
+            collections,
+            order,
+            staging: Mutex::new(Staging::new(0)),
+        })
+    }
+
+    /// Opens zip archive file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ZipError> {
+        let file = File::open(path)?;
+        ZipSource::new(BufReader::new(file))
+    }
+
+    /// Sets number of assets read ahead into staging buffer when an asset is loaded.
+    /// Zero disables read-ahead, which is the default.
+    pub fn set_read_ahead(&mut self, count: usize) -> &mut Self {
+        self.staging.get_mut().reset(count);
+        self
+    }
+
+    /// Sets number of assets read ahead into staging buffer when an asset is loaded.
+    /// Zero disables read-ahead, which is the default.
+    pub fn with_read_ahead(mut self, count: usize) -> Self {
+        self.set_read_ahead(count);
+        self
+    }
+
+    /// Returns entry with exactly specified locale.
+    fn find(&self, uuid: &Uuid, locale: Option<&str>) -> Option<&Entry> {
+        self.entries
+            .get(uuid)?
+            .iter()
+            .find(|entry| entry.locale.as_deref() == locale)
+    }
+
+    fn read(&self, index: usize) -> Result<Bytes, ZipError> {
+        let mut archive = self.archive.lock();
+        let mut file = archive.by_index(index)?;
+        let mut bytes = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut bytes)?;
+        Ok(bytes.into())
+    }
+
+    /// Reads asset data, resolving locale.
+    fn fetch(&self, uuid: &Uuid, locale: Option<&str>) -> Result<Option<AssetData>, ZipError> {
+        let entry = locale
+            .into_iter()
+            .flat_map(locale_fallbacks)
+            .find_map(|locale| self.find(uuid, Some(locale)))
+            .or_else(|| self.find(uuid, None));
+
+        match entry {
+            None => Ok(None),
+            Some(entry) => self.read(entry.index).map(|bytes| {
+                Some(AssetData {
+                    bytes,
+                    version: 0,
+                    format: entry.format.clone(),
+                    signature: None,
+                    locale: None,
+                    name: None,
+                    schema: None,
+                    deprecated: false,
+                })
+            }),
+        }
+    }
+
+    /// Reads assets stored after specified one into staging buffer.
+    fn read_ahead(&self, staging: &mut Staging<AssetData>, uuid: &Uuid, locale: Option<&str>) {
+        let position = match self.order.iter().position(|u| u == uuid) {
+            None => return,
+            Some(position) => position,
+        };
+
+        for next in self.order[position + 1..].iter().take(staging.capacity()) {
+            if staging.contains(next, locale) {
+                continue;
+            }
+            match self.fetch(next, locale) {
+                Ok(None) => {}
+                Ok(Some(data)) => staging.insert(*next, locale, data),
+                Err(err) => {
+                    // Error is reported when the asset is requested.
+                    tracing::debug!("Failed to read ahead asset '{}'. {:#}", next, err);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Source for ZipSource {
+    type Error = ZipError;
+    type Fut = Ready<Result<Option<AssetData>, ZipError>>;
+
+    fn load(&self, uuid: &Uuid, locale: Option<&str>) -> Self::Fut {
+        let mut staging = self.staging.lock();
+
+        let result = match staging.take(uuid, locale) {
+            Some(data) => Ok(Some(data)),
+            None => self.fetch(uuid, locale),
+        };
+
+        if let Ok(Some(_)) = &result {
+            self.read_ahead(&mut staging, uuid, locale);
+        }
+        ready(result)
+    }
+
+    fn update(&self, _uuid: &Uuid, _locale: Option<&str>, _version: u64) -> Self::Fut {
+        // Archives are immutable.
+        ready(Ok(None))
+    }
+
+    fn collection(&self, name: &str) -> BoxFuture<'static, Result<Option<Vec<Uuid>>, ZipError>> {
+        let result = match self.collections.get(name) {
+            None => Ok(None),
+            Some(&index) => self.read(index).and_then(|bytes| {
+                let text = std::str::from_utf8(&bytes)
+                    .map_err(|_| ZipError::InvalidArchive("Collection is not valid UTF-8"))?;
+
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(|line| {
+                        line.parse()
+                            .map_err(|_| ZipError::InvalidArchive("Invalid uuid in collection"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Some)
+            }),
+        };
+        Box::pin(ready(result))
+    }
+}
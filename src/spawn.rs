@@ -0,0 +1,205 @@
+use {
+    futures::{
+        future::BoxFuture,
+        stream::{FuturesUnordered, StreamExt as _},
+        task::{waker, ArcWake},
+    },
+    parking_lot::Mutex,
+    std::{
+        cell::RefCell,
+        future::Future,
+        mem::take,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Waker},
+        thread::{self, Thread},
+    },
+};
+
+thread_local! {
+    /// Spawner of [`block_on`] running on this thread.
+    static LOCAL: RefCell<Option<PollingSpawner>> = const { RefCell::new(None) };
+}
+
+/// Executor used by [`Loader`] to run loading tasks.
+/// Configured with [`LoaderBuilder::set_spawner`].
+///
+/// [`Loader`]: crate::Loader
+/// [`LoaderBuilder::set_spawner`]: crate::LoaderBuilder::set_spawner
+pub trait Spawner: Send + Sync + 'static {
+    /// Spawns future to be polled to completion in background.
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+}
+
+/// Spawns tasks onto current tokio runtime.
+/// Loading must be started from within the runtime context.
+///
+/// Default spawner of loaders built outside of any runtime.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioSpawner;
+
+impl Spawner for TokioSpawner {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        tokio::spawn(future);
+    }
+}
+
+/// Spawns tasks onto the runtime of the handle from any thread.
+///
+/// Default spawner of loaders built within runtime context.
+impl Spawner for tokio::runtime::Handle {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        tokio::runtime::Handle::spawn(self, future);
+    }
+}
+
+/// Spawns tasks onto global async-std executor.
+#[cfg(feature = "async-std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsyncStdSpawner;
+
+#[cfg(feature = "async-std")]
+impl Spawner for AsyncStdSpawner {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        async_std::task::spawn(future);
+    }
+}
+
+/// Single-threaded spawner that does not run tasks by itself.
+/// Tasks are polled on the calling thread by [`PollingSpawner::run_until_stalled`],
+/// which is intended to be called once per frame.
+///
+/// Clones share the same set of tasks.
+#[derive(Clone, Default)]
+pub struct PollingSpawner {
+    inner: Arc<PollingInner>,
+}
+
+#[derive(Default)]
+struct PollingInner {
+    spawned: Mutex<Vec<BoxFuture<'static, ()>>>,
+    tasks: Mutex<FuturesUnordered<BoxFuture<'static, ()>>>,
+}
+
+impl PollingSpawner {
+    /// Returns new spawner without tasks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Polls spawned tasks until none of them can make progress.
+    /// Returns number of unfinished tasks.
+    pub fn run_until_stalled(&self) -> usize {
+        self.run(None)
+    }
+
+    /// Polls spawned tasks until none of them can make progress.
+    /// `parent` is woken whenever any task is woken.
+    fn run(&self, parent: Option<Waker>) -> usize {
+        let mut tasks = self.inner.tasks.lock();
+        let woken = Arc::new(Woken {
+            flag: AtomicBool::new(false),
+            parent,
+        });
+        let waker = waker(woken.clone());
+        let mut ctx = Context::from_waker(&waker);
+
+        loop {
+            woken.flag.store(false, Ordering::Relaxed);
+
+            // Tasks may spawn more tasks while polled.
+            let spawned = take(&mut *self.inner.spawned.lock());
+            let mut progress = !spawned.is_empty();
+            tasks.extend(spawned);
+
+            while let Poll::Ready(Some(())) = tasks.poll_next_unpin(&mut ctx) {
+                progress = true;
+            }
+
+            if !progress
+                && !woken.flag.load(Ordering::Relaxed)
+                && self.inner.spawned.lock().is_empty()
+            {
+                return tasks.len();
+            }
+        }
+    }
+}
+
+/// Records that some task was woken during polling.
+struct Woken {
+    flag: AtomicBool,
+    parent: Option<Waker>,
+}
+
+impl ArcWake for Woken {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.flag.store(true, Ordering::Relaxed);
+        if let Some(parent) = &arc_self.parent {
+            parent.wake_by_ref();
+        }
+    }
+}
+
+/// Unparks the thread blocked in [`block_on`].
+struct Unpark(Thread);
+
+impl ArcWake for Unpark {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.unpark();
+    }
+}
+
+/// Spawns future with spawner of [`block_on`] running on this thread if any,
+/// or with `spawner` otherwise.
+pub(crate) fn spawn(spawner: &dyn Spawner, future: BoxFuture<'static, ()>) {
+    match LOCAL.with(|local| local.borrow().clone()) {
+        Some(local) => local.spawn(future),
+        None => spawner.spawn(future),
+    }
+}
+
+/// Drives future to completion on the current thread.
+/// Tasks spawned by the loader on this thread meanwhile run here as well.
+pub(crate) fn block_on<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    struct Restore(Option<PollingSpawner>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            LOCAL.with(|local| *local.borrow_mut() = prev);
+        }
+    }
+
+    let local = PollingSpawner::new();
+    let _restore = Restore(LOCAL.with(|l| l.borrow_mut().replace(local.clone())));
+
+    let waker = waker(Arc::new(Unpark(thread::current())));
+    let mut ctx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+
+    loop {
+        local.run(Some(waker.clone()));
+
+        if let Poll::Ready(output) = Pin::new(&mut future).poll(&mut ctx) {
+            return output;
+        }
+
+        // Tasks spawned by polled future run before parking.
+        if local.inner.spawned.lock().is_empty() {
+            thread::park();
+        }
+    }
+}
+
+impl Spawner for PollingSpawner {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        self.inner.spawned.lock().push(future);
+    }
+}
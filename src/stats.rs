@@ -0,0 +1,112 @@
+use {
+    parking_lot::Mutex,
+    std::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    },
+    uuid::Uuid,
+};
+
+/// Number of slowest executions kept for [`LoaderStats`].
+const SLOWEST_LEN: usize = 16;
+
+/// Stage of asset loading executed by the loader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LoadStage {
+    /// Decoding asset data, not including time spent waiting for referenced assets.
+    Decode,
+
+    /// Building decoded asset.
+    Build,
+}
+
+/// Decode or build execution longer than threshold
+/// set with [`LoaderBuilder::set_slow_threshold`].
+///
+/// [`LoaderBuilder::set_slow_threshold`]: crate::LoaderBuilder::set_slow_threshold
+#[derive(Clone, Debug)]
+pub struct SlowExecution {
+    pub uuid: Uuid,
+
+    /// Type name of the asset.
+    pub asset: &'static str,
+
+    pub stage: LoadStage,
+    pub elapsed: Duration,
+}
+
+/// Statistics of the loader returned by [`Loader::stats`].
+///
+/// [`Loader::stats`]: crate::Loader::stats
+#[derive(Clone, Debug, Default)]
+pub struct LoaderStats {
+    /// Number of executions longer than the threshold.
+    pub slow_count: u64,
+
+    /// Slowest executions longer than the threshold, slowest first.
+    pub slowest: Vec<SlowExecution>,
+}
+
+/// Reports decode and build executions longer than threshold.
+pub(crate) struct Watchdog {
+    threshold: Duration,
+    slow_count: AtomicU64,
+    slowest: Mutex<Vec<SlowExecution>>,
+}
+
+impl Watchdog {
+    pub(crate) fn new(threshold: Duration) -> Self {
+        Watchdog {
+            threshold,
+            slow_count: AtomicU64::new(0),
+            slowest: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn watch(
+        &self,
+        uuid: &Uuid,
+        asset: &'static str,
+        stage: LoadStage,
+        elapsed: Duration,
+    ) {
+        if elapsed < self.threshold {
+            return;
+        }
+
+        tracing::warn!(
+            uuid = %uuid,
+            asset,
+            ?stage,
+            elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+            "Slow asset {:?} of `{}` took {:?}",
+            stage,
+            uuid,
+            elapsed,
+        );
+
+        self.slow_count.fetch_add(1, Ordering::Relaxed);
+
+        let mut slowest = self.slowest.lock();
+        let index = slowest.partition_point(|slow| slow.elapsed >= elapsed);
+        if index < SLOWEST_LEN {
+            slowest.insert(
+                index,
+                SlowExecution {
+                    uuid: *uuid,
+                    asset,
+                    stage,
+                    elapsed,
+                },
+            );
+            slowest.truncate(SLOWEST_LEN);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> LoaderStats {
+        LoaderStats {
+            slow_count: self.slow_count.load(Ordering::Relaxed),
+            slowest: self.slowest.lock().clone(),
+        }
+    }
+}
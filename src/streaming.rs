@@ -0,0 +1,211 @@
+use {
+    hashbrown::HashMap,
+    parking_lot::Mutex,
+    std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Waker},
+    },
+};
+
+/// Hint for ordering fetches of streamed assets.
+/// Attached to load requests with [`Loader::load_with_hint`]
+/// and updated with [`AssetHandle::set_priority`] as the camera moves.
+///
+/// Assets with higher importance and lower distance are fetched first
+/// from sources with limited number of concurrent fetches.
+///
+/// [`Loader::load_with_hint`]: crate::Loader::load_with_hint
+/// [`AssetHandle::set_priority`]: crate::AssetHandle::set_priority
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StreamingHint {
+    /// Distance from the viewer to the place asset is used at.
+    pub distance: f32,
+
+    /// Importance of the asset. Scales the priority.
+    pub importance: f32,
+}
+
+impl Default for StreamingHint {
+    /// Hint for assets needed right away.
+    fn default() -> Self {
+        StreamingHint {
+            distance: 0.0,
+            importance: 1.0,
+        }
+    }
+}
+
+impl StreamingHint {
+    fn score(&self) -> f32 {
+        let score = self.importance / (1.0 + self.distance.max(0.0));
+        if score.is_nan() {
+            0.0
+        } else {
+            score
+        }
+    }
+}
+
+/// Priority of fetching an asset shared between its handles and loading task.
+#[derive(Clone, Debug)]
+pub(crate) struct Priority(Arc<AtomicU32>);
+
+impl Default for Priority {
+    fn default() -> Self {
+        Priority::new(StreamingHint::default())
+    }
+}
+
+impl Priority {
+    pub fn new(hint: StreamingHint) -> Self {
+        Priority(Arc::new(AtomicU32::new(hint.score().to_bits())))
+    }
+
+    pub fn set(&self, hint: StreamingHint) {
+        self.0.store(hint.score().to_bits(), Ordering::Relaxed);
+    }
+
+    fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Queue of fetches waiting for a slot of a source with limited concurrency.
+/// Priorities are read when a slot is freed,
+/// so changing priority of a waiting fetch costs nothing.
+pub(crate) struct FetchQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+struct QueueState {
+    max: usize,
+    running: usize,
+    next_id: u64,
+    waiting: HashMap<u64, Waiter>,
+}
+
+struct Waiter {
+    priority: Priority,
+    waker: Option<Waker>,
+    granted: bool,
+}
+
+impl QueueState {
+    /// Grants freed slots to waiters with highest priorities.
+    fn dispatch(&mut self) {
+        while self.running < self.max {
+            let next = self
+                .waiting
+                .values_mut()
+                .filter(|waiter| !waiter.granted)
+                .max_by(|a, b| a.priority.get().total_cmp(&b.priority.get()));
+
+            match next {
+                None => return,
+                Some(waiter) => {
+                    waiter.granted = true;
+                    self.running += 1;
+                    if let Some(waker) = waiter.waker.take() {
+                        waker.wake();
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FetchQueue {
+    pub fn new(max: usize) -> Self {
+        FetchQueue {
+            state: Arc::new(Mutex::new(QueueState {
+                max,
+                running: 0,
+                next_id: 0,
+                waiting: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Waits for a slot to run fetch in.
+    pub fn acquire(&self, priority: &Priority) -> Acquire {
+        let mut state = self.state.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.waiting.insert(
+            id,
+            Waiter {
+                priority: priority.clone(),
+                waker: None,
+                granted: false,
+            },
+        );
+        state.dispatch();
+
+        Acquire {
+            state: self.state.clone(),
+            id,
+            done: false,
+        }
+    }
+}
+
+/// Future resolving into [`FetchPermit`] once fetch is granted a slot.
+pub(crate) struct Acquire {
+    state: Arc<Mutex<QueueState>>,
+    id: u64,
+    done: bool,
+}
+
+impl Future for Acquire {
+    type Output = FetchPermit;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<FetchPermit> {
+        let me = self.get_mut();
+        let mut state = me.state.lock();
+        let waiter = state.waiting.get_mut(&me.id).unwrap();
+
+        if waiter.granted {
+            state.waiting.remove(&me.id);
+            drop(state);
+            me.done = true;
+            Poll::Ready(FetchPermit {
+                state: me.state.clone(),
+            })
+        } else {
+            waiter.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Acquire {
+    fn drop(&mut self) {
+        if !self.done {
+            let mut state = self.state.lock();
+            if let Some(waiter) = state.waiting.remove(&self.id) {
+                if waiter.granted {
+                    state.running -= 1;
+                    state.dispatch();
+                }
+            }
+        }
+    }
+}
+
+/// Slot of running fetch. Freed on drop.
+pub(crate) struct FetchPermit {
+    state: Arc<Mutex<QueueState>>,
+}
+
+impl Drop for FetchPermit {
+    fn drop(&mut self) {
+        let mut state = self.state.lock();
+        state.running -= 1;
+        state.dispatch();
+    }
+}
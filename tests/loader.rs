@@ -0,0 +1,469 @@
+use {
+    futures::{channel::mpsc, future::BoxFuture, StreamExt as _},
+    goods::{
+        bytes::Bytes,
+        source::{AssetData, Source},
+        Asset, AssetBuild, AssetHandle, BuildBudget, BuildClass, FetchLimits, Loader, ManualClock,
+        UnloadPolicy, Uuid,
+    },
+    parking_lot::Mutex,
+    std::{
+        collections::HashMap,
+        convert::Infallible,
+        future::{ready, Future, Ready},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::{Duration, Instant},
+    },
+    tokio::sync::Semaphore,
+};
+
+#[derive(Clone, Asset)]
+pub struct Number {
+    pub value: u64,
+}
+
+#[derive(Clone, Asset)]
+pub struct Counter {
+    pub value: u64,
+}
+
+/// Asset that panics when decoded or built from specific data.
+#[derive(Clone)]
+pub struct Fragile;
+
+impl Asset for Fragile {
+    type Decoded = Bytes;
+    type DecodeError = Infallible;
+    type BuildError = Infallible;
+    type Fut = Ready<Result<Bytes, Infallible>>;
+
+    fn decode(bytes: Bytes, _loader: &Loader) -> Self::Fut {
+        assert_ne!(&*bytes, b"panic on decode");
+        ready(Ok(bytes))
+    }
+}
+
+impl AssetBuild<()> for Fragile {
+    fn build(decoded: Bytes, _builder: &mut ()) -> Result<Self, Infallible> {
+        assert_ne!(&*decoded, b"panic on build");
+        Ok(Fragile)
+    }
+}
+
+/// Source serving data that can be changed by the test.
+/// Counts fetches of asset data.
+#[derive(Clone, Default)]
+struct MemorySource {
+    assets: Arc<Mutex<HashMap<Uuid, (Bytes, u64)>>>,
+    fetches: Arc<AtomicUsize>,
+}
+
+impl MemorySource {
+    /// Sets data of the asset, bumping its version.
+    fn set(&self, uuid: Uuid, data: &str) {
+        let mut assets = self.assets.lock();
+        let version = assets.get(&uuid).map_or(0, |(_, version)| version + 1);
+        assets.insert(uuid, (Bytes::copy_from_slice(data.as_bytes()), version));
+    }
+
+    fn fetches(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+
+    fn fetch(&self, uuid: &Uuid, newer_than: Option<u64>) -> Option<AssetData> {
+        let assets = self.assets.lock();
+        let (bytes, version) = assets.get(uuid)?;
+        if newer_than.is_some_and(|newer_than| *version <= newer_than) {
+            return None;
+        }
+
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        Some(AssetData {
+            bytes: bytes.clone(),
+            version: *version,
+            format: None,
+            signature: None,
+            locale: None,
+            name: None,
+            schema: None,
+            deprecated: false,
+        })
+    }
+}
+
+impl Source for MemorySource {
+    type Error = Infallible;
+    type Fut = Ready<Result<Option<AssetData>, Infallible>>;
+
+    fn load(&self, uuid: &Uuid, _locale: Option<&str>) -> Self::Fut {
+        ready(Ok(self.fetch(uuid, None)))
+    }
+
+    fn update(&self, uuid: &Uuid, _locale: Option<&str>, version: u64) -> Self::Fut {
+        ready(Ok(self.fetch(uuid, Some(version))))
+    }
+}
+
+/// Source holding updates of [`MemorySource`] data until opened.
+#[derive(Clone)]
+struct GatedSource {
+    inner: MemorySource,
+    gate: Arc<Semaphore>,
+}
+
+impl GatedSource {
+    fn new() -> Self {
+        GatedSource {
+            inner: MemorySource::default(),
+            gate: Arc::new(Semaphore::new(0)),
+        }
+    }
+
+    fn open(&self) {
+        self.gate.add_permits(1);
+    }
+}
+
+impl Source for GatedSource {
+    type Error = Infallible;
+    type Fut = BoxFuture<'static, Result<Option<AssetData>, Infallible>>;
+
+    fn load(&self, uuid: &Uuid, _locale: Option<&str>) -> Self::Fut {
+        Box::pin(ready(Ok(self.inner.fetch(uuid, None))))
+    }
+
+    fn update(&self, uuid: &Uuid, _locale: Option<&str>, version: u64) -> Self::Fut {
+        let source = self.clone();
+        let uuid = *uuid;
+        Box::pin(async move {
+            let _permit = source.gate.acquire().await.unwrap();
+            Ok(source.inner.fetch(&uuid, Some(version)))
+        })
+    }
+}
+
+fn block_on<F: Future>(f: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(f)
+}
+
+#[test]
+fn loading_as_other_type_is_type_mismatch() {
+    let uuid = Uuid::from_u128(1);
+    let source = MemorySource::default();
+    source.set(uuid, r#"{"value": 42}"#);
+
+    block_on(async {
+        let loader = Loader::builder().with(source.clone()).build();
+
+        let number = loader.load::<Number>(&uuid);
+        let counter = loader.load::<Counter>(&uuid);
+
+        let mut result = counter.await;
+        let err = result.get(&mut ()).err().unwrap();
+        assert!(err.is_type_mismatch());
+
+        let mut result = number.await;
+        assert_eq!(result.get(&mut ()).unwrap().value, 42);
+
+        assert_eq!(source.fetches(), 1);
+    });
+}
+
+#[test]
+fn panics_in_decode_and_build_fail_the_asset() {
+    let decode = Uuid::from_u128(1);
+    let build = Uuid::from_u128(2);
+    let fine = Uuid::from_u128(3);
+
+    let source = MemorySource::default();
+    source.set(decode, "panic on decode");
+    source.set(build, "panic on build");
+    source.set(fine, "fine");
+
+    block_on(async {
+        let loader = Loader::builder().with(source.clone()).build();
+
+        let mut result = loader.load::<Fragile>(&decode).await;
+        assert!(result.get(&mut ()).err().unwrap().is_build_panicked());
+
+        let mut result = loader.load::<Fragile>(&build).await;
+        assert!(result.get(&mut ()).err().unwrap().is_build_panicked());
+
+        // Failed asset reports the panic without building again.
+        let mut result = loader.load::<Fragile>(&build).await;
+        assert!(result.get(&mut ()).err().unwrap().is_build_panicked());
+
+        let mut result = loader.load::<Fragile>(&fine).await;
+        assert!(result.get(&mut ()).is_ok());
+
+        let failed: Vec<_> = loader
+            .maintain()
+            .failed
+            .into_iter()
+            .map(|(uuid, err)| (uuid, err.is_build_panicked()))
+            .collect();
+        assert!(failed.contains(&(decode, true)));
+    });
+}
+
+#[test]
+fn retry_loads_fixed_asset() {
+    let uuid = Uuid::from_u128(1);
+    let source = MemorySource::default();
+    source.set(uuid, "broken");
+
+    block_on(async {
+        let loader = Loader::builder()
+            .with(source.clone())
+            .with_retry_interval(0)
+            .build();
+
+        let mut result = loader.load::<Number>(&uuid).await;
+        assert!(result.get(&mut ()).is_err());
+
+        source.set(uuid, r#"{"value": 42}"#);
+        let mut result = loader.load::<Number>(&uuid).await;
+        assert!(result.get(&mut ()).is_err());
+
+        assert!(loader.retry(&uuid));
+        let mut result = loader.load::<Number>(&uuid).await;
+        assert_eq!(result.get(&mut ()).unwrap().value, 42);
+        assert_eq!(result.generation(), 1);
+        assert!(!loader.retry(&uuid));
+    });
+}
+
+#[test]
+fn maintain_loads_fixed_asset_after_retry_interval() {
+    let uuid = Uuid::from_u128(1);
+    let source = MemorySource::default();
+    source.set(uuid, "broken");
+
+    block_on(async {
+        let loader = Loader::builder()
+            .with(source.clone())
+            .with_retry_interval(3)
+            .build();
+
+        let mut result = loader.load::<Number>(&uuid).await;
+        assert!(result.get(&mut ()).is_err());
+        source.set(uuid, r#"{"value": 42}"#);
+
+        // Source is not checked before the interval passes.
+        for _ in 0..2 {
+            loader.maintain();
+            tokio::task::yield_now().await;
+        }
+        let mut result = loader.load::<Number>(&uuid).await;
+        assert!(result.get(&mut ()).is_err());
+        assert_eq!(source.fetches(), 1);
+
+        let mut completed = false;
+        for _ in 0..10 {
+            completed |= loader.maintain().completed.contains(&uuid);
+            tokio::task::yield_now().await;
+        }
+        assert!(completed);
+
+        let mut result = loader.load::<Number>(&uuid).await;
+        assert_eq!(result.get(&mut ()).unwrap().value, 42);
+    });
+}
+
+#[test]
+fn expired_assets_are_dropped_by_clock() {
+    let uuid = Uuid::from_u128(1);
+    let source = MemorySource::default();
+    source.set(uuid, r#"{"value": 42}"#);
+
+    block_on(async {
+        let clock = ManualClock::default();
+        let loader = Loader::builder()
+            .with(source.clone())
+            .with_unload_policy(UnloadPolicy::Expire {
+                ttl: Duration::from_secs(10),
+            })
+            .with_clock(clock.clone())
+            .build();
+
+        let handle = loader.load::<Number>(&uuid);
+        loader.load::<Number>(&uuid).await.get(&mut ()).unwrap();
+
+        // Referenced asset doesn't expire.
+        clock.advance(Duration::from_secs(60));
+        assert!(loader.maintain().evicted.is_empty());
+        drop(handle);
+
+        // Time is counted from first maintain finding asset unreferenced.
+        assert!(loader.maintain().evicted.is_empty());
+        clock.advance(Duration::from_secs(9));
+        assert!(loader.maintain().evicted.is_empty());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(loader.maintain().evicted, [uuid]);
+
+        loader.load::<Number>(&uuid).await.get(&mut ()).unwrap();
+        assert_eq!(source.fetches(), 2);
+    });
+}
+
+#[test]
+fn build_budget_carries_heavy_assets_over() {
+    let heavy: Vec<_> = (1..=3).map(Uuid::from_u128).collect();
+    let cheap: Vec<_> = (4..=5).map(Uuid::from_u128).collect();
+
+    let source = MemorySource::default();
+    for uuid in heavy.iter().chain(&cheap) {
+        source.set(*uuid, r#"{"value": 42}"#);
+    }
+
+    block_on(async {
+        let loader = Loader::builder()
+            .with(source.clone())
+            .with_build_budget_for::<Number>(
+                BuildBudget::new()
+                    .with_max_per_frame(1)
+                    .with_class(BuildClass::Background),
+            )
+            .with_build_budget_for::<Counter>(BuildBudget::new().with_class(BuildClass::Critical))
+            .build();
+
+        for uuid in &heavy {
+            loader.load::<Number>(uuid).await;
+        }
+        for uuid in &cheap {
+            loader.load::<Counter>(uuid).await;
+        }
+
+        // Cheap assets are reported first and are not held back by heavy ones.
+        assert_eq!(loader.maintain().completed, [cheap[0], cheap[1], heavy[0]]);
+        assert_eq!(loader.maintain().completed, [heavy[1]]);
+
+        let late = Uuid::from_u128(6);
+        source.set(late, r#"{"value": 42}"#);
+        loader.load::<Counter>(&late).await;
+        assert_eq!(loader.maintain().completed, [late, heavy[2]]);
+        assert!(loader.maintain().completed.is_empty());
+    });
+}
+
+#[test]
+fn reload_skips_asset_loaded_anew_as_other_type() {
+    let uuid = Uuid::from_u128(1);
+    let source = GatedSource::new();
+    source.inner.set(uuid, r#"{"value": 1}"#);
+
+    block_on(async {
+        let loader = Loader::builder()
+            .with(source.clone())
+            .with_unload_policy(UnloadPolicy::Immediate)
+            .build();
+
+        loader.load::<Number>(&uuid).await.get(&mut ()).unwrap();
+        source.inner.set(uuid, r#"{"value": 2}"#);
+
+        let reload = tokio::spawn(loader.reload::<Number>(&uuid));
+        tokio::task::yield_now().await;
+
+        // Asset is dropped and loaded as other type while reload waits for the source.
+        assert_eq!(loader.maintain().evicted, [uuid]);
+        let mut counter = loader.load::<Counter>(&uuid).await;
+        assert_eq!(counter.get(&mut ()).unwrap().value, 2);
+
+        source.open();
+        assert!(!reload.await.unwrap().unwrap());
+
+        let mut counter = loader.load::<Counter>(&uuid).await;
+        assert_eq!(counter.get(&mut ()).unwrap().value, 2);
+        assert_eq!(counter.generation(), 0);
+    });
+}
+
+#[test]
+fn loads_from_many_threads_share_cache() {
+    const THREADS: u64 = 16;
+    const ASSETS: u64 = 64;
+    const ROUNDS: u64 = 10;
+
+    let source = MemorySource::default();
+    for value in 0..ASSETS {
+        source.set(
+            Uuid::from_u128(value.into()),
+            &format!(r#"{{"value": {}}}"#, value),
+        );
+    }
+
+    block_on(async {
+        // Built within runtime, so loads may be submitted from any thread.
+        let loader = Loader::builder().with(source.clone()).build();
+
+        let (tx, mut rx) = mpsc::unbounded::<(u64, AssetHandle<Number>)>();
+        let threads: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let loader = loader.clone();
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for round in 0..ROUNDS {
+                        for index in 0..ASSETS {
+                            let value = (index + thread + round) % ASSETS;
+                            let handle = loader.load(&Uuid::from_u128(value.into()));
+                            tx.unbounded_send((value, handle)).unwrap();
+                        }
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut loaded = 0;
+        while let Some((value, handle)) = rx.next().await {
+            let mut result = handle.await;
+            assert_eq!(result.get(&mut ()).unwrap().value, value);
+            loaded += 1;
+        }
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(loaded, THREADS * ASSETS * ROUNDS);
+        assert_eq!(source.fetches(), ASSETS as usize);
+    });
+}
+
+#[test]
+fn fetch_rate_limit_is_shared_by_waiting_fetches() {
+    let source = MemorySource::default();
+    let uuids: Vec<_> = (1..=4).map(Uuid::from_u128).collect();
+    for uuid in &uuids {
+        source.set(*uuid, &"x".repeat(1000));
+    }
+
+    block_on(async {
+        let loader = Loader::builder()
+            .with_limited(
+                source.clone(),
+                FetchLimits::new().with_max_bytes_per_sec(10_000),
+            )
+            .build();
+
+        let start = Instant::now();
+        let handles: Vec<_> = uuids
+            .iter()
+            .map(|uuid| loader.load::<Fragile>(uuid))
+            .collect();
+        for handle in handles {
+            handle.await.get(&mut ()).unwrap();
+        }
+
+        // First fetch starts at once, each following one waits for previous data.
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert_eq!(source.fetches(), 4);
+    });
+}
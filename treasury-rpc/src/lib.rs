@@ -0,0 +1,149 @@
+//!
+//! JSON-RPC 2.0 API of goods treasury for editors and tools
+//! that can't link Rust code, e.g. written in C# or running in Electron.
+//!
+//! [`RpcServer::listen`] serves the API over WebSocket,
+//! one JSON-RPC message or batch per text frame.
+//! Other transports can pass messages to [`RpcServer::handle`].
+//!
+//! # Methods
+//!
+//! Params are passed by name.
+//!
+//! * `formats` - returns `[source, native]` formats pairs of registered importers.
+//! * `store` `{source, source_format, native_format, tags?, settings?, job?}` -
+//!   imports asset from source path and returns its uuid.
+//!   Import with `job` id can be cancelled from another connection.
+//! * `cancel` `{job}` - cancels running `store` with the job id.
+//!   Returns `false` if no such job is running.
+//! * `list` `{tags?, native_format?}` - returns records of assets with all tags.
+//! * `asset` `{uuid}` - returns record of the asset.
+//! * `stat` `{uuid}` - returns metadata of asset native files, see `Treasury::stat`.
+//! * `set_tags` `{uuid, tags}` - replaces tags of the asset.
+//! * `remove` `{uuid}` - removes asset.
+//! * `save` - saves manifest.
+//! * `subscribe` - starts sending `event` notifications for changes of the treasury,
+//!   e.g. `{"event": "stored", "uuid": "..."}`.
+//!   Events are `stored`, `removed`, `reimported`, `tags_changed` and `manifest_saved`.
+//!
+//! Failures of treasury operations are reported with error code `-32000`.
+//!
+
+mod methods;
+
+use {
+    goods_treasury::{Treasury, TreasuryEvent},
+    methods::Jobs,
+    std::{
+        io::ErrorKind,
+        net::{TcpListener, TcpStream, ToSocketAddrs},
+        sync::{mpsc::Receiver, Arc},
+        time::Duration,
+    },
+    tungstenite::{HandshakeError, Message},
+};
+
+/// Interval of checking treasury events while waiting for client messages.
+const EVENTS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Serves treasury API to clients.
+/// Cheap to clone, clones serve the same treasury.
+#[derive(Clone)]
+pub struct RpcServer {
+    treasury: Arc<Treasury>,
+    jobs: Arc<Jobs>,
+}
+
+/// State of a client connection.
+#[derive(Default)]
+pub struct Session {
+    events: Option<Receiver<TreasuryEvent>>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Session::default()
+    }
+
+    /// Returns notifications of treasury events since last call
+    /// if client is subscribed to them.
+    pub fn notifications(&mut self) -> Vec<String> {
+        match &self.events {
+            None => Vec::new(),
+            Some(events) => events.try_iter().map(methods::notification).collect(),
+        }
+    }
+}
+
+impl RpcServer {
+    pub fn new(treasury: Treasury) -> Self {
+        RpcServer {
+            treasury: Arc::new(treasury),
+            jobs: Arc::default(),
+        }
+    }
+
+    pub fn treasury(&self) -> &Treasury {
+        &self.treasury
+    }
+
+    /// Handles JSON-RPC message from client.
+    /// Returns response to send back, or `None` if message consists of notifications only.
+    pub fn handle(&self, session: &mut Session, message: &str) -> Option<String> {
+        methods::handle(&self.treasury, &self.jobs, session, message)
+    }
+
+    /// Binds to the address and serves WebSocket clients.
+    /// Returns only if accepting connections fails.
+    pub fn listen(&self, addr: impl ToSocketAddrs) -> std::io::Result<()> {
+        self.serve(TcpListener::bind(addr)?)
+    }
+
+    /// Serves WebSocket clients connecting to the listener, each on its own thread.
+    /// Returns only if accepting connections fails.
+    pub fn serve(&self, listener: TcpListener) -> std::io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let server = self.clone();
+            std::thread::spawn(move || {
+                let peer = stream.peer_addr();
+                if let Err(err) = server.serve_websocket(stream) {
+                    tracing::warn!("Connection with {:?} failed. {}", peer, err);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn serve_websocket(&self, stream: TcpStream) -> tungstenite::Result<()> {
+        let mut socket = tungstenite::accept(stream).map_err(|err| match err {
+            HandshakeError::Failure(err) => err,
+            HandshakeError::Interrupted(_) => tungstenite::Error::Io(ErrorKind::WouldBlock.into()),
+        })?;
+
+        // Reads time out to send notifications while client is idle.
+        socket.get_ref().set_read_timeout(Some(EVENTS_INTERVAL))?;
+
+        let mut session = Session::new();
+        loop {
+            match socket.read() {
+                Ok(Message::Text(message)) => {
+                    if let Some(response) = self.handle(&mut session, &message) {
+                        socket.send(Message::Text(response))?;
+                    }
+                }
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if err.kind() == ErrorKind::WouldBlock || err.kind() == ErrorKind::TimedOut => {
+                }
+                Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+
+            for notification in session.notifications() {
+                socket.send(Message::Text(notification))?;
+            }
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use {
+    crate::Session,
+    goods_treasury::{CancelToken, Treasury, TreasuryEvent},
+    serde::{de::DeserializeOwned, Deserialize},
+    serde_json::{json, Map, Value},
+    std::{
+        collections::HashMap,
+        path::PathBuf,
+        sync::Mutex,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    uuid::Uuid,
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Code of failed treasury operations.
+const TREASURY_ERROR: i64 = -32000;
+
+/// Tokens of running `store` calls by job id.
+pub(crate) type Jobs = Mutex<HashMap<String, CancelToken>>;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Error of treasury operation, with its sources.
+    fn treasury(err: &dyn std::error::Error) -> Self {
+        let mut message = err.to_string();
+        let mut source = err.source();
+        while let Some(err) = source {
+            message.push_str(". ");
+            message.push_str(&err.to_string());
+            source = err.source();
+        }
+        RpcError::new(TREASURY_ERROR, message)
+    }
+}
+
+pub(crate) fn handle(
+    treasury: &Treasury,
+    jobs: &Jobs,
+    session: &mut Session,
+    message: &str,
+) -> Option<String> {
+    let message: Value = match serde_json::from_str(message) {
+        Ok(message) => message,
+        Err(err) => {
+            let error = RpcError::new(PARSE_ERROR, err.to_string());
+            return Some(response(Value::Null, Err(error)).to_string());
+        }
+    };
+
+    match message {
+        Value::Array(batch) if !batch.is_empty() => {
+            let responses: Vec<_> = batch
+                .into_iter()
+                .filter_map(|request| handle_request(treasury, jobs, session, request))
+                .collect();
+
+            if responses.is_empty() {
+                None
+            } else {
+                Some(Value::Array(responses).to_string())
+            }
+        }
+        request => handle_request(treasury, jobs, session, request).map(|r| r.to_string()),
+    }
+}
+
+/// Handles single request.
+/// Returns `None` for notifications, i.e. requests without `id`.
+fn handle_request(
+    treasury: &Treasury,
+    jobs: &Jobs,
+    session: &mut Session,
+    request: Value,
+) -> Option<Value> {
+    let mut request = match request {
+        Value::Object(request) => request,
+        _ => {
+            let error = RpcError::new(INVALID_REQUEST, "Request must be an object");
+            return Some(response(Value::Null, Err(error)));
+        }
+    };
+
+    let id = request.remove("id");
+    let result = match request.remove("method") {
+        Some(Value::String(method)) => {
+            let params = request.remove("params").unwrap_or(Value::Null);
+            call(treasury, jobs, session, &method, params)
+        }
+        _ => Err(RpcError::new(INVALID_REQUEST, "Method must be a string")),
+    };
+
+    if let Err(err) = &result {
+        tracing::debug!("Request failed. {}", err.message);
+    }
+
+    id.map(|id| response(id, result))
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": err.code, "message": err.message },
+        }),
+    }
+}
+
+/// Returns notification of treasury event.
+pub(crate) fn notification(event: TreasuryEvent) -> String {
+    let params = match event {
+        TreasuryEvent::Stored { uuid } => json!({ "event": "stored", "uuid": uuid }),
+        TreasuryEvent::Removed { uuid } => json!({ "event": "removed", "uuid": uuid }),
+        TreasuryEvent::Reimported { uuid } => json!({ "event": "reimported", "uuid": uuid }),
+        TreasuryEvent::TagsChanged { uuid } => json!({ "event": "tags_changed", "uuid": uuid }),
+        TreasuryEvent::Corrupted { uuid } => json!({ "event": "corrupted", "uuid": uuid }),
+        TreasuryEvent::ManifestSaved => json!({ "event": "manifest_saved" }),
+    };
+
+    json!({ "jsonrpc": "2.0", "method": "event", "params": params }).to_string()
+}
+
+/// Deserializes named params.
+/// Absent params are treated as empty object.
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = match params {
+        Value::Null => Value::Object(Map::new()),
+        params => params,
+    };
+    serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+}
+
+#[derive(Deserialize)]
+struct StoreParams {
+    source: PathBuf,
+    source_format: String,
+    native_format: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    settings: Value,
+    #[serde(default)]
+    job: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CancelParams {
+    job: String,
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    native_format: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UuidParams {
+    uuid: Uuid,
+}
+
+#[derive(Deserialize)]
+struct SetTagsParams {
+    uuid: Uuid,
+    tags: Vec<String>,
+}
+
+fn call(
+    treasury: &Treasury,
+    jobs: &Jobs,
+    session: &mut Session,
+    method: &str,
+    params_value: Value,
+) -> Result<Value, RpcError> {
+    match method {
+        "formats" => {
+            let formats: Vec<_> = treasury
+                .formats()
+                .into_iter()
+                .map(|(source, native)| json!([source.as_str(), native.as_str()]))
+                .collect();
+            Ok(Value::Array(formats))
+        }
+        "store" => {
+            let params: StoreParams = params(params_value)?;
+
+            let cancel = CancelToken::new();
+            if let Some(job) = &params.job {
+                let mut jobs = jobs.lock().unwrap();
+                if jobs.contains_key(job) {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!("Job '{}' is already running", job),
+                    ));
+                }
+                jobs.insert(job.clone(), cancel.clone());
+            }
+
+            let result = treasury.store_cancellable(
+                &params.source,
+                &*params.source_format,
+                &*params.native_format,
+                &params.settings,
+                &params.tags,
+                (),
+                &cancel,
+            );
+
+            if let Some(job) = &params.job {
+                jobs.lock().unwrap().remove(job);
+            }
+
+            let uuid = result.map_err(|err| RpcError::treasury(&err))?;
+            Ok(json!(uuid))
+        }
+        "cancel" => {
+            let params: CancelParams = params(params_value)?;
+            match jobs.lock().unwrap().get(&params.job) {
+                Some(cancel) => {
+                    cancel.cancel();
+                    Ok(json!(true))
+                }
+                None => Ok(json!(false)),
+            }
+        }
+        "list" => {
+            let params: ListParams = params(params_value)?;
+            let assets = treasury.list(&params.tags, params.native_format.as_deref());
+            Ok(json!(assets))
+        }
+        "asset" => {
+            let params: UuidParams = params(params_value)?;
+            match treasury
+                .list(&[] as &[&str], None)
+                .into_iter()
+                .find(|asset| asset.uuid() == params.uuid)
+            {
+                Some(asset) => Ok(json!(asset)),
+                None => Err(RpcError::new(TREASURY_ERROR, "Asset not found")),
+            }
+        }
+        "stat" => {
+            let params: UuidParams = params(params_value)?;
+            let stat = treasury
+                .stat(params.uuid)
+                .map_err(|err| RpcError::treasury(&err))?;
+
+            let unix_time = |time: Option<SystemTime>| {
+                time.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|since| since.as_secs())
+            };
+
+            Ok(json!({
+                "uuid": stat.uuid,
+                "source_format": stat.source_format,
+                "native_format": stat.native_format,
+                "tags": stat.tags,
+                "version": stat.version,
+                "schema": stat.schema,
+                "native_size": stat.native_size,
+                "imported": unix_time(stat.imported),
+                "up_to_date": stat.up_to_date,
+                "stored_by": stat.stored_by,
+                "reimported_by": stat.reimported_by,
+                "stored_at": unix_time(stat.stored_at),
+                "reimported_at": unix_time(stat.reimported_at),
+            }))
+        }
+        "set_tags" => {
+            let params: SetTagsParams = params(params_value)?;
+            treasury
+                .set_tags(params.uuid, &params.tags)
+                .map_err(|err| RpcError::treasury(&err))?;
+            Ok(Value::Null)
+        }
+        "remove" => {
+            let params: UuidParams = params(params_value)?;
+            treasury.remove(params.uuid);
+            Ok(Value::Null)
+        }
+        "save" => {
+            treasury.save().map_err(|err| RpcError::treasury(&err))?;
+            Ok(Value::Null)
+        }
+        "subscribe" => {
+            if session.events.is_none() {
+                session.events = Some(treasury.events());
+            }
+            Ok(Value::Null)
+        }
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Method '{}' not found", method),
+        )),
+    }
+}
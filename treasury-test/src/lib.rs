@@ -0,0 +1,307 @@
+//!
+//! Utilities for testing importers against real treasury registry
+//! without building them into importer modules.
+//!
+//! [`ScratchTreasury`] is a treasury in temporary directory removed on drop.
+//! [`FakeImporter`] writes predefined output and counts its imports,
+//! which allows asserting when assets are reimported.
+//! Scratch treasury runs on [`ManualClock`], so times recorded for assets are deterministic.
+//! Sources are written and touched at the time of the clock,
+//! so whether assets are outdated doesn't depend on time passed or timestamp resolution.
+//!
+
+use {
+    goods_treasury::{Clock, ManualClock, StoreError, Treasury},
+    goods_treasury_import::{
+        eyre, localized_native_path, AsyncImporter, BoxFuture, ImportProgress, Registry,
+    },
+    std::{
+        fs::{self, File},
+        ops::{Deref, DerefMut},
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::{Duration, SystemTime},
+    },
+    tempfile::TempDir,
+    uuid::Uuid,
+};
+
+/// Treasury in temporary directory.
+/// Directory is removed when scratch treasury is dropped.
+///
+/// Dereferences to [`Treasury`].
+pub struct ScratchTreasury {
+    treasury: Treasury,
+    clock: ManualClock,
+    dir: TempDir,
+}
+
+impl ScratchTreasury {
+    /// Creates new empty treasury in temporary directory.
+    /// Its clock shows Unix epoch until advanced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if temporary directory or treasury cannot be created.
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let treasury = Treasury::new(dir.path(), false).expect("Failed to create treasury");
+        let clock = ManualClock::default();
+        treasury.set_clock(clock.clone());
+        ScratchTreasury {
+            treasury,
+            clock,
+            dir,
+        }
+    }
+
+    /// Returns root directory of the treasury.
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Returns clock of the treasury.
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    /// Adds fake importer to the treasury.
+    /// Returns counter of its imports.
+    pub fn add_importer(&mut self, importer: FakeImporter) -> ImportCounter {
+        let counter = importer.counter();
+        self.treasury
+            .add_async_importer(importer)
+            .expect("Failed to add importer");
+        counter
+    }
+
+    /// Writes source file with specified name into treasury root.
+    /// File is modified at current time of the clock.
+    /// Returns path to the file.
+    pub fn write_source(&self, name: impl AsRef<Path>, bytes: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.dir.path().join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create source directory");
+        }
+        fs::write(&path, bytes).expect("Failed to write source file");
+        self.set_modified(&path, self.clock.now());
+        path
+    }
+
+    /// Advances clock by a second and marks file as modified at that time,
+    /// so that assets depending on it are outdated.
+    pub fn touch(&self, path: impl AsRef<Path>) {
+        // Times are recorded in whole seconds.
+        self.clock.advance(Duration::from_secs(1));
+        self.set_modified(path.as_ref(), self.clock.now());
+    }
+
+    fn set_modified(&self, path: &Path, time: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(time))
+            .expect("Failed to set modification time");
+    }
+
+    /// Writes source file with specified name and stores it.
+    pub fn store_source(
+        &self,
+        name: impl AsRef<Path>,
+        bytes: impl AsRef<[u8]>,
+        source_format: &str,
+        native_format: &str,
+    ) -> Result<Uuid, StoreError> {
+        let path = self.write_source(name, bytes);
+        self.treasury
+            .store(path, source_format, native_format, &[] as &[&str])
+    }
+
+    /// Fetches native file of the asset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if asset cannot be fetched.
+    pub fn fetch_bytes(&mut self, uuid: &Uuid) -> Box<[u8]> {
+        self.treasury
+            .fetch(uuid, None)
+            .expect("Failed to fetch asset")
+            .bytes
+    }
+}
+
+impl Default for ScratchTreasury {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for ScratchTreasury {
+    type Target = Treasury;
+
+    fn deref(&self) -> &Treasury {
+        &self.treasury
+    }
+}
+
+impl DerefMut for ScratchTreasury {
+    fn deref_mut(&mut self) -> &mut Treasury {
+        &mut self.treasury
+    }
+}
+
+enum Output {
+    Bytes(Box<[u8]>),
+    Source,
+    Varying,
+    Error(Box<str>),
+}
+
+/// Importer that writes predefined output.
+///
+/// Copies source into native file unless configured otherwise.
+pub struct FakeImporter {
+    name: Box<str>,
+    source: Box<str>,
+    native: Box<str>,
+    output: Output,
+    locales: Vec<(Box<str>, Box<[u8]>)>,
+    counter: ImportCounter,
+}
+
+impl FakeImporter {
+    /// Returns fake importer from source to native format.
+    pub fn new(source: &str, native: &str) -> Self {
+        FakeImporter {
+            name: format!("fake {} -> {}", source, native).into(),
+            source: source.into(),
+            native: native.into(),
+            output: Output::Source,
+            locales: Vec::new(),
+            counter: ImportCounter::default(),
+        }
+    }
+
+    /// Sets name of the importer.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Makes importer write specified bytes into native file.
+    pub fn with_output(mut self, bytes: impl Into<Box<[u8]>>) -> Self {
+        self.output = Output::Bytes(bytes.into());
+        self
+    }
+
+    /// Makes importer write different bytes on each import,
+    /// like non-deterministic importer.
+    pub fn with_varying_output(mut self) -> Self {
+        self.output = Output::Varying;
+        self
+    }
+
+    /// Makes importer fail with specified error.
+    pub fn with_error(mut self, error: &str) -> Self {
+        self.output = Output::Error(error.into());
+        self
+    }
+
+    /// Makes importer write specified bytes into native file for the locale.
+    pub fn with_locale(mut self, locale: &str, bytes: impl Into<Box<[u8]>>) -> Self {
+        self.locales.push((locale.into(), bytes.into()));
+        self
+    }
+
+    /// Returns counter of imports performed by this importer.
+    pub fn counter(&self) -> ImportCounter {
+        self.counter.clone()
+    }
+}
+
+impl AsyncImporter for FakeImporter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn native(&self) -> &str {
+        &self.native
+    }
+
+    fn import<'a>(
+        &'a self,
+        source_path: &'a Path,
+        native_path: &'a Path,
+        _settings: &'a str,
+        registry: &'a mut (dyn Registry + Send),
+        _progress: &'a mut (dyn ImportProgress + Send),
+    ) -> BoxFuture<'a, eyre::Result<()>> {
+        let count = self.counter.0.fetch_add(1, Ordering::SeqCst);
+
+        let result = match &self.output {
+            Output::Bytes(bytes) => fs::write(native_path, bytes).map_err(eyre::Report::from),
+            Output::Source => fs::copy(source_path, native_path)
+                .map(drop)
+                .map_err(eyre::Report::from),
+            Output::Varying => {
+                fs::write(native_path, count.to_string()).map_err(eyre::Report::from)
+            }
+            Output::Error(error) => Err(eyre::eyre!("{}", error)),
+        };
+
+        let result = result.and_then(|()| {
+            for (locale, bytes) in &self.locales {
+                fs::write(localized_native_path(native_path, locale), bytes)?;
+                registry.add_locale(locale)?;
+            }
+            Ok(())
+        });
+
+        Box::pin(async move { result })
+    }
+}
+
+/// Counter of imports performed by [`FakeImporter`].
+/// Clones share the count.
+#[derive(Clone, Debug, Default)]
+pub struct ImportCounter(Arc<AtomicUsize>);
+
+impl ImportCounter {
+    /// Returns number of imports performed so far.
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Runs closure and asserts that it triggered import.
+    #[track_caller]
+    pub fn assert_imports<R>(&self, f: impl FnOnce() -> R) -> R {
+        let before = self.count();
+        let result = f();
+        assert!(
+            self.count() > before,
+            "Expected import to be triggered, but it was not"
+        );
+        result
+    }
+
+    /// Runs closure and asserts that it did not trigger import.
+    #[track_caller]
+    pub fn assert_no_imports<R>(&self, f: impl FnOnce() -> R) -> R {
+        let before = self.count();
+        let result = f();
+        let after = self.count();
+        assert!(
+            after == before,
+            "Expected no imports, but {} were triggered",
+            after - before
+        );
+        result
+    }
+}
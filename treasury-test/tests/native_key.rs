@@ -0,0 +1,71 @@
+use {
+    goods_treasury::{PackKey, PackOptions, PackReader, Treasury},
+    goods_treasury_test::{FakeImporter, ScratchTreasury},
+};
+
+const SECRET: &[u8] = b"launch codes";
+
+#[test]
+fn natives_are_encrypted_at_rest() {
+    let mut treasury = ScratchTreasury::new();
+    let imports = treasury.add_importer(FakeImporter::new("txt", "text"));
+    treasury.set_native_key(Some(PackKey::generate()));
+
+    let uuid = treasury
+        .store_source("secret.txt", SECRET, "txt", "text")
+        .unwrap();
+
+    let native = treasury
+        .root()
+        .join(".treasury")
+        .join(uuid.to_hyphenated().to_string());
+    let is_plain = |native| {
+        let stored = std::fs::read(native).unwrap();
+        stored.windows(SECRET.len()).any(|w| w == SECRET)
+    };
+    assert!(!is_plain(&native));
+    assert_eq!(&*treasury.fetch_bytes(&uuid), SECRET);
+
+    treasury.touch(treasury.root().join("secret.txt"));
+    let bytes = imports.assert_imports(|| treasury.fetch_bytes(&uuid));
+    assert_eq!(&*bytes, SECRET);
+    assert!(!is_plain(&native));
+
+    let pack = treasury.root().join("secret.pack");
+    treasury
+        .pack(&pack, &[] as &[&str], &PackOptions::new())
+        .unwrap();
+    let mut reader = PackReader::open(&pack, None).unwrap();
+    assert_eq!(&*reader.read(&uuid, None).unwrap().unwrap().bytes, SECRET);
+}
+
+#[test]
+fn encrypted_natives_require_key() {
+    let mut treasury = ScratchTreasury::new();
+    treasury.add_importer(FakeImporter::new("txt", "text"));
+    treasury.set_native_key(Some(PackKey::generate()));
+
+    let uuid = treasury
+        .store_source("secret.txt", SECRET, "txt", "text")
+        .unwrap();
+
+    let mut without_key = Treasury::open(treasury.root()).unwrap();
+    assert!(without_key.fetch(&uuid, None).is_err());
+
+    let mut wrong_key = Treasury::open(treasury.root()).unwrap();
+    wrong_key.set_native_key(Some(PackKey::generate()));
+    assert!(wrong_key.fetch(&uuid, None).is_err());
+}
+
+#[test]
+fn natives_written_before_key_stay_readable() {
+    let mut treasury = ScratchTreasury::new();
+    treasury.add_importer(FakeImporter::new("txt", "text"));
+
+    let uuid = treasury
+        .store_source("plain.txt", SECRET, "txt", "text")
+        .unwrap();
+
+    treasury.set_native_key(Some(PackKey::generate()));
+    assert_eq!(&*treasury.fetch_bytes(&uuid), SECRET);
+}
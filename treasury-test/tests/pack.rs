@@ -0,0 +1,126 @@
+use {
+    goods_treasury::{verify_signature, PackError, PackOptions, PackReader, SigningKey},
+    goods_treasury_test::{FakeImporter, ScratchTreasury},
+    std::{convert::TryInto, path::PathBuf},
+};
+
+/// Writes pack with single asset into scratch treasury.
+fn write_pack(treasury: &mut ScratchTreasury) -> PathBuf {
+    treasury.add_importer(FakeImporter::new("txt", "text"));
+    treasury
+        .store_source("hello.txt", "hello", "txt", "text")
+        .unwrap();
+
+    let pack = treasury.root().join("assets.pack");
+    treasury
+        .pack(&pack, &[] as &[&str], &PackOptions::new())
+        .unwrap();
+    pack
+}
+
+/// Opens pack from file and from static bytes.
+fn open_both(bytes: Vec<u8>, path: &PathBuf) -> [Result<PackReader, PackError>; 2] {
+    std::fs::write(path, &bytes).unwrap();
+    [
+        PackReader::open(path, None),
+        PackReader::from_static(Box::leak(bytes.into_boxed_slice()), None),
+    ]
+}
+
+#[test]
+fn signature_covers_locale() {
+    let mut treasury = ScratchTreasury::new();
+    treasury.add_importer(FakeImporter::new("txt", "text").with_locale("fr", *b"bonjour"));
+
+    let uuid = treasury
+        .store_source("greeting.txt", "hello", "txt", "text")
+        .unwrap();
+
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let key = signing_key.verifying_key();
+    let pack = treasury.root().join("signed.pack");
+    treasury
+        .pack(
+            &pack,
+            &[] as &[&str],
+            &PackOptions::new().with_signing_key(signing_key),
+        )
+        .unwrap();
+
+    let mut reader = PackReader::open(&pack, None).unwrap();
+    let verify = |locale, asset: &goods_treasury::PackAsset| {
+        let signature = asset.signature.as_deref().unwrap();
+        verify_signature(
+            &key,
+            &uuid,
+            locale,
+            &asset.native_format,
+            &asset.bytes,
+            signature,
+        )
+    };
+
+    let french = reader.read(&uuid, Some("fr")).unwrap().unwrap();
+    assert_eq!(&*french.bytes, b"bonjour");
+    assert_eq!(french.locale.as_deref(), Some("fr"));
+    assert!(verify(Some("fr"), &french));
+    assert!(!verify(None, &french));
+    assert!(!verify(Some("de"), &french));
+
+    let default = reader.read(&uuid, None).unwrap().unwrap();
+    assert_eq!(&*default.bytes, b"hello");
+    assert!(verify(None, &default));
+    assert!(!verify(Some("fr"), &default));
+
+    // Native file without locale served as fallback is verified without locale.
+    let fallback = reader.read(&uuid, Some("de")).unwrap().unwrap();
+    assert_eq!(fallback.locale, None);
+    assert!(verify(fallback.locale.as_deref(), &fallback));
+}
+
+#[test]
+fn truncated_pack_is_corrupted() {
+    let mut treasury = ScratchTreasury::new();
+    let pack = write_pack(&mut treasury);
+    let bytes = std::fs::read(&pack).unwrap();
+
+    // Index and checksums are at the end of the pack.
+    for len in [bytes.len() - 1, bytes.len() - 4, bytes.len() / 2] {
+        for result in open_both(bytes[..len].to_vec(), &pack) {
+            assert!(result.err().unwrap().is_corrupted());
+        }
+    }
+}
+
+#[test]
+fn corrupted_header_is_detected() {
+    let mut treasury = ScratchTreasury::new();
+    let pack = write_pack(&mut treasury);
+    let bytes = std::fs::read(&pack).unwrap();
+
+    // Index offset, index length and checksums offset.
+    for field in [16..24, 24..32, 48..56] {
+        for value in [u64::MAX, u64::MAX / 2, bytes.len() as u64] {
+            let mut corrupted = bytes.clone();
+            corrupted[field.clone()].copy_from_slice(&value.to_le_bytes());
+
+            for result in open_both(corrupted, &pack) {
+                assert!(matches!(
+                    result.err().unwrap(),
+                    PackError::PackCorrupted { .. }
+                ));
+            }
+        }
+    }
+
+    // Flipped bit in the index.
+    let index_offset = u64::from_le_bytes(bytes[16..24].try_into().unwrap()) as usize;
+    let mut corrupted = bytes.clone();
+    corrupted[index_offset] ^= 0x80;
+    for result in open_both(corrupted, &pack) {
+        match result {
+            Ok(reader) => assert!(reader.verify_all().unwrap_err().is_corrupted()),
+            Err(err) => assert!(err.is_corrupted()),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use {
+    goods_treasury_test::{FakeImporter, ScratchTreasury},
+    std::time::{Duration, UNIX_EPOCH},
+};
+
+#[test]
+fn store_imports_once() {
+    let mut treasury = ScratchTreasury::new();
+    let imports = treasury.add_importer(FakeImporter::new("txt", "text"));
+
+    let uuid = imports
+        .assert_imports(|| treasury.store_source("hello.txt", "hello", "txt", "text"))
+        .unwrap();
+    assert_eq!(imports.count(), 1);
+
+    let bytes = imports.assert_no_imports(|| treasury.fetch_bytes(&uuid));
+    assert_eq!(&*bytes, b"hello");
+
+    let again = imports
+        .assert_no_imports(|| treasury.store_source("hello.txt", "hello", "txt", "text"))
+        .unwrap();
+    assert_eq!(again, uuid);
+}
+
+#[test]
+fn modified_source_is_reimported_on_fetch() {
+    let mut treasury = ScratchTreasury::new();
+    let imports = treasury.add_importer(FakeImporter::new("txt", "text"));
+
+    let uuid = treasury
+        .store_source("hello.txt", "hello", "txt", "text")
+        .unwrap();
+
+    let source = treasury.write_source("hello.txt", "goodbye");
+    treasury.touch(&source);
+
+    let bytes = imports.assert_imports(|| treasury.fetch_bytes(&uuid));
+    assert_eq!(&*bytes, b"goodbye");
+
+    let bytes = imports.assert_no_imports(|| treasury.fetch_bytes(&uuid));
+    assert_eq!(&*bytes, b"goodbye");
+}
+
+#[test]
+fn failed_import_stores_nothing() {
+    let mut treasury = ScratchTreasury::new();
+    let imports = treasury.add_importer(FakeImporter::new("txt", "text").with_error("broken"));
+
+    let result =
+        imports.assert_imports(|| treasury.store_source("hello.txt", "hello", "txt", "text"));
+    assert!(result.is_err());
+    assert!(treasury.list(&[] as &[&str], None).is_empty());
+}
+
+#[test]
+fn reimport_is_decided_by_clock() {
+    let mut treasury = ScratchTreasury::new();
+    let imports = treasury.add_importer(FakeImporter::new("txt", "text"));
+
+    let uuid = treasury
+        .store_source("hello.txt", "hello", "txt", "text")
+        .unwrap();
+    let stat = treasury.stat(uuid).unwrap();
+    assert_eq!(stat.imported, Some(UNIX_EPOCH));
+    assert!(stat.up_to_date);
+
+    // Time passing alone doesn't outdate the asset.
+    treasury.clock().advance(Duration::from_secs(60));
+    imports.assert_no_imports(|| treasury.fetch_bytes(&uuid));
+
+    // Source written later than the import is newer.
+    treasury.write_source("hello.txt", "goodbye");
+    assert!(!treasury.stat(uuid).unwrap().up_to_date);
+    let bytes = imports.assert_imports(|| treasury.fetch_bytes(&uuid));
+    assert_eq!(&*bytes, b"goodbye");
+
+    let stat = treasury.stat(uuid).unwrap();
+    let now = UNIX_EPOCH + Duration::from_secs(60);
+    assert_eq!(stat.imported, Some(now));
+    assert_eq!(stat.reimported_at, Some(now));
+    assert!(stat.up_to_date);
+}
@@ -0,0 +1,66 @@
+//! Measures opening and saving a treasury with large manifest.
+//!
+//! `cargo bench -p goods-treasury --bench manifest [--features parallel] -- [assets]`
+
+use {
+    goods_treasury::Treasury,
+    std::time::{Duration, Instant},
+    uuid::Uuid,
+};
+
+const DEFAULT_ASSETS: usize = 200_000;
+const RUNS: u32 = 5;
+
+fn main() {
+    let count = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_ASSETS);
+
+    let root = std::env::temp_dir().join(format!("goods-treasury-bench-{}", std::process::id()));
+    std::fs::create_dir_all(root.join(".treasury")).unwrap();
+
+    let assets: Vec<_> = (0..count)
+        .map(|index| {
+            serde_json::json!({
+                "uuid": Uuid::from_u128(index as u128 + 1),
+                "source": format!("textures/level{}/rock_{}.png", index % 100, index),
+                "source_format": "png",
+                "native_format": "texture",
+                "tags": ["environment", format!("level{}", index % 100)],
+                "settings": { "mips": true, "srgb": index % 2 == 0 },
+                "meta": { "width": 1024, "height": 1024 },
+                "version": 1,
+            })
+        })
+        .collect();
+
+    let manifest = serde_json::json!({
+        "version": 1,
+        "importers_dirs": [],
+        "assets": assets,
+    });
+    std::fs::write(
+        root.join(".treasury").join("manifest.json"),
+        serde_json::to_vec_pretty(&manifest).unwrap(),
+    )
+    .unwrap();
+
+    let mut open = Duration::default();
+    let mut save = Duration::default();
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let treasury = Treasury::open(&root).unwrap();
+        open += start.elapsed();
+
+        let start = Instant::now();
+        treasury.save().unwrap();
+        save += start.elapsed();
+    }
+
+    println!("{} assets", count);
+    println!("open: {:?}", open / RUNS);
+    println!("save: {:?}", save / RUNS);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
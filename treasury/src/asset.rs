@@ -1,20 +1,80 @@
 use {
+    crate::{
+        clock::{from_timestamp, timestamp},
+        treasury::locale_fallbacks,
+    },
     std::{
         fmt::{self, Display},
         path::Path,
+        time::SystemTime,
     },
     uuid::Uuid,
 };
 
+/// Visibility of the asset.
+/// Assets that are not public are excluded from packs
+/// unless included with [`PackOptions::with_visibility`].
+///
+/// [`PackOptions::with_visibility`]: crate::PackOptions::with_visibility
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Visibility {
+    /// Asset is shipped.
+    #[default]
+    Public,
+
+    /// Asset is used by editor only.
+    EditorOnly,
+
+    /// Asset is used in debug builds only.
+    DebugOnly,
+}
+
+impl Visibility {
+    pub fn is_public(&self) -> bool {
+        *self == Visibility::Public
+    }
+}
+
+/// Review state of the asset.
+/// Drafts are excluded from packs
+/// unless included with [`PackOptions::with_lifecycle`],
+/// and loading deprecated assets is reported with a warning.
+///
+/// [`PackOptions::with_lifecycle`]: crate::PackOptions::with_lifecycle
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Lifecycle {
+    /// Asset is work in progress, not reviewed yet.
+    Draft,
+
+    /// Asset is reviewed and ready to ship.
+    #[default]
+    Approved,
+
+    /// Asset is going to be removed and should not be referenced anymore.
+    Deprecated,
+}
+
+impl Lifecycle {
+    pub fn is_approved(&self) -> bool {
+        *self == Lifecycle::Approved
+    }
+}
+
 /// Contains meta-information about an self.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Asset {
     /// Id of the self.
     uuid: Uuid,
 
-    /// Path to source file.
-    /// Relative to root path.
-    source: Box<Path>,
+    /// URI of the source.
+    /// Path relative to root path for sources in local filesystem.
+    source: Box<str>,
 
     /// Source format of the asset.
     source_format: Box<str>,
@@ -22,8 +82,91 @@ pub struct Asset {
     /// Native format of the asset.
     native_format: Box<str>,
 
-    /// Arrays of tags associated with the self.
+    /// Human-readable name of the asset.
+    /// Defaults to the source file stem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<Box<str>>,
+
+    /// Sorted array of tags associated with the self.
     tags: Box<[Box<str>]>,
+
+    /// Import settings of the asset.
+    /// Passed to importer as JSON document.
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    settings: serde_json::Value,
+
+    /// Metadata recorded by importer.
+    /// Updated on every reimport.
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    meta: serde_json::Value,
+
+    /// Files asset depends on, besides its source.
+    /// Relative to root path.
+    /// Asset is reimported when any of them is modified.
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    dependencies: Box<[Box<Path>]>,
+
+    /// Assets importer stored or fetched while importing the asset,
+    /// e.g. textures of a model.
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    references: Box<[Uuid]>,
+
+    /// Locales importer has written native files for.
+    /// Native file without locale is used for other locales.
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    locales: Box<[Box<str>]>,
+
+    /// Declared version of importer that produced native files.
+    /// Asset is reimported when version of its importer changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    importer_version: Option<Box<str>>,
+
+    /// Hash of source content at last import.
+    /// Used to detect stores of identical sources from different paths.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_hash: Option<Box<str>>,
+
+    /// Other paths to sources with content identical to the asset source.
+    /// Relative to root path, sorted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<Box<Path>>,
+
+    /// Visibility of the asset.
+    #[serde(default, skip_serializing_if = "Visibility::is_public")]
+    visibility: Visibility,
+
+    /// Review state of the asset.
+    #[serde(default, skip_serializing_if = "Lifecycle::is_approved")]
+    lifecycle: Lifecycle,
+
+    /// Asset must stay resident and must not be cleaned up.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pinned: bool,
+
+    /// Identity of the user who stored the asset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stored_by: Option<Box<str>>,
+
+    /// Time asset was stored, in seconds since Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stored_at: Option<u64>,
+
+    /// Identity of the user who last reimported the asset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reimported_by: Option<Box<str>>,
+
+    /// Time asset was last reimported, in seconds since Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reimported_at: Option<u64>,
+
+    /// Version of native files.
+    /// Incremented on every reimport.
+    #[serde(default)]
+    version: u64,
+
+    /// Schema version of native files recorded by importer.
+    #[serde(default)]
+    schema: u32,
 }
 
 impl Display for Asset {
@@ -31,39 +174,76 @@ impl Display for Asset {
         if f.alternate() {
             write!(
                 f,
-                "{{\n  uuid: {}\n  source: {}\n  source_format: {}\n  native_format: {}\n}}",
+                "{{\n  uuid: {}\n  source: {}\n  source_format: {}\n  native_format: {}\n  settings: {}\n  meta: {}\n  locales: [{}]\n",
                 self.uuid,
-                self.source.display(),
+                self.source,
                 self.source_format,
                 self.native_format,
-            )
+                self.settings,
+                self.meta,
+                self.locales.join(", "),
+            )?;
+            if let Some(stored_by) = &self.stored_by {
+                writeln!(f, "  stored_by: {}", stored_by)?;
+            }
+            if let Some(stored_at) = self.stored_at {
+                writeln!(f, "  stored_at: {}", stored_at)?;
+            }
+            if let Some(reimported_by) = &self.reimported_by {
+                writeln!(f, "  reimported_by: {}", reimported_by)?;
+            }
+            if let Some(reimported_at) = self.reimported_at {
+                writeln!(f, "  reimported_at: {}", reimported_at)?;
+            }
+            write!(f, "}}")
         } else {
             write!(
                 f,
                 "{{ {} <- '{}' : '{}' as '{}' }}",
-                self.uuid,
-                self.source.display(),
-                self.source_format,
-                self.native_format,
+                self.uuid, self.source, self.source_format, self.native_format,
             )
         }
     }
 }
 
 impl Asset {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         uuid: Uuid,
-        source: Box<Path>,
+        source: Box<str>,
         source_format: Box<str>,
         native_format: Box<str>,
         tags: Box<[Box<str>]>,
+        settings: serde_json::Value,
+        meta: serde_json::Value,
+        dependencies: Box<[Box<Path>]>,
+        locales: Box<[Box<str>]>,
+        importer_version: Option<Box<str>>,
     ) -> Asset {
         Asset {
             uuid,
             source,
             source_format,
             native_format,
-            tags,
+            name: None,
+            tags: sorted_tags(tags),
+            settings,
+            meta,
+            dependencies,
+            references: Box::new([]),
+            locales,
+            importer_version,
+            source_hash: None,
+            aliases: Vec::new(),
+            visibility: Visibility::Public,
+            lifecycle: Lifecycle::Approved,
+            pinned: false,
+            stored_by: None,
+            stored_at: None,
+            reimported_by: None,
+            reimported_at: None,
+            version: 0,
+            schema: 0,
         }
     }
 
@@ -79,11 +259,288 @@ impl Asset {
         &self.native_format
     }
 
-    pub fn source(&self) -> &Path {
+    /// Returns human-readable name of the asset used in errors and logs.
+    /// Defaults to the source file stem.
+    pub fn name(&self) -> &str {
+        match &self.name {
+            Some(name) => name,
+            None => Path::new(&*self.source)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(&self.source),
+        }
+    }
+
+    /// Returns URI of the source.
+    /// Sources in local filesystem are addressed by path relative to root path,
+    /// and sources of [`SourceProvider`]s by `<scheme>://<path>` URIs.
+    ///
+    /// [`SourceProvider`]: crate::SourceProvider
+    pub fn source(&self) -> &str {
         &self.source
     }
 
     pub fn tags(&self) -> &[Box<str>] {
         &self.tags
     }
+
+    pub fn settings(&self) -> &serde_json::Value {
+        &self.settings
+    }
+
+    /// Returns metadata recorded by importer.
+    /// `Null` if importer recorded none.
+    pub fn meta(&self) -> &serde_json::Value {
+        &self.meta
+    }
+
+    pub fn dependencies(&self) -> &[Box<Path>] {
+        &self.dependencies
+    }
+
+    /// Returns assets importer stored or fetched while importing the asset.
+    pub fn references(&self) -> &[Uuid] {
+        &self.references
+    }
+
+    /// Returns declared version of importer that produced native files.
+    pub fn importer_version(&self) -> Option<&str> {
+        self.importer_version.as_deref()
+    }
+
+    /// Returns hash of source content at last import.
+    pub fn source_hash(&self) -> Option<&str> {
+        self.source_hash.as_deref()
+    }
+
+    /// Returns other paths to sources with content identical to the asset source.
+    pub fn aliases(&self) -> &[Box<Path>] {
+        &self.aliases
+    }
+
+    /// Returns visibility of the asset.
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// Returns review state of the asset.
+    pub fn lifecycle(&self) -> Lifecycle {
+        self.lifecycle
+    }
+
+    /// Returns identity of the user who stored the asset.
+    /// `None` for assets stored before identities were recorded.
+    pub fn stored_by(&self) -> Option<&str> {
+        self.stored_by.as_deref()
+    }
+
+    /// Returns identity of the user who last reimported the asset.
+    /// `None` if asset was not reimported since it was stored.
+    pub fn reimported_by(&self) -> Option<&str> {
+        self.reimported_by.as_deref()
+    }
+
+    /// Returns time the asset was stored, according to treasury clock.
+    /// `None` for assets stored before times were recorded.
+    pub fn stored_at(&self) -> Option<SystemTime> {
+        self.stored_at.map(from_timestamp)
+    }
+
+    /// Returns time native files of the asset were last written by store or reimport,
+    /// according to treasury clock.
+    /// `None` for assets stored before times were recorded.
+    pub fn imported_at(&self) -> Option<SystemTime> {
+        self.reimported_at().or_else(|| self.stored_at())
+    }
+
+    /// Returns time the asset was last reimported, according to treasury clock.
+    /// `None` if asset was not reimported since it was stored.
+    pub fn reimported_at(&self) -> Option<SystemTime> {
+        self.reimported_at.map(from_timestamp)
+    }
+
+    /// Returns version of native files.
+    /// It changes whenever asset is reimported, regardless of file timestamps.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns schema version of native files, as recorded by importer.
+    pub fn schema(&self) -> u32 {
+        self.schema
+    }
+
+    /// Returns `true` if asset is pinned.
+    /// Pinned assets must stay resident and must not be cleaned up.
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    /// Returns copy of the asset with new uuid, source and tags.
+    /// Copy is neither pinned nor has aliases of the original,
+    /// and is attributed to `identity` storing it at `now`.
+    pub(crate) fn duplicate(
+        &self,
+        uuid: Uuid,
+        source: Box<str>,
+        tags: Box<[Box<str>]>,
+        identity: Box<str>,
+        now: SystemTime,
+    ) -> Asset {
+        Asset {
+            uuid,
+            source,
+            tags,
+            aliases: Vec::new(),
+            pinned: false,
+            stored_by: Some(identity),
+            stored_at: Some(timestamp(now)),
+            reimported_by: None,
+            reimported_at: None,
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+
+    pub(crate) fn set_tags(&mut self, tags: Box<[Box<str>]>) {
+        self.tags = sorted_tags(tags);
+    }
+
+    /// Sorts tags and aliases, so that serialized record is deterministic.
+    pub(crate) fn normalize(&mut self) {
+        self.tags = sorted_tags(std::mem::take(&mut self.tags));
+        self.aliases.sort();
+    }
+
+    pub(crate) fn set_visibility(&mut self, visibility: Visibility) {
+        self.visibility = visibility;
+    }
+
+    pub(crate) fn set_lifecycle(&mut self, lifecycle: Lifecycle) {
+        self.lifecycle = lifecycle;
+    }
+
+    pub(crate) fn set_name(&mut self, name: Option<Box<str>>) {
+        self.name = name;
+    }
+
+    /// Returns locales asset has native files for.
+    pub fn locales(&self) -> &[Box<str>] {
+        &self.locales
+    }
+
+    /// Returns most specific locale asset has native file for,
+    /// falling back from `locale` along [`locale_fallbacks`].
+    /// Returns `None` if native file without locale should be used.
+    ///
+    /// [`locale_fallbacks`]: crate::locale_fallbacks
+    pub fn resolve_locale(&self, locale: Option<&str>) -> Option<&str> {
+        let locale = locale?;
+        locale_fallbacks(locale)
+            .find_map(|locale| self.locales.iter().find(|l| ***l == *locale))
+            .map(|locale| &**locale)
+    }
+
+    /// Updates data recorded by importer on reimport.
+    #[cfg(feature = "import")]
+    pub(crate) fn set_import_output(
+        &mut self,
+        meta: serde_json::Value,
+        dependencies: Box<[Box<Path>]>,
+        references: Box<[Uuid]>,
+        locales: Box<[Box<str>]>,
+        importer_version: Option<Box<str>>,
+        schema: u32,
+    ) {
+        self.meta = meta;
+        self.dependencies = dependencies;
+        self.references = references;
+        self.locales = locales;
+        self.importer_version = importer_version;
+        self.schema = schema;
+        self.bump_version();
+    }
+
+    #[cfg(feature = "import")]
+    pub(crate) fn set_stored_by(&mut self, identity: Box<str>, now: SystemTime) {
+        self.stored_by = Some(identity);
+        self.stored_at = Some(timestamp(now));
+    }
+
+    #[cfg(feature = "import")]
+    pub(crate) fn set_reimported_by(&mut self, identity: Box<str>, now: SystemTime) {
+        self.reimported_by = Some(identity);
+        self.reimported_at = Some(timestamp(now));
+    }
+
+    #[cfg(feature = "import")]
+    pub(crate) fn set_native_format(&mut self, native_format: Box<str>) {
+        self.native_format = native_format;
+    }
+
+    #[cfg(feature = "import")]
+    pub(crate) fn set_schema(&mut self, schema: u32) {
+        self.schema = schema;
+    }
+
+    #[cfg(feature = "import")]
+    pub(crate) fn set_references(&mut self, references: Box<[Uuid]>) {
+        self.references = references;
+    }
+
+    /// Marks native files as updated.
+    #[cfg(feature = "import")]
+    pub(crate) fn bump_version(&mut self) {
+        self.version += 1;
+    }
+
+    pub(crate) fn set_version(&mut self, version: u64) {
+        self.version = version;
+    }
+
+    /// Updates hash of source content.
+    /// Aliases are dropped if content has changed,
+    /// as they refer to copies of previous content.
+    #[cfg(feature = "import")]
+    pub(crate) fn set_source_hash(&mut self, source_hash: Option<Box<str>>) {
+        if self.source_hash != source_hash {
+            self.aliases.clear();
+        }
+        self.source_hash = source_hash;
+    }
+
+    #[cfg(feature = "import")]
+    pub(crate) fn add_alias(&mut self, path: Box<Path>) {
+        if let Err(index) = self.aliases.binary_search(&path) {
+            self.aliases.insert(index, path);
+        }
+    }
+
+    /// Returns import settings as passed to importers.
+    /// Empty string if asset has no settings.
+    #[cfg(feature = "import")]
+    pub(crate) fn settings_json(&self) -> String {
+        settings_json(&self.settings)
+    }
+}
+
+/// Returns tags sorted and deduplicated.
+fn sorted_tags(tags: Box<[Box<str>]>) -> Box<[Box<str>]> {
+    let mut tags = tags.into_vec();
+    tags.sort();
+    tags.dedup();
+    tags.into()
+}
+
+/// Serializes import settings to pass to importers.
+#[cfg(feature = "import")]
+pub(crate) fn settings_json(settings: &serde_json::Value) -> String {
+    if settings.is_null() {
+        String::new()
+    } else {
+        settings.to_string()
+    }
 }
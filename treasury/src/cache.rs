@@ -0,0 +1,196 @@
+use {
+    crate::{import::ImportOutput, treasury::localized_path},
+    std::path::Path,
+};
+
+/// Cache of import results shared between machines,
+/// so that identical assets are imported once per team.
+///
+/// Entries are opaque blobs addressed by keys derived from
+/// source content, importer name and version, formats and import settings.
+/// Failures are logged and import falls back to running importer locally.
+pub trait ImportCache: Send + Sync + 'static {
+    /// Returns entry stored under the key, or `None` on miss.
+    fn get(&self, key: &str) -> eyre::Result<Option<Vec<u8>>>;
+
+    /// Stores entry under the key.
+    fn put(&self, key: &str, entry: &[u8]) -> eyre::Result<()>;
+}
+
+/// Import cache in a directory, e.g. on a network share.
+#[derive(Clone, Debug)]
+pub struct DirImportCache {
+    root: Box<Path>,
+}
+
+impl DirImportCache {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        DirImportCache {
+            root: root.as_ref().into(),
+        }
+    }
+}
+
+impl ImportCache for DirImportCache {
+    fn get(&self, key: &str) -> eyre::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.root.join(key)) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn put(&self, key: &str, entry: &[u8]) -> eyre::Result<()> {
+        std::fs::create_dir_all(&self.root)?;
+
+        // Concurrent readers never see partially written entry.
+        let tmp = self
+            .root
+            .join(format!("{}.{}.tmp", key, uuid::Uuid::new_v4()));
+        std::fs::write(&tmp, entry)?;
+        std::fs::rename(&tmp, self.root.join(key))?;
+        Ok(())
+    }
+}
+
+/// Import cache service accessed over HTTP.
+/// Entries are downloaded with `GET <url>/<key>` and uploaded with `PUT <url>/<key>`.
+#[cfg(feature = "http")]
+#[derive(Clone, Debug)]
+pub struct HttpImportCache {
+    url: Box<str>,
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "http")]
+impl HttpImportCache {
+    pub fn new(url: &str) -> Self {
+        HttpImportCache {
+            url: url.trim_end_matches('/').into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl ImportCache for HttpImportCache {
+    fn get(&self, key: &str) -> eyre::Result<Option<Vec<u8>>> {
+        match self.agent.get(&format!("{}/{}", self.url, key)).call() {
+            Ok(response) => {
+                let mut entry = Vec::new();
+                std::io::Read::read_to_end(&mut response.into_reader(), &mut entry)?;
+                Ok(Some(entry))
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn put(&self, key: &str, entry: &[u8]) -> eyre::Result<()> {
+        self.agent
+            .put(&format!("{}/{}", self.url, key))
+            .send_bytes(entry)?;
+        Ok(())
+    }
+}
+
+/// Import result as stored in the cache.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CachedImport {
+    /// Metadata as JSON document.
+    meta: String,
+    importer_version: Option<Box<str>>,
+    schema: u32,
+    native: Vec<u8>,
+    localized: Vec<(Box<str>, Vec<u8>)>,
+}
+
+/// Returns cache key of the import.
+pub(crate) fn cache_key(
+    source_hash: &str,
+    importer: &str,
+    importer_version: Option<&str>,
+    source_format: &str,
+    native_format: &str,
+    settings: &str,
+) -> String {
+    use {sha2::Digest as _, std::fmt::Write as _};
+
+    let mut hasher = sha2::Sha256::new();
+    for part in [
+        source_hash,
+        importer,
+        importer_version.unwrap_or(""),
+        source_format,
+        native_format,
+        settings,
+    ] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+
+    let mut hex = String::with_capacity(64);
+    for byte in hasher.finalize() {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
+
+/// Writes native files of cached import.
+/// Returns `None` on cache miss.
+pub(crate) fn load(
+    cache: &dyn ImportCache,
+    key: &str,
+    native_absolute: &Path,
+) -> eyre::Result<Option<ImportOutput>> {
+    let entry = match cache.get(key)? {
+        None => return Ok(None),
+        Some(entry) => entry,
+    };
+
+    let cached: CachedImport = bincode::deserialize(&entry)?;
+    std::fs::write(native_absolute, &cached.native)?;
+    for (locale, bytes) in &cached.localized {
+        std::fs::write(localized_path(native_absolute, locale), bytes)?;
+    }
+
+    Ok(Some(ImportOutput {
+        meta: serde_json::from_str(&cached.meta)?,
+        dependencies: Vec::new(),
+        locales: cached.localized.into_iter().map(|(l, _)| l).collect(),
+        importer_version: cached.importer_version,
+        schema: cached.schema,
+        references: Vec::new(),
+    }))
+}
+
+/// Uploads native files written by import.
+/// Imports with dependencies or references to other assets are not cached,
+/// since key does not cover content of dependencies
+/// and cached import does not register referenced assets.
+pub(crate) fn store(
+    cache: &dyn ImportCache,
+    key: &str,
+    native_absolute: &Path,
+    output: &ImportOutput,
+) -> eyre::Result<()> {
+    if !output.dependencies.is_empty() || !output.references.is_empty() {
+        return Ok(());
+    }
+
+    let mut localized = Vec::new();
+    for locale in &output.locales {
+        let bytes = std::fs::read(localized_path(native_absolute, locale))?;
+        localized.push((locale.clone(), bytes));
+    }
+
+    let cached = CachedImport {
+        meta: output.meta.to_string(),
+        importer_version: output.importer_version.clone(),
+        schema: output.schema,
+        native: std::fs::read(native_absolute)?,
+        localized,
+    };
+
+    cache.put(key, &bincode::serialize(&cached)?)
+}
@@ -0,0 +1,87 @@
+use {
+    parking_lot::Mutex,
+    std::{
+        sync::Arc,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Source of current time for the treasury.
+/// Set with [`Treasury::set_clock`].
+///
+/// [`Treasury::set_clock`]: crate::Treasury::set_clock
+pub trait Clock: Send + Sync {
+    /// Returns current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// Clock reading system time.
+/// Used by treasury unless other clock is set.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Clock that stands still until set or advanced.
+/// Makes time deterministic in tests.
+///
+/// Cheap to clone, clones share the time.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<Mutex<SystemTime>>,
+}
+
+impl ManualClock {
+    /// Returns clock showing specified time.
+    pub fn new(now: SystemTime) -> Self {
+        ManualClock {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Sets time the clock shows.
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock() = now;
+    }
+
+    /// Moves clock forward.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    /// Returns clock showing Unix epoch.
+    fn default() -> Self {
+        ManualClock::new(UNIX_EPOCH)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock()
+    }
+}
+
+/// Returns seconds since Unix epoch as recorded in manifest.
+/// Times before the epoch are recorded as the epoch.
+pub(crate) fn timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Checks if time is later than time recorded in manifest.
+/// Compared in whole seconds, as times are recorded.
+#[cfg(feature = "import")]
+pub(crate) fn is_after(time: SystemTime, recorded: SystemTime) -> bool {
+    timestamp(time) > timestamp(recorded)
+}
+
+/// Returns time of timestamp recorded in manifest.
+pub(crate) fn from_timestamp(timestamp: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(timestamp)
+}
@@ -0,0 +1,58 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum GitError {
+    #[error("Failed to run git")]
+    SpawnError { source: std::io::Error },
+
+    #[error("'git {command}' failed: {stderr}")]
+    CommandFailed { command: Box<str>, stderr: Box<str> },
+}
+
+/// Returns files changed in work tree since `commit`,
+/// including uncommitted and untracked files.
+/// Paths are relative to `root`, and files outside of it are omitted.
+pub(crate) fn changed_since(root: &Path, commit: &str) -> Result<Vec<PathBuf>, GitError> {
+    let mut changed = git(
+        root,
+        &[
+            "diff",
+            "--name-only",
+            "--no-renames",
+            "--relative",
+            "-z",
+            commit,
+            "--",
+        ],
+    )?;
+    changed.extend(git(
+        root,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?);
+    Ok(changed)
+}
+
+/// Runs git command in `root` and returns NUL-separated paths it printed.
+fn git(root: &Path, args: &[&str]) -> Result<Vec<PathBuf>, GitError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|source| GitError::SpawnError { source })?;
+
+    if !output.status.success() {
+        return Err(GitError::CommandFailed {
+            command: args.join(" ").into(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().into(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
@@ -0,0 +1,321 @@
+use {
+    crate::asset::Asset,
+    std::{
+        collections::{HashMap, HashSet},
+        fmt::Write as _,
+        path::Path,
+    },
+};
+
+/// Format of asset dependency graph exported with [`Treasury::export_graph`].
+///
+/// [`Treasury::export_graph`]: crate::Treasury::export_graph
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT document.
+    Dot,
+
+    /// JSON Graph Format document, see <https://jsongraphformat.info>.
+    GraphJson,
+}
+
+/// Appearance of asset nodes in exported graph.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeStyle {
+    /// Fill color, Graphviz color name or `#rrggbb`.
+    pub color: Option<Box<str>>,
+
+    /// Graphviz node shape, e.g. `box` or `ellipse`.
+    pub shape: Option<Box<str>>,
+}
+
+impl NodeStyle {
+    /// Returns style with fill color.
+    pub fn color(color: &str) -> Self {
+        NodeStyle {
+            color: Some(color.into()),
+            shape: None,
+        }
+    }
+
+    /// Returns style with node shape.
+    pub fn shape(shape: &str) -> Self {
+        NodeStyle {
+            color: None,
+            shape: Some(shape.into()),
+        }
+    }
+
+    pub fn with_color(mut self, color: &str) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn with_shape(mut self, shape: &str) -> Self {
+        self.shape = Some(shape.into());
+        self
+    }
+
+    /// Overrides properties set in `other`.
+    fn merge(&mut self, other: &NodeStyle) {
+        if other.color.is_some() {
+            self.color = other.color.clone();
+        }
+        if other.shape.is_some() {
+            self.shape = other.shape.clone();
+        }
+    }
+}
+
+/// Styles of asset nodes by native format and tags.
+///
+/// Tag styles override native format styles,
+/// and styles of tags added earlier override later ones.
+#[derive(Clone, Debug, Default)]
+pub struct GraphStyle {
+    formats: Vec<(Box<str>, NodeStyle)>,
+    tags: Vec<(Box<str>, NodeStyle)>,
+}
+
+impl GraphStyle {
+    pub fn new() -> Self {
+        GraphStyle::default()
+    }
+
+    /// Styles assets of the native format.
+    pub fn with_format(mut self, native_format: &str, style: NodeStyle) -> Self {
+        self.formats.push((native_format.into(), style));
+        self
+    }
+
+    /// Styles assets with the tag.
+    pub fn with_tag(mut self, tag: &str, style: NodeStyle) -> Self {
+        self.tags.push((tag.into(), style));
+        self
+    }
+
+    fn node_style(&self, asset: &Asset) -> NodeStyle {
+        let mut style = NodeStyle::default();
+
+        if let Some((_, format_style)) = self
+            .formats
+            .iter()
+            .find(|(format, _)| **format == *asset.native_format())
+        {
+            style.merge(format_style);
+        }
+
+        for (_, tag_style) in self
+            .tags
+            .iter()
+            .rev()
+            .filter(|(tag, _)| asset.tags().contains(tag))
+        {
+            style.merge(tag_style);
+        }
+
+        style
+    }
+}
+
+/// Kind of edge between assets.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EdgeKind {
+    /// Asset stored or fetched by importer of another asset.
+    Reference,
+
+    /// Source of the asset is a file another asset depends on.
+    Dependency,
+}
+
+impl EdgeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EdgeKind::Reference => "reference",
+            EdgeKind::Dependency => "dependency",
+        }
+    }
+}
+
+/// Builds dependency graph of the assets and writes it in specified format.
+/// `size` returns size of asset native files.
+pub(crate) fn export<'a>(
+    assets: impl Iterator<Item = &'a Asset>,
+    format: GraphFormat,
+    style: &GraphStyle,
+    size: impl Fn(&Asset) -> u64,
+) -> String {
+    let assets: Vec<&Asset> = assets.collect();
+    let sizes: Vec<u64> = assets.iter().map(|asset| size(asset)).collect();
+
+    let mut index = HashMap::new();
+    let mut sources = HashMap::new();
+    for (i, asset) in assets.iter().enumerate() {
+        index.insert(asset.uuid(), i);
+        sources.insert(Path::new(asset.source()), i);
+        for alias in asset.aliases() {
+            sources.insert(&**alias, i);
+        }
+    }
+
+    let mut edges: Vec<Vec<(usize, EdgeKind)>> = vec![Vec::new(); assets.len()];
+    for (i, asset) in assets.iter().enumerate() {
+        // References to removed assets are dropped.
+        let references = asset
+            .references()
+            .iter()
+            .filter_map(|uuid| index.get(uuid))
+            .map(|&j| (j, EdgeKind::Reference));
+
+        let dependencies = asset
+            .dependencies()
+            .iter()
+            .filter_map(|path| sources.get(&**path))
+            .map(|&j| (j, EdgeKind::Dependency));
+
+        for (j, kind) in references.chain(dependencies) {
+            if j != i && !edges[i].iter().any(|&(k, _)| k == j) {
+                edges[i].push((j, kind));
+            }
+        }
+    }
+
+    // Size of the asset along with all assets it pulls in.
+    let total_sizes: Vec<u64> = (0..assets.len())
+        .map(|i| {
+            let mut visited = HashSet::new();
+            let mut stack = vec![i];
+            let mut total = 0;
+            while let Some(i) = stack.pop() {
+                if visited.insert(i) {
+                    total += sizes[i];
+                    stack.extend(edges[i].iter().map(|&(j, _)| j));
+                }
+            }
+            total
+        })
+        .collect();
+
+    let nodes = assets.iter().enumerate().map(|(i, asset)| Node {
+        asset,
+        size: sizes[i],
+        total_size: total_sizes[i],
+        style: style.node_style(asset),
+    });
+
+    match format {
+        GraphFormat::Dot => {
+            let mut dot =
+                String::from("digraph assets {\n    node [style=filled, fillcolor=white];\n");
+            for node in nodes {
+                let _ = write!(
+                    dot,
+                    "    \"{}\" [label=\"{}\\n{}\\n{} / {}\"",
+                    node.asset.uuid(),
+                    dot_escape(node.asset.name()),
+                    dot_escape(node.asset.native_format()),
+                    human_size(node.size),
+                    human_size(node.total_size),
+                );
+                if let Some(color) = &node.style.color {
+                    let _ = write!(dot, ", fillcolor=\"{}\"", dot_escape(color));
+                }
+                if let Some(shape) = &node.style.shape {
+                    let _ = write!(dot, ", shape=\"{}\"", dot_escape(shape));
+                }
+                dot.push_str("];\n");
+            }
+            for (i, edges) in edges.iter().enumerate() {
+                for &(j, kind) in edges {
+                    let _ = write!(
+                        dot,
+                        "    \"{}\" -> \"{}\"",
+                        assets[i].uuid(),
+                        assets[j].uuid()
+                    );
+                    if kind == EdgeKind::Dependency {
+                        dot.push_str(" [style=dashed]");
+                    }
+                    dot.push_str(";\n");
+                }
+            }
+            dot.push_str("}\n");
+            dot
+        }
+        GraphFormat::GraphJson => {
+            let nodes: serde_json::Map<_, _> = nodes
+                .map(|node| {
+                    let mut metadata = serde_json::json!({
+                        "source": node.asset.source(),
+                        "native_format": node.asset.native_format(),
+                        "tags": node.asset.tags(),
+                        "size": node.size,
+                        "total_size": node.total_size,
+                    });
+                    if let Some(color) = &node.style.color {
+                        metadata["color"] = color.as_ref().into();
+                    }
+                    if let Some(shape) = &node.style.shape {
+                        metadata["shape"] = shape.as_ref().into();
+                    }
+
+                    (
+                        node.asset.uuid().to_string(),
+                        serde_json::json!({ "label": node.asset.name(), "metadata": metadata }),
+                    )
+                })
+                .collect();
+
+            let edges: Vec<_> = edges
+                .iter()
+                .enumerate()
+                .flat_map(|(i, edges)| {
+                    let assets = &assets;
+                    edges.iter().map(move |&(j, kind)| {
+                        serde_json::json!({
+                            "source": assets[i].uuid(),
+                            "target": assets[j].uuid(),
+                            "relation": kind.as_str(),
+                        })
+                    })
+                })
+                .collect();
+
+            let graph = serde_json::json!({
+                "graph": {
+                    "directed": true,
+                    "nodes": nodes,
+                    "edges": edges,
+                }
+            });
+            serde_json::to_string_pretty(&graph).unwrap()
+        }
+    }
+}
+
+struct Node<'a> {
+    asset: &'a Asset,
+    size: u64,
+    total_size: u64,
+    style: NodeStyle,
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
@@ -1,12 +1,23 @@
 use {
-    crate::treasury::Registry,
+    crate::{
+        pack,
+        process::{ProcessImporter, ProcessManifest, MANIFEST_FILE_NAME},
+        treasury::Registry,
+    },
     eyre::WrapErr,
-    parking_lot::{Mutex, MutexGuard},
+    goods_treasury_import::{
+        is_valid_locale, AsyncImporter, FormatId, ImportProgress, IMPORTER_FFI_VERSION,
+    },
+    parking_lot::Mutex,
     std::{
+        cell::{Cell, RefCell},
         collections::hash_map::HashMap,
         path::{Path, PathBuf},
-        sync::{Arc, Weak},
+        ptr::NonNull,
+        sync::{mpsc, Arc, Weak},
+        time::Duration,
     },
+    tokio::{runtime::Runtime, sync::watch},
     uuid::Uuid,
     wasmer::{
         Array, Function, Instance, LazyInit, Memory, Module, NativeFunc, Store, WasmPtr, WasmerEnv,
@@ -23,10 +34,232 @@ pub struct ImporterOpaque {
 const WASM_IMPORTERS_INITIAL_COUNT: u32 = 64;
 const ERROR_BUFFER_LEN: u32 = 2048;
 
+#[derive(Debug, thiserror::Error)]
+pub enum ImporterLoadError {
+    #[error("Failed to read importers module")]
+    IoError { source: std::io::Error },
+
+    #[error("Not a WASM module")]
+    NotWasm,
+
+    #[error("Importers module FFI version {found} is not compatible with version {expected}")]
+    AbiMismatch { expected: u32, found: u32 },
+
+    #[error("Failed to instantiate importers module")]
+    WasmError { source: eyre::Report },
+
+    #[error("Invalid importers manifest")]
+    InvalidManifest { source: toml::de::Error },
+
+    #[error("Importers module requires treasury features {features:?} that are not enabled")]
+    MissingFeatures { features: Vec<Box<str>> },
+
+    #[error("Invalid importers module metadata")]
+    InvalidMetadata { source: serde_json::Error },
+}
+
+/// Importers module found by treasury, as returned by [`Treasury::describe_importers`].
+///
+/// [`Treasury::describe_importers`]: crate::Treasury::describe_importers
+#[derive(Clone, Debug, Default)]
+pub struct ImporterModule {
+    /// Path to WASM module or importers manifest.
+    /// `None` for importers added with `Treasury::add_async_importer`.
+    pub path: Option<Box<Path>>,
+
+    /// Name and version of the crate WASM module is built from.
+    /// `None` if module does not export metadata.
+    pub name: Option<Box<str>>,
+    pub version: Option<Box<str>>,
+
+    /// FFI version WASM module is built with.
+    pub ffi_version: Option<u32>,
+
+    /// Treasury features module requires.
+    pub features: Vec<Box<str>>,
+
+    pub importers: Vec<ImporterInfo>,
+
+    /// Reason importers of the module are not loaded.
+    /// `None` if they are loaded.
+    pub error: Option<Box<str>>,
+}
+
+/// Importer of [`ImporterModule`].
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct ImporterInfo {
+    pub name: Box<str>,
+    pub source: Box<str>,
+    pub native: Box<str>,
+
+    /// Declared version of the importer.
+    /// Assets imported with different version are reimported.
+    #[serde(default)]
+    pub version: Option<Box<str>>,
+
+    /// Path to WASM module or importers manifest the importer is loaded from.
+    /// `None` for importers added with `Treasury::add_async_importer`.
+    #[serde(skip)]
+    pub origin: Option<Box<Path>>,
+}
+
+/// Metadata exported by WASM module.
+#[derive(serde::Deserialize)]
+struct ModuleMetadata {
+    name: Box<str>,
+    version: Box<str>,
+    ffi_version: u32,
+    features: Vec<Box<str>>,
+    importers: Vec<ImporterInfo>,
+}
+
+/// Checks if treasury is built with feature importers may require.
+fn has_feature(feature: &str) -> bool {
+    match feature {
+        "import" => true,
+        "git" => cfg!(feature = "git"),
+        "http" => cfg!(feature = "http"),
+        _ => false,
+    }
+}
+
+/// Formats error with its sources.
+fn error_chain(err: &dyn std::error::Error) -> Box<str> {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message.push_str(". ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+    message.into()
+}
+
+/// Importer found for source and native formats.
+#[derive(Clone)]
+pub(crate) enum ImporterEntry {
+    Wasm(Arc<WasmImporter>),
+    Async(AsyncImporterEntry),
+}
+
+#[derive(Clone)]
+pub(crate) struct AsyncImporterEntry {
+    importer: Arc<dyn AsyncImporter>,
+    version: Option<Box<str>>,
+    origin: Option<Box<Path>>,
+    runtime: tokio::runtime::Handle,
+    registry: Weak<Mutex<Registry>>,
+}
+
+/// Error of a single import.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ImportError {
+    #[error(transparent)]
+    Failed(eyre::Report),
+
+    #[error("Import timed out")]
+    Timeout,
+
+    #[error("Import cancelled")]
+    Cancelled,
+}
+
+/// Limits of a single import.
+#[derive(Clone, Default)]
+pub(crate) struct ImportControl {
+    /// Time given to async importer to finish.
+    pub timeout: Option<Duration>,
+
+    /// Token to cancel the import.
+    pub cancel: Option<CancelToken>,
+
+    /// Assets registered by the import and its sub-imports.
+    /// They are removed if the import is cancelled.
+    pub stored: Arc<Mutex<Vec<Uuid>>>,
+}
+
+impl ImportControl {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Returns control of sub-imports performed by the import.
+    /// Sub-imports are cancelled and rolled back with the import,
+    /// but are not limited by its timeout.
+    pub fn nested(&self) -> Self {
+        ImportControl {
+            timeout: None,
+            cancel: self.cancel.clone(),
+            stored: self.stored.clone(),
+        }
+    }
+}
+
+/// Token to cancel running imports.
+///
+/// Cancelling drops futures of running async importers
+/// and prevents scheduler from starting new jobs.
+/// Running WASM importers are not interrupted,
+/// but may check [`ImportProgress::is_cancelled`] to stop early,
+/// and their results are discarded.
+///
+/// Cancelled imports leave no native files,
+/// and assets registered by their sub-imports are removed.
+#[derive(Clone, Debug)]
+pub struct CancelToken {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        CancelToken::new()
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// Cancels imports this token is passed to.
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves once token is cancelled.
+    async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        while !*receiver.borrow_and_update() {
+            // Sender is owned by `self` and outlives receiver.
+            let _ = receiver.changed().await;
+        }
+    }
+}
+
+/// Runtime of async importers.
+struct AsyncRuntime(Option<Runtime>);
+
+impl Drop for AsyncRuntime {
+    fn drop(&mut self) {
+        // Treasury may be dropped within async context where runtime can't block.
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
 pub(crate) struct Importers {
-    map: HashMap<Box<str>, HashMap<Box<str>, Arc<WasmImporter>>>,
+    map: HashMap<Box<str>, HashMap<Box<str>, ImporterEntry>>,
+    modules: Vec<ImporterModule>,
     store: Store,
     wasi: WasiEnv,
+    runtime: AsyncRuntime,
 }
 
 impl Importers {
@@ -54,14 +287,135 @@ impl Importers {
         Importers {
             wasi,
             map: HashMap::new(),
+            modules: Vec::new(),
             store,
+            runtime: AsyncRuntime(None),
         }
     }
 
-    pub fn get_importer(&self, source: &str, native: &str) -> Option<Arc<WasmImporter>> {
+    pub fn get_importer(&self, source: &str, native: &str) -> Option<ImporterEntry> {
         self.map.get(source)?.get(native).cloned()
     }
 
+    /// Returns source and native formats of all importers.
+    pub fn formats(&self) -> Vec<(FormatId, FormatId)> {
+        self.map
+            .iter()
+            .flat_map(|(source, natives)| {
+                natives
+                    .keys()
+                    .map(move |native| (FormatId::from(&**source), FormatId::from(&**native)))
+            })
+            .collect()
+    }
+
+    /// Returns importers used for imports, one per source and native formats pair.
+    pub fn registered(&self) -> Vec<ImporterInfo> {
+        self.map
+            .values()
+            .flat_map(|natives| natives.values().map(ImporterEntry::info))
+            .collect()
+    }
+
+    /// Returns native formats importers from `source` format produce.
+    pub fn natives(&self, source: &str) -> Vec<Box<str>> {
+        match self.map.get(source) {
+            None => Vec::new(),
+            Some(natives) => {
+                let mut natives: Vec<_> = natives.keys().cloned().collect();
+                natives.sort();
+                natives
+            }
+        }
+    }
+
+    /// Returns all importers modules found, including ones that failed to load.
+    pub fn describe(&self) -> Vec<ImporterModule> {
+        self.modules.clone()
+    }
+
+    /// Records module, replacing previous record of module at the same path.
+    fn add_module(&mut self, module: ImporterModule) {
+        match &module.path {
+            Some(path) => match self
+                .modules
+                .iter_mut()
+                .find(|m| m.path.as_ref() == Some(path))
+            {
+                Some(slot) => *slot = module,
+                None => self.modules.push(module),
+            },
+            None => self.modules.push(module),
+        }
+    }
+
+    /// Registers async importer added by user,
+    /// replacing importer with same source and native formats.
+    pub fn add_user_async_importer(
+        &mut self,
+        importer: Arc<dyn AsyncImporter>,
+        registry: &Arc<Mutex<Registry>>,
+    ) -> std::io::Result<()> {
+        let info = ImporterInfo {
+            name: importer.name().into(),
+            source: importer.source().into(),
+            native: importer.native().into(),
+            version: None,
+            origin: None,
+        };
+
+        self.add_async_importer(importer, None, None, registry)?;
+        self.add_module(ImporterModule {
+            importers: vec![info],
+            ..ImporterModule::default()
+        });
+        Ok(())
+    }
+
+    /// Registers async importer, replacing importer with same source and native formats.
+    /// Runtime for async importers is started on first call.
+    ///
+    /// Assets imported with different version of the importer are reimported.
+    pub fn add_async_importer(
+        &mut self,
+        importer: Arc<dyn AsyncImporter>,
+        version: Option<Box<str>>,
+        origin: Option<Box<Path>>,
+        registry: &Arc<Mutex<Registry>>,
+    ) -> std::io::Result<()> {
+        let runtime = match &mut self.runtime.0 {
+            Some(runtime) => runtime,
+            slot => slot.get_or_insert(
+                tokio::runtime::Builder::new_multi_thread()
+                    .thread_name("treasury-import")
+                    .enable_all()
+                    .build()?,
+            ),
+        };
+
+        tracing::info!(
+            "Async importer '{}' from '{}' to '{}' added",
+            importer.name(),
+            importer.source(),
+            importer.native()
+        );
+
+        let entry = AsyncImporterEntry {
+            runtime: runtime.handle().clone(),
+            registry: Arc::downgrade(registry),
+            importer,
+            version,
+            origin,
+        };
+
+        self.map
+            .entry(entry.importer.source().into())
+            .or_default()
+            .insert(entry.importer.native().into(), ImporterEntry::Async(entry));
+
+        Ok(())
+    }
+
     pub fn load_importers_dir(
         &mut self,
         dir_path: &Path,
@@ -69,6 +423,17 @@ impl Importers {
     ) -> std::io::Result<()> {
         let dir = std::fs::read_dir(dir_path)?;
 
+        let manifest_path = dir_path.join(MANIFEST_FILE_NAME);
+        if manifest_path.is_file() {
+            if let Err(err) = self.load_process_importers(&manifest_path, registry) {
+                tracing::warn!(
+                    "Could not load importers from '{}'. {:#}",
+                    manifest_path.display(),
+                    err
+                );
+            }
+        }
+
         for e in dir {
             let e = e?;
             let path = PathBuf::from(e.file_name());
@@ -89,20 +454,158 @@ impl Importers {
         Ok(())
     }
 
+    fn load_process_importers(
+        &mut self,
+        manifest_path: &Path,
+        registry: &Arc<Mutex<Registry>>,
+    ) -> Result<(), ImporterLoadError> {
+        let mut module = ImporterModule {
+            path: Some(manifest_path.into()),
+            ..ImporterModule::default()
+        };
+
+        let result = self.load_process_module(manifest_path, registry, &mut module);
+        if let Err(err) = &result {
+            module.error = Some(error_chain(err));
+        }
+        self.add_module(module);
+        result
+    }
+
+    fn load_process_module(
+        &mut self,
+        manifest_path: &Path,
+        registry: &Arc<Mutex<Registry>>,
+        module: &mut ImporterModule,
+    ) -> Result<(), ImporterLoadError> {
+        tracing::trace!("Load importers from: {}", manifest_path.display());
+
+        let manifest = std::fs::read_to_string(manifest_path)
+            .map_err(|source| ImporterLoadError::IoError { source })?;
+
+        let manifest: ProcessManifest = toml::from_str(&manifest)
+            .map_err(|source| ImporterLoadError::InvalidManifest { source })?;
+
+        let dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+
+        for decl in manifest.importers {
+            match ProcessImporter::new(decl, dir) {
+                None => tracing::warn!(
+                    "Importer with empty command in '{}' ignored",
+                    manifest_path.display()
+                ),
+                Some(importer) => {
+                    let version: Box<str> = importer.version().into();
+                    module.importers.push(ImporterInfo {
+                        name: importer.name().into(),
+                        source: importer.source().into(),
+                        native: importer.native().into(),
+                        version: Some(version.clone()),
+                        origin: Some(manifest_path.into()),
+                    });
+                    self.add_async_importer(
+                        Arc::new(importer),
+                        Some(version),
+                        Some(manifest_path.into()),
+                        registry,
+                    )
+                    .map_err(|source| ImporterLoadError::IoError { source })?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn load_importers(
         &mut self,
         wasm_path: &Path,
         registry: &Arc<Mutex<Registry>>,
-    ) -> eyre::Result<()> {
+    ) -> Result<(), ImporterLoadError> {
+        let mut module = ImporterModule {
+            path: Some(wasm_path.into()),
+            ..ImporterModule::default()
+        };
+
+        let result = self.load_wasm_module(wasm_path, registry, &mut module);
+        if let Err(err) = &result {
+            module.error = Some(error_chain(err));
+        }
+        self.add_module(module);
+        result
+    }
+
+    fn load_wasm_module(
+        &mut self,
+        wasm_path: &Path,
+        registry: &Arc<Mutex<Registry>>,
+        module: &mut ImporterModule,
+    ) -> Result<(), ImporterLoadError> {
         tracing::trace!("Load importers from: {}", wasm_path.display());
 
-        let bytes = std::fs::read(wasm_path)?;
+        let bytes =
+            std::fs::read(wasm_path).map_err(|source| ImporterLoadError::IoError { source })?;
 
         if !wasmer::is_wasm(&bytes) {
-            return Err(eyre::eyre!("Not a WASM module"));
+            return Err(ImporterLoadError::NotWasm);
+        }
+
+        let instance = self
+            .instantiate(&bytes, registry)
+            .map_err(|source| ImporterLoadError::WasmError { source })?;
+
+        // Metadata is read before checking FFI version,
+        // so that incompatible modules are described too.
+        if let Some(metadata) = read_metadata(&instance)? {
+            module.name = Some(metadata.name);
+            module.version = Some(metadata.version);
+            module.ffi_version = Some(metadata.ffi_version);
+            module.features = metadata.features;
+            module.importers = metadata.importers;
         }
 
-        let module = Module::new(&self.store, &bytes)?;
+        // Modules built before FFI versioning do not export version.
+        let found = match instance
+            .exports
+            .get_native_function::<(), u32>("treasury_importer_ffi_version")
+        {
+            Ok(version) => version
+                .call()
+                .map_err(|err| ImporterLoadError::WasmError { source: err.into() })?,
+            Err(_) => 0,
+        };
+
+        module.ffi_version = Some(found);
+        if found != IMPORTER_FFI_VERSION {
+            return Err(ImporterLoadError::AbiMismatch {
+                expected: IMPORTER_FFI_VERSION,
+                found,
+            });
+        }
+
+        let missing: Vec<_> = module
+            .features
+            .iter()
+            .filter(|feature| !has_feature(feature))
+            .cloned()
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(ImporterLoadError::MissingFeatures { features: missing });
+        }
+
+        module.importers = self
+            .enumerate_importers(&instance, wasm_path)
+            .map_err(|source| ImporterLoadError::WasmError { source })?;
+        Ok(())
+    }
+
+    fn instantiate(
+        &mut self,
+        bytes: &[u8],
+        registry: &Arc<Mutex<Registry>>,
+    ) -> eyre::Result<Instance> {
+        let module = Module::new(&self.store, bytes)?;
 
         let mut imports = self.wasi.import_object(&module)?;
 
@@ -114,10 +617,23 @@ impl Importers {
         imports.register("env", wasmer::import_namespace! {{
             "treasury_registry_store" => Function::new_native_with_env(&self.store, env.clone(), treasury_registry_store),
             "treasury_registry_fetch" => Function::new_native_with_env(&self.store, env.clone(), treasury_registry_fetch),
+            "treasury_import_progress" => Function::new_native_with_env(&self.store, env.clone(), treasury_import_progress),
+            "treasury_import_cancelled" => Function::new_native_with_env(&self.store, env.clone(), treasury_import_cancelled),
+            "treasury_import_meta" => Function::new_native_with_env(&self.store, env.clone(), treasury_import_meta),
+            "treasury_import_dependency" => Function::new_native_with_env(&self.store, env.clone(), treasury_import_dependency),
+            "treasury_import_locale" => Function::new_native_with_env(&self.store, env.clone(), treasury_import_locale),
+            "treasury_import_schema" => Function::new_native_with_env(&self.store, env.clone(), treasury_import_schema),
         }});
 
         let instance = Instance::new(&module, &imports)?;
+        Ok(instance)
+    }
 
+    fn enumerate_importers(
+        &mut self,
+        instance: &Instance,
+        wasm_path: &Path,
+    ) -> eyre::Result<Vec<ImporterInfo>> {
         let memory = instance.exports.get_memory("memory")?;
 
         let alloc = instance
@@ -139,17 +655,11 @@ impl Importers {
             "treasury_importer_name_source_native_trampoline",
         )?;
 
-        let importer_import_trampoline =
-            instance.exports.get_native_function::<(
-                WasmPtr<fn()>,
-                WasmPtr<ImporterOpaque>,
-                WasmStrPtr,
-                u32,
-                WasmStrPtr,
-                u32,
-                WasmStrPtr,
-                u32,
-            ), i32>("treasury_importer_import_trampoline")?;
+        let importer_import_trampoline = instance
+            .exports
+            .get_native_function::<ImportFunctionArgs, i32>(
+                "treasury_importer_import_trampoline",
+            )?;
 
         let enumerate_importers = instance
             .exports
@@ -194,6 +704,7 @@ impl Importers {
 
         let ptrs = importers_ptr_u32.deref(memory, 0, count * 5).unwrap();
 
+        let mut infos = Vec::with_capacity(count as usize);
         for ptrs in ptrs.chunks_exact(5) {
             let ffi = match ptrs {
                 [data, name, source, native, import] => WasmImporterFFI {
@@ -206,7 +717,15 @@ impl Importers {
                 _ => unreachable!(),
             };
 
-            let importer = WasmImporter::new(ffi, state.clone());
+            let importer = WasmImporter::new(ffi, state.clone(), wasm_path);
+            infos.push(ImporterInfo {
+                name: importer.name().into(),
+                source: importer.source().into(),
+                native: importer.native().into(),
+                version: None,
+                origin: Some(wasm_path.into()),
+            });
+
             tracing::info!(
                 "Importer '{}' from '{}' to '{}' loaded",
                 importer.name(),
@@ -218,15 +737,57 @@ impl Importers {
                 .entry(importer.source().into())
                 .or_default()
                 .entry(importer.native().into())
-                .or_insert_with(|| Arc::new(importer));
+                .or_insert_with(|| ImporterEntry::Wasm(Arc::new(importer)));
         }
 
         state.dealloc.call(ptr, allocated_size, 4)?;
 
-        Ok(())
+        Ok(infos)
     }
 }
 
+/// Reads metadata exported by WASM module.
+/// Returns `None` if module does not export metadata.
+fn read_metadata(instance: &Instance) -> Result<Option<ModuleMetadata>, ImporterLoadError> {
+    let metadata = match instance
+        .exports
+        .get_native_function::<(WasmStrPtr, u32), u32>("treasury_importer_metadata")
+    {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(None),
+    };
+
+    let read = || -> eyre::Result<String> {
+        let memory = instance.exports.get_memory("memory")?;
+        let alloc = instance
+            .exports
+            .get_native_function::<(u32, u32), WasmStrPtr>("treasury_importer_alloc")?;
+        let dealloc = instance
+            .exports
+            .get_native_function::<(WasmStrPtr, u32, u32), ()>("treasury_importer_dealloc")?;
+
+        let mut cap = 1024;
+        loop {
+            let ptr = alloc.call(cap, 1)?;
+            let len = metadata.call(ptr, cap)?;
+            let json = ptr.get_utf8_string(memory, len.min(cap));
+            dealloc.call(ptr, cap, 1)?;
+
+            if len > cap {
+                cap = len;
+                continue;
+            }
+
+            return json.ok_or_else(|| eyre::eyre!("Metadata is not valid UTF-8"));
+        }
+    };
+
+    let json = read().map_err(|source| ImporterLoadError::WasmError { source })?;
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|source| ImporterLoadError::InvalidMetadata { source })
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 pub struct WasmImporterFFI {
@@ -248,6 +809,8 @@ type ImportFunctionArgs = (
     u32,
     WasmStrPtr,
     u32,
+    WasmStrPtr,
+    u32,
 );
 
 struct WasmState {
@@ -265,10 +828,11 @@ pub struct WasmImporter {
     name: String,
     source: String,
     native: String,
+    origin: Box<Path>,
 }
 
 impl WasmImporter {
-    fn new(ffi: WasmImporterFFI, state: Arc<WasmState>) -> Self {
+    fn new(ffi: WasmImporterFFI, state: Arc<WasmState>, origin: &Path) -> Self {
         const STRING_CAP: u32 = 256;
         let wasm_ptr = state.alloc.call(STRING_CAP, 1).unwrap();
 
@@ -301,6 +865,7 @@ impl WasmImporter {
             name,
             source,
             native,
+            origin: origin.into(),
         }
     }
 }
@@ -318,14 +883,16 @@ impl WasmImporter {
         &self.native
     }
 
+    /// Runs importer.
+    /// Returns metadata and dependencies recorded by importer.
     pub(crate) fn import(
         &self,
         source_path: &Path,
         native_path: &Path,
-        registry: MutexGuard<'_, Registry>,
-    ) -> eyre::Result<()> {
-        drop(registry);
-
+        settings: &str,
+        progress: &mut dyn ImportProgress,
+        control: &ImportControl,
+    ) -> eyre::Result<ImportOutput> {
         #[cfg(unix)]
         use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
         #[cfg(target_os = "wasi")]
@@ -365,15 +932,25 @@ impl WasmImporter {
         #[cfg(windows)]
         let native_path = native_path.as_bytes();
 
-        let size = source_path.len() as u32 + native_path.len() as u32 + ERROR_BUFFER_LEN;
+        let settings = settings.as_bytes();
+
+        let size = source_path.len() as u32
+            + native_path.len() as u32
+            + settings.len() as u32
+            + ERROR_BUFFER_LEN;
 
         let ptr = self.state.alloc.call(size, 1).unwrap();
 
         let source_ptr = WasmStrPtr::new(ptr.offset());
         let native_ptr = WasmStrPtr::new(ptr.offset() + source_path.len() as u32);
+        let settings_ptr =
+            WasmStrPtr::new(ptr.offset() + source_path.len() as u32 + native_path.len() as u32);
 
         let error_ptr = WasmPtr::<u8, Array>::new(
-            ptr.offset() + source_path.len() as u32 + native_path.len() as u32,
+            ptr.offset()
+                + source_path.len() as u32
+                + native_path.len() as u32
+                + settings.len() as u32,
         );
 
         let slice = source_ptr
@@ -394,16 +971,31 @@ impl WasmImporter {
             .zip(native_path)
             .for_each(|(cell, c)| cell.set(*c));
 
-        let result = self.state.importer_import_trampoline.call(
-            self.ffi.import,
-            self.ffi.data,
-            source_ptr,
-            source_path.len() as u32,
-            native_ptr,
-            native_path.len() as u32,
-            error_ptr,
-            ERROR_BUFFER_LEN,
-        )?;
+        let slice = settings_ptr
+            .deref(&self.state.memory, 0, settings.len() as u32)
+            .unwrap();
+
+        slice
+            .iter()
+            .zip(settings)
+            .for_each(|(cell, c)| cell.set(*c));
+
+        let (result, output) = with_import(progress, control, || {
+            self.state.importer_import_trampoline.call(
+                self.ffi.import,
+                self.ffi.data,
+                source_ptr,
+                source_path.len() as u32,
+                native_ptr,
+                native_path.len() as u32,
+                settings_ptr,
+                settings.len() as u32,
+                error_ptr,
+                ERROR_BUFFER_LEN,
+            )
+        });
+
+        let result = result?;
 
         if result < 0 {
             let len = result.abs() as u32;
@@ -414,13 +1006,39 @@ impl WasmImporter {
         } else {
             self.state.dealloc.call(ptr, size, 1)?;
             debug_assert_eq!(result, 0);
-            Ok(())
+            Ok(output)
         }
     }
 }
 
 type WasmStrPtr = WasmPtr<u8, Array>;
 
+/// Writes error message into importer's buffer, truncating it if necessary.
+/// Returns negated length of written message.
+fn write_error(memory: &Memory, ptr: WasmStrPtr, len: u32, error: &str) -> i32 {
+    let len = len.min(error.len() as u32);
+
+    match ptr.deref(memory, 0, len) {
+        Some(cells) => {
+            cells
+                .iter()
+                .zip(error.as_bytes())
+                .for_each(|(cell, byte)| cell.set(*byte));
+
+            -(len as i32)
+        }
+        None => {
+            tracing::error!("Importer provided error buffer out of memory bounds");
+            -1
+        }
+    }
+}
+
+/// Host function for `Registry::store` calls from importers.
+///
+/// Registry is not locked while importers run,
+/// so sub-imports never wait for the caller.
+/// Storing a source that is being imported by the caller fails with an error.
 #[allow(clippy::too_many_arguments)]
 fn treasury_registry_store(
     env: &ImporterEnv,
@@ -436,98 +1054,97 @@ fn treasury_registry_store(
     result_ptr: WasmStrPtr,
     result_len: u32,
 ) -> i32 {
-    let memory = env.memory_ref().unwrap();
-
-    let source = source_ptr.deref(memory, 0, source_len).unwrap();
-    let source = source.iter().map(std::cell::Cell::get).collect::<Vec<_>>();
-    let source = std::str::from_utf8(&source).unwrap();
-
-    #[cfg(windows)]
-    let source = &source.replace("/", "\\");
-
-    let source_format = source_format_ptr
-        .deref(memory, 0, source_format_len)
-        .unwrap();
-
-    let source_format = source_format
-        .iter()
-        .map(std::cell::Cell::get)
-        .collect::<Vec<_>>();
-
-    let source_format = std::str::from_utf8(&source_format).unwrap();
+    let memory = match env.memory_ref() {
+        Some(memory) => memory,
+        None => {
+            tracing::error!("Importer memory is not initialized");
+            return -1;
+        }
+    };
 
-    let native_format = native_format_ptr
-        .deref(memory, 0, native_format_len)
-        .unwrap();
+    let args = (|| {
+        let source = source_ptr.get_utf8_string(memory, source_len)?;
+        let source_format = source_format_ptr.get_utf8_string(memory, source_format_len)?;
+        let native_format = native_format_ptr.get_utf8_string(memory, native_format_len)?;
 
-    let native_format = native_format
-        .iter()
-        .map(std::cell::Cell::get)
-        .collect::<Vec<_>>();
+        let tag_ptrs = tag_ptrs.deref(memory, 0, tag_count)?;
+        let tag_lens = tag_lens.deref(memory, 0, tag_count)?;
 
-    let native_format = std::str::from_utf8(&native_format).unwrap();
+        let tags = tag_ptrs
+            .iter()
+            .zip(tag_lens)
+            .map(|(ptr, len)| ptr.get().get_utf8_string(memory, len.get()))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some((source, source_format, native_format, tags))
+    })();
+
+    let (source, source_format, native_format, tags) = match args {
+        Some(args) => args,
+        None => {
+            return write_error(
+                memory,
+                result_ptr,
+                result_len,
+                "Arguments must be valid UTF-8 strings within importer memory",
+            )
+        }
+    };
 
-    let tag_ptrs = tag_ptrs.deref(memory, 0, tag_count).unwrap();
-    let tag_lens = tag_lens.deref(memory, 0, tag_count).unwrap();
+    #[cfg(windows)]
+    let source = source.replace("/", "\\");
 
-    let tags = tag_ptrs
-        .iter()
-        .zip(tag_lens)
-        .map(|(ptr, len)| ptr.get().get_utf8_string(memory, len.get()).unwrap())
-        .collect::<Vec<_>>();
+    let registry = match env.registry.upgrade() {
+        Some(registry) => registry,
+        None => return write_error(memory, result_ptr, result_len, "Treasury is closed"),
+    };
 
     let result = Registry::store(
-        &env.registry.upgrade().unwrap(),
-        Path::new(source),
-        source_format,
-        native_format,
+        &registry,
+        Path::new(&source),
+        &source_format,
+        &native_format,
+        &serde_json::Value::Null,
         &tags,
+        &mut (),
+        &CONTROL.with(|cell| cell.borrow().nested()),
     );
 
+    if let Ok(uuid) = result {
+        OUTPUT.with(|cell| cell.borrow_mut().add_reference(uuid));
+    }
+
     match result {
-        Ok(uuid) if result_len >= 16 => {
-            let result = result_ptr.deref(memory, 0, 16).unwrap();
-            result
-                .iter()
-                .zip(uuid.as_bytes())
-                .for_each(|(cell, byte)| cell.set(*byte));
-            16
-        }
+        Ok(uuid) if result_len >= 16 => match result_ptr.deref(memory, 0, 16) {
+            Some(result) => {
+                result
+                    .iter()
+                    .zip(uuid.as_bytes())
+                    .for_each(|(cell, byte)| cell.set(*byte));
+                16
+            }
+            None => {
+                tracing::error!("Importer provided result buffer out of memory bounds");
+                -1
+            }
+        },
         Ok(_) => {
             tracing::error!(
                 "Importer provided to short result buffer. At least 16 bytes long buffer is required for UUID on successful sub-import"
             );
 
-            let error = b"Too short";
-            let len = result_len.min(error.len() as u32);
-
-            let result = result_ptr.deref(memory, 0, len).unwrap();
-
-            result
-                .iter()
-                .zip(error)
-                .for_each(|(cell, byte)| cell.set(*byte));
-
-            -(len as i32)
+            write_error(memory, result_ptr, result_len, "Too short")
         }
         Err(err) => {
             tracing::error!("Sub-import failed with. {:#}", err);
-
-            let error = format!("{:#}", err);
-            let len = result_len.min(error.len() as u32);
-
-            let result = result_ptr.deref(memory, 0, len).unwrap();
-
-            result
-                .iter()
-                .zip(error.as_bytes())
-                .for_each(|(cell, byte)| cell.set(*byte));
-
-            -(len as i32)
+            write_error(memory, result_ptr, result_len, &format!("{:#}", err))
         }
     }
 }
 
+/// Host function for `Registry::fetch` calls from importers.
+///
+/// Fetching an asset that is being imported by the caller fails with an error.
 fn treasury_registry_fetch(
     env: &ImporterEnv,
     uuid: WasmPtr<u8, Array>,
@@ -538,84 +1155,344 @@ fn treasury_registry_fetch(
 ) -> i32 {
     #[cfg(unix)]
     use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+    #[cfg(target_os = "wasi")]
+    use std::{ffi::OsStr, os::wasi::ffi::OsStrExt};
     #[cfg(windows)]
     use std::{ffi::OsStr, os::windows::ffi::OsStrExt};
-    #[cfg(target_os = "wasi")]
-    use std::{ffi::Ostr, os::wasi::ffi::OsStrExt};
 
-    let uuid = uuid.deref(env.memory_ref().unwrap(), 0, 16).unwrap();
-    let mut bytes = [0u8; 16];
-    bytes
-        .iter_mut()
-        .zip(uuid)
-        .for_each(|(byte, cell)| *byte = cell.get());
+    let memory = match env.memory_ref() {
+        Some(memory) => memory,
+        None => {
+            tracing::error!("Importer memory is not initialized");
+            return -1;
+        }
+    };
+
+    let uuid = match uuid.deref(memory, 0, 16) {
+        Some(uuid) => {
+            let mut bytes = [0u8; 16];
+            bytes
+                .iter_mut()
+                .zip(uuid)
+                .for_each(|(byte, cell)| *byte = cell.get());
+
+            Uuid::from_bytes(bytes)
+        }
+        None => {
+            return write_error(
+                memory,
+                error_ptr,
+                error_len,
+                "UUID must be within importer memory",
+            )
+        }
+    };
+
+    let registry = match env.registry.upgrade() {
+        Some(registry) => registry,
+        None => return write_error(memory, error_ptr, error_len, "Treasury is closed"),
+    };
 
-    let uuid = Uuid::from_bytes(bytes);
+    let result = Registry::fetch(&registry, &uuid, None, None);
+    let sealed = matches!(&result, Ok(Some(info)) if pack::is_sealed_file(&info.native_path));
 
-    let result = Registry::fetch(&env.registry.upgrade().unwrap(), &uuid, 0);
+    if let (Ok(Some(_)), false) = (&result, sealed) {
+        OUTPUT.with(|cell| cell.borrow_mut().add_reference(uuid));
+    }
 
     match result {
         Ok(None) => unreachable!(),
+        Ok(Some(_)) if sealed => write_error(
+            memory,
+            error_ptr,
+            error_len,
+            "Native file of the asset is encrypted and cannot be read by importers",
+        ),
         Ok(Some(info)) => {
-            let native_path = info.native_path;
-            let native_path = OsStr::new(&*native_path);
+            let native_path = OsStr::new(&*info.native_path);
+
+            #[cfg(any(unix, target_os = "wasi"))]
+            let native_path_utf8 = native_path.as_bytes();
+
+            #[cfg(windows)]
+            let native_path_utf16 = native_path.encode_wide().collect::<Vec<_>>();
+
+            #[cfg(windows)]
+            let native_path = String::from_utf16(&native_path_utf16).unwrap();
 
-            if path_len < native_path.len() as u32 {
+            #[cfg(windows)]
+            let native_path = native_path.replace("\\", "/");
+
+            #[cfg(windows)]
+            let native_path_utf8 = native_path.as_bytes();
+
+            if path_len < native_path_utf8.len() as u32 {
                 tracing::error!(
-                    "Importer provided to short result buffer. At least {} bytes long buffer is required.\nImporter should allocate buffer large enough to fit any sensible path length", native_path.len(),
+                    "Importer provided to short result buffer. At least {} bytes long buffer is required.\nImporter should allocate buffer large enough to fit any sensible path length", native_path_utf8.len(),
                 );
 
-                let err = b"Path buffer is too small";
-                let len = error_len.min(err.len() as u32);
-                let error = error_ptr.deref(env.memory_ref().unwrap(), 0, len).unwrap();
+                return write_error(memory, error_ptr, error_len, "Path buffer is too small");
+            }
+
+            let len = native_path_utf8.len() as u32;
 
-                error
-                    .iter()
-                    .zip(&err[..])
-                    .for_each(|(cell, byte)| cell.set(*byte));
+            match path_ptr.deref(memory, 0, len) {
+                Some(path) => {
+                    path.iter()
+                        .zip(native_path_utf8)
+                        .for_each(|(cell, c)| cell.set(*c));
 
-                -(len as i32)
-            } else {
-                let len = native_path.len() as u32;
+                    len as i32
+                }
+                None => write_error(
+                    memory,
+                    error_ptr,
+                    error_len,
+                    "Path buffer must be within importer memory",
+                ),
+            }
+        }
+        Err(err) => write_error(memory, error_ptr, error_len, &format!("{:#}", err)),
+    }
+}
 
-                let path = path_ptr.deref(env.memory_ref().unwrap(), 0, len).unwrap();
+thread_local! {
+    /// Progress receiver of the import running on this thread.
+    static PROGRESS: Cell<Option<NonNull<dyn ImportProgress>>> = Cell::new(None);
 
-                #[cfg(any(unix, target_os = "wasi"))]
-                let native_path_utf8 = native_path.as_bytes();
+    /// Output recorded by the import running on this thread.
+    static OUTPUT: RefCell<ImportOutput> = RefCell::new(ImportOutput::default());
 
-                #[cfg(windows)]
-                let native_path_utf16 = native_path.encode_wide().collect::<Vec<_>>();
+    /// Control of the import running on this thread.
+    static CONTROL: RefCell<ImportControl> = RefCell::new(ImportControl::default());
+}
 
-                #[cfg(windows)]
-                let native_path = String::from_utf16(&native_path_utf16).unwrap();
+/// Data recorded by importer in addition to native file.
+#[derive(Default)]
+pub(crate) struct ImportOutput {
+    /// Metadata of the asset. `Null` if importer recorded none.
+    pub meta: serde_json::Value,
 
-                #[cfg(windows)]
-                let native_path = native_path.replace("\\", "/");
+    /// Files asset depends on, besides its source.
+    pub dependencies: Vec<PathBuf>,
 
-                #[cfg(windows)]
-                let native_path_utf8 = native_path.as_bytes();
+    /// Locales importer has written native files for.
+    pub locales: Vec<Box<str>>,
 
-                path.iter()
-                    .zip(native_path_utf8)
-                    .for_each(|(cell, c)| cell.set(*c));
+    /// Declared version of the importer.
+    pub importer_version: Option<Box<str>>,
 
-                len as i32
-            }
+    /// Schema version of the native file.
+    pub schema: u32,
+
+    /// Assets stored or fetched by importer.
+    pub references: Vec<Uuid>,
+}
+
+impl ImportOutput {
+    fn add_reference(&mut self, uuid: Uuid) {
+        if !self.references.contains(&uuid) {
+            self.references.push(uuid);
         }
-        Err(err) => {
-            let err = format!("{:#}", err);
-            let err = err.as_bytes();
-            let len = error_len.min(err.len() as u32);
-            let error = error_ptr.deref(env.memory_ref().unwrap(), 0, len).unwrap();
+    }
+}
 
-            error
-                .iter()
-                .zip(err)
-                .for_each(|(cell, byte)| cell.set(*byte));
+/// Calls `f` with `progress` receiving reports from importer running on this thread
+/// and `control` applied to its sub-imports.
+/// Returns result of `f` and output recorded by importer.
+///
+/// Sub-imports performed by `f` on this thread are nested
+/// and do not affect progress and output of this import.
+fn with_import<R>(
+    progress: &mut dyn ImportProgress,
+    control: &ImportControl,
+    f: impl FnOnce() -> R,
+) -> (R, ImportOutput) {
+    struct Restore(
+        Option<NonNull<dyn ImportProgress>>,
+        ImportOutput,
+        ImportControl,
+    );
 
-            -(len as i32)
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            PROGRESS.with(|cell| cell.set(self.0));
+            let output = std::mem::take(&mut self.1);
+            OUTPUT.with(|cell| *cell.borrow_mut() = output);
+            let control = std::mem::take(&mut self.2);
+            CONTROL.with(|cell| *cell.borrow_mut() = control);
         }
     }
+
+    // Safety: Lifetime is erased to store pointer in thread-local.
+    // Pointer is removed from thread-local before `progress` borrow ends,
+    // even on unwinding.
+    let ptr: NonNull<dyn ImportProgress> = unsafe { std::mem::transmute(NonNull::from(progress)) };
+
+    let _restore = Restore(
+        PROGRESS.with(|cell| cell.replace(Some(ptr))),
+        OUTPUT.with(|cell| cell.take()),
+        CONTROL.with(|cell| cell.replace(control.clone())),
+    );
+
+    let result = f();
+    (result, OUTPUT.with(|cell| cell.take()))
+}
+
+/// Host function for `ImportProgress::report` calls from importers.
+fn treasury_import_progress(
+    env: &ImporterEnv,
+    done: u64,
+    total: u64,
+    stage_ptr: WasmStrPtr,
+    stage_len: u32,
+) {
+    let stage = match env.memory_ref() {
+        Some(memory) => stage_ptr.get_utf8_string(memory, stage_len),
+        None => None,
+    };
+
+    let stage = stage.as_deref().unwrap_or_default();
+
+    PROGRESS.with(|cell| {
+        if let Some(mut ptr) = cell.get() {
+            // Safety: Pointer is valid while it is set in thread-local.
+            unsafe { ptr.as_mut() }.report(done, total, stage);
+        }
+    });
+}
+
+/// Host function for `ImportProgress::is_cancelled` calls from importers.
+fn treasury_import_cancelled(_env: &ImporterEnv) -> u32 {
+    CONTROL.with(|cell| cell.borrow().is_cancelled()) as u32
+}
+
+/// Host function for `Registry::set_meta` calls from importers.
+fn treasury_import_meta(
+    env: &ImporterEnv,
+    meta_ptr: WasmStrPtr,
+    meta_len: u32,
+    error_ptr: WasmStrPtr,
+    error_len: u32,
+) -> i32 {
+    let memory = match env.memory_ref() {
+        Some(memory) => memory,
+        None => {
+            tracing::error!("Importer memory is not initialized");
+            return -1;
+        }
+    };
+
+    let meta = match meta_ptr.get_utf8_string(memory, meta_len) {
+        Some(meta) => meta,
+        None => {
+            return write_error(
+                memory,
+                error_ptr,
+                error_len,
+                "Metadata must be valid UTF-8 string within importer memory",
+            )
+        }
+    };
+
+    match serde_json::from_str(&meta) {
+        Ok(meta) => {
+            OUTPUT.with(|cell| cell.borrow_mut().meta = meta);
+            0
+        }
+        Err(err) => write_error(
+            memory,
+            error_ptr,
+            error_len,
+            &format!("Metadata is not valid JSON. {:#}", err),
+        ),
+    }
+}
+
+/// Host function for `Registry::add_dependency` calls from importers.
+fn treasury_import_dependency(
+    env: &ImporterEnv,
+    path_ptr: WasmStrPtr,
+    path_len: u32,
+    error_ptr: WasmStrPtr,
+    error_len: u32,
+) -> i32 {
+    let memory = match env.memory_ref() {
+        Some(memory) => memory,
+        None => {
+            tracing::error!("Importer memory is not initialized");
+            return -1;
+        }
+    };
+
+    let path = match path_ptr.get_utf8_string(memory, path_len) {
+        Some(path) => path,
+        None => {
+            return write_error(
+                memory,
+                error_ptr,
+                error_len,
+                "Path must be valid UTF-8 string within importer memory",
+            )
+        }
+    };
+
+    #[cfg(windows)]
+    let path = path.replace("/", "\\");
+
+    OUTPUT.with(|cell| cell.borrow_mut().dependencies.push(path.into()));
+    0
+}
+
+/// Host function for `Registry::add_locale` calls from importers.
+fn treasury_import_locale(
+    env: &ImporterEnv,
+    locale_ptr: WasmStrPtr,
+    locale_len: u32,
+    error_ptr: WasmStrPtr,
+    error_len: u32,
+) -> i32 {
+    let memory = match env.memory_ref() {
+        Some(memory) => memory,
+        None => {
+            tracing::error!("Importer memory is not initialized");
+            return -1;
+        }
+    };
+
+    let locale = match locale_ptr.get_utf8_string(memory, locale_len) {
+        Some(locale) => locale,
+        None => {
+            return write_error(
+                memory,
+                error_ptr,
+                error_len,
+                "Locale must be valid UTF-8 string within importer memory",
+            )
+        }
+    };
+
+    if !is_valid_locale(&locale) {
+        return write_error(
+            memory,
+            error_ptr,
+            error_len,
+            &format!("Invalid locale '{}'", locale),
+        );
+    }
+
+    OUTPUT.with(|cell| {
+        let mut output = cell.borrow_mut();
+        if !output.locales.iter().any(|l| **l == *locale) {
+            output.locales.push(locale.into());
+        }
+    });
+    0
+}
+
+/// Host function for `Registry::set_schema` calls from importers.
+fn treasury_import_schema(_env: &ImporterEnv, schema: u32) {
+    OUTPUT.with(|cell| cell.borrow_mut().schema = schema);
 }
 
 #[derive(Clone, WasmerEnv)]
@@ -625,3 +1502,272 @@ pub struct ImporterEnv {
 
     registry: Weak<Mutex<Registry>>,
 }
+
+impl ImporterEntry {
+    pub fn name(&self) -> &str {
+        match self {
+            ImporterEntry::Wasm(importer) => importer.name(),
+            ImporterEntry::Async(entry) => entry.importer.name(),
+        }
+    }
+
+    /// Returns source format of the importer.
+    pub fn source(&self) -> &str {
+        match self {
+            ImporterEntry::Wasm(importer) => importer.source(),
+            ImporterEntry::Async(entry) => entry.importer.source(),
+        }
+    }
+
+    /// Returns native format of the importer.
+    pub fn native(&self) -> &str {
+        match self {
+            ImporterEntry::Wasm(importer) => importer.native(),
+            ImporterEntry::Async(entry) => entry.importer.native(),
+        }
+    }
+
+    /// Returns declared version of the importer.
+    pub fn version(&self) -> Option<&str> {
+        match self {
+            ImporterEntry::Wasm(_) => None,
+            ImporterEntry::Async(entry) => entry.version.as_deref(),
+        }
+    }
+
+    /// Returns path to WASM module or importers manifest the importer is loaded from.
+    pub fn origin(&self) -> Option<&Path> {
+        match self {
+            ImporterEntry::Wasm(importer) => Some(&importer.origin),
+            ImporterEntry::Async(entry) => entry.origin.as_deref(),
+        }
+    }
+
+    /// Returns description of the importer.
+    pub fn info(&self) -> ImporterInfo {
+        ImporterInfo {
+            name: self.name().into(),
+            source: self.source().into(),
+            native: self.native().into(),
+            version: self.version().map(Into::into),
+            origin: self.origin().map(Into::into),
+        }
+    }
+
+    /// Runs importer.
+    /// `native_path` is relative to `root`.
+    /// Returns metadata and dependencies recorded by importer.
+    pub fn import(
+        &self,
+        root: &Path,
+        source_path: &Path,
+        native_path: &Path,
+        settings: &str,
+        progress: &mut dyn ImportProgress,
+        control: &ImportControl,
+    ) -> Result<ImportOutput, ImportError> {
+        match self {
+            ImporterEntry::Wasm(importer) => {
+                let result = importer.import(source_path, native_path, settings, progress, control);
+
+                // Running WASM importer can't be interrupted.
+                if control.is_cancelled() {
+                    return Err(ImportError::Cancelled);
+                }
+                result.map_err(ImportError::Failed)
+            }
+            ImporterEntry::Async(entry) => entry.import(
+                source_path,
+                &root.join(native_path),
+                settings,
+                progress,
+                control,
+            ),
+        }
+    }
+}
+
+/// Message from async import to the thread waiting for it.
+enum AsyncMessage {
+    Progress(u64, u64, Box<str>),
+    Done(Result<ImportOutput, ImportError>),
+}
+
+impl AsyncImporterEntry {
+    /// Spawns import on the runtime and blocks until it is finished,
+    /// forwarding progress reports to the caller.
+    fn import(
+        &self,
+        source_path: &Path,
+        native_path: &Path,
+        settings: &str,
+        progress: &mut dyn ImportProgress,
+        control: &ImportControl,
+    ) -> Result<ImportOutput, ImportError> {
+        let (sender, receiver) = mpsc::channel();
+
+        let importer = self.importer.clone();
+        let mut registry = HostRegistry {
+            registry: self.registry.clone(),
+            output: ImportOutput::default(),
+            control: control.nested(),
+        };
+        let source_path = source_path.to_owned();
+        let native_path = native_path.to_owned();
+        let settings = settings.to_owned();
+        let control = control.clone();
+        let version = self.version.clone();
+
+        self.runtime.spawn(async move {
+            let mut progress = ChannelProgress(sender.clone(), control.cancel.clone());
+
+            let result = {
+                let import = importer.import(
+                    &source_path,
+                    &native_path,
+                    &settings,
+                    &mut registry,
+                    &mut progress,
+                );
+
+                let import = async {
+                    match control.timeout {
+                        None => import.await.map_err(ImportError::Failed),
+                        Some(timeout) => match tokio::time::timeout(timeout, import).await {
+                            Ok(result) => result.map_err(ImportError::Failed),
+                            Err(_) => Err(ImportError::Timeout),
+                        },
+                    }
+                };
+
+                match &control.cancel {
+                    None => import.await,
+                    Some(cancel) => tokio::select! {
+                        result = import => result,
+                        () = cancel.cancelled() => Err(ImportError::Cancelled),
+                    },
+                }
+            };
+
+            let output = ImportOutput {
+                importer_version: version,
+                ..registry.output
+            };
+            let _ = sender.send(AsyncMessage::Done(result.map(|()| output)));
+        });
+
+        for message in receiver {
+            match message {
+                AsyncMessage::Progress(done, total, stage) => progress.report(done, total, &stage),
+                AsyncMessage::Done(result) => return result,
+            }
+        }
+
+        // Sender is dropped without result only if import panicked.
+        Err(ImportError::Failed(eyre::eyre!(
+            "Importer '{}' panicked",
+            self.importer.name()
+        )))
+    }
+}
+
+/// Forwards progress of async import to the thread waiting for it.
+struct ChannelProgress(mpsc::Sender<AsyncMessage>, Option<CancelToken>);
+
+impl ImportProgress for ChannelProgress {
+    fn report(&mut self, done: u64, total: u64, stage: &str) {
+        let _ = self
+            .0
+            .send(AsyncMessage::Progress(done, total, stage.into()));
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.1.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+}
+
+/// Registry given to async importers.
+struct HostRegistry {
+    registry: Weak<Mutex<Registry>>,
+    output: ImportOutput,
+    control: ImportControl,
+}
+
+impl goods_treasury_import::Registry for HostRegistry {
+    fn store(
+        &mut self,
+        source: &Path,
+        source_format: &str,
+        native_format: &str,
+        tags: &[&str],
+    ) -> eyre::Result<Uuid> {
+        let registry = self
+            .registry
+            .upgrade()
+            .ok_or_else(|| eyre::eyre!("Treasury is closed"))?;
+
+        // Nested import may wait for other imports on this runtime.
+        let uuid = tokio::task::block_in_place(|| {
+            Registry::store(
+                &registry,
+                source,
+                source_format,
+                native_format,
+                &serde_json::Value::Null,
+                tags,
+                &mut (),
+                &self.control,
+            )
+        })?;
+
+        self.output.add_reference(uuid);
+        Ok(uuid)
+    }
+
+    fn fetch(&mut self, asset: &Uuid) -> eyre::Result<Box<Path>> {
+        let registry = self
+            .registry
+            .upgrade()
+            .ok_or_else(|| eyre::eyre!("Treasury is closed"))?;
+
+        match tokio::task::block_in_place(|| Registry::fetch(&registry, asset, None, None))? {
+            None => unreachable!(),
+            Some(info) => {
+                if pack::is_sealed_file(&info.native_path) {
+                    eyre::bail!(
+                        "Native file of asset '{}' is encrypted and cannot be read by importers",
+                        asset
+                    );
+                }
+                self.output.add_reference(*asset);
+                Ok(info.native_path)
+            }
+        }
+    }
+
+    fn set_meta(&mut self, meta: &str) -> eyre::Result<()> {
+        self.output.meta = serde_json::from_str(meta).wrap_err("Metadata is not valid JSON")?;
+        Ok(())
+    }
+
+    fn add_dependency(&mut self, path: &Path) -> eyre::Result<()> {
+        self.output.dependencies.push(path.to_owned());
+        Ok(())
+    }
+
+    fn add_locale(&mut self, locale: &str) -> eyre::Result<()> {
+        if !is_valid_locale(locale) {
+            eyre::bail!("Invalid locale '{}'", locale);
+        }
+
+        if !self.output.locales.iter().any(|l| **l == *locale) {
+            self.output.locales.push(locale.into());
+        }
+        Ok(())
+    }
+
+    fn set_schema(&mut self, schema: u32) -> eyre::Result<()> {
+        self.output.schema = schema;
+        Ok(())
+    }
+}
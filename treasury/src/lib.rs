@@ -14,13 +14,81 @@
 //!
 
 mod asset;
+mod clock;
+mod graph;
+mod pack;
+mod policy;
 
 #[cfg(feature = "import")]
 mod import;
 
+#[cfg(feature = "import")]
+mod cache;
+
+#[cfg(feature = "git")]
+mod git;
+
+#[cfg(feature = "import")]
+mod process;
+
+#[cfg(feature = "import")]
+mod presets;
+
+#[cfg(feature = "import")]
+mod provider;
+
+#[cfg(feature = "import")]
+mod reimport;
+
+#[cfg(feature = "import")]
+mod scheduler;
+
+#[cfg(feature = "import")]
+mod scrub;
+
+#[cfg(feature = "import")]
+mod validate;
+
+mod recording;
+mod records;
+mod subset;
 mod treasury;
+mod workspace;
 
 #[cfg(feature = "import")]
-pub use goods_treasury_import::*;
+pub use {
+    self::{
+        cache::{DirImportCache, ImportCache},
+        import::{CancelToken, ImporterInfo, ImporterLoadError, ImporterModule},
+        provider::SourceProvider,
+        scheduler::{ImportEvent, ImportJob, ImportScheduler, JobId},
+        validate::{StoreValidator, ValidatedNative, ValidatedSource},
+    },
+    goods_treasury_import::*,
+};
 
-pub use self::treasury::*;
+#[cfg(feature = "http")]
+pub use self::{cache::HttpImportCache, provider::HttpProvider};
+
+#[cfg(feature = "git")]
+pub use self::git::GitError;
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+pub use self::{
+    asset::{Asset, Lifecycle, Visibility},
+    clock::{Clock, ManualClock, SystemClock},
+    graph::{GraphFormat, GraphStyle, NodeStyle},
+    pack::{
+        apply_patch, pack_diff, verify_signature, PackAsset, PackError, PackKey, PackOptions,
+        PackReader,
+    },
+    policy::{PolicyAction, SizeLimit, SizePolicy},
+    recording::{RecordedLoad, Recording, RecordingError},
+    subset::{SubsetBundle, SubsetConflict, SubsetError},
+    treasury::*,
+    workspace::{Workspace, WorkspaceMember, WorkspaceOpenError, WORKSPACE_MANIFEST},
+};
+
+#[cfg(feature = "import")]
+pub use self::workspace::WorkspaceStoreError;
@@ -0,0 +1,1406 @@
+//! Pack archives of native files for shipping.
+//!
+//! Pack is a single file containing native files of selected assets,
+//! including their localized native files, and an index to find them by uuid.
+//! Packs are written by [`Treasury::pack`] and read with [`PackReader`].
+//!
+//! Native files may be encrypted with XChaCha20-Poly1305 using [`PackKey`].
+//! Each entry is encrypted with its own random nonce and authenticated
+//! together with its uuid and locale, so entries cannot be swapped.
+//! The same key set with [`Treasury::set_native_key`] encrypts native files
+//! in treasury directory at rest.
+//!
+//! Native files may be signed with ed25519 [`SigningKey`],
+//! so that loaders can verify assets delivered over untrusted channels
+//! with [`verify_signature`].
+//!
+//! Patches share pack format and contain differences between two versions
+//! of a pack. They are written by [`pack_diff`] and either applied to the pack
+//! on disk with [`apply_patch`] or layered over the pack with [`PackReader::add_patch`].
+//! Changed native files are stored as zstd compressed bsdiff patches.
+//!
+//! Packs written by [`Treasury::pack_with_layout`] store native files of assets
+//! accessed during recorded session first, in the order of first access,
+//! so that loading them reads the pack sequentially.
+//! The order is kept in the index and serves as read-ahead hints, see [`PackReader::read_ahead`].
+//!
+//! Packs store CRC32 checksum of each entry and of the whole pack,
+//! so that corrupted files on player machines are detected.
+//! Entries are verified when first read, whole pack with [`PackReader::verify_all`].
+//!
+//! [`Treasury::pack`]: crate::Treasury::pack
+//! [`Treasury::set_native_key`]: crate::Treasury::set_native_key
+//! [`Treasury::pack_with_layout`]: crate::Treasury::pack_with_layout
+
+use {
+    crate::{
+        asset::{Lifecycle, Visibility},
+        treasury::{locale_fallbacks, FetchError},
+    },
+    chacha20poly1305::{
+        aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+        XChaCha20Poly1305, XNonce,
+    },
+    ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey},
+    parking_lot::Mutex,
+    std::{
+        collections::{HashMap, HashSet},
+        convert::TryInto,
+        fmt::{self, Debug},
+        fs::File,
+        io::{BufWriter, Read, Seek, SeekFrom, Write},
+        path::Path,
+    },
+    uuid::Uuid,
+};
+
+const MAGIC: [u8; 4] = *b"GPAK";
+const VERSION: u32 = 4;
+const HEADER_LEN: u64 = 64;
+
+/// Length of header of version 1 packs, written without project namespace.
+const HEADER_V1_LEN: u64 = 32;
+
+/// Length of header of version 2 packs, written without checksums.
+const HEADER_V2_LEN: u64 = 48;
+
+/// Size of blocks pack is read by when verified.
+const VERIFY_BLOCK_LEN: usize = 1 << 20;
+
+/// Flag set in header of packs with encrypted entries.
+const FLAG_ENCRYPTED: u32 = 1;
+
+/// Flag set in header of patches.
+const FLAG_PATCH: u32 = 2;
+
+/// Compression level of native file deltas in patches.
+const DELTA_COMPRESSION_LEVEL: i32 = 19;
+
+/// Magic of native files encrypted at rest.
+/// Followed by nonce and encrypted native file.
+const SEALED_MAGIC: [u8; 4] = *b"GENC";
+
+/// Length of nonce of XChaCha20-Poly1305.
+const NONCE_LEN: usize = 24;
+
+/// Key to encrypt and decrypt pack entries.
+#[derive(Clone)]
+pub struct PackKey([u8; 32]);
+
+impl PackKey {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        PackKey(bytes)
+    }
+
+    /// Returns key from slice of 32 bytes.
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        Some(PackKey(bytes.try_into().ok()?))
+    }
+
+    /// Generates random key.
+    pub fn generate() -> Self {
+        PackKey(XChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.0.into())
+    }
+}
+
+impl Debug for PackKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PackKey(..)")
+    }
+}
+
+/// Options of [`Treasury::pack`].
+///
+/// [`Treasury::pack`]: crate::Treasury::pack
+#[derive(Clone, Debug, Default)]
+pub struct PackOptions {
+    key: Option<PackKey>,
+    signing_key: Option<SigningKey>,
+    visibilities: Vec<Visibility>,
+    lifecycles: Vec<Lifecycle>,
+}
+
+impl PackOptions {
+    pub fn new() -> Self {
+        PackOptions::default()
+    }
+
+    /// Encrypts native files with specified key.
+    pub fn with_key(mut self, key: PackKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Signs native files with specified key.
+    pub fn with_signing_key(mut self, signing_key: SigningKey) -> Self {
+        self.signing_key = Some(signing_key);
+        self
+    }
+
+    /// Includes assets with specified visibility.
+    /// Public assets are always included.
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        if !self.visibilities.contains(&visibility) {
+            self.visibilities.push(visibility);
+        }
+        self
+    }
+
+    /// Includes assets with specified lifecycle, e.g. drafts in internal builds.
+    /// Approved and deprecated assets are always included.
+    pub fn with_lifecycle(mut self, lifecycle: Lifecycle) -> Self {
+        if !self.lifecycles.contains(&lifecycle) {
+            self.lifecycles.push(lifecycle);
+        }
+        self
+    }
+
+    /// Checks if assets with specified visibility are included.
+    pub(crate) fn includes(&self, visibility: Visibility) -> bool {
+        visibility.is_public() || self.visibilities.contains(&visibility)
+    }
+
+    /// Checks if assets with specified lifecycle are included.
+    pub(crate) fn includes_lifecycle(&self, lifecycle: Lifecycle) -> bool {
+        lifecycle != Lifecycle::Draft || self.lifecycles.contains(&lifecycle)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PackError {
+    #[error("Failed to access pack file '{path}'")]
+    IoError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to fetch asset '{uuid}'")]
+    FetchError { uuid: Uuid, source: FetchError },
+
+    #[error("File '{path}' is not a pack")]
+    InvalidPack { path: Box<Path> },
+
+    #[error("File '{path}' is not a patch")]
+    InvalidPatch { path: Box<Path> },
+
+    #[error("Patch does not match base of asset '{uuid}'")]
+    PatchMismatch { uuid: Uuid },
+
+    #[error("Pack version {found} is not supported")]
+    UnsupportedVersion { found: u32 },
+
+    #[error("Pack index is corrupted")]
+    CorruptedIndex { source: bincode::Error },
+
+    #[error("Pack is encrypted and requires key")]
+    KeyRequired,
+
+    #[error("Failed to decrypt asset '{uuid}'. Wrong key or corrupted pack")]
+    DecryptionFailed { uuid: Uuid },
+
+    #[error("Patch of project '{found}' cannot be applied to pack of project '{expected}'")]
+    NamespaceMismatch { expected: Uuid, found: Uuid },
+
+    #[error("Data of asset '{uuid}' in pack '{path}' is corrupted")]
+    ChecksumMismatch { path: Box<Path>, uuid: Uuid },
+
+    #[error("Pack '{path}' is corrupted")]
+    PackCorrupted { path: Box<Path> },
+}
+
+impl PackError {
+    /// Returns `true` if error is caused by corrupted pack file,
+    /// e.g. to ask player to repair game installation.
+    pub fn is_corrupted(&self) -> bool {
+        matches!(
+            self,
+            PackError::ChecksumMismatch { .. }
+                | PackError::PackCorrupted { .. }
+                | PackError::CorruptedIndex { .. }
+        )
+    }
+}
+
+/// Kind of data stored in the pack entry.
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+enum EntryKind {
+    /// Whole native file.
+    Native,
+
+    /// Delta to native file of the same asset and locale in base pack.
+    /// Only in patches.
+    Delta { base_len: u64 },
+
+    /// Native file is removed from base pack.
+    /// Only in patches.
+    Removed,
+}
+
+/// Location of a native file in the pack.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PackEntry {
+    uuid: Uuid,
+    locale: Option<Box<str>>,
+    native_format: Box<str>,
+    kind: EntryKind,
+    offset: u64,
+    len: u64,
+
+    /// Nonce of encrypted entry.
+    nonce: Option<[u8; 24]>,
+
+    /// Signature of decrypted native file.
+    signature: Option<Box<[u8]>>,
+}
+
+/// Index of the pack.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct PackIndex {
+    entries: Vec<PackEntry>,
+
+    /// Collections of assets in the pack.
+    /// Patches contain all collections of the new pack.
+    collections: Vec<(Box<str>, Vec<Uuid>)>,
+
+    /// Assets in the order of first access in recorded session.
+    /// Empty unless pack is written with layout.
+    /// Patches contain layout of the new pack.
+    layout: Vec<Uuid>,
+
+    /// Deprecated assets in the pack.
+    /// Patches contain deprecated assets of the new pack.
+    deprecated: Vec<Uuid>,
+}
+
+/// Index of packs before version 4, written without deprecated assets.
+#[derive(serde::Deserialize)]
+struct PackIndexV3 {
+    entries: Vec<PackEntry>,
+    collections: Vec<(Box<str>, Vec<Uuid>)>,
+    layout: Vec<Uuid>,
+}
+
+/// Native file read from the pack.
+pub struct PackAsset {
+    pub bytes: Box<[u8]>,
+    pub native_format: Box<str>,
+
+    /// Locale of the native file.
+    /// May be less specific than requested locale it falls back from.
+    pub locale: Option<Box<str>>,
+
+    /// Signature of the native file, if pack is signed.
+    /// See [`verify_signature`].
+    pub signature: Option<Box<[u8]>>,
+}
+
+/// Message signed for native file.
+/// Locale is prefixed with `@` and native file without locale has `0` in its place.
+fn signed_message(uuid: &Uuid, locale: Option<&str>, native_format: &str, bytes: &[u8]) -> Vec<u8> {
+    let locale_len = locale.map_or(0, |locale| locale.len() + 1);
+    let mut message =
+        Vec::with_capacity(16 + locale_len + 1 + native_format.len() + 1 + bytes.len());
+    message.extend_from_slice(uuid.as_bytes());
+    if let Some(locale) = locale {
+        message.push(b'@');
+        message.extend_from_slice(locale.as_bytes());
+    }
+    message.push(0);
+    message.extend_from_slice(native_format.as_bytes());
+    message.push(0);
+    message.extend_from_slice(bytes);
+    message
+}
+
+/// Verifies signature of native file of the asset in specified locale and native format.
+/// Signature is produced by [`Treasury::pack`] with [`PackOptions::with_signing_key`].
+/// Packs signed before locales were signed must be signed again.
+///
+/// [`Treasury::pack`]: crate::Treasury::pack
+pub fn verify_signature(
+    key: &VerifyingKey,
+    uuid: &Uuid,
+    locale: Option<&str>,
+    native_format: &str,
+    bytes: &[u8],
+    signature: &[u8],
+) -> bool {
+    match Signature::from_slice(signature) {
+        Err(_) => false,
+        Ok(signature) => key
+            .verify(
+                &signed_message(uuid, locale, native_format, bytes),
+                &signature,
+            )
+            .is_ok(),
+    }
+}
+
+/// Data authenticated with encrypted entry.
+fn associated_data(uuid: &Uuid, locale: Option<&str>) -> Vec<u8> {
+    let mut data = uuid.as_bytes().to_vec();
+    if let Some(locale) = locale {
+        data.push(b'@');
+        data.extend_from_slice(locale.as_bytes());
+    }
+    data
+}
+
+/// Encrypts native file to be stored in treasury directory.
+/// Sealed files are not bound to the asset, so they stay valid when copied to another asset.
+pub(crate) fn seal(key: &PackKey, bytes: &[u8]) -> Vec<u8> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let payload = Payload {
+        msg: bytes,
+        aad: &SEALED_MAGIC,
+    };
+
+    // Encryption with valid key fails only on overflowing payload length.
+    let encrypted = key
+        .cipher()
+        .encrypt(&nonce, payload)
+        .expect("Failed to encrypt native file");
+
+    let mut sealed = Vec::with_capacity(SEALED_MAGIC.len() + NONCE_LEN + encrypted.len());
+    sealed.extend_from_slice(&SEALED_MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&encrypted);
+    sealed
+}
+
+/// Checks if native file is encrypted at rest.
+pub(crate) fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.len() >= SEALED_MAGIC.len() + NONCE_LEN && bytes[..SEALED_MAGIC.len()] == SEALED_MAGIC
+}
+
+/// Checks if native file in treasury directory is encrypted at rest.
+#[cfg(feature = "import")]
+pub(crate) fn is_sealed_file(path: &Path) -> bool {
+    let mut head = [0; SEALED_MAGIC.len() + NONCE_LEN];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut head))
+        .is_ok()
+        && is_sealed(&head)
+}
+
+/// Decrypts native file read from treasury directory.
+/// Native files written before key was set are returned as is.
+pub(crate) fn unseal(key: Option<&PackKey>, bytes: Vec<u8>) -> std::io::Result<Vec<u8>> {
+    use std::io::{Error, ErrorKind};
+
+    if !is_sealed(&bytes) {
+        return Ok(bytes);
+    }
+
+    let key = key.ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            "Native file is encrypted and requires key",
+        )
+    })?;
+
+    let (nonce, encrypted) = bytes[SEALED_MAGIC.len()..].split_at(NONCE_LEN);
+    let payload = Payload {
+        msg: encrypted,
+        aad: &SEALED_MAGIC,
+    };
+
+    key.cipher()
+        .decrypt(XNonce::from_slice(nonce), payload)
+        .map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "Failed to decrypt native file. Wrong key or corrupted file",
+            )
+        })
+}
+
+/// Encrypts native file in treasury directory in place.
+#[cfg(feature = "import")]
+pub(crate) fn seal_file(key: &PackKey, path: &Path) -> std::io::Result<()> {
+    let bytes = std::fs::read(path)?;
+    std::fs::write(path, seal(key, &bytes))
+}
+
+/// Writes pack file entry by entry.
+pub(crate) struct PackWriter {
+    path: Box<Path>,
+    file: BufWriter<File>,
+    offset: u64,
+    flags: u32,
+    index: PackIndex,
+    cipher: Option<XChaCha20Poly1305>,
+    signing_key: Option<SigningKey>,
+    namespace: Option<Uuid>,
+
+    /// Checksums of entries in the order of the index.
+    checksums: Vec<u32>,
+
+    /// Checksum of everything written after the header.
+    hasher: crc32fast::Hasher,
+}
+
+impl PackWriter {
+    pub fn create(path: &Path, options: &PackOptions) -> Result<Self, PackError> {
+        let io_error = |source| PackError::IoError {
+            path: path.into(),
+            source,
+        };
+
+        let mut file = BufWriter::new(File::create(path).map_err(io_error)?);
+
+        // Header is written on finish.
+        file.write_all(&[0; HEADER_LEN as usize])
+            .map_err(io_error)?;
+
+        let flags = if options.key.is_some() {
+            FLAG_ENCRYPTED
+        } else {
+            0
+        };
+
+        Ok(PackWriter {
+            path: path.into(),
+            file,
+            offset: HEADER_LEN,
+            flags,
+            index: PackIndex::default(),
+            cipher: options.key.as_ref().map(PackKey::cipher),
+            signing_key: options.signing_key.clone(),
+            namespace: None,
+            checksums: Vec::new(),
+            hasher: crc32fast::Hasher::new(),
+        })
+    }
+
+    fn create_patch(path: &Path, options: &PackOptions) -> Result<Self, PackError> {
+        let mut writer = PackWriter::create(path, options)?;
+        writer.flags |= FLAG_PATCH;
+        Ok(writer)
+    }
+
+    pub fn add(
+        &mut self,
+        uuid: Uuid,
+        locale: Option<&str>,
+        native_format: &str,
+        bytes: &[u8],
+    ) -> Result<(), PackError> {
+        let signature = self.sign(&uuid, locale, native_format, bytes, None);
+        self.add_entry(
+            uuid,
+            locale,
+            native_format,
+            EntryKind::Native,
+            bytes,
+            signature,
+        )
+    }
+
+    /// Returns signature of native file.
+    /// Native file is signed if writer has signing key,
+    /// otherwise provided signature is kept.
+    fn sign(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        native_format: &str,
+        bytes: &[u8],
+        signature: Option<Box<[u8]>>,
+    ) -> Option<Box<[u8]>> {
+        match &self.signing_key {
+            None => signature,
+            Some(signing_key) => {
+                let message = signed_message(uuid, locale, native_format, bytes);
+                Some(signing_key.sign(&message).to_vec().into_boxed_slice())
+            }
+        }
+    }
+
+    fn add_entry(
+        &mut self,
+        uuid: Uuid,
+        locale: Option<&str>,
+        native_format: &str,
+        kind: EntryKind,
+        bytes: &[u8],
+        signature: Option<Box<[u8]>>,
+    ) -> Result<(), PackError> {
+        let (bytes, nonce) = match &self.cipher {
+            None => (bytes.to_vec(), None),
+            Some(cipher) => {
+                let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+                let payload = Payload {
+                    msg: bytes,
+                    aad: &associated_data(&uuid, locale),
+                };
+
+                // Encryption with valid key fails only on overflowing payload length.
+                let bytes = cipher
+                    .encrypt(&nonce, payload)
+                    .expect("Failed to encrypt pack entry");
+
+                (bytes, Some(nonce.into()))
+            }
+        };
+
+        self.file
+            .write_all(&bytes)
+            .map_err(|source| PackError::IoError {
+                path: self.path.clone(),
+                source,
+            })?;
+        self.hasher.update(&bytes);
+        self.checksums.push(crc32fast::hash(&bytes));
+
+        self.index.entries.push(PackEntry {
+            uuid,
+            locale: locale.map(Into::into),
+            native_format: native_format.into(),
+            kind,
+            offset: self.offset,
+            len: bytes.len() as u64,
+            nonce,
+            signature,
+        });
+
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Sets project namespace written into the header.
+    pub fn set_namespace(&mut self, namespace: Option<Uuid>) {
+        self.namespace = namespace;
+    }
+
+    /// Adds collection of assets to the pack.
+    pub fn add_collection(&mut self, name: &str, members: Vec<Uuid>) {
+        self.index.collections.push((name.into(), members));
+    }
+
+    /// Sets order of first access to assets.
+    pub fn set_layout(&mut self, layout: Vec<Uuid>) {
+        self.index.layout = layout;
+    }
+
+    /// Sets deprecated assets.
+    pub fn set_deprecated(&mut self, mut deprecated: Vec<Uuid>) {
+        deprecated.sort();
+        self.index.deprecated = deprecated;
+    }
+
+    /// Writes index and header.
+    /// Returns number of entries in the pack.
+    pub fn finish(mut self) -> Result<usize, PackError> {
+        let path = &self.path;
+        let io_error = |source| PackError::IoError {
+            path: path.clone(),
+            source,
+        };
+
+        let index = bincode::serialize(&self.index)
+            .map_err(|source| PackError::CorruptedIndex { source })?;
+
+        self.file.write_all(&index).map_err(io_error)?;
+        self.hasher.update(&index);
+
+        let checksums_offset = self.offset + index.len() as u64;
+        let checksums: Vec<u8> = self
+            .checksums
+            .iter()
+            .flat_map(|checksum| checksum.to_le_bytes())
+            .collect();
+        self.file.write_all(&checksums).map_err(io_error)?;
+        self.hasher.update(&checksums);
+
+        let mut header = [0; HEADER_LEN as usize];
+        header[0..4].copy_from_slice(&MAGIC);
+        header[4..8].copy_from_slice(&VERSION.to_le_bytes());
+        header[8..12].copy_from_slice(&self.flags.to_le_bytes());
+        header[16..24].copy_from_slice(&self.offset.to_le_bytes());
+        header[24..32].copy_from_slice(&(index.len() as u64).to_le_bytes());
+        if let Some(namespace) = &self.namespace {
+            header[32..48].copy_from_slice(namespace.as_bytes());
+        }
+        header[48..56].copy_from_slice(&checksums_offset.to_le_bytes());
+        header[56..60].copy_from_slice(&self.hasher.clone().finalize().to_le_bytes());
+
+        self.file.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        self.file.write_all(&header).map_err(io_error)?;
+        self.file.flush().map_err(io_error)?;
+
+        Ok(self.index.entries.len())
+    }
+}
+
+/// Storage of pack or patch data.
+enum LayerData {
+    File(File),
+    Static(&'static [u8]),
+}
+
+impl LayerData {
+    fn len(&self) -> std::io::Result<u64> {
+        match self {
+            LayerData::File(file) => file.metadata().map(|metadata| metadata.len()),
+            LayerData::Static(data) => Ok(data.len() as u64),
+        }
+    }
+
+    fn read_at(&self, offset: u64, bytes: &mut [u8]) -> std::io::Result<()> {
+        match self {
+            LayerData::File(file) => {
+                let mut file = file;
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(bytes)
+            }
+            LayerData::Static(data) => {
+                let data = TryInto::<usize>::try_into(offset)
+                    .ok()
+                    .and_then(|offset| data.get(offset..)?.get(..bytes.len()))
+                    .ok_or(std::io::ErrorKind::UnexpectedEof)?;
+                bytes.copy_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Checks that `len` bytes at `offset` are within data of `data_len` bytes.
+fn within(data_len: u64, offset: u64, len: u64) -> bool {
+    offset.checked_add(len).is_some_and(|end| end <= data_len)
+}
+
+/// Opened layer with its flags, index and checksums of entries.
+/// Checksums are empty for packs written before checksums.
+type LayerOpened = (Layer, u32, PackIndex, Vec<u32>);
+
+/// Pack or patch opened by [`PackReader`].
+struct Layer {
+    path: Box<Path>,
+    data: LayerData,
+    len: u64,
+    encrypted: bool,
+    namespace: Option<Uuid>,
+
+    /// Checksum of the layer and offset its data ends at.
+    /// `None` for packs written before checksums.
+    checksum: Option<(u32, u64)>,
+}
+
+impl Layer {
+    fn open(path: &Path, key: Option<&PackKey>) -> Result<LayerOpened, PackError> {
+        let file = File::open(path).map_err(|source| PackError::IoError {
+            path: path.into(),
+            source,
+        })?;
+        Layer::new(path, LayerData::File(file), key)
+    }
+
+    fn new(path: &Path, data: LayerData, key: Option<&PackKey>) -> Result<LayerOpened, PackError> {
+        let io_error = |source| PackError::IoError {
+            path: path.into(),
+            source,
+        };
+
+        let mut header = [0; HEADER_LEN as usize];
+        match data.read_at(0, &mut header[..HEADER_V1_LEN as usize]) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Err(PackError::InvalidPack { path: path.into() })
+            }
+            Err(err) => return Err(io_error(err)),
+        }
+
+        if header[0..4] != MAGIC {
+            return Err(PackError::InvalidPack { path: path.into() });
+        }
+
+        let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let header_len = match version {
+            1 => HEADER_V1_LEN,
+            2 => HEADER_V2_LEN,
+            3 | VERSION => HEADER_LEN,
+            _ => return Err(PackError::UnsupportedVersion { found: version }),
+        };
+        data.read_at(
+            HEADER_V1_LEN,
+            &mut header[HEADER_V1_LEN as usize..header_len as usize],
+        )
+        .map_err(io_error)?;
+
+        let namespace = Uuid::from_slice(&header[32..48])
+            .ok()
+            .filter(|namespace| !namespace.is_nil());
+
+        let flags = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        let index_offset = u64::from_le_bytes(header[16..24].try_into().unwrap());
+        let index_len = u64::from_le_bytes(header[24..32].try_into().unwrap());
+
+        let encrypted = flags & FLAG_ENCRYPTED != 0;
+        if encrypted && key.is_none() {
+            return Err(PackError::KeyRequired);
+        }
+
+        let len = data.len().map_err(io_error)?;
+        let corrupted = || PackError::PackCorrupted { path: path.into() };
+        if !within(len, index_offset, index_len) {
+            return Err(corrupted());
+        }
+
+        let mut index = vec![0; index_len as usize];
+        data.read_at(index_offset, &mut index).map_err(io_error)?;
+        let index: PackIndex = match version {
+            1..=3 => bincode::deserialize(&index).map(|index: PackIndexV3| PackIndex {
+                entries: index.entries,
+                collections: index.collections,
+                layout: index.layout,
+                deprecated: Vec::new(),
+            }),
+            _ => bincode::deserialize(&index),
+        }
+        .map_err(|source| PackError::CorruptedIndex { source })?;
+
+        let mut checksums = Vec::new();
+        let mut checksum = None;
+        if version >= 3 {
+            let checksums_offset = u64::from_le_bytes(header[48..56].try_into().unwrap());
+            let checksums_len = (index.entries.len() as u64)
+                .checked_mul(4)
+                .filter(|&checksums_len| within(len, checksums_offset, checksums_len))
+                .ok_or_else(corrupted)?;
+
+            let mut bytes = vec![0; checksums_len as usize];
+            data.read_at(checksums_offset, &mut bytes)
+                .map_err(io_error)?;
+
+            checksums = bytes
+                .chunks_exact(4)
+                .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+                .collect();
+
+            checksum = Some((
+                u32::from_le_bytes(header[56..60].try_into().unwrap()),
+                checksums_offset + bytes.len() as u64,
+            ));
+        }
+
+        let layer = Layer {
+            path: path.into(),
+            data,
+            len,
+            encrypted,
+            namespace,
+            checksum,
+        };
+
+        Ok((layer, flags, index, checksums))
+    }
+}
+
+/// Part of native file stored in a layer.
+#[derive(Clone)]
+struct Chunk {
+    layer: usize,
+    offset: u64,
+    len: u64,
+    nonce: Option<[u8; 24]>,
+    checksum: Option<u32>,
+
+    /// Length of native file this delta applies to.
+    /// `None` for whole native files.
+    delta_base_len: Option<u64>,
+}
+
+/// Native file resolved from pack and patches layered over it.
+#[derive(Clone)]
+struct Resolved {
+    locale: Option<Box<str>>,
+    native_format: Box<str>,
+    signature: Option<Box<[u8]>>,
+
+    /// Whole native file followed by deltas from patches.
+    chunks: Vec<Chunk>,
+}
+
+/// Reads native files from pack.
+pub struct PackReader {
+    layers: Vec<Layer>,
+    key: Option<PackKey>,
+    entries: HashMap<Uuid, Vec<Resolved>>,
+    collections: HashMap<Box<str>, Vec<Uuid>>,
+    layout: Vec<Uuid>,
+    deprecated: HashSet<Uuid>,
+
+    /// Assets in the order their native files are stored,
+    /// followed by assets added by patches.
+    order: Vec<Uuid>,
+
+    /// Layers and offsets of chunks verified against checksums.
+    verified: Mutex<HashSet<(usize, u64)>>,
+}
+
+impl PackReader {
+    /// Opens pack file.
+    /// Key is required if pack is encrypted and ignored otherwise.
+    pub fn open(path: impl AsRef<Path>, key: Option<&PackKey>) -> Result<Self, PackError> {
+        let path = path.as_ref();
+        PackReader::new(path, Layer::open(path, key)?, key)
+    }
+
+    /// Reads pack from static bytes, e.g. embedded into the binary with `include_bytes!`.
+    /// Key is required if pack is encrypted and ignored otherwise.
+    pub fn from_static(bytes: &'static [u8], key: Option<&PackKey>) -> Result<Self, PackError> {
+        let path = Path::new("<static>");
+        PackReader::new(path, Layer::new(path, LayerData::Static(bytes), key)?, key)
+    }
+
+    fn new(
+        path: &Path,
+        (layer, flags, index, checksums): LayerOpened,
+        key: Option<&PackKey>,
+    ) -> Result<Self, PackError> {
+        if flags & FLAG_PATCH != 0 {
+            return Err(PackError::InvalidPack { path: path.into() });
+        }
+
+        let mut by_uuid = HashMap::<_, Vec<_>>::new();
+        let mut order = Vec::new();
+        for (entry_index, entry) in index.entries.into_iter().enumerate() {
+            if !by_uuid.contains_key(&entry.uuid) {
+                order.push(entry.uuid);
+            }
+            by_uuid.entry(entry.uuid).or_default().push(Resolved {
+                locale: entry.locale,
+                native_format: entry.native_format,
+                signature: entry.signature,
+                chunks: vec![Chunk {
+                    layer: 0,
+                    offset: entry.offset,
+                    len: entry.len,
+                    nonce: entry.nonce,
+                    checksum: checksums.get(entry_index).copied(),
+                    delta_base_len: None,
+                }],
+            });
+        }
+
+        Ok(PackReader {
+            layers: vec![layer],
+            key: key.cloned(),
+            entries: by_uuid,
+            collections: index.collections.into_iter().collect(),
+            layout: index.layout,
+            deprecated: index.deprecated.into_iter().collect(),
+            order,
+            verified: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Returns namespace of the project pack was written from,
+    /// if the project has one.
+    pub fn namespace(&self) -> Option<Uuid> {
+        self.layers[0].namespace
+    }
+
+    /// Layers patch written by [`pack_diff`] over the pack.
+    /// Patches must be added in the order they were made.
+    /// Patch is decrypted with the key the pack was opened with.
+    pub fn add_patch(&mut self, path: impl AsRef<Path>) -> Result<(), PackError> {
+        let path = path.as_ref();
+        let (layer, flags, patch_index, checksums) = Layer::open(path, self.key.as_ref())?;
+
+        if flags & FLAG_PATCH == 0 {
+            return Err(PackError::InvalidPatch { path: path.into() });
+        }
+
+        if let (Some(expected), Some(found)) = (self.namespace(), layer.namespace) {
+            if expected != found {
+                return Err(PackError::NamespaceMismatch { expected, found });
+            }
+        }
+
+        let layer_index = self.layers.len();
+
+        // Patch is applied to a copy so that failed patch leaves reader intact.
+        let mut entries = self.entries.clone();
+        let mut added = Vec::new();
+
+        for (entry_index, entry) in patch_index.entries.into_iter().enumerate() {
+            if !entries.contains_key(&entry.uuid) && !added.contains(&entry.uuid) {
+                added.push(entry.uuid);
+            }
+            let resolved = entries.entry(entry.uuid).or_default();
+            let position = resolved
+                .iter()
+                .position(|resolved| resolved.locale == entry.locale);
+
+            let chunk = Chunk {
+                layer: layer_index,
+                offset: entry.offset,
+                len: entry.len,
+                nonce: entry.nonce,
+                checksum: checksums.get(entry_index).copied(),
+                delta_base_len: None,
+            };
+
+            match (entry.kind, position) {
+                (EntryKind::Native, position) => {
+                    let native = Resolved {
+                        locale: entry.locale,
+                        native_format: entry.native_format,
+                        signature: entry.signature,
+                        chunks: vec![chunk],
+                    };
+
+                    match position {
+                        None => resolved.push(native),
+                        Some(position) => resolved[position] = native,
+                    }
+                }
+                (EntryKind::Delta { base_len }, Some(position)) => {
+                    let resolved = &mut resolved[position];
+                    resolved.native_format = entry.native_format;
+                    resolved.signature = entry.signature;
+                    resolved.chunks.push(Chunk {
+                        delta_base_len: Some(base_len),
+                        ..chunk
+                    });
+                }
+                (EntryKind::Removed, Some(position)) => {
+                    resolved.remove(position);
+                    if resolved.is_empty() {
+                        entries.remove(&entry.uuid);
+                    }
+                }
+                (EntryKind::Delta { .. }, None) | (EntryKind::Removed, None) => {
+                    return Err(PackError::PatchMismatch { uuid: entry.uuid });
+                }
+            }
+        }
+
+        self.layers.push(layer);
+        self.entries = entries;
+        self.collections = patch_index.collections.into_iter().collect();
+        self.layout = patch_index.layout;
+        self.deprecated = patch_index.deprecated.into_iter().collect();
+        self.order.extend(added);
+        Ok(())
+    }
+
+    /// Returns uuids of all assets in the pack.
+    pub fn uuids(&self) -> impl Iterator<Item = &Uuid> {
+        self.entries.keys()
+    }
+
+    /// Returns members of the collection in order.
+    /// Returns `None` if collection is not in the pack.
+    pub fn collection(&self, name: &str) -> Option<&[Uuid]> {
+        self.collections.get(name).map(|members| &members[..])
+    }
+
+    /// Checks if the asset was deprecated when pack was written.
+    pub fn is_deprecated(&self, uuid: &Uuid) -> bool {
+        self.deprecated.contains(uuid)
+    }
+
+    /// Returns assets in the order of first access in session
+    /// recorded for [`Treasury::pack_with_layout`].
+    /// Native files of these assets are stored in the pack in this order.
+    /// Returns empty slice if pack is written without layout.
+    ///
+    /// [`Treasury::pack_with_layout`]: crate::Treasury::pack_with_layout
+    pub fn layout(&self) -> &[Uuid] {
+        &self.layout
+    }
+
+    /// Returns up to `count` assets likely to be loaded after specified asset:
+    /// accessed after it in recorded session if the pack is laid out by one,
+    /// otherwise stored after it in the pack.
+    /// They are stored right after the asset, so they can be read ahead cheaply.
+    pub fn read_ahead(&self, uuid: &Uuid, count: usize) -> &[Uuid] {
+        fn next<'a>(order: &'a [Uuid], uuid: &Uuid, count: usize) -> Option<&'a [Uuid]> {
+            let position = order.iter().position(|u| u == uuid)?;
+            let next = &order[position + 1..];
+            Some(&next[..count.min(next.len())])
+        }
+
+        next(&self.layout, uuid, count)
+            .or_else(|| next(&self.order, uuid, count))
+            .unwrap_or(&[])
+    }
+
+    /// Reads native file of the asset.
+    /// Locale is resolved as in [`Treasury::fetch`].
+    /// Returns `Ok(None)` if asset is not in the pack.
+    ///
+    /// [`Treasury::fetch`]: crate::Treasury::fetch
+    pub fn read(
+        &mut self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+    ) -> Result<Option<PackAsset>, PackError> {
+        let entries = match self.entries.get(uuid) {
+            None => return Ok(None),
+            Some(entries) => entries,
+        };
+
+        let localized = locale.and_then(|locale| {
+            locale_fallbacks(locale).find_map(|locale| {
+                entries
+                    .iter()
+                    .find(|entry| entry.locale.as_deref() == Some(locale))
+            })
+        });
+
+        match localized.or_else(|| entries.iter().find(|e| e.locale.is_none())) {
+            None => Ok(None),
+            Some(resolved) => self.read_resolved(uuid, resolved).map(Some),
+        }
+    }
+
+    /// Verifies checksums of the pack and patches layered over it.
+    /// Packs written before checksums are not verified.
+    ///
+    /// Returns [`PackError::ChecksumMismatch`] with the first corrupted asset,
+    /// or [`PackError::PackCorrupted`] if the index is corrupted.
+    pub fn verify_all(&self) -> Result<(), PackError> {
+        for (index, layer) in self.layers.iter().enumerate() {
+            let (checksum, end) = match layer.checksum {
+                None => continue,
+                Some(checksum) => checksum,
+            };
+
+            let mut hasher = crc32fast::Hasher::new();
+            let mut block = vec![0; VERIFY_BLOCK_LEN];
+            let mut offset = HEADER_LEN;
+            while offset < end {
+                let len = (end - offset).min(VERIFY_BLOCK_LEN as u64) as usize;
+                layer
+                    .data
+                    .read_at(offset, &mut block[..len])
+                    .map_err(|source| PackError::IoError {
+                        path: layer.path.clone(),
+                        source,
+                    })?;
+                hasher.update(&block[..len]);
+                offset += len as u64;
+            }
+
+            if hasher.finalize() == checksum {
+                continue;
+            }
+
+            // Find corrupted asset to report.
+            for (uuid, resolved) in self.all() {
+                for chunk in resolved.chunks.iter().filter(|c| c.layer == index) {
+                    if let Err(err @ PackError::ChecksumMismatch { .. }) =
+                        self.read_chunk(uuid, resolved.locale.as_deref(), chunk)
+                    {
+                        return Err(err);
+                    }
+                }
+            }
+
+            return Err(PackError::PackCorrupted {
+                path: layer.path.clone(),
+            });
+        }
+
+        let mut verified = self.verified.lock();
+        for (_, resolved) in self.all() {
+            for chunk in &resolved.chunks {
+                if chunk.checksum.is_some() {
+                    verified.insert((chunk.layer, chunk.offset));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns native file with exactly specified locale.
+    fn find(&self, uuid: &Uuid, locale: Option<&str>) -> Option<&Resolved> {
+        self.entries
+            .get(uuid)?
+            .iter()
+            .find(|resolved| resolved.locale.as_deref() == locale)
+    }
+
+    /// Returns all native files in the pack.
+    fn all(&self) -> impl Iterator<Item = (&Uuid, &Resolved)> {
+        self.entries
+            .iter()
+            .flat_map(|(uuid, resolved)| resolved.iter().map(move |resolved| (uuid, resolved)))
+    }
+
+    fn read_resolved(&self, uuid: &Uuid, resolved: &Resolved) -> Result<PackAsset, PackError> {
+        let mut bytes = Vec::new();
+
+        for chunk in &resolved.chunks {
+            let data = self.read_chunk(uuid, resolved.locale.as_deref(), chunk)?;
+
+            bytes = match chunk.delta_base_len {
+                None => data,
+                Some(base_len) => {
+                    if base_len != bytes.len() as u64 {
+                        return Err(PackError::PatchMismatch { uuid: *uuid });
+                    }
+
+                    let mut patched = Vec::new();
+                    zstd::decode_all(&*data)
+                        .and_then(|delta| bsdiff::patch(&bytes, &mut &*delta, &mut patched))
+                        .map_err(|_| PackError::PatchMismatch { uuid: *uuid })?;
+                    patched
+                }
+            };
+        }
+
+        Ok(PackAsset {
+            bytes: bytes.into_boxed_slice(),
+            native_format: resolved.native_format.clone(),
+            locale: resolved.locale.clone(),
+            signature: resolved.signature.clone(),
+        })
+    }
+
+    fn read_chunk(
+        &self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        chunk: &Chunk,
+    ) -> Result<Vec<u8>, PackError> {
+        let layer = &self.layers[chunk.layer];
+        let io_error = |source| PackError::IoError {
+            path: layer.path.clone(),
+            source,
+        };
+
+        if !within(layer.len, chunk.offset, chunk.len) {
+            return Err(PackError::PackCorrupted {
+                path: layer.path.clone(),
+            });
+        }
+
+        let mut bytes = vec![0; chunk.len as usize];
+        layer
+            .data
+            .read_at(chunk.offset, &mut bytes)
+            .map_err(io_error)?;
+
+        if let Some(checksum) = chunk.checksum {
+            let mut verified = self.verified.lock();
+            if !verified.contains(&(chunk.layer, chunk.offset)) {
+                if crc32fast::hash(&bytes) != checksum {
+                    return Err(PackError::ChecksumMismatch {
+                        path: layer.path.clone(),
+                        uuid: *uuid,
+                    });
+                }
+                verified.insert((chunk.layer, chunk.offset));
+            }
+        }
+
+        match (&self.key, &chunk.nonce) {
+            (_, None) if !layer.encrypted => Ok(bytes),
+            (Some(key), Some(nonce)) if layer.encrypted => {
+                let payload = Payload {
+                    msg: &bytes,
+                    aad: &associated_data(uuid, locale),
+                };
+
+                key.cipher()
+                    .decrypt(XNonce::from_slice(nonce), payload)
+                    .map_err(|_| PackError::DecryptionFailed { uuid: *uuid })
+            }
+            _ => Err(PackError::DecryptionFailed { uuid: *uuid }),
+        }
+    }
+}
+
+/// Writes patch that turns `old` pack into `new` pack.
+/// Packs are decrypted and patch is encrypted and signed as configured by options.
+///
+/// Returns number of entries written into patch.
+#[tracing::instrument(skip(old, new, output, options), fields(output = %output.as_ref().display()))]
+pub fn pack_diff(
+    old: impl AsRef<Path>,
+    new: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &PackOptions,
+) -> Result<usize, PackError> {
+    let old = PackReader::open(old, options.key.as_ref())?;
+    let new = PackReader::open(new, options.key.as_ref())?;
+
+    if let (Some(expected), Some(found)) = (old.namespace(), new.namespace()) {
+        if expected != found {
+            return Err(PackError::NamespaceMismatch { expected, found });
+        }
+    }
+
+    let output = output.as_ref();
+    let tmp_path = output.with_extension("tmp");
+
+    let mut writer = PackWriter::create_patch(&tmp_path, options)?;
+    writer.set_namespace(new.namespace());
+
+    for (uuid, resolved) in new.all() {
+        let locale = resolved.locale.as_deref();
+        let asset = new.read_resolved(uuid, resolved)?;
+        let signature = writer.sign(
+            uuid,
+            locale,
+            &asset.native_format,
+            &asset.bytes,
+            asset.signature,
+        );
+
+        let base = match old.find(uuid, locale) {
+            None => None,
+            Some(resolved) => Some(old.read_resolved(uuid, resolved)?),
+        };
+
+        let delta = match &base {
+            Some(base) if base.native_format == asset.native_format => {
+                if base.bytes == asset.bytes && base.signature == signature {
+                    continue;
+                }
+
+                let native_len = asset.bytes.len();
+                let mut delta = Vec::new();
+                bsdiff::diff(&base.bytes, &asset.bytes, &mut delta)
+                    .and_then(|()| zstd::encode_all(&*delta, DELTA_COMPRESSION_LEVEL))
+                    .ok()
+                    .filter(|delta| delta.len() < native_len)
+            }
+            _ => None,
+        };
+
+        match (base, delta) {
+            (Some(base), Some(delta)) => {
+                let kind = EntryKind::Delta {
+                    base_len: base.bytes.len() as u64,
+                };
+                writer.add_entry(*uuid, locale, &asset.native_format, kind, &delta, signature)?;
+            }
+            _ => {
+                writer.add_entry(
+                    *uuid,
+                    locale,
+                    &asset.native_format,
+                    EntryKind::Native,
+                    &asset.bytes,
+                    signature,
+                )?;
+            }
+        }
+    }
+
+    for (name, members) in &new.collections {
+        writer.add_collection(name, members.clone());
+    }
+    writer.set_layout(new.layout.clone());
+    writer.set_deprecated(new.deprecated.iter().copied().collect());
+
+    for (uuid, resolved) in old.all() {
+        let locale = resolved.locale.as_deref();
+        if new.find(uuid, locale).is_none() {
+            writer.add_entry(
+                *uuid,
+                locale,
+                &resolved.native_format,
+                EntryKind::Removed,
+                &[],
+                None,
+            )?;
+        }
+    }
+
+    let count = writer.finish()?;
+
+    std::fs::rename(&tmp_path, output).map_err(|source| PackError::IoError {
+        path: output.into(),
+        source,
+    })?;
+
+    tracing::info!("{} entries written into patch", count);
+    Ok(count)
+}
+
+/// Applies patches written by [`pack_diff`] to the pack and writes result into `output`.
+/// `output` may be the same path as `pack`.
+/// Pack and patches are decrypted and result is encrypted and signed as configured by options.
+/// Signatures from patches are kept unless options contain signing key.
+///
+/// Returns number of native files in resulting pack.
+#[tracing::instrument(skip(pack, patches, output, options), fields(output = %output.as_ref().display()))]
+pub fn apply_patch(
+    pack: impl AsRef<Path>,
+    patches: &[impl AsRef<Path>],
+    output: impl AsRef<Path>,
+    options: &PackOptions,
+) -> Result<usize, PackError> {
+    let mut reader = PackReader::open(pack, options.key.as_ref())?;
+    for patch in patches {
+        reader.add_patch(patch)?;
+    }
+
+    let output = output.as_ref();
+    let tmp_path = output.with_extension("tmp");
+
+    let mut writer = PackWriter::create(&tmp_path, options)?;
+    writer.set_namespace(reader.namespace());
+
+    // Keep native files of assets in layout first and in order.
+    let mut all: Vec<_> = reader.all().collect();
+    all.sort_by_key(|(uuid, _)| {
+        reader
+            .layout
+            .iter()
+            .position(|u| u == *uuid)
+            .unwrap_or(usize::MAX)
+    });
+
+    for (uuid, resolved) in all {
+        let asset = reader.read_resolved(uuid, resolved)?;
+        let signature = writer.sign(
+            uuid,
+            resolved.locale.as_deref(),
+            &asset.native_format,
+            &asset.bytes,
+            asset.signature,
+        );
+
+        writer.add_entry(
+            *uuid,
+            resolved.locale.as_deref(),
+            &asset.native_format,
+            EntryKind::Native,
+            &asset.bytes,
+            signature,
+        )?;
+    }
+
+    for (name, members) in &reader.collections {
+        writer.add_collection(name, members.clone());
+    }
+    writer.set_layout(reader.layout.clone());
+    writer.set_deprecated(reader.deprecated.iter().copied().collect());
+
+    let count = writer.finish()?;
+
+    std::fs::rename(&tmp_path, output).map_err(|source| PackError::IoError {
+        path: output.into(),
+        source,
+    })?;
+
+    tracing::info!("{} native files written into patched pack", count);
+    Ok(count)
+}
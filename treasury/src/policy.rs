@@ -0,0 +1,98 @@
+use std::fmt::{self, Display};
+
+/// Limit on size of native files, evaluated on store and reimport.
+/// Added with [`Treasury::add_size_policy`].
+///
+/// [`Treasury::add_size_policy`]: crate::Treasury::add_size_policy
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SizePolicy {
+    /// Assets the limit applies to.
+    pub limit: SizeLimit,
+
+    /// Maximum size in bytes, including localized native files.
+    pub max: u64,
+
+    /// What happens when the limit is exceeded.
+    #[serde(default, skip_serializing_if = "PolicyAction::is_reject")]
+    pub action: PolicyAction,
+}
+
+impl SizePolicy {
+    /// Returns policy rejecting native files of the format larger than `max` bytes.
+    pub fn native_format(format: &str, max: u64) -> Self {
+        SizePolicy {
+            limit: SizeLimit::NativeFormat(format.into()),
+            max,
+            action: PolicyAction::Reject,
+        }
+    }
+
+    /// Returns policy rejecting assets with the tag once their native files
+    /// exceed `max` bytes in total.
+    pub fn tag(tag: &str, max: u64) -> Self {
+        SizePolicy {
+            limit: SizeLimit::Tag(tag.into()),
+            max,
+            action: PolicyAction::Reject,
+        }
+    }
+
+    /// Makes policy only warn about violations.
+    pub fn with_action(mut self, action: PolicyAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+impl Display for SizePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at most {} bytes", self.limit, self.max)?;
+        if self.action == PolicyAction::Warn {
+            f.write_str(" (warn)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Assets size policy applies to.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizeLimit {
+    /// Native file of each asset with the native format.
+    NativeFormat(Box<str>),
+
+    /// Native files of all assets with the tag in total.
+    Tag(Box<str>),
+}
+
+impl Display for SizeLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeLimit::NativeFormat(format) => write!(f, "native format '{}'", format),
+            SizeLimit::Tag(tag) => write!(f, "total of tag '{}'", tag),
+        }
+    }
+}
+
+/// Action taken when size policy is violated.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum PolicyAction {
+    /// Store fails with [`StoreError::PolicyViolation`]
+    /// and reimport keeps old native files.
+    ///
+    /// [`StoreError::PolicyViolation`]: crate::StoreError::PolicyViolation
+    #[default]
+    Reject,
+
+    /// Violation is logged as warning.
+    Warn,
+}
+
+impl PolicyAction {
+    pub fn is_reject(&self) -> bool {
+        *self == PolicyAction::Reject
+    }
+}
@@ -0,0 +1,169 @@
+//! Default import settings declared per directory.
+//!
+//! Presets are declared in `.treasury-presets.toml` files
+//! in treasury root and its subdirectories.
+//!
+//! ```toml
+//! [[preset]]
+//! # Matched against source path relative to the directory of presets file.
+//! # All sources in the directory and subdirectories if omitted.
+//! glob = "ui/**/*.png"
+//! # Optional filters by formats.
+//! native_format = "texture"
+//!
+//! [preset.settings]
+//! mips = false
+//! srgb = true
+//! ```
+//!
+//! Settings of matching presets are merged,
+//! presets in subdirectories override presets in parent directories,
+//! later presets in a file override earlier ones,
+//! and settings specified on store override presets.
+
+use {
+    crate::treasury::StoreError,
+    serde::Deserialize,
+    std::path::{Component, Path},
+};
+
+pub(crate) const PRESETS_FILE_NAME: &str = ".treasury-presets.toml";
+
+#[derive(Deserialize)]
+struct PresetsFile {
+    #[serde(default)]
+    preset: Vec<Preset>,
+}
+
+#[derive(Deserialize)]
+struct Preset {
+    glob: Option<Box<str>>,
+    source_format: Option<Box<str>>,
+    native_format: Option<Box<str>>,
+    settings: toml::Value,
+}
+
+/// Returns import settings of the source with presets applied.
+/// Sources outside treasury root get no presets.
+pub(crate) fn resolve_settings(
+    root: &Path,
+    source: &Path,
+    source_format: &str,
+    native_format: &str,
+    settings: &serde_json::Value,
+) -> Result<serde_json::Value, StoreError> {
+    let relative = match source.strip_prefix(root) {
+        Ok(relative) => relative,
+        Err(_) => return Ok(settings.clone()),
+    };
+
+    let components: Vec<&str> = match relative
+        .components()
+        .map(|c| match c {
+            Component::Normal(c) => c.to_str(),
+            _ => None,
+        })
+        .collect::<Option<_>>()
+    {
+        Some(components) => components,
+        None => return Ok(settings.clone()),
+    };
+
+    let mut resolved = serde_json::Value::Null;
+    let mut dir = root.to_path_buf();
+
+    // Directories from root to the source's parent.
+    for depth in 0..components.len() {
+        if depth > 0 {
+            dir.push(components[depth - 1]);
+        }
+
+        let path = dir.join(PRESETS_FILE_NAME);
+        let presets = match std::fs::read_to_string(&path) {
+            Ok(presets) => presets,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(source) => {
+                return Err(StoreError::PresetsIoError {
+                    path: path.into(),
+                    source,
+                })
+            }
+        };
+
+        let presets: PresetsFile =
+            toml::from_str(&presets).map_err(|source| StoreError::InvalidPresets {
+                path: path.clone().into(),
+                source: Box::new(source),
+            })?;
+
+        for preset in presets.preset {
+            let matches = preset
+                .source_format
+                .as_deref()
+                .is_none_or(|f| f == source_format)
+                && preset
+                    .native_format
+                    .as_deref()
+                    .is_none_or(|f| f == native_format)
+                && preset
+                    .glob
+                    .as_deref()
+                    .is_none_or(|g| glob_matches(g, &components[depth..]));
+
+            if matches {
+                let settings = serde_json::to_value(preset.settings)
+                    .expect("TOML values are representable in JSON");
+                merge(&mut resolved, settings);
+            }
+        }
+    }
+
+    merge(&mut resolved, settings.clone());
+    Ok(resolved)
+}
+
+/// Merges settings into base, overriding fields recursively.
+/// `Null` overrides nothing.
+fn merge(base: &mut serde_json::Value, settings: serde_json::Value) {
+    match (base, settings) {
+        (_, serde_json::Value::Null) => {}
+        (serde_json::Value::Object(base), serde_json::Value::Object(settings)) => {
+            for (key, value) in settings {
+                merge(base.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, settings) => *base = settings,
+    }
+}
+
+/// Matches path components against glob with `/` separated segments.
+/// `**` segment matches any number of components,
+/// `*` and `?` match any characters and single character in a component.
+fn glob_matches(glob: &str, path: &[&str]) -> bool {
+    let segments: Vec<&str> = glob.split('/').filter(|s| !s.is_empty()).collect();
+    match_segments(&segments, path)
+}
+
+fn match_segments(segments: &[&str], path: &[&str]) -> bool {
+    match segments.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            None => false,
+            Some((component, path)) => {
+                let pattern: Vec<char> = segment.chars().collect();
+                let name: Vec<char> = component.chars().collect();
+                match_component(&pattern, &name) && match_segments(rest, path)
+            }
+        },
+    }
+}
+
+fn match_component(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| match_component(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && match_component(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_component(rest, &name[1..]),
+    }
+}
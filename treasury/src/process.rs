@@ -0,0 +1,148 @@
+//! Importers running external tools.
+//!
+//! Importers are declared in `importers.toml` file in importers directory.
+//!
+//! ```toml
+//! [[importer]]
+//! name = "texconv"
+//! source = "png"
+//! native = "dds"
+//! command = ["./texconv", "{src}", "-o", "{dst}"]
+//! version = "1.2"
+//! ```
+//!
+//! `{src}` and `{dst}` in command arguments are replaced with absolute paths
+//! to source file and native file to write.
+//! Relative program path is resolved against importers directory,
+//! which is also working directory of the process.
+//!
+//! Assets are reimported when `version` of their importer changes.
+
+use {
+    goods_treasury_import::{AsyncImporter, BoxFuture, ImportProgress, Registry},
+    std::path::{Path, PathBuf},
+};
+
+/// Name of file with process importers in importers directory.
+pub(crate) const MANIFEST_FILE_NAME: &str = "importers.toml";
+
+#[derive(serde::Deserialize)]
+pub(crate) struct ProcessManifest {
+    #[serde(default, rename = "importer")]
+    pub importers: Vec<ProcessImporterDecl>,
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct ProcessImporterDecl {
+    name: Box<str>,
+    source: Box<str>,
+    native: Box<str>,
+    command: Vec<Box<str>>,
+    version: Box<str>,
+}
+
+/// Importer that spawns external process.
+pub(crate) struct ProcessImporter {
+    name: Box<str>,
+    source: Box<str>,
+    native: Box<str>,
+    program: PathBuf,
+    args: Vec<Box<str>>,
+    version: Box<str>,
+    dir: Box<Path>,
+}
+
+impl ProcessImporter {
+    /// Returns `None` if command is empty.
+    pub fn new(decl: ProcessImporterDecl, dir: &Path) -> Option<Self> {
+        let mut command = decl.command.into_iter();
+        let program = command.next()?;
+        let program = Path::new(&*program);
+
+        // Bare program names are looked up in `PATH`.
+        let program = if program.components().count() > 1 {
+            dir.join(program)
+        } else {
+            program.to_owned()
+        };
+
+        Some(ProcessImporter {
+            name: decl.name,
+            source: decl.source,
+            native: decl.native,
+            program,
+            args: command.collect(),
+            version: decl.version,
+            dir: dir.into(),
+        })
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+impl AsyncImporter for ProcessImporter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn native(&self) -> &str {
+        &self.native
+    }
+
+    fn import<'a>(
+        &'a self,
+        source_path: &'a Path,
+        native_path: &'a Path,
+        _settings: &'a str,
+        _registry: &'a mut (dyn Registry + Send),
+        _progress: &'a mut (dyn ImportProgress + Send),
+    ) -> BoxFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            let src = source_path.to_string_lossy();
+            let dst = native_path.to_string_lossy();
+
+            let args = self
+                .args
+                .iter()
+                .map(|arg| arg.replace("{src}", &src).replace("{dst}", &dst));
+
+            tracing::debug!("Running importer '{}'", self.program.display());
+
+            let output = tokio::process::Command::new(&self.program)
+                .args(args)
+                .current_dir(&self.dir)
+                .stdin(std::process::Stdio::null())
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|err| {
+                    eyre::eyre!("Failed to run '{}'. {:#}", self.program.display(), err)
+                })?;
+
+            if !output.stdout.is_empty() {
+                tracing::trace!(
+                    "Importer '{}' output: {}",
+                    self.name,
+                    String::from_utf8_lossy(&output.stdout)
+                );
+            }
+
+            if !output.status.success() {
+                eyre::bail!(
+                    "'{}' exited with {}. {}",
+                    self.program.display(),
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                );
+            }
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,90 @@
+use std::{path::Path, time::SystemTime};
+
+/// Provider of sources that do not live in local filesystem,
+/// e.g. on HTTP server, in git LFS storage or perforce depot.
+///
+/// Sources of a provider are addressed by `<scheme>://<path>` URIs.
+/// Source is pulled into local file before it is imported,
+/// and reimported when provider reports newer modification time.
+pub trait SourceProvider: Send + Sync + 'static {
+    /// Returns URI scheme handled by this provider.
+    fn scheme(&self) -> &str;
+
+    /// Returns modification time of the source.
+    fn modified(&self, path: &str) -> eyre::Result<SystemTime>;
+
+    /// Writes content of the source into local file.
+    fn fetch(&self, path: &str, local: &Path) -> eyre::Result<()>;
+}
+
+/// Splits URI into scheme and path.
+/// Returns `None` for paths in local filesystem.
+pub(crate) fn split_uri(uri: &str) -> Option<(&str, &str)> {
+    let (scheme, path) = uri.split_once("://")?;
+
+    let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+
+    if valid {
+        Some((scheme, path))
+    } else {
+        None
+    }
+}
+
+/// Provider of sources served over HTTP.
+/// Handles `http` scheme, or `https` if created with [`HttpProvider::https`].
+///
+/// Modification time is taken from `Last-Modified` header.
+#[cfg(feature = "http")]
+#[derive(Clone, Debug)]
+pub struct HttpProvider {
+    scheme: &'static str,
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "http")]
+impl HttpProvider {
+    pub fn http() -> Self {
+        HttpProvider {
+            scheme: "http",
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    pub fn https() -> Self {
+        HttpProvider {
+            scheme: "https",
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}://{}", self.scheme, path)
+    }
+}
+
+#[cfg(feature = "http")]
+impl SourceProvider for HttpProvider {
+    fn scheme(&self) -> &str {
+        self.scheme
+    }
+
+    fn modified(&self, path: &str) -> eyre::Result<SystemTime> {
+        let response = self.agent.head(&self.url(path)).call()?;
+        let last_modified = response
+            .header("Last-Modified")
+            .ok_or_else(|| eyre::eyre!("Server did not report modification time"))?;
+
+        Ok(httpdate::parse_http_date(last_modified)?)
+    }
+
+    fn fetch(&self, path: &str, local: &Path) -> eyre::Result<()> {
+        let response = self.agent.get(&self.url(path)).call()?;
+        let mut file = std::fs::File::create(local)?;
+        std::io::copy(&mut response.into_reader(), &mut file)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use {
+    std::path::{Path, PathBuf},
+    uuid::Uuid,
+};
+
+/// Ordered sequence of asset data served to a loader during a session.
+///
+/// Recorded by `goods::Loader::start_recording`
+/// and replayed with `goods::source::replay::ReplaySource`,
+/// e.g. to reproduce load-order dependent bugs.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Recording {
+    loads: Vec<RecordedLoad>,
+}
+
+/// Asset data served to a loader, in [`Recording`].
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RecordedLoad {
+    pub uuid: Uuid,
+
+    /// Locale the data was requested for.
+    pub locale: Option<Box<str>>,
+
+    /// Version reported by the source.
+    pub version: u64,
+
+    /// FNV-1a hash of the data.
+    pub hash: u64,
+
+    pub format: Option<Box<str>>,
+    pub schema: Option<u32>,
+    pub bytes: Box<[u8]>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    /// Records data served to a loader.
+    pub fn push(
+        &mut self,
+        uuid: Uuid,
+        locale: Option<&str>,
+        version: u64,
+        format: Option<&str>,
+        schema: Option<u32>,
+        bytes: &[u8],
+    ) {
+        self.loads.push(RecordedLoad {
+            uuid,
+            locale: locale.map(Into::into),
+            version,
+            hash: fnv1a(bytes),
+            format: format.map(Into::into),
+            schema,
+            bytes: bytes.into(),
+        });
+    }
+
+    /// Returns recorded loads in the order data was served.
+    pub fn loads(&self) -> &[RecordedLoad] {
+        &self.loads
+    }
+
+    pub fn len(&self) -> usize {
+        self.loads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.loads.is_empty()
+    }
+
+    /// Writes recording into a file.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), RecordingError> {
+        let path = path.as_ref();
+        let bytes = bincode::serialize(self).map_err(|source| RecordingError::BincodeError {
+            path: path.into(),
+            source,
+        })?;
+        write(path.to_owned(), &bytes)
+    }
+
+    /// Reads recording written with [`Recording::write`].
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|source| RecordingError::IoError {
+            path: path.into(),
+            source,
+        })?;
+        bincode::deserialize(&bytes).map_err(|source| RecordingError::BincodeError {
+            path: path.into(),
+            source,
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error("Failed to access file '{path}'")]
+    IoError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+
+    #[error("Recording file '{path}' is corrupted")]
+    BincodeError {
+        path: Box<Path>,
+        source: bincode::Error,
+    },
+}
+
+fn write(path: PathBuf, bytes: &[u8]) -> Result<(), RecordingError> {
+    let result = match path.parent() {
+        Some(parent) => std::fs::create_dir_all(parent),
+        None => Ok(()),
+    };
+
+    result
+        .and_then(|()| std::fs::write(&path, bytes))
+        .map_err(|source| RecordingError::IoError {
+            path: path.into(),
+            source,
+        })
+}
+
+/// Hash that is stable across platforms and versions,
+/// so that recordings can be compared between machines.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
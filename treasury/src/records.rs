@@ -0,0 +1,311 @@
+use {
+    crate::asset::Asset,
+    std::{
+        collections::HashMap,
+        convert::TryInto as _,
+        fs::File,
+        io::{BufWriter, Read, Seek, SeekFrom, Write},
+        path::Path,
+    },
+    uuid::Uuid,
+};
+
+/// Magic bytes at the end of the records file.
+const MAGIC: &[u8; 8] = b"GTRECIDX";
+
+/// Registered assets.
+///
+/// Small projects keep all records in the JSON manifest.
+/// Indexed manifest keeps records in a separate file with an index of them,
+/// only the index is read on open, and records are parsed when first accessed.
+///
+/// Records file is laid out as JSON records followed by bincode index
+/// of uuid, offset and length of each record,
+/// index offset as little-endian `u64` and [`MAGIC`] bytes.
+#[derive(Default)]
+pub(crate) struct AssetRecords {
+    loaded: Vec<Asset>,
+
+    /// Offset and length of records not parsed yet.
+    pending: HashMap<Uuid, (u64, u64)>,
+
+    /// Opened records file while there are pending records.
+    file: Option<File>,
+}
+
+/// Record to write, loaded one by index or pending one by offset and length.
+enum Record {
+    Loaded(usize),
+    Pending(u64, u64),
+}
+
+impl AssetRecords {
+    /// Reads index of the records file.
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let mut file = File::open(path)?;
+
+        let mut trailer = [0; 16];
+        file.seek(SeekFrom::End(-16))?;
+        file.read_exact(&mut trailer)?;
+        if trailer[8..] != MAGIC[..] {
+            return Err(invalid_data("records file trailer is missing"));
+        }
+        let index_offset = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+
+        let end = file.seek(SeekFrom::End(-16))?;
+        let mut index = vec![0; end.saturating_sub(index_offset) as usize];
+        file.seek(SeekFrom::Start(index_offset))?;
+        file.read_exact(&mut index)?;
+
+        let index: Vec<(Uuid, u64, u64)> = bincode::deserialize(&index)
+            .map_err(|err| invalid_data(&format!("records index is corrupted. {:#}", err)))?;
+
+        Ok(AssetRecords {
+            loaded: Vec::new(),
+            pending: index
+                .into_iter()
+                .map(|(uuid, offset, len)| (uuid, (offset, len)))
+                .collect(),
+            file: Some(file),
+        })
+    }
+
+    /// Writes all records into records file ordered by uuid,
+    /// which is replaced atomically.
+    pub fn write(&mut self, path: &Path) -> std::io::Result<()> {
+        let tmp = path.with_extension("tmp");
+
+        let mut records: Vec<(Uuid, Record)> = self
+            .loaded
+            .iter()
+            .enumerate()
+            .map(|(index, asset)| (asset.uuid(), Record::Loaded(index)))
+            .chain(
+                self.pending
+                    .iter()
+                    .map(|(uuid, (offset, len))| (*uuid, Record::Pending(*offset, *len))),
+            )
+            .collect();
+        records.sort_by_key(|(uuid, _)| *uuid);
+
+        let mut index = Vec::with_capacity(self.len());
+        let mut offset = 0;
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+
+        for (uuid, record) in records {
+            let record = match record {
+                Record::Loaded(index) => serde_json::to_vec(&self.loaded[index])?,
+                // Pending records are copied without parsing.
+                Record::Pending(from, len) => self.read(from, len)?,
+            };
+            writer.write_all(&record)?;
+            index.push((uuid, offset, record.len() as u64));
+            offset += record.len() as u64;
+        }
+
+        let index_bytes = bincode::serialize(&index).map_err(|err| {
+            invalid_data(&format!("failed to serialize records index. {:#}", err))
+        })?;
+        writer.write_all(&index_bytes)?;
+        writer.write_all(&offset.to_le_bytes())?;
+        writer.write_all(MAGIC)?;
+        writer
+            .into_inner()
+            .map_err(|err| err.into_error())?
+            .sync_all()?;
+
+        self.file = None;
+        std::fs::rename(&tmp, path)?;
+
+        let pending = std::mem::take(&mut self.pending);
+        self.pending = index
+            .into_iter()
+            .filter(|(uuid, _, _)| pending.contains_key(uuid))
+            .map(|(uuid, offset, len)| (uuid, (offset, len)))
+            .collect();
+
+        if !self.pending.is_empty() {
+            self.file = Some(File::open(path)?);
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.loaded.len() + self.pending.len()
+    }
+
+    /// Checks if asset is registered without parsing its record.
+    pub fn contains(&self, uuid: Uuid) -> bool {
+        self.pending.contains_key(&uuid) || self.loaded.iter().any(|a| a.uuid() == uuid)
+    }
+
+    /// Returns index of the asset record, parsing it if necessary.
+    pub fn position(&mut self, uuid: Uuid) -> Option<usize> {
+        if let Some(index) = self.loaded.iter().position(|a| a.uuid() == uuid) {
+            return Some(index);
+        }
+
+        let (offset, len) = *self.pending.get(&uuid)?;
+        match self.parse(offset, len) {
+            Ok(asset) => {
+                self.pending.remove(&uuid);
+                self.loaded.push(asset);
+                self.release();
+                Some(self.loaded.len() - 1)
+            }
+            Err(err) => {
+                tracing::error!("Failed to load record of asset '{}'. {:#}", uuid, err);
+                None
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self, uuid: Uuid) -> Option<&mut Asset> {
+        let index = self.position(uuid)?;
+        Some(&mut self.loaded[index])
+    }
+
+    /// Parses all pending records and sorts loaded ones by uuid,
+    /// so that iteration order is deterministic.
+    /// Records that fail to parse stay pending and are kept on save.
+    pub fn load_all(&mut self) {
+        if self.pending.is_empty() {
+            self.sort();
+            return;
+        }
+
+        let mut pending: Vec<_> = self.pending.iter().map(|(u, r)| (*u, *r)).collect();
+        pending.sort_by_key(|(_, (offset, _))| *offset);
+
+        for (uuid, (offset, len)) in pending {
+            match self.parse(offset, len) {
+                Ok(asset) => {
+                    self.pending.remove(&uuid);
+                    self.loaded.push(asset);
+                }
+                Err(err) => {
+                    tracing::error!("Failed to load record of asset '{}'. {:#}", uuid, err);
+                }
+            }
+        }
+        self.release();
+        self.sort();
+    }
+
+    /// Iterates over loaded records.
+    /// Call [`AssetRecords::load_all`] first to visit all of them.
+    pub fn iter(&self) -> std::slice::Iter<'_, Asset> {
+        self.loaded.iter()
+    }
+
+    /// Iterates over loaded records.
+    /// Call [`AssetRecords::load_all`] first to visit all of them.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Asset> {
+        self.loaded.iter_mut()
+    }
+
+    /// Sorts loaded records by uuid.
+    pub fn sort(&mut self) {
+        self.loaded.sort_by_key(|a| a.uuid());
+    }
+
+    pub fn push(&mut self, asset: Asset) {
+        debug_assert!(!self.contains(asset.uuid()));
+        self.loaded.push(asset);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Asset {
+        self.loaded.remove(index)
+    }
+
+    fn read(&self, offset: u64, len: u64) -> std::io::Result<Vec<u8>> {
+        let mut file = self
+            .file
+            .as_ref()
+            .ok_or_else(|| invalid_data("records file is not opened"))?;
+
+        let mut record = vec![0; len as usize];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut record)?;
+        Ok(record)
+    }
+
+    fn parse(&self, offset: u64, len: u64) -> std::io::Result<Asset> {
+        let record = self.read(offset, len)?;
+        Ok(serde_json::from_slice(&record)?)
+    }
+
+    /// Closes records file when no records are pending.
+    fn release(&mut self) {
+        if self.pending.is_empty() {
+            self.file = None;
+        }
+    }
+}
+
+impl std::ops::Index<usize> for AssetRecords {
+    type Output = Asset;
+
+    fn index(&self, index: usize) -> &Asset {
+        &self.loaded[index]
+    }
+}
+
+impl std::ops::IndexMut<usize> for AssetRecords {
+    fn index_mut(&mut self, index: usize) -> &mut Asset {
+        &mut self.loaded[index]
+    }
+}
+
+impl serde::Serialize for AssetRecords {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        debug_assert!(self.pending.is_empty());
+        self.loaded.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AssetRecords {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(AssetRecords {
+            loaded: deserialize_assets(deserializer)?,
+            pending: HashMap::new(),
+            file: None,
+        })
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+fn deserialize_assets<'de, D>(deserializer: D) -> Result<Vec<Asset>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    serde::Deserialize::deserialize(deserializer)
+}
+
+/// Splits records without parsing them and parses them on all cores.
+#[cfg(feature = "parallel")]
+fn deserialize_assets<'de, D>(deserializer: D) -> Result<Vec<Asset>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use {rayon::prelude::*, serde::de::Error as _};
+
+    let records: Vec<Box<serde_json::value::RawValue>> =
+        serde::Deserialize::deserialize(deserializer)?;
+
+    records
+        .par_iter()
+        .map(|record| serde_json::from_str(record.get()))
+        .collect::<Result<_, _>>()
+        .map_err(D::Error::custom)
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_owned())
+}
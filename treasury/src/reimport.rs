@@ -0,0 +1,69 @@
+use {
+    crate::treasury::Registry,
+    parking_lot::Mutex,
+    std::{
+        collections::HashSet,
+        sync::{
+            mpsc::{channel, Receiver, Sender},
+            Arc, Weak,
+        },
+    },
+    uuid::Uuid,
+};
+
+/// Queue of assets to reimport on worker threads.
+/// Workers exit when queue is dropped.
+pub(crate) struct ReimportQueue {
+    sender: Sender<Uuid>,
+    queued: Arc<Mutex<HashSet<Uuid>>>,
+}
+
+impl ReimportQueue {
+    pub fn new(registry: Weak<Mutex<Registry>>) -> Self {
+        let (sender, receiver) = channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let queued = Arc::new(Mutex::new(HashSet::new()));
+
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+        for _ in 0..workers {
+            let registry = registry.clone();
+            let receiver = receiver.clone();
+            let queued = queued.clone();
+            std::thread::spawn(move || work(&registry, &receiver, &queued));
+        }
+
+        ReimportQueue { sender, queued }
+    }
+
+    /// Queues asset for reimport.
+    /// Returns `false` if asset is already queued.
+    pub fn push(&self, uuid: Uuid) -> bool {
+        if !self.queued.lock().insert(uuid) {
+            return false;
+        }
+        // Workers outlive the sender.
+        self.sender.send(uuid).is_ok()
+    }
+}
+
+fn work(
+    registry: &Weak<Mutex<Registry>>,
+    receiver: &Mutex<Receiver<Uuid>>,
+    queued: &Mutex<HashSet<Uuid>>,
+) {
+    loop {
+        let uuid = match receiver.lock().recv() {
+            Ok(uuid) => uuid,
+            Err(_) => return,
+        };
+
+        if let Some(registry) = registry.upgrade() {
+            // Fetch reimports out-of-date asset.
+            if let Err(err) = Registry::fetch(&registry, &uuid, None, None) {
+                tracing::warn!("Failed to reimport asset '{}'. {:#}", uuid, err);
+            }
+        }
+
+        queued.lock().remove(&uuid);
+    }
+}
@@ -0,0 +1,323 @@
+use {
+    crate::{
+        import::{CancelToken, ImportControl},
+        treasury::{Registry, StoreError},
+    },
+    parking_lot::{Condvar, Mutex},
+    std::{
+        collections::VecDeque,
+        path::PathBuf,
+        sync::{
+            mpsc::{channel, Receiver, Sender},
+            Arc,
+        },
+        time::Duration,
+    },
+    uuid::Uuid,
+};
+
+/// Identifier of a job added to [`ImportScheduler`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JobId(usize);
+
+/// Request to import single source.
+pub struct ImportJob {
+    source: PathBuf,
+    source_format: Box<str>,
+    native_format: Box<str>,
+    settings: serde_json::Value,
+    tags: Vec<Box<str>>,
+    dependencies: Vec<JobId>,
+    timeout: Option<Duration>,
+}
+
+impl ImportJob {
+    pub fn new(source: impl Into<PathBuf>, source_format: &str, native_format: &str) -> Self {
+        ImportJob {
+            source: source.into(),
+            source_format: source_format.into(),
+            native_format: native_format.into(),
+            settings: serde_json::Value::Null,
+            tags: Vec::new(),
+            dependencies: Vec::new(),
+            timeout: None,
+        }
+    }
+
+    /// Sets import settings passed to importer.
+    pub fn with_settings(mut self, settings: serde_json::Value) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Adds tags to assign to imported asset.
+    pub fn with_tags(mut self, tags: &[impl AsRef<str>]) -> Self {
+        self.tags.extend(tags.iter().map(|tag| tag.as_ref().into()));
+        self
+    }
+
+    /// Fails the job if async importer does not finish in specified time.
+    /// WASM importers are not limited.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Makes this job run only after specified job is successfully finished.
+    pub fn after(mut self, job: JobId) -> Self {
+        self.dependencies.push(job);
+        self
+    }
+}
+
+/// Event emitted for every job when it is finished.
+#[derive(Debug)]
+pub enum ImportEvent {
+    /// Job finished successfully.
+    Stored { job: JobId, uuid: Uuid },
+
+    /// Job failed.
+    Failed { job: JobId, error: StoreError },
+
+    /// Job was not started because one of its dependencies failed or was skipped.
+    Skipped { job: JobId, dependency: JobId },
+
+    /// Job was cancelled with scheduler's [`CancelToken`].
+    Cancelled { job: JobId },
+}
+
+impl ImportEvent {
+    pub fn job(&self) -> JobId {
+        match *self {
+            ImportEvent::Stored { job, .. }
+            | ImportEvent::Failed { job, .. }
+            | ImportEvent::Skipped { job, .. }
+            | ImportEvent::Cancelled { job } => job,
+        }
+    }
+}
+
+/// Runs many import jobs in parallel,
+/// starting each job after all its dependencies.
+pub struct ImportScheduler {
+    registry: Arc<Mutex<Registry>>,
+    workers: usize,
+    jobs: Vec<ImportJob>,
+    cancel: CancelToken,
+}
+
+impl ImportScheduler {
+    pub(crate) fn new(registry: Arc<Mutex<Registry>>) -> Self {
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+        ImportScheduler {
+            registry,
+            workers,
+            jobs: Vec::new(),
+            cancel: CancelToken::new(),
+        }
+    }
+
+    /// Returns token to cancel jobs of this scheduler.
+    /// Once cancelled, jobs that are not started are reported as cancelled,
+    /// running async importers are stopped and results of running WASM importers are discarded.
+    /// Cancelled jobs leave no native files or assets registered by their sub-imports.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Sets number of worker threads.
+    /// Defaults to available parallelism.
+    pub fn set_workers(&mut self, workers: usize) -> &mut Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Sets number of worker threads.
+    /// Defaults to available parallelism.
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Adds job to the scheduler.
+    ///
+    /// # Panics
+    ///
+    /// Panics if job depends on a job not added to this scheduler.
+    pub fn add(&mut self, job: ImportJob) -> JobId {
+        let id = JobId(self.jobs.len());
+        assert!(
+            job.dependencies.iter().all(|dep| *dep < id),
+            "Job dependencies must be added before the job"
+        );
+        self.jobs.push(job);
+        id
+    }
+
+    /// Starts all added jobs on worker threads.
+    /// Returns receiver of job events.
+    /// Receiver is disconnected once all jobs are finished.
+    pub fn run(self) -> Receiver<ImportEvent> {
+        let (sender, receiver) = channel();
+
+        let mut dependents = vec![Vec::new(); self.jobs.len()];
+        let mut unfinished = Vec::with_capacity(self.jobs.len());
+        let mut ready = VecDeque::new();
+
+        for (index, job) in self.jobs.iter().enumerate() {
+            for dep in &job.dependencies {
+                dependents[dep.0].push(index);
+            }
+            unfinished.push(job.dependencies.len());
+            if job.dependencies.is_empty() {
+                ready.push_back(index);
+            }
+        }
+
+        let workers = self.workers.min(self.jobs.len());
+
+        let shared = Arc::new(Shared {
+            registry: self.registry,
+            cancel: self.cancel,
+            state: Mutex::new(State {
+                jobs: self.jobs.into_iter().map(Some).collect(),
+                ready,
+                dependents,
+                unfinished,
+                running: 0,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        for _ in 0..workers {
+            let shared = shared.clone();
+            let sender = sender.clone();
+            std::thread::spawn(move || shared.work(&sender));
+        }
+
+        receiver
+    }
+}
+
+struct Shared {
+    registry: Arc<Mutex<Registry>>,
+    cancel: CancelToken,
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+struct State {
+    /// Jobs not yet started or skipped.
+    jobs: Vec<Option<ImportJob>>,
+
+    /// Jobs with all dependencies finished.
+    ready: VecDeque<usize>,
+
+    /// Jobs that depend on a job.
+    dependents: Vec<Vec<usize>>,
+
+    /// Number of unfinished dependencies of a job.
+    unfinished: Vec<usize>,
+
+    /// Number of jobs being run.
+    running: usize,
+}
+
+impl Shared {
+    fn work(&self, sender: &Sender<ImportEvent>) {
+        let mut state = self.state.lock();
+
+        loop {
+            let index = match state.ready.pop_front() {
+                Some(index) => index,
+                None if state.running == 0 => {
+                    // Nothing to run and nothing could become ready.
+                    self.condvar.notify_all();
+                    return;
+                }
+                None => {
+                    self.condvar.wait(&mut state);
+                    continue;
+                }
+            };
+
+            let job = state.jobs[index].take().unwrap();
+
+            if self.cancel.is_cancelled() {
+                let _ = sender.send(ImportEvent::Cancelled { job: JobId(index) });
+                skip_dependents(&mut state, index, sender);
+                continue;
+            }
+
+            state.running += 1;
+            drop(state);
+
+            let result = Registry::store(
+                &self.registry,
+                &job.source,
+                &job.source_format,
+                &job.native_format,
+                &job.settings,
+                &job.tags,
+                &mut (),
+                &ImportControl {
+                    timeout: job.timeout,
+                    cancel: Some(self.cancel.clone()),
+                    ..ImportControl::default()
+                },
+            );
+
+            state = self.state.lock();
+            state.running -= 1;
+
+            match result {
+                Ok(uuid) => {
+                    let _ = sender.send(ImportEvent::Stored {
+                        job: JobId(index),
+                        uuid,
+                    });
+
+                    for dependent in std::mem::take(&mut state.dependents[index]) {
+                        state.unfinished[dependent] -= 1;
+
+                        // Skipped jobs are never ready.
+                        if state.unfinished[dependent] == 0 && state.jobs[dependent].is_some() {
+                            state.ready.push_back(dependent);
+                        }
+                    }
+                }
+                Err(StoreError::ImportCancelled) => {
+                    let _ = sender.send(ImportEvent::Cancelled { job: JobId(index) });
+                    skip_dependents(&mut state, index, sender);
+                }
+                Err(error) => {
+                    let _ = sender.send(ImportEvent::Failed {
+                        job: JobId(index),
+                        error,
+                    });
+                    skip_dependents(&mut state, index, sender);
+                }
+            }
+
+            self.condvar.notify_all();
+        }
+    }
+}
+
+fn skip_dependents(state: &mut State, index: usize, sender: &Sender<ImportEvent>) {
+    let mut stack = vec![index];
+
+    while let Some(index) = stack.pop() {
+        for dependent in std::mem::take(&mut state.dependents[index]) {
+            // Job is skipped once, on the first failed dependency.
+            if state.jobs[dependent].take().is_some() {
+                let _ = sender.send(ImportEvent::Skipped {
+                    job: JobId(dependent),
+                    dependency: JobId(index),
+                });
+                stack.push(dependent);
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use {
+    crate::treasury::Registry,
+    parking_lot::Mutex,
+    sha2::Digest as _,
+    std::{
+        fmt::Write as _,
+        io::Read as _,
+        path::{Path, PathBuf},
+        sync::{
+            mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+            Weak,
+        },
+        time::{Duration, Instant},
+    },
+    uuid::Uuid,
+};
+
+/// Pause between passes over native files.
+const PASS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Size of reads between rate limiting pauses.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Worker thread verifying native files against hashes in `.treasury/checksums`.
+/// Worker exits when scrubber is dropped.
+pub(crate) struct Scrubber {
+    _stop: Sender<()>,
+}
+
+impl Scrubber {
+    pub fn new(registry: Weak<Mutex<Registry>>, root: PathBuf, bytes_per_second: u64) -> Self {
+        let (stop, stopped) = channel();
+        std::thread::spawn(move || work(&registry, &root, bytes_per_second.max(1), &stopped));
+        Scrubber { _stop: stop }
+    }
+}
+
+/// Native file state found by the scrubber.
+enum Scrubbed {
+    Intact,
+    Corrupted,
+
+    /// Native file is rewritten after checksums file, so its hash is unknown.
+    Changed,
+}
+
+fn work(
+    registry: &Weak<Mutex<Registry>>,
+    root: &Path,
+    bytes_per_second: u64,
+    stopped: &Receiver<()>,
+) {
+    loop {
+        let checksums_path = root.join(".treasury").join("checksums");
+        let written = std::fs::metadata(&checksums_path).and_then(|m| m.modified());
+        let checksums = std::fs::read_to_string(&checksums_path);
+
+        if let (Ok(written), Ok(checksums)) = (written, checksums) {
+            for line in checksums.lines() {
+                let mut parts = line.split(' ');
+                let (uuid, _source_hash, native_hash, size) =
+                    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                        (Some(uuid), Some(source_hash), Some(native_hash), Some(size)) => {
+                            (uuid, source_hash, native_hash, size)
+                        }
+                        _ => continue,
+                    };
+
+                let (uuid, size) = match (uuid.parse::<Uuid>(), size.parse::<u64>()) {
+                    (Ok(uuid), Ok(size)) if native_hash != "-" => (uuid, size),
+                    _ => continue,
+                };
+
+                let native = root
+                    .join(".treasury")
+                    .join(uuid.to_hyphenated().to_string());
+                let scrubbed = match scrub(
+                    &native,
+                    native_hash,
+                    size,
+                    written,
+                    bytes_per_second,
+                    stopped,
+                ) {
+                    Ok(Some(scrubbed)) => scrubbed,
+                    Ok(None) => return,
+                    Err(err) => {
+                        // Removed assets leave their lines until next save.
+                        tracing::debug!("Failed to scrub '{}'. {:#}", native.display(), err);
+                        continue;
+                    }
+                };
+
+                let registry = match registry.upgrade() {
+                    Some(registry) => registry,
+                    None => return,
+                };
+                let mut lock = registry.lock();
+                match scrubbed {
+                    Scrubbed::Corrupted => lock.set_corrupted(uuid, true),
+                    Scrubbed::Intact | Scrubbed::Changed => lock.set_corrupted(uuid, false),
+                }
+            }
+        }
+
+        match stopped.recv_timeout(PASS_INTERVAL) {
+            Err(RecvTimeoutError::Timeout) => {}
+            _ => return,
+        }
+    }
+}
+
+/// Hashes native file reading at most `bytes_per_second`.
+/// Returns `None` if scrubber is stopped.
+fn scrub(
+    native: &Path,
+    native_hash: &str,
+    size: u64,
+    written: std::time::SystemTime,
+    bytes_per_second: u64,
+    stopped: &Receiver<()>,
+) -> std::io::Result<Option<Scrubbed>> {
+    let mut file = std::fs::File::open(native)?;
+    let metadata = file.metadata()?;
+    if metadata.modified()? >= written {
+        return Ok(Some(Scrubbed::Changed));
+    }
+
+    // Truncated by partial write.
+    if metadata.len() != size {
+        return Ok(Some(Scrubbed::Corrupted));
+    }
+
+    let mut hasher = sha2::Sha256::new();
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let start = Instant::now();
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&chunk[..read]);
+
+        let budget = Duration::from_secs_f64(read as f64 / bytes_per_second as f64);
+        if let Some(pause) = budget.checked_sub(start.elapsed()) {
+            match stopped.recv_timeout(pause) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    let mut hash = String::with_capacity(64);
+    for byte in hasher.finalize() {
+        let _ = write!(hash, "{:02x}", byte);
+    }
+
+    match hash == native_hash {
+        true => Ok(Some(Scrubbed::Intact)),
+        false => Ok(Some(Scrubbed::Corrupted)),
+    }
+}
@@ -0,0 +1,168 @@
+use {
+    crate::{
+        asset::Asset,
+        pack::{self, PackKey},
+    },
+    std::path::{Path, PathBuf},
+    uuid::Uuid,
+};
+
+/// Assets exported with [`Treasury::export_subset`]
+/// to be added into another treasury with [`Treasury::import_subset`].
+///
+/// Bundle holds asset records, native files and sources managed by treasury,
+/// and can be serialized to be transferred between projects.
+/// Native files encrypted at rest are decrypted into the bundle
+/// and encrypted with the key of the treasury bundle is imported into.
+///
+/// [`Treasury::export_subset`]: crate::Treasury::export_subset
+/// [`Treasury::import_subset`]: crate::Treasury::import_subset
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SubsetBundle {
+    pub(crate) assets: Vec<SubsetAsset>,
+}
+
+impl SubsetBundle {
+    /// Returns records of assets in the bundle.
+    pub fn assets(&self) -> impl Iterator<Item = &Asset> + '_ {
+        self.assets.iter().map(|entry| &entry.asset)
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    /// Writes bundle into a file.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), SubsetError> {
+        let bytes = bincode::serialize(self).map_err(|source| SubsetError::BincodeError {
+            path: path.as_ref().into(),
+            source,
+        })?;
+        write(path.as_ref().to_owned(), &bytes)
+    }
+
+    /// Reads bundle written with [`SubsetBundle::write`].
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, SubsetError> {
+        let bytes = read(path.as_ref().to_owned())?;
+        bincode::deserialize(&bytes).map_err(|source| SubsetError::BincodeError {
+            path: path.as_ref().into(),
+            source,
+        })
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct SubsetAsset {
+    /// Record is kept as JSON document,
+    /// since its optional fields and settings require self-describing format.
+    #[serde(with = "json")]
+    pub asset: Asset,
+    pub native: Vec<u8>,
+    pub localized: Vec<(Box<str>, Vec<u8>)>,
+
+    /// Content of the source if it is managed by treasury.
+    /// Other sources are not transferred.
+    pub source: Option<Vec<u8>>,
+}
+
+/// Handling of bundled assets with UUIDs already registered
+/// in the treasury they are imported into.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubsetConflict {
+    /// Existing asset is kept.
+    #[default]
+    Skip,
+
+    /// Existing asset is replaced with bundled one.
+    Overwrite,
+
+    /// Import fails before any asset is added.
+    Fail,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubsetError {
+    #[error("Asset '{uuid}' is already registered")]
+    Conflict { uuid: Uuid },
+
+    #[error("Failed to access file '{path}'")]
+    IoError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+
+    #[error("Bundle file '{path}' is corrupted")]
+    BincodeError {
+        path: Box<Path>,
+        source: bincode::Error,
+    },
+}
+
+mod json {
+    use {
+        crate::asset::Asset,
+        serde::{de::Error as _, ser::Error as _, Deserialize as _, Deserializer, Serializer},
+    };
+
+    pub fn serialize<S: Serializer>(asset: &Asset, serializer: S) -> Result<S::Ok, S::Error> {
+        let json = serde_json::to_string(asset).map_err(S::Error::custom)?;
+        serializer.serialize_str(&json)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Asset, D::Error> {
+        let json = String::deserialize(deserializer)?;
+        serde_json::from_str(&json).map_err(D::Error::custom)
+    }
+}
+
+/// Returns whether source is stored by treasury itself,
+/// e.g. with `Treasury::store_bytes`.
+pub(crate) fn is_managed_source(source: &str) -> bool {
+    Path::new(source).starts_with(Path::new(".treasury").join("sources"))
+}
+
+pub(crate) fn read(path: PathBuf) -> Result<Vec<u8>, SubsetError> {
+    std::fs::read(&path).map_err(|source| SubsetError::IoError {
+        path: path.into(),
+        source,
+    })
+}
+
+/// Reads native file, decrypting it if encrypted at rest.
+pub(crate) fn read_native(path: PathBuf, key: Option<&PackKey>) -> Result<Vec<u8>, SubsetError> {
+    let bytes = read(path.clone())?;
+    pack::unseal(key, bytes).map_err(|source| SubsetError::IoError {
+        path: path.into(),
+        source,
+    })
+}
+
+/// Writes native file, encrypting it at rest if key is set.
+pub(crate) fn write_native(
+    path: PathBuf,
+    bytes: &[u8],
+    key: Option<&PackKey>,
+) -> Result<(), SubsetError> {
+    match key {
+        None => write(path, bytes),
+        Some(key) => write(path, &pack::seal(key, bytes)),
+    }
+}
+
+pub(crate) fn write(path: PathBuf, bytes: &[u8]) -> Result<(), SubsetError> {
+    let result = match path.parent() {
+        Some(parent) => std::fs::create_dir_all(parent),
+        None => Ok(()),
+    };
+
+    result
+        .and_then(|()| std::fs::write(&path, bytes))
+        .map_err(|source| SubsetError::IoError {
+            path: path.into(),
+            source,
+        })
+}
@@ -1,23 +1,70 @@
 use {
-    crate::asset::Asset,
+    crate::{
+        asset::{Asset, Lifecycle, Visibility},
+        clock::{Clock, SystemClock},
+        graph::{self, GraphFormat, GraphStyle},
+        pack::{self, PackError, PackKey, PackOptions, PackWriter},
+        policy::SizePolicy,
+        recording::Recording,
+        records::AssetRecords,
+        subset::{self, is_managed_source, SubsetAsset, SubsetBundle, SubsetConflict, SubsetError},
+    },
     parking_lot::Mutex,
-    std::{io::Read, path::Path, sync::Arc, time::SystemTime},
+    std::{
+        collections::BTreeMap,
+        io::{Read, Write as _},
+        path::Path,
+        sync::{
+            mpsc::{channel, Receiver, Sender},
+            Arc,
+        },
+        time::SystemTime,
+    },
     uuid::Uuid,
 };
 
 #[cfg(feature = "import")]
-use crate::import::Importers;
+use {
+    crate::{
+        asset::settings_json,
+        cache::{self, ImportCache},
+        import::{
+            CancelToken, ImportControl, ImportError, ImportOutput, ImporterEntry, ImporterInfo,
+            ImporterModule, Importers,
+        },
+        policy::{PolicyAction, SizeLimit},
+        presets::resolve_settings,
+        provider::{split_uri, SourceProvider},
+        reimport::ReimportQueue,
+        scheduler::ImportScheduler,
+        scrub::Scrubber,
+        validate::{validate, StoreValidator, ValidatedNative, ValidatedSource},
+    },
+    goods_treasury_import::{AsyncImporter, FormatId, ImportProgress},
+    parking_lot::{Condvar, MutexGuard},
+    std::{
+        collections::{HashMap, HashSet},
+        path::PathBuf,
+        thread::ThreadId,
+    },
+};
+
+#[cfg(feature = "git")]
+use crate::git::{changed_since, GitError};
 
 /// Storage for goods.
 pub struct Treasury {
     registry: Arc<Mutex<Registry>>,
 }
 
-#[derive(PartialEq, Hash)]
+/// Source with formats it is imported from and into, and import settings.
+#[cfg(feature = "import")]
+#[derive(Clone, PartialEq, Eq, Hash)]
 struct Kind {
-    source_path: Arc<Path>,
+    source: Arc<str>,
     source_format: Arc<str>,
     native_format: Arc<str>,
+    settings: Arc<str>,
 }
 
 pub(crate) struct Registry {
@@ -30,18 +77,264 @@ pub(crate) struct Registry {
     /// Importers
     #[cfg(feature = "import")]
     importers: Importers,
+
+    /// Import states of assets.
+    #[cfg(feature = "import")]
+    states: HashMap<Uuid, ImportState>,
+
+    /// Sources being imported by `store`.
+    #[cfg(feature = "import")]
+    storing: HashMap<Kind, Arc<ImportWait>>,
+
+    /// Imports threads are waiting for.
+    #[cfg(feature = "import")]
+    waiting: HashMap<ThreadId, Arc<ImportWait>>,
+
+    /// Handling of stored sources identical to sources of existing assets.
+    #[cfg(feature = "import")]
+    dedup: Dedup,
+
+    /// Whether sources are copied as native files instead of imported.
+    #[cfg(feature = "import")]
+    copy_through: CopyThrough,
+
+    /// Providers of sources outside local filesystem by URI scheme.
+    #[cfg(feature = "import")]
+    providers: HashMap<Box<str>, Arc<dyn SourceProvider>>,
+
+    /// Assets known to be outdated regardless of modification times,
+    /// e.g. with sources changed according to version control.
+    #[cfg(feature = "import")]
+    dirty: HashSet<Uuid>,
+
+    /// Imports are repeated to check that importers are deterministic.
+    #[cfg(feature = "import")]
+    check_determinism: bool,
+
+    /// Cache of import results shared with other machines.
+    #[cfg(feature = "import")]
+    import_cache: Option<Arc<dyn ImportCache>>,
+
+    /// Imports found to be non-deterministic.
+    #[cfg(feature = "import")]
+    nondeterministic: Vec<NondeterministicImport>,
+
+    /// Index of the asset [`Treasury::maintain`] checks next.
+    #[cfg(feature = "import")]
+    maintain_cursor: usize,
+
+    /// Assets queued for reimport by [`Treasury::maintain`].
+    /// Started on first use.
+    #[cfg(feature = "import")]
+    reimport: Option<ReimportQueue>,
+
+    /// Extensions of sources stored by [`Treasury::store_unimported`]
+    /// with source and native formats.
+    #[cfg(feature = "import")]
+    auto_store: Vec<(Box<str>, FormatId, FormatId)>,
+
+    /// Background verification of native files started by [`Treasury::set_scrubber`].
+    #[cfg(feature = "import")]
+    scrubber: Option<Scrubber>,
+
+    /// Assets with native files not matching stored hashes.
+    #[cfg(feature = "import")]
+    corrupted: HashSet<Uuid>,
+
+    /// Checks run on stored and reimported assets.
+    #[cfg(feature = "import")]
+    validators: Vec<Arc<dyn StoreValidator>>,
+
+    /// Identity of the user stored and reimported assets are attributed to.
+    identity: Box<str>,
+
+    /// Source of time recorded for stored and reimported assets.
+    clock: Arc<dyn Clock>,
+
+    /// Key native files are encrypted with at rest.
+    native_key: Option<PackKey>,
+
+    /// Senders of events for subscribers.
+    subscribers: Vec<Sender<TreasuryEvent>>,
+}
+
+/// Changes of the treasury reported to receivers returned by [`Treasury::events`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreasuryEvent {
+    /// New asset is registered.
+    Stored { uuid: Uuid },
+
+    /// Asset is removed.
+    Removed { uuid: Uuid },
+
+    /// Native files of the asset are reimported from updated source.
+    Reimported { uuid: Uuid },
+
+    /// Tags of the asset are changed.
+    TagsChanged { uuid: Uuid },
+
+    /// Native file of the asset doesn't match hash in checksums file,
+    /// found by scrubber started with `Treasury::set_scrubber`.
+    Corrupted { uuid: Uuid },
+
+    /// Manifest is saved to disk.
+    ManifestSaved,
+}
+
+/// Handling of stored sources with content identical
+/// to the source of already registered asset,
+/// e.g. copy-pasted source files.
+///
+/// Only assets with the same formats and settings
+/// and without dependencies are considered identical,
+/// since dependencies may be resolved relative to the source path.
+#[cfg(feature = "import")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dedup {
+    /// Identical sources are imported as separate assets.
+    #[default]
+    Off,
+
+    /// Store returns UUID of the existing asset.
+    Reuse,
+
+    /// Store returns UUID of the existing asset
+    /// and records source path as its alias,
+    /// so that following stores from that path do not hash the source.
+    Alias,
+}
+
+/// Whether sources are copied as native files instead of being imported.
+/// Set with [`Treasury::set_copy_through`].
+#[cfg(feature = "import")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CopyThrough {
+    /// Source is copied if source and native formats are the same,
+    /// otherwise store fails if no importer is found.
+    #[default]
+    SameFormat,
+
+    /// Source is also copied with a warning if no importer is found.
+    /// Convenient for prototyping with formats the engine reads directly.
+    Fallback,
+
+    /// Source is never copied.
+    /// Store fails if no importer is found, even for the same source and native formats.
+    Never,
+}
+
+/// Import that produced different native files when repeated.
+/// Reported by [`Treasury::determinism_report`].
+#[cfg(feature = "import")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NondeterministicImport {
+    pub uuid: Uuid,
+    pub importer: Box<str>,
+
+    /// Description of the difference.
+    pub reason: Box<str>,
 }
 
+/// Version of manifest layout.
+/// Manifests of older versions are migrated when opened.
+///
+/// 1 - assets are sorted by uuid, tags, aliases and importers dirs are sorted.
+const MANIFEST_VERSION: u32 = 1;
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct Data {
+    /// Version of manifest layout, see [`MANIFEST_VERSION`].
+    #[serde(default)]
+    version: u32,
+
+    /// Sorted directories importers are loaded from.
+    /// Importers from later directories replace ones with same formats.
     importers_dirs: Vec<Box<Path>>,
-    /// Array with all registered assets.
-    assets: Vec<Asset>,
+    /// Array with all registered assets sorted by uuid.
+    /// Empty with indexed manifest.
+    #[serde(default)]
+    assets: AssetRecords,
+
+    /// Named collections of assets.
+    /// Order of members is their load priority.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    collections: BTreeMap<Box<str>, Vec<Uuid>>,
+
+    /// Namespace of the project.
+    /// Uuids of new assets are generated from it,
+    /// and packs record it, so that assets of different projects can't collide.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    namespace: Option<Uuid>,
+
+    /// Whether `.treasury/checksums` file is written on save.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    checksums: bool,
+
+    /// Whether asset records are kept in `.treasury/assets.db` file
+    /// and loaded on demand.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    indexed: bool,
+
+    /// Limits on native file sizes checked on store and reimport.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    size_policies: Vec<SizePolicy>,
+}
+
+/// Asset metadata returned by [`Treasury::stat`].
+#[derive(Clone, Debug)]
+pub struct AssetStat {
+    pub uuid: Uuid,
+    pub source_format: Box<str>,
+    pub native_format: Box<str>,
+    pub tags: Box<[Box<str>]>,
+
+    /// Version of native files, see [`Asset::version`].
+    pub version: u64,
+
+    /// Schema version of native files, see [`Asset::schema`].
+    pub schema: u32,
+
+    /// Size of native file without locale.
+    /// `None` if native file is missing.
+    pub native_size: Option<u64>,
+
+    /// Time native file was last written, see [`Asset::imported_at`].
+    /// Modification time of native file for assets stored before times were recorded.
+    /// `None` if native file is missing.
+    pub imported: Option<SystemTime>,
+
+    /// Whether native file is up-to-date with source, dependencies and importer.
+    /// Always `true` without `import` feature.
+    pub up_to_date: bool,
+
+    /// Identity of the user who stored the asset, see [`Asset::stored_by`].
+    pub stored_by: Option<Box<str>>,
+
+    /// Identity of the user who last reimported the asset, see [`Asset::reimported_by`].
+    pub reimported_by: Option<Box<str>>,
+
+    /// Time the asset was stored, see [`Asset::stored_at`].
+    pub stored_at: Option<SystemTime>,
+
+    /// Time the asset was last reimported, see [`Asset::reimported_at`].
+    pub reimported_at: Option<SystemTime>,
 }
 
 pub struct AssetData {
     pub bytes: Box<[u8]>,
+
+    /// Human-readable name of the asset, see [`Asset::name`].
+    pub name: Box<str>,
+
+    /// Version of native files, see [`Asset::version`].
     pub version: u64,
+    pub native_format: Box<str>,
+
+    /// Schema version of native files, see [`Asset::schema`].
+    pub schema: u32,
+
+    /// Review state of the asset, see [`Asset::lifecycle`].
+    pub lifecycle: Lifecycle,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -71,6 +364,63 @@ pub enum NewError {
     SaveError(#[from] SaveError),
 }
 
+/// Files removed by [`Treasury::compact`].
+#[derive(Clone, Debug, Default)]
+pub struct CompactReport {
+    /// Removed files relative to root path.
+    pub removed: Vec<Box<Path>>,
+
+    /// Total size of removed files in bytes.
+    pub freed: u64,
+}
+
+/// Differences between two treasuries found by [`Treasury::diff`].
+/// Assets are sorted by uuid.
+#[derive(Clone, Debug, Default)]
+pub struct TreasuryDiff {
+    /// Assets only in the other treasury.
+    pub added: Vec<Asset>,
+
+    /// Assets only in this treasury.
+    pub removed: Vec<Asset>,
+
+    /// Assets in both treasuries that differ.
+    pub changed: Vec<AssetChange>,
+}
+
+/// Asset that differs between two treasuries.
+#[derive(Clone, Debug)]
+pub struct AssetChange {
+    pub uuid: Uuid,
+
+    /// Name of the asset in the other treasury.
+    pub name: Box<str>,
+
+    /// Native files differ in content, format or locales.
+    pub native: bool,
+
+    /// Source path or source content differ.
+    pub source: bool,
+
+    /// Import settings differ.
+    pub settings: bool,
+
+    /// Tags differ.
+    pub tags: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CompactError {
+    #[error("Treasury cannot be compacted while assets are being imported")]
+    ImportInProgress,
+
+    #[error("Failed to access treasury file '{path}'")]
+    IoError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum SaveError {
     #[error("Failed to open goods path '{path}'")]
@@ -84,6 +434,18 @@ pub enum SaveError {
         path: Box<Path>,
         source: serde_json::Error,
     },
+
+    #[error("Failed to write checksums file '{path}'")]
+    ChecksumsIoError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write asset records file '{path}'")]
+    RecordsError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -108,6 +470,30 @@ pub enum OpenError {
         path: Box<Path>,
         source: serde_json::Error,
     },
+
+    #[error("Failed to read asset records file '{path}'")]
+    RecordsError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DuplicateError {
+    #[error("Asset not found")]
+    NotFound,
+
+    #[error("Failed to copy source file '{path}'")]
+    SourceIoError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to copy native file '{path}'")]
+    NativeIoError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -115,24 +501,40 @@ pub enum FetchError {
     #[error("Asset not found")]
     NotFound,
 
-    #[error("Failed to access native file '{path}'")]
+    #[error("Failed to access native file '{path}' of asset '{name}'")]
     NativeIoError {
+        name: Box<str>,
         path: Box<Path>,
         source: std::io::Error,
     },
+
+    #[error("Asset is being imported by the caller")]
+    ImportCycle,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum StoreError {
-    #[error("No importer from '{source_format}' to '{native_format}' found")]
+    #[error(
+        "No importer from '{source_format}' to '{native_format}' found{}",
+        available_natives(available)
+    )]
     ImporterNotFound {
         source_format: String,
         native_format: String,
+
+        /// Native formats importers from the source format produce.
+        available: Vec<Box<str>>,
     },
 
     #[error("Import failed")]
     ImportError { source: eyre::Report },
 
+    #[error("Import timed out")]
+    ImportTimeout,
+
+    #[error("Import cancelled")]
+    ImportCancelled,
+
     #[error("Failed to access source file '{path}'")]
     SourceIoError {
         path: Box<Path>,
@@ -144,6 +546,41 @@ pub enum StoreError {
         path: Box<Path>,
         source: std::io::Error,
     },
+
+    #[error("Source '{path}' is being imported by the caller")]
+    ImportCycle { path: Box<Path> },
+
+    #[error("Virtual name '{name}' is not a relative path inside sources directory")]
+    InvalidVirtualName { name: Box<str> },
+
+    #[error("No provider for sources with '{scheme}' scheme")]
+    ProviderNotFound { scheme: Box<str> },
+
+    #[error("Failed to fetch source '{uri}'")]
+    SourceFetchError { uri: Box<str>, source: eyre::Report },
+
+    #[error("Native size {size} violates size policy: {policy}")]
+    PolicyViolation { policy: SizePolicy, size: u64 },
+
+    #[error("Validator '{validator}' rejected the asset")]
+    ValidationFailed {
+        validator: Box<str>,
+        source: eyre::Report,
+    },
+
+    #[cfg(feature = "import")]
+    #[error("Failed to read presets file '{path}'")]
+    PresetsIoError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+
+    #[cfg(feature = "import")]
+    #[error("Failed to parse presets file '{path}'")]
+    InvalidPresets {
+        path: Box<Path>,
+        source: Box<toml::de::Error>,
+    },
 }
 
 impl Treasury {
@@ -187,10 +624,52 @@ impl Treasury {
             registry: Arc::new(Mutex::new(Registry {
                 #[cfg(feature = "import")]
                 importers: Importers::new(&root),
+                #[cfg(feature = "import")]
+                states: HashMap::new(),
+                #[cfg(feature = "import")]
+                storing: HashMap::new(),
+                #[cfg(feature = "import")]
+                waiting: HashMap::new(),
+                #[cfg(feature = "import")]
+                dedup: Dedup::Off,
+                #[cfg(feature = "import")]
+                copy_through: CopyThrough::SameFormat,
+                #[cfg(feature = "import")]
+                providers: HashMap::new(),
+                #[cfg(feature = "import")]
+                dirty: HashSet::new(),
+                #[cfg(feature = "import")]
+                check_determinism: false,
+                #[cfg(feature = "import")]
+                import_cache: None,
+                #[cfg(feature = "import")]
+                nondeterministic: Vec::new(),
+                #[cfg(feature = "import")]
+                maintain_cursor: 0,
+                #[cfg(feature = "import")]
+                reimport: None,
+                #[cfg(feature = "import")]
+                auto_store: Vec::new(),
+                #[cfg(feature = "import")]
+                scrubber: None,
+                #[cfg(feature = "import")]
+                corrupted: HashSet::new(),
+                #[cfg(feature = "import")]
+                validators: Vec::new(),
+                identity: default_identity(),
+                clock: Arc::new(SystemClock),
+                native_key: None,
+                subscribers: Vec::new(),
                 root: root.into(),
                 data: Data {
-                    assets: Vec::new(),
+                    assets: AssetRecords::default(),
+                    version: MANIFEST_VERSION,
                     importers_dirs: Vec::new(),
+                    collections: BTreeMap::new(),
+                    namespace: None,
+                    checksums: false,
+                    indexed: false,
+                    size_policies: Vec::new(),
                 },
             })),
         };
@@ -206,20 +685,81 @@ impl Treasury {
         let treasury_path = root.join(".treasury");
         let manifest_path = treasury_path.join("manifest.json");
 
-        let file =
-            std::fs::File::open(&manifest_path).map_err(|source| OpenError::GoodsOpenError {
+        let manifest =
+            std::fs::read(&manifest_path).map_err(|source| OpenError::GoodsOpenError {
                 source,
                 path: manifest_path.clone().into(),
             })?;
 
-        let data: Data = serde_json::from_reader(file).map_err(|source| OpenError::JsonError {
-            source,
-            path: manifest_path.clone().into(),
-        })?;
+        let mut data: Data =
+            serde_json::from_slice(&manifest).map_err(|source| OpenError::JsonError {
+                source,
+                path: manifest_path.clone().into(),
+            })?;
+
+        if data.indexed {
+            let records_path = treasury_path.join("assets.db");
+            data.assets =
+                AssetRecords::open(&records_path).map_err(|source| OpenError::RecordsError {
+                    source,
+                    path: records_path.into(),
+                })?;
+        }
+
+        let migrate = data.version < MANIFEST_VERSION;
+        if migrate {
+            tracing::info!(
+                "Migrating manifest '{}' from version {} to {}",
+                manifest_path.display(),
+                data.version,
+                MANIFEST_VERSION
+            );
+            data.assets.load_all();
+            for asset in data.assets.iter_mut() {
+                asset.normalize();
+            }
+            data.version = MANIFEST_VERSION;
+        }
 
         let registry = Arc::new(Mutex::new(Registry {
             #[cfg(feature = "import")]
             importers: Importers::new(&root),
+            #[cfg(feature = "import")]
+            states: HashMap::new(),
+            #[cfg(feature = "import")]
+            storing: HashMap::new(),
+            #[cfg(feature = "import")]
+            waiting: HashMap::new(),
+            #[cfg(feature = "import")]
+            dedup: Dedup::Off,
+            #[cfg(feature = "import")]
+            copy_through: CopyThrough::SameFormat,
+            #[cfg(feature = "import")]
+            providers: HashMap::new(),
+            #[cfg(feature = "import")]
+            dirty: HashSet::new(),
+            #[cfg(feature = "import")]
+            check_determinism: false,
+            #[cfg(feature = "import")]
+            import_cache: None,
+            #[cfg(feature = "import")]
+            nondeterministic: Vec::new(),
+            #[cfg(feature = "import")]
+            maintain_cursor: 0,
+            #[cfg(feature = "import")]
+            reimport: None,
+            #[cfg(feature = "import")]
+            auto_store: Vec::new(),
+            #[cfg(feature = "import")]
+            scrubber: None,
+            #[cfg(feature = "import")]
+            corrupted: HashSet::new(),
+            #[cfg(feature = "import")]
+            validators: Vec::new(),
+            identity: default_identity(),
+            clock: Arc::new(SystemClock),
+            native_key: None,
+            subscribers: Vec::new(),
             data,
             root: root.into(),
         }));
@@ -248,6 +788,14 @@ impl Treasury {
             drop(lock);
         }
 
+        // Normalized manifest is written right away,
+        // so that normalization is not mixed with following changes.
+        if migrate {
+            if let Err(err) = Registry::save(&registry) {
+                tracing::warn!("Failed to save migrated manifest. {:#}", err);
+            }
+        }
+
         Ok(Treasury { registry })
     }
 
@@ -299,74 +847,679 @@ impl Treasury {
     pub fn store(
         &self,
         source: impl AsRef<Path>,
-        source_format: &str,
-        native_format: &str,
+        source_format: impl Into<FormatId>,
+        native_format: impl Into<FormatId>,
         tags: &[impl AsRef<str>],
     ) -> Result<Uuid, StoreError> {
         Registry::store(
             &self.registry,
             source.as_ref(),
-            source_format,
-            native_format,
+            &source_format.into(),
+            &native_format.into(),
+            &serde_json::Value::Null,
             tags,
+            &mut (),
+            &ImportControl::default(),
         )
     }
 
-    /// Fetches asset in native format.
-    /// Performs conversion if native format is absent or out of date.
-    #[tracing::instrument(skip(self))]
-    pub fn fetch(&mut self, uuid: &Uuid) -> Result<AssetData, FetchError> {
-        match Registry::fetch(&self.registry, uuid, 0)? {
-            None => unreachable!(),
-            Some(mut info) => {
-                let mut bytes = Vec::new();
-                info.native_file.read_to_end(&mut bytes).map_err(|source| {
-                    FetchError::NativeIoError {
-                        source,
-                        path: info.native_path.to_path_buf().into(),
-                    }
-                })?;
+    /// Import asset into goods instance.
+    /// Importer reports progress to provided receiver.
+    #[cfg(feature = "import")]
+    pub fn store_with_progress(
+        &self,
+        source: impl AsRef<Path>,
+        source_format: impl Into<FormatId>,
+        native_format: impl Into<FormatId>,
+        tags: &[impl AsRef<str>],
+        mut progress: impl ImportProgress,
+    ) -> Result<Uuid, StoreError> {
+        Registry::store(
+            &self.registry,
+            source.as_ref(),
+            &source_format.into(),
+            &native_format.into(),
+            &serde_json::Value::Null,
+            tags,
+            &mut progress,
+            &ImportControl::default(),
+        )
+    }
 
-                Ok(AssetData {
-                    bytes: bytes.into_boxed_slice(),
-                    version: info.version,
-                })
-            }
-        }
+    /// Import asset into goods instance with specified import settings.
+    /// Importer reports progress to provided receiver.
+    ///
+    /// Settings are part of asset identity,
+    /// same source imported with different settings becomes different assets.
+    #[cfg(feature = "import")]
+    pub fn store_with_settings(
+        &self,
+        source: impl AsRef<Path>,
+        source_format: impl Into<FormatId>,
+        native_format: impl Into<FormatId>,
+        settings: &serde_json::Value,
+        tags: &[impl AsRef<str>],
+        mut progress: impl ImportProgress,
+    ) -> Result<Uuid, StoreError> {
+        Registry::store(
+            &self.registry,
+            source.as_ref(),
+            &source_format.into(),
+            &native_format.into(),
+            settings,
+            tags,
+            &mut progress,
+            &ImportControl::default(),
+        )
     }
 
-    /// Fetches asset in native format.
-    /// Returns `Ok(None)` if native file is up-to-date.
-    /// Performs conversion if native format is absent or out of date.
-    #[tracing::instrument(skip(self))]
-    pub fn fetch_updated(
-        &mut self,
-        uuid: &Uuid,
-        version: u64,
-    ) -> Result<Option<AssetData>, FetchError> {
-        match Registry::fetch(&self.registry, uuid, version + 1)? {
-            None => Ok(None),
-            Some(mut info) => {
-                let mut bytes = Vec::new();
-                info.native_file.read_to_end(&mut bytes).map_err(|source| {
-                    FetchError::NativeIoError {
-                        source,
-                        path: info.native_path.to_path_buf().into(),
+    /// Import asset into goods instance with specified import settings,
+    /// unless cancelled with provided token.
+    /// Importer reports progress to provided receiver.
+    ///
+    /// Cancelled import fails with [`StoreError::ImportCancelled`],
+    /// leaving no native files and removing assets registered by its sub-imports.
+    #[cfg(feature = "import")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn store_cancellable(
+        &self,
+        source: impl AsRef<Path>,
+        source_format: impl Into<FormatId>,
+        native_format: impl Into<FormatId>,
+        settings: &serde_json::Value,
+        tags: &[impl AsRef<str>],
+        mut progress: impl ImportProgress,
+        cancel: &CancelToken,
+    ) -> Result<Uuid, StoreError> {
+        Registry::store(
+            &self.registry,
+            source.as_ref(),
+            &source_format.into(),
+            &native_format.into(),
+            settings,
+            tags,
+            &mut progress,
+            &ImportControl {
+                cancel: Some(cancel.clone()),
+                ..ImportControl::default()
+            },
+        )
+    }
+
+    /// Imports asset from bytes without source file,
+    /// e.g. procedurally generated or created in editor.
+    ///
+    /// Bytes are persisted as managed source file `.treasury/sources/<virtual_name>`
+    /// and imported as any other source.
+    /// Storing bytes with the same virtual name again
+    /// replaces the source, and the asset is reimported when fetched.
+    #[cfg(feature = "import")]
+    pub fn store_bytes(
+        &self,
+        bytes: &[u8],
+        source_format: impl Into<FormatId>,
+        native_format: impl Into<FormatId>,
+        tags: &[impl AsRef<str>],
+        virtual_name: &str,
+    ) -> Result<Uuid, StoreError> {
+        let relative = Path::new(virtual_name);
+        let valid = !virtual_name.is_empty()
+            && relative
+                .components()
+                .all(|c| matches!(c, std::path::Component::Normal(_)));
+
+        if !valid {
+            return Err(StoreError::InvalidVirtualName {
+                name: virtual_name.into(),
+            });
+        }
+
+        let root = self.registry.lock().root.clone();
+        let path = root.join(".treasury").join("sources").join(relative);
+        let io_error = |source| StoreError::SourceIoError {
+            path: path.clone().into(),
+            source,
+        };
+
+        // Unchanged source is not rewritten to not make the asset outdated.
+        if std::fs::read(&path).ok().as_deref() != Some(bytes) {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(io_error)?;
+            }
+            std::fs::write(&path, bytes).map_err(io_error)?;
+        }
+
+        self.store(&path, source_format, native_format, tags)
+    }
+
+    /// Adds importer that awaits external tools or services,
+    /// replacing importer with same source and native formats.
+    ///
+    /// Async importers run on runtime owned by the treasury,
+    /// which is started when first async importer is added.
+    /// Registry is not locked while async importer runs.
+    #[cfg(feature = "import")]
+    pub fn add_async_importer(&mut self, importer: impl AsyncImporter) -> std::io::Result<()> {
+        let registry_clone = self.registry.clone();
+        self.registry
+            .lock()
+            .importers
+            .add_user_async_importer(Arc::new(importer), &registry_clone)
+    }
+
+    /// Marks assets with sources or dependencies changed in git work tree since `commit`
+    /// as outdated, so they are reimported on next fetch.
+    /// Only files reported by git are considered, sources are not stat-ed.
+    ///
+    /// Returns marked assets.
+    #[cfg(feature = "git")]
+    pub fn mark_changed_since(&self, commit: &str) -> Result<Vec<Uuid>, GitError> {
+        let mut lock = self.registry.lock();
+        let changed: HashSet<_> = changed_since(&lock.root, commit)?.into_iter().collect();
+        lock.data.assets.load_all();
+
+        let marked: Vec<_> = lock
+            .data
+            .assets
+            .iter()
+            .filter(|asset| {
+                changed.contains(Path::new(asset.source()))
+                    || asset
+                        .dependencies()
+                        .iter()
+                        .any(|dependency| changed.contains(&**dependency))
+            })
+            .map(Asset::uuid)
+            .collect();
+
+        lock.dirty.extend(marked.iter().copied());
+        Ok(marked)
+    }
+
+    /// Adds rule for [`Treasury::store_unimported`]:
+    /// sources with the extension are stored from source format into native format.
+    #[cfg(feature = "import")]
+    pub fn add_auto_store(
+        &mut self,
+        extension: &str,
+        source_format: impl Into<FormatId>,
+        native_format: impl Into<FormatId>,
+    ) {
+        self.registry.lock().auto_store.push((
+            extension.into(),
+            source_format.into(),
+            native_format.into(),
+        ));
+    }
+
+    /// Returns uuid the asset stored from the source gets,
+    /// unless it is taken by another asset.
+    /// Returns `None` if treasury has no namespace and uuids are random.
+    ///
+    /// Allows tools to reference assets before they are imported,
+    /// see [`Treasury::store_unimported`].
+    #[cfg(feature = "import")]
+    pub fn source_uuid(
+        &self,
+        source: impl AsRef<Path>,
+        source_format: impl Into<FormatId>,
+        native_format: impl Into<FormatId>,
+    ) -> Option<Uuid> {
+        let lock = self.registry.lock();
+        let namespace = lock.data.namespace?;
+
+        let source = source.as_ref();
+        let source_absolute = match source.is_absolute() {
+            true => source.to_path_buf(),
+            false => std::env::current_dir().ok()?.join(source),
+        };
+        let source_uri = relative_to(&source_absolute, &lock.root);
+
+        Some(source_uuid(
+            &namespace,
+            &source_uri.to_string_lossy(),
+            &source_format.into(),
+            &native_format.into(),
+            0,
+        ))
+    }
+
+    /// Stores source that is not imported yet if asset stored from it gets specified uuid,
+    /// so that assets referenced before being imported are loaded in development.
+    ///
+    /// Sources are searched in treasury root, skipping hidden directories,
+    /// by extensions added with [`Treasury::add_auto_store`].
+    /// Works only for treasuries with namespace, see [`Treasury::source_uuid`].
+    ///
+    /// Returns `false` if no such source is found.
+    #[cfg(feature = "import")]
+    pub fn store_unimported(&self, uuid: Uuid) -> Result<bool, StoreError> {
+        let (root, namespace, rules) = {
+            let lock = self.registry.lock();
+            if lock.data.assets.contains(uuid) {
+                return Ok(true);
+            }
+            match lock.data.namespace {
+                Some(namespace) if !lock.auto_store.is_empty() => {
+                    (lock.root.clone(), namespace, lock.auto_store.clone())
+                }
+                _ => return Ok(false),
+            }
+        };
+
+        let mut files = Vec::new();
+        find_sources(&root, &mut files);
+
+        for file in files {
+            let extension = match file.extension().and_then(|e| e.to_str()) {
+                None => continue,
+                Some(extension) => extension,
+            };
+
+            let source_uri = relative_to(&file, &root);
+            let source_uri = source_uri.to_string_lossy();
+            for (_, source_format, native_format) in
+                rules.iter().filter(|(e, _, _)| **e == *extension)
+            {
+                let candidate =
+                    source_uuid(&namespace, &source_uri, source_format, native_format, 0);
+                if candidate == uuid {
+                    tracing::info!("Storing unimported source '{}'", source_uri);
+                    self.store(&file, *source_format, *native_format, &[] as &[&str])?;
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Marks the asset as outdated, so it is reimported on next fetch
+    /// even if its source did not change.
+    #[cfg(feature = "import")]
+    pub fn mark_outdated(&self, uuid: Uuid) -> Result<(), FetchError> {
+        let mut lock = self.registry.lock();
+        if !lock.data.assets.contains(uuid) {
+            return Err(FetchError::NotFound);
+        }
+        lock.dirty.insert(uuid);
+        Ok(())
+    }
+
+    /// Marks assets of `native_format` with native files of schema older than `schema`
+    /// as outdated, so they are reimported on next fetch.
+    /// Intended to be called after importer starts writing new schema,
+    /// to reimport only assets the loader would have to migrate.
+    ///
+    /// Returns marked assets.
+    #[cfg(feature = "import")]
+    pub fn mark_schema_outdated(&self, native_format: &str, schema: u32) -> Vec<Uuid> {
+        let mut lock = self.registry.lock();
+        lock.data.assets.load_all();
+
+        let marked: Vec<_> = lock
+            .data
+            .assets
+            .iter()
+            .filter(|asset| asset.native_format() == native_format && asset.schema() < schema)
+            .map(Asset::uuid)
+            .collect();
+
+        lock.dirty.extend(marked.iter().copied());
+        marked
+    }
+
+    /// Reimports assets matching `filter` into `native_format`, keeping their uuids,
+    /// e.g. when switching texture compression scheme project-wide.
+    ///
+    /// Assets without importer from their source format into `native_format`
+    /// and assets that fail to reimport keep their native format.
+    ///
+    /// Returns converted assets.
+    #[cfg(feature = "import")]
+    pub fn convert(
+        &self,
+        mut filter: impl FnMut(&Asset) -> bool,
+        native_format: impl Into<FormatId>,
+    ) -> Vec<Uuid> {
+        let native_format = native_format.into().as_str();
+
+        let matching: Vec<_> = {
+            let mut lock = self.registry.lock();
+            lock.data.assets.load_all();
+            lock.data
+                .assets
+                .iter()
+                .filter(|asset| asset.native_format() != native_format && filter(asset))
+                .filter(|asset| {
+                    let convertible = lock.copies_through(asset.source_format(), native_format)
+                        || lock
+                            .importers
+                            .get_importer(asset.source_format(), native_format)
+                            .is_some();
+                    if !convertible {
+                        tracing::warn!(
+                            "Importer from '{}' to '{}' not found, asset '{}' cannot be converted",
+                            asset.source_format(),
+                            native_format,
+                            asset.uuid(),
+                        );
                     }
-                })?;
+                    convertible
+                })
+                .map(Asset::uuid)
+                .collect()
+        };
+
+        let mut converted = Vec::new();
+        for uuid in matching {
+            let mut lock = self.registry.lock();
+            let asset = match lock.data.assets.get_mut(uuid) {
+                None => continue,
+                Some(asset) => asset,
+            };
+            let old_format: Box<str> = asset.native_format().into();
+            let old_version = asset.version();
+            asset.set_native_format(native_format.into());
+            lock.dirty.insert(uuid);
+            drop(lock);
+
+            // Reimport is forced here so that native file never lags behind the record.
+            let fetched = Registry::fetch(&self.registry, &uuid, None, None);
+
+            let mut lock = self.registry.lock();
+            if let Some(asset) = lock.data.assets.get_mut(uuid) {
+                if fetched.is_ok() && asset.version() != old_version {
+                    converted.push(uuid);
+                } else {
+                    tracing::warn!(
+                        "Failed to reimport asset '{}' into '{}'",
+                        uuid,
+                        native_format
+                    );
+                    asset.set_native_format(old_format);
+                }
+            }
+            lock.dirty.remove(&uuid);
+        }
+
+        converted
+    }
+
+    /// Marks assets changed in git work tree since `commit` as outdated,
+    /// like [`Treasury::mark_changed_since`], and queues their reimport on worker threads.
+    /// Intended to be called after pull, with commit checked out before it.
+    ///
+    /// Returns queued assets.
+    #[cfg(feature = "git")]
+    pub fn reimport_changed_since(&self, commit: &str) -> Result<Vec<Uuid>, GitError> {
+        let mut marked = self.mark_changed_since(commit)?;
+
+        let registry = Arc::downgrade(&self.registry);
+        let mut lock = self.registry.lock();
+        let queue = lock
+            .reimport
+            .get_or_insert_with(|| ReimportQueue::new(registry));
+
+        marked.retain(|uuid| queue.push(*uuid));
+        Ok(marked)
+    }
+
+    /// Adds provider of sources outside local filesystem,
+    /// replacing provider with same URI scheme.
+    /// Sources of the provider are stored with `<scheme>://<path>` URIs.
+    #[cfg(feature = "import")]
+    pub fn add_source_provider(&mut self, provider: impl SourceProvider) {
+        self.registry
+            .lock()
+            .providers
+            .insert(provider.scheme().into(), Arc::new(provider));
+    }
+
+    /// Sets cache of import results shared with other machines.
+    /// Importers are not run for sources imported with same importer and settings before,
+    /// and results of local imports are uploaded to the cache.
+    #[cfg(feature = "import")]
+    pub fn set_import_cache(&mut self, cache: impl ImportCache) {
+        self.registry.lock().import_cache = Some(Arc::new(cache));
+    }
+
+    /// Adds check run on every stored and reimported asset.
+    /// Validators run in order they were added.
+    #[cfg(feature = "import")]
+    pub fn add_validator(&mut self, validator: impl StoreValidator) {
+        self.registry.lock().validators.push(Arc::new(validator));
+    }
+
+    /// Enables validation mode where each import is performed twice
+    /// and outputs are compared.
+    /// Imports with differing outputs are reported by [`Treasury::determinism_report`].
+    ///
+    /// Deterministic importers are required for content-addressed deduplication
+    /// and distributed caching of native files.
+    #[cfg(feature = "import")]
+    pub fn set_check_determinism(&mut self, check: bool) {
+        self.registry.lock().check_determinism = check;
+    }
+
+    /// Returns imports found non-deterministic since determinism check was enabled.
+    /// Asset is listed until it is imported deterministically.
+    #[cfg(feature = "import")]
+    pub fn determinism_report(&self) -> Vec<NondeterministicImport> {
+        self.registry.lock().nondeterministic.clone()
+    }
+
+    /// Sets handling of stored sources with content identical
+    /// to the source of already registered asset.
+    /// Defaults to [`Dedup::Off`].
+    #[cfg(feature = "import")]
+    pub fn set_dedup(&mut self, dedup: Dedup) {
+        self.registry.lock().dedup = dedup;
+    }
+
+    /// Sets whether sources are copied as native files instead of imported.
+    /// Applies to stores and reimports.
+    /// Defaults to [`CopyThrough::SameFormat`].
+    #[cfg(feature = "import")]
+    pub fn set_copy_through(&mut self, copy_through: CopyThrough) {
+        self.registry.lock().copy_through = copy_through;
+    }
+
+    /// Checks up to `budget` assets for out-of-date native files
+    /// and queues reimport of them on worker threads.
+    /// Following calls continue from where previous one stopped,
+    /// wrapping around the list of assets.
+    ///
+    /// Allows keeping the whole treasury fresh incrementally,
+    /// e.g. by calling this every frame of an editor,
+    /// instead of reimporting on first fetch.
+    /// Returns number of assets queued.
+    #[cfg(feature = "import")]
+    #[tracing::instrument(skip(self))]
+    pub fn maintain(&self, budget: usize) -> usize {
+        let mut lock = self.registry.lock();
+        lock.data.assets.load_all();
+
+        let len = lock.data.assets.len();
+        let mut outdated = Vec::new();
+        for _ in 0..budget.min(len) {
+            let index = lock.maintain_cursor % len;
+            lock.maintain_cursor = index + 1;
+
+            let asset = &lock.data.assets[index];
+            if lock.is_outdated(asset) {
+                outdated.push(asset.uuid());
+            }
+        }
+
+        if outdated.is_empty() {
+            return 0;
+        }
+
+        let registry = Arc::downgrade(&self.registry);
+        let queue = lock
+            .reimport
+            .get_or_insert_with(|| ReimportQueue::new(registry));
+
+        outdated.retain(|uuid| queue.push(*uuid));
+        outdated.len()
+    }
+
+    /// Returns source and native formats of registered importers.
+    #[cfg(feature = "import")]
+    pub fn formats(&self) -> Vec<(FormatId, FormatId)> {
+        self.registry.lock().importers.formats()
+    }
+
+    /// Returns importers the treasury imports with, one per source and native formats pair,
+    /// along with paths they are loaded from.
+    #[cfg(feature = "import")]
+    pub fn importers(&self) -> Vec<ImporterInfo> {
+        self.registry.lock().importers.registered()
+    }
+
+    /// Returns importers modules found by the treasury with their metadata,
+    /// including modules that failed to load and why,
+    /// e.g. to display importers inventory and diagnose missing importers before importing.
+    #[cfg(feature = "import")]
+    pub fn describe_importers(&self) -> Vec<ImporterModule> {
+        self.registry.lock().importers.describe()
+    }
+
+    /// Returns identifier of the format with specified name
+    /// if any registered importer uses it as source or native format.
+    /// Allows validating format names from user input before storing assets.
+    #[cfg(feature = "import")]
+    pub fn format(&self, name: &str) -> Option<FormatId> {
+        self.formats()
+            .into_iter()
+            .flat_map(|(source, native)| [source, native])
+            .find(|format| **format == *name)
+    }
+
+    /// Returns scheduler to import many sources in parallel.
+    #[cfg(feature = "import")]
+    pub fn scheduler(&self) -> ImportScheduler {
+        ImportScheduler::new(self.registry.clone())
+    }
+
+    /// Fetches asset in native format.
+    /// Performs conversion if native format is absent or out of date.
+    ///
+    /// If `locale` is specified, native file for the most specific of
+    /// [`locale_fallbacks`] the asset has is returned,
+    /// otherwise native file without locale.
+    #[tracing::instrument(skip(self))]
+    pub fn fetch(&mut self, uuid: &Uuid, locale: Option<&str>) -> Result<AssetData, FetchError> {
+        match Registry::fetch(&self.registry, uuid, locale, None)? {
+            None => unreachable!(),
+            Some(mut info) => {
+                let bytes = info
+                    .read_native()
+                    .map_err(|source| FetchError::NativeIoError {
+                        name: info.name.clone(),
+                        source,
+                        path: info.native_path.to_path_buf().into(),
+                    })?;
+
+                Ok(AssetData {
+                    bytes: bytes.into_boxed_slice(),
+                    name: info.name,
+                    version: info.version,
+                    native_format: info.native_format,
+                    schema: info.schema,
+                    lifecycle: info.lifecycle,
+                })
+            }
+        }
+    }
+
+    /// Fetches asset in native format.
+    /// Returns `Ok(None)` if native file still has `version`
+    /// previously returned by [`Treasury::fetch`] or [`Treasury::fetch_updated`].
+    /// Performs conversion if native format is absent or out of date.
+    ///
+    /// Locale is resolved as in [`Treasury::fetch`].
+    #[tracing::instrument(skip(self))]
+    pub fn fetch_updated(
+        &mut self,
+        uuid: &Uuid,
+        locale: Option<&str>,
+        version: u64,
+    ) -> Result<Option<AssetData>, FetchError> {
+        match Registry::fetch(&self.registry, uuid, locale, Some(version))? {
+            None => Ok(None),
+            Some(mut info) => {
+                let bytes = info
+                    .read_native()
+                    .map_err(|source| FetchError::NativeIoError {
+                        name: info.name.clone(),
+                        source,
+                        path: info.native_path.to_path_buf().into(),
+                    })?;
 
                 Ok(Some(AssetData {
                     bytes: bytes.into_boxed_slice(),
+                    name: info.name,
                     version: info.version,
+                    native_format: info.native_format,
+                    schema: info.schema,
+                    lifecycle: info.lifecycle,
                 }))
             }
         }
     }
 
-    /// Returns assets information.
+    /// Returns metadata of the asset without opening its native file
+    /// or reimporting it when outdated.
+    pub fn stat(&self, uuid: Uuid) -> Result<AssetStat, FetchError> {
+        let mut lock = self.registry.lock();
+
+        let asset = match lock.data.assets.position(uuid) {
+            None => return Err(FetchError::NotFound),
+            Some(index) => &lock.data.assets[index],
+        };
+
+        let native = lock
+            .root
+            .join(".treasury")
+            .join(uuid.to_hyphenated().to_string());
+        let metadata = std::fs::metadata(native).ok();
+
+        #[cfg(feature = "import")]
+        let up_to_date = !lock.is_outdated(asset);
+
+        #[cfg(not(feature = "import"))]
+        let up_to_date = true;
+
+        Ok(AssetStat {
+            uuid,
+            source_format: asset.source_format().into(),
+            native_format: asset.native_format().into(),
+            tags: asset.tags().into(),
+            version: asset.version(),
+            schema: asset.schema(),
+            native_size: metadata.as_ref().map(|m| m.len()),
+            imported: metadata.and_then(|m| imported_at(asset, &m)),
+            up_to_date,
+            stored_by: asset.stored_by().map(Into::into),
+            reimported_by: asset.reimported_by().map(Into::into),
+            stored_at: asset.stored_at(),
+            reimported_at: asset.reimported_at(),
+        })
+    }
+
+    /// Returns `true` if the asset is registered in the treasury.
+    pub(crate) fn contains(&self, uuid: Uuid) -> bool {
+        let lock = self.registry.lock();
+        lock.data.assets.contains(uuid)
+    }
+
+    /// Returns assets information sorted by uuid.
     #[tracing::instrument(skip(self, tags))]
     pub fn list(&self, tags: &[impl AsRef<str>], native_format: Option<&str>) -> Vec<Asset> {
-        let lock = self.registry.lock();
+        let mut lock = self.registry.lock();
+        lock.data.assets.load_all();
 
         lock.data
             .assets
@@ -387,265 +1540,2292 @@ impl Treasury {
             .collect()
     }
 
-    /// Returns assets information.
-    #[tracing::instrument(skip(self))]
-    pub fn remove<'a>(&self, uuid: Uuid) {
+    /// Returns receiver of events about changes of the treasury.
+    /// Allows tools to stay in sync without polling [`Treasury::list`].
+    ///
+    /// Dropped receivers are unsubscribed on next event.
+    pub fn events(&self) -> Receiver<TreasuryEvent> {
+        let (sender, receiver) = channel();
+        self.registry.lock().subscribers.push(sender);
+        receiver
+    }
+
+    /// Replaces tags of the asset.
+    #[tracing::instrument(skip(self, tags))]
+    pub fn set_tags(&self, uuid: Uuid, tags: &[impl AsRef<str>]) -> Result<(), FetchError> {
         let mut lock = self.registry.lock();
 
-        if let Some(index) = lock.data.assets.iter().position(|a| a.uuid() == uuid) {
-            let native = Path::new(".treasury").join(uuid.to_hyphenated().to_string());
-            let native_absolute = lock.root.join(native);
-            if let Err(err) = std::fs::remove_file(&native_absolute) {
-                tracing::error!(
-                    "Failed to remove native asset file '{}': {}",
-                    native_absolute.display(),
-                    err
-                );
+        match lock.data.assets.get_mut(uuid) {
+            None => Err(FetchError::NotFound),
+            Some(asset) => {
+                asset.set_tags(tags.iter().map(|tag| tag.as_ref().into()).collect());
+                lock.emit(TreasuryEvent::TagsChanged { uuid });
+                Ok(())
             }
-            lock.data.assets.remove(index);
         }
     }
-}
 
-pub(crate) struct FetchInfo {
-    pub native_path: Box<Path>,
-    pub native_file: std::fs::File,
-    pub version: u64,
-}
+    /// Pins the asset, marking it as always resident.
+    /// Pinned assets are never cleaned up
+    /// and should be pinned in the loader with `goods::Loader::pin`.
+    /// Flag is persisted in the manifest.
+    #[tracing::instrument(skip(self))]
+    pub fn pin(&self, uuid: Uuid) -> Result<(), FetchError> {
+        self.set_pinned(uuid, true)
+    }
 
-impl Registry {
-    fn save(me: &Mutex<Self>) -> Result<(), SaveError> {
-        let lock = me.lock();
-        let treasury_path = lock.root.join(".treasury").join("manifest.json");
-        let file =
-            std::fs::File::create(&treasury_path).map_err(|source| SaveError::GoodsOpenError {
-                source,
-                path: treasury_path.clone().into(),
-            })?;
-        serde_json::to_writer_pretty(file, &lock.data).map_err(|source| SaveError::JsonError {
-            source,
-            path: treasury_path.into(),
-        })
+    /// Unpins the asset.
+    #[tracing::instrument(skip(self))]
+    pub fn unpin(&self, uuid: Uuid) -> Result<(), FetchError> {
+        self.set_pinned(uuid, false)
+    }
+
+    /// Returns UUIDs of pinned assets.
+    pub fn pinned(&self) -> Vec<Uuid> {
+        let mut lock = self.registry.lock();
+        lock.data.assets.load_all();
+        lock.data
+            .assets
+            .iter()
+            .filter(|a| a.is_pinned())
+            .map(|a| a.uuid())
+            .collect()
+    }
+
+    /// Returns uuids from `uuids` that the treasury has no asset for.
+    /// Intended to check uuids reported missing by `goods::Loader::missing_assets`
+    /// to find references dangling after assets were removed.
+    ///
+    /// Assets found corrupted by the scrubber are reported too,
+    /// see [`Treasury::set_scrubber`].
+    pub fn verify(&self, uuids: &[Uuid]) -> Vec<Uuid> {
+        let lock = self.registry.lock();
+        uuids
+            .iter()
+            .filter(|uuid| {
+                #[cfg(feature = "import")]
+                if lock.corrupted.contains(*uuid) {
+                    return true;
+                }
+                !lock.data.assets.contains(**uuid)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Starts background thread verifying native files against hashes
+    /// in `.treasury/checksums` file, reading at most `bytes_per_second`
+    /// so that foreground work is not slowed down.
+    /// `None` stops it.
+    ///
+    /// Native files corrupted by bit-rot or partial writes are reported by [`Treasury::corrupted`]
+    /// and [`Treasury::verify`], and [`TreasuryEvent::Corrupted`] is emitted.
+    /// Native files rewritten after checksums file was saved are skipped.
+    /// Requires checksums, see [`Treasury::set_checksums`].
+    #[cfg(feature = "import")]
+    pub fn set_scrubber(&self, bytes_per_second: Option<u64>) {
+        let registry = Arc::downgrade(&self.registry);
+        let mut lock = self.registry.lock();
+        lock.scrubber = bytes_per_second.map(|bytes_per_second| {
+            Scrubber::new(registry, lock.root.to_path_buf(), bytes_per_second)
+        });
+    }
+
+    /// Returns assets with native files found corrupted by the scrubber.
+    /// Reimport fixes them.
+    #[cfg(feature = "import")]
+    pub fn corrupted(&self) -> Vec<Uuid> {
+        self.registry.lock().corrupted.iter().copied().collect()
+    }
+
+    /// Returns namespace of the project, if set.
+    pub fn namespace(&self) -> Option<Uuid> {
+        self.registry.lock().data.namespace
+    }
+
+    /// Sets namespace of the project.
+    /// Uuids of assets stored afterwards are derived from the namespace
+    /// and the source path, and packs record the namespace,
+    /// so that assets of different projects can't collide
+    /// and loaders can reject assets of other projects.
+    ///
+    /// Uuids of already stored assets are not changed.
+    pub fn set_namespace(&self, namespace: Option<Uuid>) {
+        self.registry.lock().data.namespace = namespace;
+    }
+
+    /// Returns identity of the user stored and reimported assets are attributed to.
+    pub fn identity(&self) -> Box<str> {
+        self.registry.lock().identity.clone()
+    }
+
+    /// Sets identity of the user stored and reimported assets are attributed to.
+    /// Defaults to `TREASURY_USER`, `USER` or `USERNAME` environment variable.
+    pub fn set_identity(&self, identity: &str) {
+        self.registry.lock().identity = identity.into();
+    }
+
+    /// Sets clock providing time recorded for stored and reimported assets.
+    /// Defaults to [`SystemClock`].
+    /// Tests may set [`ManualClock`] to control the time.
+    ///
+    /// [`ManualClock`]: crate::ManualClock
+    pub fn set_clock(&self, clock: impl Clock + 'static) {
+        self.registry.lock().clock = Arc::new(clock);
+    }
+
+    /// Sets key to encrypt native files in treasury directory with, or disables encryption.
+    /// Key is not stored in the treasury and must be set each time it is opened.
+    ///
+    /// Native files written afterwards are encrypted, and encrypted native files
+    /// are decrypted when fetched, packed or exported.
+    /// Native files written before are left as is and read without decryption.
+    /// Importers cannot fetch encrypted native files of other assets.
+    pub fn set_native_key(&self, key: Option<PackKey>) {
+        self.registry.lock().native_key = key;
+    }
+
+    /// Returns size policies checked on store and reimport.
+    pub fn size_policies(&self) -> Vec<SizePolicy> {
+        self.registry.lock().data.size_policies.clone()
+    }
+
+    /// Adds policy limiting size of native files.
+    /// Stores violating rejecting policies fail with [`StoreError::PolicyViolation`],
+    /// and reimports violating them keep old native files.
+    ///
+    /// Policies are persisted in the manifest.
+    #[cfg(feature = "import")]
+    pub fn add_size_policy(&self, policy: SizePolicy) {
+        let mut lock = self.registry.lock();
+        if !lock.data.size_policies.contains(&policy) {
+            lock.data.size_policies.push(policy);
+        }
+    }
+
+    /// Removes all size policies.
+    #[cfg(feature = "import")]
+    pub fn clear_size_policies(&self) {
+        self.registry.lock().data.size_policies.clear();
+    }
+
+    /// Enables writing `.treasury/checksums` file on save.
+    /// File has a line `<uuid> <source hash> <native hash> <native size>` per asset,
+    /// with `-` for unknown hash, so that external sync and build tools
+    /// can detect stale assets without parsing the manifest.
+    /// Hashes are hex-encoded SHA-256.
+    ///
+    /// Native files not modified since the file was last written are not hashed again.
+    /// Setting is persisted in the manifest.
+    #[cfg(feature = "import")]
+    pub fn set_checksums(&self, enabled: bool) {
+        self.registry.lock().data.checksums = enabled;
+    }
+
+    /// Keeps asset records in `.treasury/assets.db` file instead of the manifest.
+    ///
+    /// Only index of the records is read when treasury is opened,
+    /// and records are parsed when assets are accessed,
+    /// so opening huge treasuries doesn't parse all records.
+    /// Small projects are better off with default JSON manifest.
+    /// Setting is persisted in the manifest.
+    pub fn set_indexed_manifest(&self, enabled: bool) {
+        self.registry.lock().data.indexed = enabled;
+    }
+
+    fn set_pinned(&self, uuid: Uuid, pinned: bool) -> Result<(), FetchError> {
+        let mut lock = self.registry.lock();
+
+        match lock.data.assets.get_mut(uuid) {
+            None => Err(FetchError::NotFound),
+            Some(asset) => {
+                asset.set_pinned(pinned);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns information of assets with any of specified visibilities,
+    /// filtered as in [`Treasury::list`].
+    #[tracing::instrument(skip(self, tags))]
+    pub fn list_visible(
+        &self,
+        tags: &[impl AsRef<str>],
+        native_format: Option<&str>,
+        visibilities: &[Visibility],
+    ) -> Vec<Asset> {
+        let mut assets = self.list(tags, native_format);
+        assets.retain(|a| visibilities.contains(&a.visibility()));
+        assets
+    }
+
+    /// Sets human-readable name of the asset used in errors and logs.
+    /// `None` resets it to the source file stem.
+    #[tracing::instrument(skip(self))]
+    pub fn set_name(&self, uuid: Uuid, name: Option<&str>) -> Result<(), FetchError> {
+        let mut lock = self.registry.lock();
+
+        match lock.data.assets.get_mut(uuid) {
+            None => Err(FetchError::NotFound),
+            Some(asset) => {
+                asset.set_name(name.map(Into::into));
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns information of assets in any of specified lifecycle states,
+    /// filtered as in [`Treasury::list`].
+    #[tracing::instrument(skip(self, tags))]
+    pub fn list_lifecycle(
+        &self,
+        tags: &[impl AsRef<str>],
+        native_format: Option<&str>,
+        lifecycles: &[Lifecycle],
+    ) -> Vec<Asset> {
+        let mut assets = self.list(tags, native_format);
+        assets.retain(|a| lifecycles.contains(&a.lifecycle()));
+        assets
+    }
+
+    /// Sets review state of the asset.
+    #[tracing::instrument(skip(self))]
+    pub fn set_lifecycle(&self, uuid: Uuid, lifecycle: Lifecycle) -> Result<(), FetchError> {
+        let mut lock = self.registry.lock();
+
+        match lock.data.assets.get_mut(uuid) {
+            None => Err(FetchError::NotFound),
+            Some(asset) => {
+                asset.set_lifecycle(lifecycle);
+                Ok(())
+            }
+        }
+    }
+
+    /// Sets visibility of the asset.
+    #[tracing::instrument(skip(self))]
+    pub fn set_visibility(&self, uuid: Uuid, visibility: Visibility) -> Result<(), FetchError> {
+        let mut lock = self.registry.lock();
+
+        match lock.data.assets.get_mut(uuid) {
+            None => Err(FetchError::NotFound),
+            Some(asset) => {
+                asset.set_visibility(visibility);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns assets information.
+    #[tracing::instrument(skip(self))]
+    pub fn remove<'a>(&self, uuid: Uuid) {
+        self.registry.lock().remove(uuid);
+    }
+
+    /// Removes files in treasury directory no asset refers to:
+    /// native files of removed assets and locales, leftovers of interrupted imports
+    /// and stored sources of removed assets.
+    /// Without compaction long-lived treasuries, e.g. of editors, grow unbounded.
+    ///
+    /// Imports wait for compaction to finish, and compaction fails
+    /// if assets are being imported when it starts.
+    /// `progress` is called with number of checked and total files.
+    #[tracing::instrument(skip(self, progress))]
+    pub fn compact(
+        &self,
+        mut progress: impl FnMut(u64, u64),
+    ) -> Result<CompactReport, CompactError> {
+        let mut lock = self.registry.lock();
+
+        #[cfg(feature = "import")]
+        if !lock.storing.is_empty()
+            || lock
+                .states
+                .values()
+                .any(|state| matches!(state, ImportState::Importing(_)))
+        {
+            return Err(CompactError::ImportInProgress);
+        }
+
+        let treasury_path = Path::new(".treasury");
+        let mut files = Vec::new();
+        list_files(&lock.root, treasury_path, &mut files)?;
+        lock.data.assets.load_all();
+
+        let total = files.len() as u64;
+        let mut report = CompactReport::default();
+
+        for (index, path) in files.into_iter().enumerate() {
+            progress(index as u64, total);
+
+            let dead = if path.starts_with(treasury_path.join("sources")) {
+                !lock.data.assets.iter().any(|a| {
+                    Path::new(a.source()) == path || a.aliases().iter().any(|p| **p == *path)
+                })
+            } else if path.parent() == Some(treasury_path) {
+                let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                match file_name.split_once('@') {
+                    None => match file_name.parse::<Uuid>() {
+                        Ok(uuid) => !lock.data.assets.contains(uuid),
+                        // Interrupted imports leave `<uuid>.tmp` files.
+                        Err(_) => file_name
+                            .strip_suffix(".tmp")
+                            .is_some_and(|uuid| uuid.parse::<Uuid>().is_ok()),
+                    },
+                    Some((native, locale)) => match native.parse::<Uuid>() {
+                        Ok(uuid) => !lock.data.assets.iter().any(|a| {
+                            a.uuid() == uuid && a.locales().iter().any(|l| **l == *locale)
+                        }),
+                        Err(_) => native
+                            .strip_suffix(".tmp")
+                            .is_some_and(|uuid| uuid.parse::<Uuid>().is_ok()),
+                    },
+                }
+            } else {
+                false
+            };
+
+            if dead {
+                let absolute = lock.root.join(&path);
+                let io_error = |source| CompactError::IoError {
+                    path: absolute.clone().into(),
+                    source,
+                };
+
+                let len = std::fs::metadata(&absolute).map_err(io_error)?.len();
+                std::fs::remove_file(&absolute).map_err(io_error)?;
+
+                tracing::debug!("Removed dead file '{}'", path.display());
+                report.freed += len;
+                report.removed.push(path.into());
+            }
+        }
+
+        progress(total, total);
+        Ok(report)
+    }
+
+    /// Lists assets added, removed and changed in `other` treasury compared to this one,
+    /// e.g. to validate what a content branch changes before merging
+    /// or to produce release notes of content changes.
+    ///
+    /// Native files are compared by content, so reimport producing the same files is not a change.
+    /// Missing native files are compared as empty.
+    #[tracing::instrument(skip(self, other))]
+    pub fn diff(&self, other: &Treasury) -> TreasuryDiff {
+        if Arc::ptr_eq(&self.registry, &other.registry) {
+            return TreasuryDiff::default();
+        }
+
+        let mut lock = self.registry.lock();
+        let mut other_lock = other.registry.lock();
+        lock.data.assets.load_all();
+        other_lock.data.assets.load_all();
+
+        let mut diff = TreasuryDiff::default();
+
+        for asset in lock.data.assets.iter() {
+            let other_asset = match other_lock.data.assets.position(asset.uuid()) {
+                None => {
+                    diff.removed.push(asset.clone());
+                    continue;
+                }
+                Some(index) => &other_lock.data.assets[index],
+            };
+
+            let mut tags = asset.tags().to_vec();
+            let mut other_tags = other_asset.tags().to_vec();
+            tags.sort();
+            other_tags.sort();
+
+            let native = asset.native_format() != other_asset.native_format()
+                || asset.locales() != other_asset.locales()
+                || {
+                    let native = lock
+                        .root
+                        .join(".treasury")
+                        .join(asset.uuid().to_hyphenated().to_string());
+                    let other_native = other_lock
+                        .root
+                        .join(".treasury")
+                        .join(asset.uuid().to_hyphenated().to_string());
+
+                    let keys = (lock.native_key.as_ref(), other_lock.native_key.as_ref());
+
+                    !same_native(&native, &other_native, keys)
+                        || asset.locales().iter().any(|locale| {
+                            !same_native(
+                                &localized_path(&native, locale),
+                                &localized_path(&other_native, locale),
+                                keys,
+                            )
+                        })
+                };
+
+            let change = AssetChange {
+                uuid: asset.uuid(),
+                name: other_asset.name().into(),
+                native,
+                source: asset.source() != other_asset.source()
+                    || asset.source_format() != other_asset.source_format()
+                    || asset.source_hash() != other_asset.source_hash(),
+                settings: asset.settings() != other_asset.settings(),
+                tags: tags != other_tags,
+            };
+
+            if change.native || change.source || change.settings || change.tags {
+                diff.changed.push(change);
+            }
+        }
+
+        diff.added = other_lock
+            .data
+            .assets
+            .iter()
+            .filter(|a| !lock.data.assets.contains(a.uuid()))
+            .cloned()
+            .collect();
+
+        diff.added.sort_by_key(|a| a.uuid());
+        diff.removed.sort_by_key(|a| a.uuid());
+        diff.changed.sort_by_key(|c| c.uuid);
+        diff
+    }
+
+    /// Registers copy of the asset with new UUID and tags, e.g. to duplicate a prefab in editor.
+    /// Native files are copied, so the copy is available without reimport.
+    ///
+    /// Sources managed by treasury are copied too, so that the copy can be edited independently.
+    /// Copy of an asset with source in the filesystem or from a provider shares the source.
+    #[tracing::instrument(skip(self, tags))]
+    pub fn duplicate(&self, uuid: Uuid, tags: &[impl AsRef<str>]) -> Result<Uuid, DuplicateError> {
+        let mut lock = self.registry.lock();
+
+        let index = lock
+            .data
+            .assets
+            .position(uuid)
+            .ok_or(DuplicateError::NotFound)?;
+        let asset = &lock.data.assets[index];
+
+        let mut attempt = 0u32;
+        let copy_uuid = loop {
+            let copy_uuid = match &lock.data.namespace {
+                None => Uuid::new_v4(),
+                Some(namespace) => {
+                    // Copies are derived from the original, as stored assets are from sources.
+                    let name = format!("copy\0{}\0{}", uuid, attempt);
+                    attempt += 1;
+                    Uuid::new_v5(namespace, name.as_bytes())
+                }
+            };
+            if !lock.data.assets.contains(copy_uuid) {
+                break copy_uuid;
+            }
+        };
+
+        let source: Box<str> = match is_managed_source(asset.source()) {
+            false => asset.source().into(),
+            true => {
+                let source = Path::new(asset.source());
+                let copy = Path::new(".treasury")
+                    .join("sources")
+                    .join(copy_uuid.to_hyphenated().to_string())
+                    .join(source.file_name().unwrap_or_default());
+
+                let copy_absolute = lock.root.join(&copy);
+                let io_error = |source| DuplicateError::SourceIoError {
+                    path: copy_absolute.clone().into(),
+                    source,
+                };
+                std::fs::create_dir_all(copy_absolute.parent().unwrap()).map_err(io_error)?;
+                std::fs::copy(lock.root.join(source), &copy_absolute).map_err(io_error)?;
+
+                match copy.to_str() {
+                    Some(copy) => copy.into(),
+                    None => unreachable!("Managed source paths are valid UTF-8"),
+                }
+            }
+        };
+
+        let native = lock
+            .root
+            .join(".treasury")
+            .join(uuid.to_hyphenated().to_string());
+        let copy_native = lock
+            .root
+            .join(".treasury")
+            .join(copy_uuid.to_hyphenated().to_string());
+
+        let mut natives = vec![(native.clone(), copy_native.clone())];
+        for locale in asset.locales() {
+            natives.push((
+                localized_path(&native, locale),
+                localized_path(&copy_native, locale),
+            ));
+        }
+
+        for (from, to) in natives {
+            match std::fs::copy(&from, &to) {
+                Ok(_) => {}
+                // Missing native file is imported on fetch.
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(DuplicateError::NativeIoError {
+                        path: to.into(),
+                        source: err,
+                    })
+                }
+            }
+        }
+
+        let tags = tags.iter().map(|tag| tag.as_ref().into()).collect();
+        let copy = asset.duplicate(
+            copy_uuid,
+            source,
+            tags,
+            lock.identity.clone(),
+            lock.clock.now(),
+        );
+        lock.data.assets.push(copy);
+        lock.emit(TreasuryEvent::Stored { uuid: copy_uuid });
+
+        tracing::info!("Asset '{}' duplicated as '{}'", uuid, copy_uuid);
+        Ok(copy_uuid)
+    }
+
+    /// Exports graph of assets and assets they pull in,
+    /// i.e. assets their importers stored or fetched
+    /// and assets with sources they depend on.
+    /// Nodes are labeled with native size of the asset
+    /// and total size of all assets it pulls in.
+    pub fn export_graph(&self, format: GraphFormat) -> String {
+        self.export_graph_with_style(format, &GraphStyle::new())
+    }
+
+    /// Exports graph of assets like [`Treasury::export_graph`],
+    /// styling nodes by native format and tags.
+    pub fn export_graph_with_style(&self, format: GraphFormat, style: &GraphStyle) -> String {
+        let mut lock = self.registry.lock();
+        lock.data.assets.load_all();
+
+        let treasury = lock.root.join(".treasury");
+        graph::export(lock.data.assets.iter(), format, style, |asset| {
+            let native = treasury.join(asset.uuid().to_hyphenated().to_string());
+            native_size(&native, asset.locales())
+        })
+    }
+
+    /// Exports assets accepted by `filter` along with their native files
+    /// and sources managed by treasury.
+    pub fn export_subset(
+        &self,
+        filter: impl Fn(&Asset) -> bool,
+    ) -> Result<SubsetBundle, SubsetError> {
+        let mut lock = self.registry.lock();
+        lock.data.assets.load_all();
+
+        let mut bundle = SubsetBundle::default();
+        for asset in lock.data.assets.iter().filter(|a| filter(a)) {
+            let native = lock
+                .root
+                .join(".treasury")
+                .join(asset.uuid().to_hyphenated().to_string());
+
+            let key = lock.native_key.as_ref();
+            let mut localized = Vec::new();
+            for locale in asset.locales() {
+                let bytes = subset::read_native(localized_path(&native, locale), key)?;
+                localized.push((locale.clone(), bytes));
+            }
+
+            let source = if is_managed_source(asset.source()) {
+                Some(subset::read(lock.root.join(asset.source()))?)
+            } else {
+                None
+            };
+
+            bundle.assets.push(SubsetAsset {
+                asset: asset.clone(),
+                native: subset::read_native(native, key)?,
+                localized,
+                source,
+            });
+        }
+
+        Ok(bundle)
+    }
+
+    /// Adds assets exported from another treasury, preserving their UUIDs.
+    /// Returns number of assets added or replaced.
+    pub fn import_subset(
+        &self,
+        bundle: SubsetBundle,
+        conflict: SubsetConflict,
+    ) -> Result<usize, SubsetError> {
+        let mut lock = self.registry.lock();
+
+        if conflict == SubsetConflict::Fail {
+            for entry in &bundle.assets {
+                let uuid = entry.asset.uuid();
+                if lock.data.assets.contains(uuid) {
+                    return Err(SubsetError::Conflict { uuid });
+                }
+            }
+        }
+
+        let mut count = 0;
+        for entry in bundle.assets {
+            let uuid = entry.asset.uuid();
+            let existing = lock.data.assets.position(uuid);
+            if existing.is_some() && conflict == SubsetConflict::Skip {
+                continue;
+            }
+
+            let native = lock
+                .root
+                .join(".treasury")
+                .join(uuid.to_hyphenated().to_string());
+
+            if let Some(index) = existing {
+                for locale in lock.data.assets[index].locales() {
+                    if !entry.asset.locales().contains(locale) {
+                        let _ = std::fs::remove_file(localized_path(&native, locale));
+                    }
+                }
+            }
+
+            let key = lock.native_key.as_ref();
+            subset::write_native(native.clone(), &entry.native, key)?;
+            for (locale, bytes) in &entry.localized {
+                subset::write_native(localized_path(&native, locale), bytes, key)?;
+            }
+            if let Some(source) = &entry.source {
+                subset::write(lock.root.join(entry.asset.source()), source)?;
+            }
+
+            let mut asset = entry.asset;
+            match existing {
+                Some(index) => {
+                    // Loaders must see replaced native files as updated.
+                    let version = lock.data.assets[index].version() + 1;
+                    asset.set_version(asset.version().max(version));
+                    lock.data.assets[index] = asset;
+                    lock.emit(TreasuryEvent::Reimported { uuid });
+                }
+                None => {
+                    lock.data.assets.push(asset);
+                    lock.emit(TreasuryEvent::Stored { uuid });
+                }
+            }
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Returns names of all collections.
+    pub fn collections(&self) -> Vec<Box<str>> {
+        self.registry
+            .lock()
+            .data
+            .collections
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns members of the collection in order.
+    /// Returns `None` if collection does not exist.
+    pub fn collection(&self, name: &str) -> Option<Vec<Uuid>> {
+        self.registry.lock().data.collections.get(name).cloned()
+    }
+
+    /// Appends asset to the collection, creating collection if necessary.
+    /// Does nothing if asset is already a member.
+    #[tracing::instrument(skip(self))]
+    pub fn add_to_collection(&self, name: &str, uuid: Uuid) -> Result<(), FetchError> {
+        let mut lock = self.registry.lock();
+
+        if !lock.data.assets.contains(uuid) {
+            return Err(FetchError::NotFound);
+        }
+
+        let members = lock.data.collections.entry(name.into()).or_default();
+        if !members.contains(&uuid) {
+            members.push(uuid);
+        }
+        Ok(())
+    }
+
+    /// Removes asset from the collection.
+    /// Collection is removed with its last member.
+    #[tracing::instrument(skip(self))]
+    pub fn remove_from_collection(&self, name: &str, uuid: Uuid) {
+        let mut lock = self.registry.lock();
+
+        if let Some(members) = lock.data.collections.get_mut(name) {
+            members.retain(|member| *member != uuid);
+            if members.is_empty() {
+                lock.data.collections.remove(name);
+            }
+        }
+    }
+
+    /// Moves member of the collection to specified position.
+    /// Position is clamped to the collection length.
+    /// Returns `false` if asset is not a member of the collection.
+    #[tracing::instrument(skip(self))]
+    pub fn move_in_collection(&self, name: &str, uuid: Uuid, index: usize) -> bool {
+        let mut lock = self.registry.lock();
+
+        match lock.data.collections.get_mut(name) {
+            None => false,
+            Some(members) => match members.iter().position(|member| *member == uuid) {
+                None => false,
+                Some(position) => {
+                    members.remove(position);
+                    members.insert(index.min(members.len()), uuid);
+                    true
+                }
+            },
+        }
+    }
+}
+
+impl Treasury {
+    /// Writes native files of assets having all specified tags into pack file,
+    /// including localized native files.
+    /// Assets that are not public are written only if included by options.
+    /// Collections are written with members that are packed.
+    /// Native files are encrypted and signed as configured by options.
+    /// Out-of-date native files are reimported first.
+    ///
+    /// Returns number of native files written.
+    #[tracing::instrument(skip(self, path, tags, options), fields(path = %path.as_ref().display()))]
+    pub fn pack(
+        &self,
+        path: impl AsRef<Path>,
+        tags: &[impl AsRef<str>],
+        options: &PackOptions,
+    ) -> Result<usize, PackError> {
+        self.pack_ordered(path.as_ref(), tags, options, &[])
+    }
+
+    /// Writes pack as [`Treasury::pack`] does, laid out by recorded load session.
+    ///
+    /// Native files of assets loaded during the session are written first,
+    /// together and in the order of first access, cutting seeks
+    /// when the session is replayed from slow storage, e.g. HDD or optical disc.
+    /// The order is stored in the pack as read-ahead hints for pack sources.
+    ///
+    /// Returns number of native files written.
+    #[tracing::instrument(skip(self, path, tags, options, recording), fields(path = %path.as_ref().display()))]
+    pub fn pack_with_layout(
+        &self,
+        path: impl AsRef<Path>,
+        tags: &[impl AsRef<str>],
+        options: &PackOptions,
+        recording: &Recording,
+    ) -> Result<usize, PackError> {
+        let mut layout = Vec::new();
+        for load in recording.loads() {
+            if !layout.contains(&load.uuid) {
+                layout.push(load.uuid);
+            }
+        }
+        self.pack_ordered(path.as_ref(), tags, options, &layout)
+    }
+
+    /// Writes pack with assets in layout first and in order.
+    fn pack_ordered(
+        &self,
+        path: &Path,
+        tags: &[impl AsRef<str>],
+        options: &PackOptions,
+        layout: &[Uuid],
+    ) -> Result<usize, PackError> {
+        let tmp_path = path.with_extension("tmp");
+
+        let mut writer = PackWriter::create(&tmp_path, options)?;
+        writer.set_namespace(self.namespace());
+
+        let mut assets = self.list(tags, None);
+        assets.retain(|a| {
+            options.includes(a.visibility()) && options.includes_lifecycle(a.lifecycle())
+        });
+        writer.set_deprecated(
+            assets
+                .iter()
+                .filter(|a| a.lifecycle() == Lifecycle::Deprecated)
+                .map(|a| a.uuid())
+                .collect(),
+        );
+
+        let position = |uuid| layout.iter().position(|u| *u == uuid);
+        assets.sort_by_key(|a| position(a.uuid()).unwrap_or(usize::MAX));
+        writer.set_layout(
+            layout
+                .iter()
+                .copied()
+                .filter(|uuid| assets.iter().any(|a| a.uuid() == *uuid))
+                .collect(),
+        );
+
+        let collections = self.registry.lock().data.collections.clone();
+        for (name, mut members) in collections {
+            members.retain(|uuid| assets.iter().any(|a| a.uuid() == *uuid));
+            if !members.is_empty() {
+                writer.add_collection(&name, members);
+            }
+        }
+
+        for asset in assets {
+            let uuid = asset.uuid();
+            let locales = std::iter::once(None).chain(asset.locales().iter().map(|l| Some(&**l)));
+
+            for locale in locales {
+                let fetch_error = |source| PackError::FetchError { uuid, source };
+
+                let mut info = match Registry::fetch(&self.registry, &uuid, locale, None) {
+                    Ok(Some(info)) => info,
+                    Ok(None) => unreachable!(),
+                    Err(err) => return Err(fetch_error(err)),
+                };
+
+                let bytes = info.read_native().map_err(|source| {
+                    fetch_error(FetchError::NativeIoError {
+                        name: asset.name().into(),
+                        source,
+                        path: info.native_path.clone(),
+                    })
+                })?;
+
+                writer.add(uuid, locale, &info.native_format, &bytes)?;
+            }
+        }
+
+        let count = writer.finish()?;
+
+        std::fs::rename(&tmp_path, path).map_err(|source| PackError::IoError {
+            path: path.into(),
+            source,
+        })?;
+
+        tracing::info!("{} native files packed", count);
+        Ok(count)
+    }
+}
+
+/// Runs importer, or reuses result of identical import from import cache.
+/// Results of imports not found in the cache are uploaded to it.
+/// `native_path` is relative to `root`.
+#[cfg(feature = "import")]
+#[allow(clippy::too_many_arguments)]
+fn import_cached(
+    import_cache: Option<&dyn ImportCache>,
+    importer: &ImporterEntry,
+    root: &Path,
+    source_absolute: &Path,
+    source_hash: Option<&str>,
+    native_path: &Path,
+    settings_json: &str,
+    progress: &mut dyn ImportProgress,
+    control: &ImportControl,
+) -> Result<ImportOutput, ImportError> {
+    let cached = import_cache
+        .zip(source_hash)
+        .map(|(import_cache, source_hash)| {
+            let key = cache::cache_key(
+                source_hash,
+                importer.name(),
+                importer.version(),
+                importer.source(),
+                importer.native(),
+                settings_json,
+            );
+            (import_cache, key)
+        });
+
+    let native_absolute = root.join(native_path);
+    if let Some((import_cache, key)) = &cached {
+        match cache::load(*import_cache, key, &native_absolute) {
+            Ok(Some(output)) => {
+                tracing::debug!("Import result found in cache '{}'", key);
+                return Ok(output);
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!("Failed to load import result from cache. {:#}", err),
+        }
+    }
+
+    let output = importer.import(
+        root,
+        source_absolute,
+        native_path,
+        settings_json,
+        progress,
+        control,
+    )?;
+
+    if let Some((import_cache, key)) = &cached {
+        if let Err(err) = cache::store(*import_cache, key, &native_absolute, &output) {
+            tracing::warn!("Failed to store import result in cache. {:#}", err);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Runs importer again into separate files and compares them
+/// with files written by the first import.
+/// `native_tmp_path` is relative to `root`.
+/// Returns description of the difference if outputs differ.
+#[cfg(feature = "import")]
+fn check_determinism(
+    importer: &ImporterEntry,
+    root: &Path,
+    source_absolute: &Path,
+    native_tmp_path: &Path,
+    settings_json: &str,
+    output: &ImportOutput,
+    control: &ImportControl,
+) -> Result<(), Box<str>> {
+    let check_path = native_tmp_path.with_extension("check");
+    let first_absolute = root.join(native_tmp_path);
+    let check_absolute = root.join(&check_path);
+
+    let result = importer.import(
+        root,
+        source_absolute,
+        &check_path,
+        settings_json,
+        &mut (),
+        control,
+    );
+
+    let compare = |locale: Option<&str>| {
+        let (first, check) = match locale {
+            None => (first_absolute.clone(), check_absolute.clone()),
+            Some(locale) => (
+                localized_path(&first_absolute, locale),
+                localized_path(&check_absolute, locale),
+            ),
+        };
+        let equal = matches!(
+            (hash_source(&first), hash_source(&check)),
+            (Ok(a), Ok(b)) if a == b
+        );
+        let _ = std::fs::remove_file(&check);
+
+        match (equal, locale) {
+            (true, _) => Ok(()),
+            (false, None) => Err("Native file differs".into()),
+            (false, Some(locale)) => {
+                Err(format!("Native file for locale '{}' differs", locale).into())
+            }
+        }
+    };
+
+    let check = match result {
+        Err(err) => {
+            let _ = std::fs::remove_file(&check_absolute);
+            return Err(format!("Repeated import failed. {:#}", err).into());
+        }
+        Ok(check) => check,
+    };
+
+    let mut result = compare(None);
+    if output.locales == check.locales {
+        for locale in &output.locales {
+            let compared = compare(Some(locale));
+            result = result.and(compared);
+        }
+    } else {
+        for locale in &check.locales {
+            let _ = std::fs::remove_file(localized_path(&check_absolute, locale));
+        }
+        result = result.and(Err("Locales differ".into()));
+    }
+    result
+}
+
+/// Pulls remote source into local file.
+#[cfg(feature = "import")]
+fn fetch_remote(provider: &dyn SourceProvider, path: &str, local: &Path) -> eyre::Result<()> {
+    if let Some(parent) = local.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    provider.fetch(path, local)
+}
+
+/// Formats hint listing available native formats for missing importer error.
+fn available_natives(available: &[Box<str>]) -> String {
+    if available.is_empty() {
+        String::new()
+    } else {
+        format!(". Available native formats: {}", available.join(", "))
+    }
+}
+
+/// Returns hex-encoded SHA-256 hash of the source file content.
+#[cfg(feature = "import")]
+fn hash_source(path: &Path) -> std::io::Result<Box<str>> {
+    use {sha2::Digest as _, std::fmt::Write as _};
+
+    let mut hasher = sha2::Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+
+    let mut hex = String::with_capacity(64);
+    for byte in hasher.finalize() {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    Ok(hex.into())
+}
+
+pub(crate) struct FetchInfo {
+    pub name: Box<str>,
+    pub native_path: Box<Path>,
+    pub native_file: std::fs::File,
+    pub version: u64,
+    pub native_format: Box<str>,
+    pub schema: u32,
+    pub lifecycle: Lifecycle,
+
+    /// Key native file is decrypted with.
+    pub key: Option<PackKey>,
+}
+
+impl FetchInfo {
+    /// Reads native file, decrypting it if encrypted at rest.
+    pub fn read_native(&mut self) -> std::io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.native_file.read_to_end(&mut bytes)?;
+        pack::unseal(self.key.as_ref(), bytes)
+    }
+}
+
+impl Registry {
+    fn remove(&mut self, uuid: Uuid) {
+        if let Some(index) = self.data.assets.position(uuid) {
+            let native = Path::new(".treasury").join(uuid.to_hyphenated().to_string());
+            let native_absolute = self.root.join(native);
+            if let Err(err) = std::fs::remove_file(&native_absolute) {
+                tracing::error!(
+                    "Failed to remove native asset file '{}': {}",
+                    native_absolute.display(),
+                    err
+                );
+            }
+            for locale in self.data.assets[index].locales() {
+                let localized = localized_path(&native_absolute, locale);
+                if let Err(err) = std::fs::remove_file(&localized) {
+                    tracing::error!(
+                        "Failed to remove native asset file '{}': {}",
+                        localized.display(),
+                        err
+                    );
+                }
+            }
+            self.data.assets.remove(index);
+
+            self.data.collections.retain(|_, members| {
+                members.retain(|member| *member != uuid);
+                !members.is_empty()
+            });
+
+            #[cfg(feature = "import")]
+            {
+                self.states.remove(&uuid);
+                self.corrupted.remove(&uuid);
+            }
+
+            self.emit(TreasuryEvent::Removed { uuid });
+        }
+    }
+
+    /// Removes assets registered by cancelled import and its sub-imports.
+    /// Returns `true` if any asset was removed.
+    #[cfg(feature = "import")]
+    fn rollback(&mut self, control: &ImportControl) -> bool {
+        let stored = std::mem::take(&mut *control.stored.lock());
+        for &uuid in &stored {
+            tracing::debug!("Removing '{}' registered by cancelled import", uuid);
+            self.remove(uuid);
+        }
+        !stored.is_empty()
+    }
+
+    /// Sends event to subscribers, dropping disconnected ones.
+    fn emit(&mut self, event: TreasuryEvent) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    /// Flags or clears corruption of the asset's native file found by scrubber.
+    #[cfg(feature = "import")]
+    pub(crate) fn set_corrupted(&mut self, uuid: Uuid, corrupted: bool) {
+        if !corrupted {
+            self.corrupted.remove(&uuid);
+        } else if self.data.assets.contains(uuid) && self.corrupted.insert(uuid) {
+            tracing::error!("Native file of asset '{}' is corrupted", uuid);
+            self.emit(TreasuryEvent::Corrupted { uuid });
+        }
+    }
+
+    fn save(me: &Mutex<Self>) -> Result<(), SaveError> {
+        let mut lock = me.lock();
+
+        // Sorted, so that manifest diffs are meaningful and reproducible.
+        lock.data.assets.sort();
+        lock.data.importers_dirs.sort();
+        lock.data.importers_dirs.dedup();
+
+        let records_path = lock.root.join(".treasury").join("assets.db");
+
+        // Indexed manifest keeps records out of JSON.
+        let records = if lock.data.indexed {
+            lock.data
+                .assets
+                .write(&records_path)
+                .map_err(|source| SaveError::RecordsError {
+                    source,
+                    path: records_path.into(),
+                })?;
+            Some(std::mem::take(&mut lock.data.assets))
+        } else {
+            lock.data.assets.load_all();
+            match std::fs::remove_file(&records_path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    tracing::warn!("Failed to remove '{}'. {:#}", records_path.display(), err);
+                }
+                _ => {}
+            }
+            None
+        };
+
+        let treasury_path = lock.root.join(".treasury").join("manifest.json");
+        let result = std::fs::File::create(&treasury_path)
+            .map_err(|source| SaveError::GoodsOpenError {
+                source,
+                path: treasury_path.clone().into(),
+            })
+            .and_then(|file| {
+                let mut writer = std::io::BufWriter::new(file);
+                serde_json::to_writer_pretty(&mut writer, &lock.data).map_err(|source| {
+                    SaveError::JsonError {
+                        source,
+                        path: treasury_path.clone().into(),
+                    }
+                })?;
+                writer.flush().map_err(|source| SaveError::GoodsOpenError {
+                    source,
+                    path: treasury_path.into(),
+                })
+            });
+
+        if let Some(records) = records {
+            lock.data.assets = records;
+        }
+        result?;
+
+        #[cfg(feature = "import")]
+        if lock.data.checksums {
+            lock.data.assets.load_all();
+            lock.write_checksums()?;
+        }
+
+        lock.emit(TreasuryEvent::ManifestSaved);
+        Ok(())
+    }
+
+    #[cfg(feature = "import")]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn store(
+        me: &Mutex<Self>,
+        source: &Path,
+        source_format: &str,
+        native_format: &str,
+        settings: &serde_json::Value,
+        tags: &[impl AsRef<str>],
+        progress: &mut dyn ImportProgress,
+        control: &ImportControl,
+    ) -> Result<Uuid, StoreError> {
+        if control.is_cancelled() {
+            return Err(StoreError::ImportCancelled);
+        }
+
+        let mut lock = me.lock();
+
+        // Find the source
+
+        let remote = match source.to_str().and_then(split_uri) {
+            None => None,
+            Some((scheme, path)) => match lock.providers.get(scheme) {
+                None => {
+                    return Err(StoreError::ProviderNotFound {
+                        scheme: scheme.into(),
+                    })
+                }
+                Some(provider) => Some((provider.clone(), path)),
+            },
+        };
+
+        let source_absolute;
+        let source_uri: Arc<str> = if remote.is_some() {
+            source_absolute = lock.remote_path(source.to_str().unwrap());
+            source.to_str().unwrap().into()
+        } else if source.is_absolute() {
+            source_absolute = source.to_path_buf();
+            relative_to(source, &lock.root).to_string_lossy().into()
+        } else {
+            let cd = std::env::current_dir().map_err(|_| StoreError::SourceIoError {
+                path: source.into(),
+                source: std::io::ErrorKind::NotFound.into(),
+            })?;
+            source_absolute = cd.join(source);
+            relative_to(&source_absolute, &lock.root)
+                .to_string_lossy()
+                .into()
+        };
+
+        // Remote sources are not in directories with presets.
+        let resolved;
+        let settings = match remote {
+            Some(_) => settings,
+            None => {
+                resolved = resolve_settings(
+                    &lock.root,
+                    &source_absolute,
+                    source_format,
+                    native_format,
+                    settings,
+                )?;
+                &resolved
+            }
+        };
+
+        let settings_json = settings_json(settings);
+
+        let kind = Kind {
+            source: source_uri,
+            source_format: source_format.into(),
+            native_format: native_format.into(),
+            settings: settings_json.as_str().into(),
+        };
+
+        loop {
+            lock.data.assets.load_all();
+            if let Some(asset) = lock.data.assets.iter().find(|a| {
+                (*a.source() == *kind.source
+                    || a.aliases()
+                        .iter()
+                        .any(|alias| **alias == *Path::new(&*kind.source)))
+                    && a.source_format() == source_format
+                    && a.native_format() == native_format
+                    && a.settings() == settings
+            }) {
+                tracing::trace!("Already imported");
+                return Ok(asset.uuid());
+            }
+
+            // Same source may be imported by another thread.
+            match lock.storing.get(&kind).filter(|wait| !wait.is_done()) {
+                None => break,
+                Some(wait) => {
+                    let wait = wait.clone();
+                    if lock.would_deadlock(&wait) {
+                        return Err(StoreError::ImportCycle {
+                            path: source.into(),
+                        });
+                    }
+                    lock = Self::wait_import(lock, wait);
+                }
+            }
+        }
+
+        if let Some((provider, path)) = &remote {
+            tracing::debug!("Fetching source '{}'", kind.source);
+            let result = MutexGuard::unlocked(&mut lock, || {
+                fetch_remote(&**provider, path, &source_absolute)
+            });
+            if let Err(err) = result {
+                return Err(StoreError::SourceFetchError {
+                    uri: (*kind.source).into(),
+                    source: err,
+                });
+            }
+        }
+
+        let source_hash = match hash_source(&source_absolute) {
+            Ok(hash) => Some(hash),
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to hash source '{}'. {:#}",
+                    source_absolute.display(),
+                    err
+                );
+                None
+            }
+        };
+
+        if lock.dedup != Dedup::Off {
+            if let Some(source_hash) = &source_hash {
+                let dedup = lock.dedup;
+                lock.data.assets.load_all();
+                if let Some(asset) = lock.data.assets.iter_mut().find(|a| {
+                    a.source_hash() == Some(&**source_hash)
+                        && a.source_format() == source_format
+                        && a.native_format() == native_format
+                        && a.settings() == settings
+                        && a.dependencies().is_empty()
+                }) {
+                    tracing::debug!(
+                        "Source '{}' is identical to source of '{}'",
+                        source.display(),
+                        asset.uuid()
+                    );
+
+                    let uuid = asset.uuid();
+                    if dedup == Dedup::Alias && remote.is_none() {
+                        asset.add_alias(Path::new(&*kind.source).into());
+                        drop(lock);
+                        let _ = Self::save(me);
+                    }
+                    return Ok(uuid);
+                }
+            }
+        }
+
+        tracing::debug!(
+            "Importing {} as {} @ {}",
+            source_format,
+            native_format,
+            source.display()
+        );
+
+        let mut attempt = 0u32;
+        let uuid = loop {
+            let uuid = match &lock.data.namespace {
+                None => Uuid::new_v4(),
+                Some(namespace) => {
+                    // Same source gets same uuid in every copy of the project.
+                    let uuid = source_uuid(
+                        namespace,
+                        &kind.source,
+                        source_format,
+                        native_format,
+                        attempt,
+                    );
+                    attempt += 1;
+                    uuid
+                }
+            };
+            if !lock.data.assets.contains(uuid) {
+                break uuid;
+            }
+        };
+
+        let native = Path::new(".treasury").join(uuid.to_hyphenated().to_string());
+        let native_absolute = lock.root.join(&native);
+
+        let importing = ImportGuard::new();
+        lock.storing.insert(kind.clone(), importing.wait());
+
+        let mut output = ImportOutput::default();
+
+        if lock.copies_through(source_format, native_format) {
+            if source_format != native_format {
+                tracing::warn!(
+                    "No importer from '{}' to '{}' found, source '{}' is copied as is",
+                    source_format,
+                    native_format,
+                    source.display()
+                );
+            }
+            if let Err(err) = std::fs::copy(&source_absolute, &native_absolute) {
+                return Err(StoreError::SourceIoError {
+                    source: err,
+                    path: source.into(),
+                });
+            }
+        } else {
+            match lock.importers.get_importer(source_format, native_format) {
+                None => {
+                    return Err(StoreError::ImporterNotFound {
+                        source_format: source_format.to_owned(),
+                        native_format: native_format.to_owned(),
+                        available: lock.importers.natives(source_format),
+                    })
+                }
+                Some(importer_entry) => {
+                    tracing::trace!("Importer found. {}", importer_entry.name());
+
+                    let native_tmp_path = native.with_extension("tmp");
+                    let native_tmp_path_absolute = native_absolute.with_extension("tmp");
+
+                    let root = lock.root.clone();
+                    let import_cache = lock.import_cache.clone();
+                    let result = MutexGuard::unlocked(&mut lock, || {
+                        import_cached(
+                            import_cache.as_deref(),
+                            &importer_entry,
+                            &root,
+                            &source_absolute,
+                            source_hash.as_deref(),
+                            &native_tmp_path,
+                            &settings_json,
+                            progress,
+                            control,
+                        )
+                    });
+
+                    match result {
+                        Ok(imported) => output = imported,
+                        Err(ImportError::Failed(err)) => {
+                            remove_partial(&native_tmp_path_absolute);
+                            return Err(StoreError::ImportError { source: err });
+                        }
+                        Err(ImportError::Timeout) => {
+                            remove_partial(&native_tmp_path_absolute);
+                            return Err(StoreError::ImportTimeout);
+                        }
+                        Err(ImportError::Cancelled) => {
+                            remove_partial(&native_tmp_path_absolute);
+                            if lock.rollback(control) {
+                                drop(lock);
+                                let _ = Self::save(me);
+                            }
+                            return Err(StoreError::ImportCancelled);
+                        }
+                    }
+
+                    if lock.check_determinism {
+                        let check = MutexGuard::unlocked(&mut lock, || {
+                            check_determinism(
+                                &importer_entry,
+                                &root,
+                                &source_absolute,
+                                &native_tmp_path,
+                                &settings_json,
+                                &output,
+                                control,
+                            )
+                        });
+                        lock.record_determinism(uuid, importer_entry.name(), check);
+                    }
+
+                    tracing::trace!("Imported successfully");
+                    if let Err(err) = std::fs::rename(&native_tmp_path_absolute, &native_absolute) {
+                        tracing::error!(
+                            "Failed to rename '{}' to '{}'",
+                            native_tmp_path_absolute.display(),
+                            native_absolute.display(),
+                        );
+
+                        return Err(StoreError::NativeIoError {
+                            path: native_absolute.into(),
+                            source: err,
+                        });
+                    }
+
+                    for locale in &output.locales {
+                        let localized_tmp = localized_path(&native_tmp_path_absolute, locale);
+                        let localized = localized_path(&native_absolute, locale);
+                        if let Err(err) = std::fs::rename(&localized_tmp, &localized) {
+                            tracing::error!(
+                                "Failed to rename '{}' to '{}'",
+                                localized_tmp.display(),
+                                localized.display(),
+                            );
+
+                            return Err(StoreError::NativeIoError {
+                                path: localized.into(),
+                                source: err,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        let size = native_size(&native_absolute, &output.locales);
+        let mut checked = lock.check_size_policies(uuid, native_format, tags, size);
+        if checked.is_ok() && !lock.validators.is_empty() {
+            let validators = lock.validators.clone();
+            checked = MutexGuard::unlocked(&mut lock, || {
+                let source_bytes =
+                    std::fs::read(&source_absolute).map_err(|err| StoreError::SourceIoError {
+                        path: source.into(),
+                        source: err,
+                    })?;
+                let native_bytes =
+                    std::fs::read(&native_absolute).map_err(|err| StoreError::NativeIoError {
+                        path: native_absolute.clone().into(),
+                        source: err,
+                    })?;
+
+                validate(
+                    &validators,
+                    &ValidatedSource {
+                        uri: &kind.source,
+                        format: source_format,
+                        settings,
+                        bytes: &source_bytes,
+                    },
+                    &ValidatedNative {
+                        uuid,
+                        format: native_format,
+                        meta: &output.meta,
+                        bytes: &native_bytes,
+                    },
+                )
+            });
+        }
+
+        if let (Ok(()), Some(key)) = (&checked, &lock.native_key) {
+            checked = seal_natives(key, &native_absolute, &output.locales).map_err(|source| {
+                StoreError::NativeIoError {
+                    path: native_absolute.clone().into(),
+                    source,
+                }
+            });
+        }
+
+        if let Err(err) = checked {
+            let _ = std::fs::remove_file(&native_absolute);
+            for locale in &output.locales {
+                let _ = std::fs::remove_file(localized_path(&native_absolute, locale));
+            }
+            lock.storing.remove(&kind);
+            return Err(err);
+        }
+
+        lock.storing.remove(&kind);
+        let dependencies = lock.relative_dependencies(output.dependencies);
+        let mut asset = Asset::new(
+            uuid,
+            (*kind.source).into(),
+            source_format.into(),
+            native_format.into(),
+            tags.iter().map(|tag| tag.as_ref().into()).collect(),
+            settings.clone(),
+            output.meta,
+            dependencies,
+            output.locales.into(),
+            output.importer_version,
+        );
+        asset.set_source_hash(source_hash);
+        asset.set_schema(output.schema);
+        asset.set_references(output.references.into());
+        asset.set_stored_by(lock.identity.clone(), lock.clock.now());
+        lock.data.assets.push(asset);
+        control.stored.lock().push(uuid);
+        lock.emit(TreasuryEvent::Stored { uuid });
+
+        tracing::info!("Asset '{}' registered by '{}'", uuid, lock.identity);
+        drop(lock);
+        drop(importing);
+        let _ = Self::save(me);
+
+        Ok(uuid)
+    }
+
+    /// Writes `.treasury/checksums` file.
+    /// Native hashes from previous file are reused
+    /// for native files of same size not modified since then.
+    #[cfg(feature = "import")]
+    fn write_checksums(&self) -> Result<(), SaveError> {
+        use std::fmt::Write as _;
+
+        let path = self.root.join(".treasury").join("checksums");
+
+        let previous_written = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let previous = std::fs::read_to_string(&path).unwrap_or_default();
+        let previous: HashMap<&str, (&str, &str)> = previous
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split(' ');
+                let uuid = parts.next()?;
+                let _source_hash = parts.next()?;
+                let native_hash = parts.next()?;
+                let size = parts.next()?;
+                Some((uuid, (native_hash, size)))
+            })
+            .collect();
+
+        let mut checksums = String::new();
+        for asset in self.data.assets.iter() {
+            let uuid = asset.uuid().to_hyphenated().to_string();
+            let native = self.root.join(".treasury").join(&uuid);
+
+            let (native_hash, size) = match std::fs::metadata(&native) {
+                Err(_) => ("-".into(), 0),
+                Ok(metadata) => {
+                    let size = metadata.len();
+                    let unchanged = match (metadata.modified(), previous_written) {
+                        (Ok(modified), Some(written)) => modified < written,
+                        _ => false,
+                    };
+
+                    let previous_hash = previous
+                        .get(&*uuid)
+                        .filter(|(_, previous_size)| {
+                            unchanged && *previous_size == size.to_string()
+                        })
+                        .map(|(hash, _)| Box::<str>::from(*hash));
+
+                    match previous_hash {
+                        Some(hash) => (hash, size),
+                        None => match hash_source(&native) {
+                            Ok(hash) => (hash, size),
+                            Err(_) => ("-".into(), size),
+                        },
+                    }
+                }
+            };
+
+            let _ = writeln!(
+                checksums,
+                "{} {} {} {}",
+                uuid,
+                asset.source_hash().unwrap_or("-"),
+                native_hash,
+                size
+            );
+        }
+
+        std::fs::write(&path, checksums).map_err(|source| SaveError::ChecksumsIoError {
+            path: path.into(),
+            source,
+        })
+    }
+
+    /// Checks if source is copied as native file instead of imported,
+    /// according to [`CopyThrough`] policy.
+    #[cfg(feature = "import")]
+    fn copies_through(&self, source_format: &str, native_format: &str) -> bool {
+        match self.copy_through {
+            CopyThrough::SameFormat => source_format == native_format,
+            CopyThrough::Fallback => {
+                source_format == native_format
+                    || self
+                        .importers
+                        .get_importer(source_format, native_format)
+                        .is_none()
+            }
+            CopyThrough::Never => false,
+        }
+    }
+
+    /// Checks if native file of the asset is older than its source or dependencies,
+    /// or was produced by different version of the importer.
+    /// Assets being imported and assets that cannot be reimported are not outdated.
+    #[cfg(feature = "import")]
+    fn is_outdated(&self, asset: &Asset) -> bool {
+        if let Some(ImportState::Importing(_)) = self.states.get(&asset.uuid()) {
+            return false;
+        }
+
+        let native = self
+            .root
+            .join(".treasury")
+            .join(asset.uuid().to_hyphenated().to_string());
+
+        let imported = match std::fs::metadata(native) {
+            Ok(metadata) => match imported_at(asset, &metadata) {
+                Some(imported) => imported,
+                None => return false,
+            },
+            Err(_) => return false,
+        };
+
+        let importer_updated = if self.copies_through(asset.source_format(), asset.native_format())
+        {
+            false
+        } else {
+            match self
+                .importers
+                .get_importer(asset.source_format(), asset.native_format())
+            {
+                None => return false,
+                Some(importer) => match importer.version() {
+                    Some(version) => asset.importer_version() != Some(version),
+                    None => false,
+                },
+            }
+        };
+
+        match self.source_modified(asset) {
+            Ok(source_modified) => {
+                crate::clock::is_after(source_modified, imported)
+                    || importer_updated
+                    || self.dirty.contains(&asset.uuid())
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Converts dependencies reported by importer to paths relative to root.
+    #[cfg(feature = "import")]
+    fn relative_dependencies(&self, dependencies: Vec<PathBuf>) -> Box<[Box<Path>]> {
+        dependencies
+            .iter()
+            .map(|path| {
+                if path.is_absolute() {
+                    relative_to(path, &self.root).into_owned().into_boxed_path()
+                } else {
+                    path.clone().into_boxed_path()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns latest modification time of asset source and its dependencies.
+    /// Missing dependencies are ignored.
+    #[cfg(feature = "import")]
+    fn source_modified(&self, asset: &Asset) -> eyre::Result<SystemTime> {
+        let source_modified = match split_uri(asset.source()) {
+            None => std::fs::metadata(self.root.join(asset.source()))?.modified()?,
+            Some((scheme, path)) => match self.providers.get(scheme) {
+                None => eyre::bail!("No provider for sources with '{}' scheme", scheme),
+                Some(provider) => provider.modified(path)?,
+            },
+        };
+
+        Ok(asset
+            .dependencies()
+            .iter()
+            .filter_map(|dependency| {
+                std::fs::metadata(self.root.join(dependency))
+                    .and_then(|m| m.modified())
+                    .ok()
+            })
+            .fold(source_modified, SystemTime::max))
     }
 
+    /// Checks native files of the asset against size policies.
+    /// Violations of warning policies are logged,
+    /// and violation of the first rejecting policy is returned.
     #[cfg(feature = "import")]
-    pub(crate) fn store(
-        me: &Mutex<Self>,
-        source: &Path,
-        source_format: &str,
+    fn check_size_policies(
+        &mut self,
+        uuid: Uuid,
         native_format: &str,
         tags: &[impl AsRef<str>],
-    ) -> Result<Uuid, StoreError> {
-        let mut lock = me.lock();
+        size: u64,
+    ) -> Result<(), StoreError> {
+        if self.data.size_policies.is_empty() {
+            return Ok(());
+        }
 
-        // Find the source
+        let mut violation = None;
+        for policy in &self.data.size_policies {
+            let total = match &policy.limit {
+                SizeLimit::NativeFormat(format) if **format == *native_format => size,
+                SizeLimit::Tag(tag) if tags.iter().any(|t| t.as_ref() == &**tag) => {
+                    self.data.assets.load_all();
+                    let others: u64 = self
+                        .data
+                        .assets
+                        .iter()
+                        .filter(|a| a.uuid() != uuid && a.tags().contains(tag))
+                        .map(|a| {
+                            let native = self
+                                .root
+                                .join(".treasury")
+                                .join(a.uuid().to_hyphenated().to_string());
+                            native_size(&native, a.locales())
+                        })
+                        .sum();
+                    others + size
+                }
+                _ => continue,
+            };
 
-        let source_absolute;
-        let source_from_root = if source.is_absolute() {
-            source_absolute = source.to_path_buf();
-            relative_to(source, &lock.root)
-        } else {
-            let cd = std::env::current_dir().map_err(|_| StoreError::SourceIoError {
-                path: source.into(),
-                source: std::io::ErrorKind::NotFound.into(),
-            })?;
-            source_absolute = cd.join(source);
-            relative_to(&source_absolute, &lock.root)
-        };
+            if total <= policy.max {
+                continue;
+            }
 
-        if let Some(asset) = lock.data.assets.iter().find(|a| {
-            *a.source() == *source_from_root
-                && a.source_format() == source_format
-                && a.native_format() == native_format
-        }) {
-            tracing::trace!("Already imported");
-            return Ok(asset.uuid());
+            match policy.action {
+                PolicyAction::Warn => tracing::warn!(
+                    "Asset '{}' native size {} violates size policy: {}",
+                    uuid,
+                    total,
+                    policy,
+                ),
+                PolicyAction::Reject => {
+                    violation.get_or_insert(StoreError::PolicyViolation {
+                        policy: policy.clone(),
+                        size: total,
+                    });
+                }
+            }
         }
 
-        tracing::debug!(
-            "Importing {} as {} @ {}",
-            source_format,
-            native_format,
-            source.display()
-        );
+        match violation {
+            None => Ok(()),
+            Some(err) => Err(err),
+        }
+    }
 
-        let uuid = loop {
-            let uuid = Uuid::new_v4();
-            if !lock.data.assets.iter().any(|a| a.uuid() == uuid) {
-                break uuid;
-            }
-        };
+    /// Checks native files of reimported asset against size policies and validators.
+    #[cfg(feature = "import")]
+    fn check_reimport(
+        me: &Mutex<Self>,
+        asset: &Asset,
+        source_absolute: &Path,
+        native_absolute: &Path,
+        locales: &[Box<str>],
+        meta: &serde_json::Value,
+    ) -> Result<(), StoreError> {
+        let size = native_size(native_absolute, locales);
+        let mut lock = me.lock();
+        lock.check_size_policies(asset.uuid(), asset.native_format(), asset.tags(), size)?;
 
-        let native = Path::new(".treasury").join(uuid.to_hyphenated().to_string());
-        let native_absolute = lock.root.join(&native);
+        if lock.validators.is_empty() {
+            return Ok(());
+        }
+        let validators = lock.validators.clone();
+        drop(lock);
 
-        if source_format == native_format {
-            if let Err(err) = std::fs::copy(&source, &native_absolute) {
-                return Err(StoreError::SourceIoError {
-                    source: err,
-                    path: source.into(),
-                });
-            }
-        } else {
-            match lock.importers.get_importer(source_format, native_format) {
-                None => {
-                    return Err(StoreError::ImporterNotFound {
-                        source_format: source_format.to_owned(),
-                        native_format: native_format.to_owned(),
-                    })
-                }
-                Some(importer_entry) => {
-                    tracing::trace!("Importer found. {}", importer_entry.name());
+        let source_bytes =
+            std::fs::read(source_absolute).map_err(|err| StoreError::SourceIoError {
+                path: source_absolute.into(),
+                source: err,
+            })?;
+        let native_bytes =
+            std::fs::read(native_absolute).map_err(|err| StoreError::NativeIoError {
+                path: native_absolute.into(),
+                source: err,
+            })?;
 
-                    let native_tmp_path = native.with_extension("tmp");
-                    let native_tmp_path_absolute = native_absolute.with_extension("tmp");
+        validate(
+            &validators,
+            &ValidatedSource {
+                uri: asset.source(),
+                format: asset.source_format(),
+                settings: asset.settings(),
+                bytes: &source_bytes,
+            },
+            &ValidatedNative {
+                uuid: asset.uuid(),
+                format: asset.native_format(),
+                meta,
+                bytes: &native_bytes,
+            },
+        )
+    }
 
-                    let result = importer_entry.import(&source_absolute, &native_tmp_path, lock);
+    /// Records result of determinism check of the asset import.
+    #[cfg(feature = "import")]
+    fn record_determinism(&mut self, uuid: Uuid, importer: &str, check: Result<(), Box<str>>) {
+        self.nondeterministic.retain(|import| import.uuid != uuid);
 
-                    if let Err(err) = result {
-                        return Err(StoreError::ImportError { source: err });
-                    }
+        if let Err(reason) = check {
+            tracing::warn!(
+                "Importer '{}' is not deterministic for '{}'. {}",
+                importer,
+                uuid,
+                reason
+            );
+            self.nondeterministic.push(NondeterministicImport {
+                uuid,
+                importer: importer.into(),
+                reason,
+            });
+        }
+    }
 
-                    tracing::trace!("Imported successfully");
-                    if let Err(err) = std::fs::rename(&native_tmp_path_absolute, &native_absolute) {
-                        tracing::error!(
-                            "Failed to rename '{}' to '{}'",
-                            native_tmp_path_absolute.display(),
-                            native_absolute.display(),
-                        );
+    /// Returns provider of the source, or `None` for source in local filesystem.
+    #[cfg(feature = "import")]
+    fn source_provider(&self, uri: &str) -> Option<Arc<dyn SourceProvider>> {
+        let (scheme, _) = split_uri(uri)?;
+        self.providers.get(scheme).cloned()
+    }
 
-                        return Err(StoreError::NativeIoError {
-                            path: native_absolute.into(),
-                            source: err,
-                        });
-                    }
+    /// Returns path to local file of the source.
+    #[cfg(feature = "import")]
+    fn source_absolute(&self, uri: &str) -> PathBuf {
+        match split_uri(uri) {
+            None => self.root.join(uri),
+            Some(_) => self.remote_path(uri),
+        }
+    }
 
-                    lock = me.lock();
-                }
+    /// Returns path to local copy of source pulled from provider.
+    /// File name is preserved for importers that look at it.
+    #[cfg(feature = "import")]
+    fn remote_path(&self, uri: &str) -> PathBuf {
+        let dir = Uuid::new_v5(&Uuid::NAMESPACE_URL, uri.as_bytes());
+        let name = uri.rsplit('/').find(|s| !s.is_empty()).unwrap_or("source");
+        self.root
+            .join(".treasury")
+            .join("remote")
+            .join(dir.to_hyphenated().to_string())
+            .join(name)
+    }
+
+    /// Checks if waiting for the import would deadlock.
+    /// That is, if import is performed by current thread
+    /// or by a thread that waits for current thread, directly or transitively.
+    #[cfg(feature = "import")]
+    fn would_deadlock(&self, wait: &ImportWait) -> bool {
+        let current = std::thread::current().id();
+        let mut owner = wait.thread;
+
+        loop {
+            if owner == current {
+                return true;
+            }
+
+            match self.waiting.get(&owner) {
+                Some(wait) if !wait.is_done() => owner = wait.thread,
+                _ => return false,
             }
         }
+    }
 
-        lock.data.assets.push(Asset::new(
-            uuid,
-            source_from_root.into_owned().into(),
-            source_format.into(),
-            native_format.into(),
-            tags.iter().map(|tag| tag.as_ref().into()).collect(),
-        ));
+    /// Waits for the import to finish.
+    /// Registry is unlocked while waiting.
+    #[cfg(feature = "import")]
+    fn wait_import(mut lock: MutexGuard<'_, Self>, wait: Arc<ImportWait>) -> MutexGuard<'_, Self> {
+        let current = std::thread::current().id();
+        lock.waiting.insert(current, wait.clone());
+        MutexGuard::unlocked(&mut lock, || wait.wait());
+        lock.waiting.remove(&current);
+        lock
+    }
 
-        tracing::info!("Asset '{}' registered", uuid);
-        drop(lock);
-        let _ = Self::save(me);
+    /// Waits until asset is not being imported by another thread.
+    /// Registry is unlocked while waiting.
+    #[cfg(feature = "import")]
+    fn wait_idle<'a>(
+        mut lock: MutexGuard<'a, Self>,
+        uuid: &Uuid,
+    ) -> Result<MutexGuard<'a, Self>, FetchError> {
+        while let Some(ImportState::Importing(wait)) = lock.states.get(uuid) {
+            if wait.is_done() {
+                break;
+            }
 
-        Ok(uuid)
+            let wait = wait.clone();
+            if lock.would_deadlock(&wait) {
+                return Err(FetchError::ImportCycle);
+            }
+            lock = Self::wait_import(lock, wait);
+        }
+        Ok(lock)
     }
 
     pub(crate) fn fetch(
         me: &Mutex<Self>,
         uuid: &Uuid,
-        next_version: u64,
+        locale: Option<&str>,
+        known_version: Option<u64>,
     ) -> Result<Option<FetchInfo>, FetchError> {
         let lock = me.lock();
 
-        match lock.data.assets.iter().position(|a| a.uuid() == *uuid) {
+        #[cfg(feature = "import")]
+        let lock = Self::wait_idle(lock, uuid)?;
+
+        let mut lock = lock;
+
+        match lock.data.assets.position(*uuid) {
             None => Err(FetchError::NotFound),
             #[cfg(not(feature = "import"))]
-            Some(_) => {
+            Some(index) => {
+                let name: Box<str> = lock.data.assets[index].name().into();
+                let key = lock.native_key.clone();
                 let native_path = Path::new(".treasury").join(uuid.to_hyphenated().to_string());
                 let native_absolute_path = lock.root.join(&native_path);
                 let native_file = std::fs::File::open(&native_absolute_path).map_err(|source| {
                     FetchError::NativeIoError {
+                        name,
                         source,
                         path: native_absolute_path.clone().into(),
                     }
                 })?;
 
-                let native_modified =
-                    native_file
-                        .metadata()
-                        .and_then(|m| m.modified())
-                        .map_err(|source| FetchError::NativeIoError {
-                            source,
-                            path: native_absolute_path.clone().into(),
-                        })?;
-
-                let version = version_from_systime(native_modified);
-                if next_version > version {
+                let asset = &lock.data.assets[index];
+                let version = asset.version();
+                if known_version == Some(version) {
                     tracing::trace!("Native asset is not updated");
                     return Ok(None);
                 }
 
-                let native_path = native_absolute_path.into();
+                let (native_path, native_file) =
+                    open_localized(native_absolute_path, native_file, asset, locale)?;
 
                 Ok(Some(FetchInfo {
+                    name: asset.name().into(),
                     native_path,
                     native_file,
                     version,
+                    native_format: asset.native_format().into(),
+                    schema: asset.schema(),
+                    lifecycle: asset.lifecycle(),
+                    key,
                 }))
             }
             #[cfg(feature = "import")]
             Some(index) => {
+                let name: Box<str> = lock.data.assets[index].name().into();
+                let key = lock.native_key.clone();
                 let native_path = Path::new(".treasury").join(uuid.to_hyphenated().to_string());
                 let native_absolute_path = lock.root.join(&native_path);
                 let mut native_file =
                     std::fs::File::open(&native_absolute_path).map_err(|source| {
                         FetchError::NativeIoError {
+                            name: name.clone(),
                             source,
                             path: native_absolute_path.clone().into(),
                         }
                     })?;
 
-                let native_modified =
+                let native_metadata =
                     native_file
                         .metadata()
-                        .and_then(|m| m.modified())
                         .map_err(|source| FetchError::NativeIoError {
+                            name: name.clone(),
                             source,
                             path: native_absolute_path.clone().into(),
                         })?;
 
-                let asset = &lock.data.assets[index];
-                let source_absolute = lock.root.join(asset.source());
+                let mut asset = lock.data.assets[index].clone();
+                let imported = imported_at(&asset, &native_metadata);
+                let root = lock.root.clone();
+                let source_absolute = lock.source_absolute(asset.source());
+                let provider = lock.source_provider(asset.source());
 
-                if let Ok(source_modified) =
-                    std::fs::metadata(source_absolute).and_then(|m| m.modified())
-                {
-                    if native_modified < source_modified {
+                let importer = lock
+                    .importers
+                    .get_importer(asset.source_format(), asset.native_format());
+                let copy_through =
+                    lock.copies_through(asset.source_format(), asset.native_format());
+
+                let importer_updated = match importer.as_ref().and_then(|i| i.version()) {
+                    Some(version) => asset.importer_version() != Some(version),
+                    None => false,
+                };
+
+                if let Ok(source_modified) = lock.source_modified(&asset) {
+                    if imported
+                        .is_some_and(|imported| crate::clock::is_after(source_modified, imported))
+                        || importer_updated
+                        || lock.dirty.contains(uuid)
+                    {
                         tracing::trace!("Native asset file is out-of-date. Perform reimport");
+                        lock.dirty.remove(uuid);
+
+                        // Registry lock is released for the duration of reimport.
+                        // Concurrent fetches of this asset wait for it to finish.
+                        let importing = ImportGuard::new();
+                        lock.states
+                            .insert(*uuid, ImportState::Importing(importing.wait()));
+                        drop(lock);
+
+                        let fetched = match &provider {
+                            None => Ok(()),
+                            Some(provider) => {
+                                let (_, path) = split_uri(asset.source()).unwrap();
+                                fetch_remote(&**provider, path, &source_absolute)
+                            }
+                        };
+
+                        if let Err(err) = fetched {
+                            tracing::warn!(
+                                "Failed to fetch source '{}'. Fallback to old file. {:#}",
+                                asset.source(),
+                                err,
+                            );
+                        } else if copy_through {
+                            if let Err(err) = Self::check_reimport(
+                                me,
+                                &asset,
+                                &source_absolute,
+                                &source_absolute,
+                                &[],
+                                asset.meta(),
+                            ) {
+                                tracing::warn!(
+                                    "Native file reimport rejected. {:#}. Fallback to old file",
+                                    err,
+                                );
+                            } else {
+                                std::fs::copy(&source_absolute, &native_absolute_path)
+                                    .and_then(|_| match &key {
+                                        None => Ok(()),
+                                        Some(key) => pack::seal_file(key, &native_absolute_path),
+                                    })
+                                    .map_err(|source| FetchError::NativeIoError {
+                                        name: name.clone(),
+                                        source,
+                                        path: native_absolute_path.clone().into(),
+                                    })?;
 
-                        if asset.source_format() == asset.native_format() {
-                            let source_path = lock.root.join(asset.source());
-                            std::fs::copy(&source_path, &native_absolute_path).map_err(
-                                |source| FetchError::NativeIoError {
-                                    source,
-                                    path: native_absolute_path.clone().into(),
-                                },
-                            )?;
+                                let mut lock = me.lock();
+                                let identity = lock.identity.clone();
+                                let now = lock.clock.now();
+                                asset.bump_version();
+                                asset.set_reimported_by(identity.clone(), now);
+                                if let Some(entry) = lock.data.assets.get_mut(*uuid) {
+                                    entry.bump_version();
+                                    entry.set_reimported_by(identity.clone(), now);
+                                }
+                                tracing::info!("Asset '{}' reimported by '{}'", uuid, identity);
+                                lock.emit(TreasuryEvent::Reimported { uuid: *uuid });
+                            }
                         } else {
-                            match lock
-                                .importers
-                                .get_importer(asset.source_format(), asset.native_format())
-                            {
+                            match importer {
                                 None => {
                                     tracing::warn!(
                                         "Importer from '{}' to '{}' not found, asset '{}@{}' cannot be updated",
                                         asset.source_format(),
                                         asset.native_format(),
                                         asset.uuid(),
-                                        asset.source().display(),
+                                        asset.source(),
                                     );
                                 }
                                 Some(importer) => {
                                     let native_tmp_path = native_path.with_extension("tmp");
                                     let native_tmp_absolute_path =
                                         native_absolute_path.with_extension("tmp");
-                                    let source_path = lock.root.join(asset.source());
 
-                                    let result =
-                                        importer.import(&source_path, &native_tmp_path, lock);
+                                    let source_hash = hash_source(&source_absolute).ok();
+                                    let import_cache = me.lock().import_cache.clone();
+                                    let result = import_cached(
+                                        import_cache.as_deref(),
+                                        &importer,
+                                        &root,
+                                        &source_absolute,
+                                        source_hash.as_deref(),
+                                        &native_tmp_path,
+                                        &asset.settings_json(),
+                                        &mut (),
+                                        &ImportControl::default(),
+                                    );
+
+                                    let check = match &result {
+                                        Ok(output) if me.lock().check_determinism => {
+                                            Some(check_determinism(
+                                                &importer,
+                                                &root,
+                                                &source_absolute,
+                                                &native_tmp_path,
+                                                &asset.settings_json(),
+                                                output,
+                                                &ImportControl::default(),
+                                            ))
+                                        }
+                                        _ => None,
+                                    };
+
+                                    if let Some(check) = check {
+                                        me.lock().record_determinism(*uuid, importer.name(), check);
+                                    }
+
+                                    let result = result.and_then(|output| {
+                                        let checked = Self::check_reimport(
+                                            me,
+                                            &asset,
+                                            &source_absolute,
+                                            &native_tmp_absolute_path,
+                                            &output.locales,
+                                            &output.meta,
+                                        )
+                                        .and_then(|()| match &key {
+                                            None => Ok(()),
+                                            Some(key) => seal_natives(
+                                                key,
+                                                &native_tmp_absolute_path,
+                                                &output.locales,
+                                            )
+                                            .map_err(|source| StoreError::NativeIoError {
+                                                path: native_tmp_absolute_path.clone().into(),
+                                                source,
+                                            }),
+                                        });
+                                        match checked {
+                                            Ok(()) => Ok(output),
+                                            Err(err) => {
+                                                let _ =
+                                                    std::fs::remove_file(&native_tmp_absolute_path);
+                                                for locale in &output.locales {
+                                                    let _ = std::fs::remove_file(localized_path(
+                                                        &native_tmp_absolute_path,
+                                                        locale,
+                                                    ));
+                                                }
+                                                Err(ImportError::Failed(err.into()))
+                                            }
+                                        }
+                                    });
 
                                     match result {
-                                        Ok(()) => {
+                                        Ok(output) => {
+                                            asset.set_source_hash(source_hash);
+
+                                            let stale_locales: Vec<_> = asset
+                                                .locales()
+                                                .iter()
+                                                .filter(|l| !output.locales.contains(l))
+                                                .cloned()
+                                                .collect();
+
+                                            let mut lock = me.lock();
+                                            let dependencies =
+                                                lock.relative_dependencies(output.dependencies);
+
+                                            asset.set_import_output(
+                                                output.meta,
+                                                dependencies,
+                                                output.references.into(),
+                                                output.locales.into(),
+                                                output.importer_version,
+                                                output.schema,
+                                            );
+                                            asset.set_reimported_by(
+                                                lock.identity.clone(),
+                                                lock.clock.now(),
+                                            );
+                                            tracing::info!(
+                                                "Asset '{}' reimported by '{}'",
+                                                uuid,
+                                                lock.identity,
+                                            );
+
+                                            if let Some(entry) = lock.data.assets.get_mut(*uuid) {
+                                                *entry = asset.clone();
+                                            }
+                                            drop(lock);
+
+                                            for locale in asset.locales() {
+                                                let localized_tmp = localized_path(
+                                                    &native_tmp_absolute_path,
+                                                    locale,
+                                                );
+                                                let localized =
+                                                    localized_path(&native_absolute_path, locale);
+
+                                                if let Err(err) =
+                                                    std::fs::rename(&localized_tmp, &localized)
+                                                {
+                                                    tracing::warn!(
+                                                        "Failed to copy native file '{}' from '{}'. {:#}",
+                                                        localized.display(),
+                                                        localized_tmp.display(),
+                                                        err
+                                                    )
+                                                }
+                                            }
+
+                                            for locale in &stale_locales {
+                                                let localized =
+                                                    localized_path(&native_absolute_path, locale);
+                                                let _ = std::fs::remove_file(&localized);
+                                            }
+
                                             drop(native_file);
                                             match std::fs::rename(
                                                 &native_tmp_absolute_path,
@@ -653,6 +3833,9 @@ impl Registry {
                                             ) {
                                                 Ok(()) => {
                                                     tracing::trace!("Native file updated");
+                                                    me.lock().emit(TreasuryEvent::Reimported {
+                                                        uuid: *uuid,
+                                                    });
                                                 }
                                                 Err(err) => {
                                                     tracing::warn!(
@@ -663,17 +3846,18 @@ impl Registry {
                                                         )
                                                 }
                                             }
-                                            match std::fs::File::open(&native_path) {
+                                            match std::fs::File::open(&native_absolute_path) {
                                                 Ok(file) => native_file = file,
                                                 Err(err) => {
                                                     tracing::warn!(
                                                         "Failed to reopen native file '{}'. {:#}",
-                                                        native_path.display(),
+                                                        native_absolute_path.display(),
                                                         err,
                                                     );
                                                     return Err(FetchError::NativeIoError {
+                                                        name: name.clone(),
                                                         source: err,
-                                                        path: native_path.to_path_buf().into(),
+                                                        path: native_absolute_path.into(),
                                                     });
                                                 }
                                             }
@@ -688,6 +3872,10 @@ impl Registry {
                                 }
                             }
                         }
+
+                        me.lock().states.insert(*uuid, ImportState::Idle);
+                        drop(importing);
+                        let _ = Self::save(me);
                     } else {
                         tracing::trace!("Native asset file is up-to-date");
                     }
@@ -695,24 +3883,108 @@ impl Registry {
                     tracing::warn!("Failed to determine if native file is up-to-date");
                 }
 
-                let version = version_from_systime(native_modified);
-                if next_version > version {
+                let version = asset.version();
+                if known_version == Some(version) {
                     tracing::trace!("Native asset is not updated");
                     return Ok(None);
                 }
 
-                let native_path = native_absolute_path.into();
+                let (native_path, native_file) =
+                    open_localized(native_absolute_path, native_file, &asset, locale)?;
 
                 Ok(Some(FetchInfo {
+                    name: asset.name().into(),
                     native_path,
                     native_file,
                     version,
+                    native_format: asset.native_format().into(),
+                    schema: asset.schema(),
+                    lifecycle: asset.lifecycle(),
+                    key,
                 }))
             }
         }
     }
 }
 
+/// Import state of an asset.
+#[cfg(feature = "import")]
+#[derive(Clone)]
+pub(crate) enum ImportState {
+    /// Asset is not being imported.
+    Idle,
+
+    /// Asset is being (re)imported.
+    /// Registry is unlocked while importer runs.
+    Importing(Arc<ImportWait>),
+}
+
+/// Used to wait for asset import to finish.
+#[cfg(feature = "import")]
+pub(crate) struct ImportWait {
+    /// Thread that performs import.
+    thread: ThreadId,
+    done: Mutex<bool>,
+    condvar: Condvar,
+}
+
+#[cfg(feature = "import")]
+impl ImportWait {
+    fn is_done(&self) -> bool {
+        *self.done.lock()
+    }
+
+    /// Blocks until import is finished.
+    fn wait(&self) {
+        let mut done = self.done.lock();
+        while !*done {
+            self.condvar.wait(&mut done);
+        }
+    }
+
+    fn finish(&self) {
+        *self.done.lock() = true;
+        self.condvar.notify_all();
+    }
+}
+
+/// Finishes import started by current thread when dropped,
+/// including early returns and unwinding.
+/// Finished imports left in registry are treated as absent.
+#[cfg(feature = "import")]
+struct ImportGuard(Arc<ImportWait>);
+
+#[cfg(feature = "import")]
+impl ImportGuard {
+    fn new() -> Self {
+        ImportGuard(Arc::new(ImportWait {
+            thread: std::thread::current().id(),
+            done: Mutex::new(false),
+            condvar: Condvar::new(),
+        }))
+    }
+
+    fn wait(&self) -> Arc<ImportWait> {
+        self.0.clone()
+    }
+}
+
+#[cfg(feature = "import")]
+impl Drop for ImportGuard {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+/// Returns identity of the user from environment.
+fn default_identity() -> Box<str> {
+    ["TREASURY_USER", "USER", "USERNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|user| !user.is_empty()))
+        .unwrap_or_else(|| "unknown".to_owned())
+        .into()
+}
+
 #[cfg(feature = "import")]
 fn relative_to<'a>(path: &'a Path, root: &Path) -> std::borrow::Cow<'a, Path> {
     use std::path::PathBuf;
@@ -755,9 +4027,232 @@ fn relative_to<'a>(path: &'a Path, root: &Path) -> std::borrow::Cow<'a, Path> {
     }
 }
 
-fn version_from_systime(systime: SystemTime) -> u64 {
-    systime
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64
+/// Lists files in directory recursively.
+/// Paths are relative to root.
+/// Returns uuid of asset stored from the source in project with the namespace.
+/// Attempt is incremented while uuid is taken.
+#[cfg(feature = "import")]
+fn source_uuid(
+    namespace: &Uuid,
+    source: &str,
+    source_format: &str,
+    native_format: &str,
+    attempt: u32,
+) -> Uuid {
+    let name = format!(
+        "{}\0{}\0{}\0{}",
+        source, source_format, native_format, attempt
+    );
+    Uuid::new_v5(namespace, name.as_bytes())
+}
+
+/// Collects files in the directory recursively, skipping hidden ones.
+/// Unreadable directories are skipped.
+#[cfg(feature = "import")]
+fn find_sources(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            tracing::debug!("Failed to read directory '{}'. {:#}", dir.display(), err);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => find_sources(&entry.path(), files),
+            Ok(_) => files.push(entry.path()),
+            Err(_) => {}
+        }
+    }
+}
+
+fn list_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<std::path::PathBuf>,
+) -> Result<(), CompactError> {
+    let absolute = root.join(dir);
+    let io_error = |source| CompactError::IoError {
+        path: absolute.clone().into(),
+        source,
+    };
+
+    for entry in std::fs::read_dir(&absolute).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        let path = dir.join(entry.file_name());
+        if entry.file_type().map_err(io_error)?.is_dir() {
+            list_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Returns locales to try for specified locale, most specific first.
+/// E.g. `pt-BR` yields `pt-BR` and `pt`.
+/// Native file without locale is the last fallback and is not yielded.
+pub fn locale_fallbacks(locale: &str) -> impl Iterator<Item = &str> {
+    let mut next = Some(locale).filter(|locale| !locale.is_empty());
+    std::iter::from_fn(move || {
+        let locale = next?;
+        next = locale.rfind(['-', '_']).map(|pos| &locale[..pos]);
+        Some(locale)
+    })
+}
+
+/// Returns path to native file for the locale.
+/// Matches `localized_native_path` used by importers.
+/// Checks if two native files have the same content,
+/// decrypting native files encrypted at rest with respective keys.
+/// Missing or unreadable files are compared as empty.
+fn same_native(a: &Path, b: &Path, (a_key, b_key): (Option<&PackKey>, Option<&PackKey>)) -> bool {
+    if a_key.is_none() && b_key.is_none() {
+        return same_content(a, b);
+    }
+
+    let read = |path: &Path, key| {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+            Err(err) => {
+                tracing::warn!("Failed to read '{}'. {:#}", path.display(), err);
+                return Vec::new();
+            }
+        };
+        pack::unseal(key, bytes).unwrap_or_else(|err| {
+            tracing::warn!("Failed to read '{}'. {:#}", path.display(), err);
+            Vec::new()
+        })
+    };
+
+    read(a, a_key) == read(b, b_key)
+}
+
+/// Checks if two files have the same content.
+/// Missing or unreadable files are compared as empty.
+fn same_content(a: &Path, b: &Path) -> bool {
+    let open = |path: &Path| match std::fs::File::open(path) {
+        Ok(file) => Some(file),
+        Err(err) => {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to open '{}'. {:#}", path.display(), err);
+            }
+            None
+        }
+    };
+
+    let (mut a, mut b) = match (open(a), open(b)) {
+        (None, None) => return true,
+        (Some(a), Some(b)) => (a, b),
+        (Some(file), None) | (None, Some(file)) => {
+            return file.metadata().is_ok_and(|m| m.len() == 0)
+        }
+    };
+
+    match (a.metadata(), b.metadata()) {
+        (Ok(a), Ok(b)) if a.len() == b.len() => {}
+        _ => return false,
+    }
+
+    let mut chunk_a = [0; 8192];
+    let mut chunk_b = [0; 8192];
+    loop {
+        let read = match a.read(&mut chunk_a) {
+            Ok(0) => return true,
+            Ok(read) => read,
+            Err(_) => return false,
+        };
+        if b.read_exact(&mut chunk_b[..read]).is_err() || chunk_a[..read] != chunk_b[..read] {
+            return false;
+        }
+    }
+}
+
+/// Encrypts native file and its localized versions at rest.
+#[cfg(feature = "import")]
+fn seal_natives(
+    key: &PackKey,
+    native_path: &Path,
+    locales: &[impl AsRef<str>],
+) -> std::io::Result<()> {
+    pack::seal_file(key, native_path)?;
+    for locale in locales {
+        pack::seal_file(key, &localized_path(native_path, locale.as_ref()))?;
+    }
+    Ok(())
+}
+
+/// Returns time native files of the asset were imported according to treasury clock,
+/// or modification time of native file for assets stored before times were recorded.
+fn imported_at(asset: &Asset, native: &std::fs::Metadata) -> Option<SystemTime> {
+    asset.imported_at().or_else(|| native.modified().ok())
+}
+
+/// Returns total size of native file and its localized versions.
+/// Missing files count as empty.
+fn native_size(native_path: &Path, locales: &[impl AsRef<str>]) -> u64 {
+    std::iter::once(native_path.to_path_buf())
+        .chain(
+            locales
+                .iter()
+                .map(|locale| localized_path(native_path, locale.as_ref())),
+        )
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Removes native file written by failed import along with native files for locales.
+#[cfg(feature = "import")]
+fn remove_partial(native_path: &Path) {
+    let _ = std::fs::remove_file(native_path);
+
+    let (dir, prefix) = match (native_path.parent(), native_path.file_name()) {
+        (Some(dir), Some(name)) => (dir, format!("{}@", name.to_string_lossy())),
+        _ => return,
+    };
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+pub(crate) fn localized_path(native_path: &Path, locale: &str) -> std::path::PathBuf {
+    let mut file_name = native_path.file_name().unwrap_or_default().to_owned();
+    file_name.push("@");
+    file_name.push(locale);
+    native_path.with_file_name(file_name)
+}
+
+/// Opens native file of the asset for the locale.
+/// Returns already opened native file without locale if asset has no file for the locale.
+fn open_localized(
+    native_path: std::path::PathBuf,
+    native_file: std::fs::File,
+    asset: &Asset,
+    locale: Option<&str>,
+) -> Result<(Box<Path>, std::fs::File), FetchError> {
+    match asset.resolve_locale(locale) {
+        None => Ok((native_path.into(), native_file)),
+        Some(locale) => {
+            let localized = localized_path(&native_path, locale);
+            match std::fs::File::open(&localized) {
+                Ok(file) => Ok((localized.into(), file)),
+                Err(source) => Err(FetchError::NativeIoError {
+                    name: asset.name().into(),
+                    source,
+                    path: localized.into(),
+                }),
+            }
+        }
+    }
 }
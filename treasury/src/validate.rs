@@ -0,0 +1,63 @@
+use {crate::treasury::StoreError, std::sync::Arc, uuid::Uuid};
+
+/// Check run on every stored or reimported asset, before its native file is accepted,
+/// e.g. to enforce naming conventions, texture dimensions or audio loudness limits.
+///
+/// Failed store returns [`StoreError::ValidationFailed`],
+/// and failed reimport keeps old native files.
+pub trait StoreValidator: Send + Sync + 'static {
+    /// Returns name of the validator reported in errors.
+    fn name(&self) -> &str;
+
+    /// Validates the asset. Error rejects its native file.
+    fn validate(
+        &self,
+        source: &ValidatedSource<'_>,
+        native: &ValidatedNative<'_>,
+    ) -> eyre::Result<()>;
+}
+
+/// Source of the asset passed to [`StoreValidator`].
+pub struct ValidatedSource<'a> {
+    /// URI of the source, see [`Asset::source`].
+    ///
+    /// [`Asset::source`]: crate::Asset::source
+    pub uri: &'a str,
+
+    pub format: &'a str,
+
+    /// Import settings of the asset.
+    pub settings: &'a serde_json::Value,
+
+    pub bytes: &'a [u8],
+}
+
+/// Native file of the asset passed to [`StoreValidator`].
+pub struct ValidatedNative<'a> {
+    pub uuid: Uuid,
+
+    pub format: &'a str,
+
+    /// Metadata recorded by importer.
+    pub meta: &'a serde_json::Value,
+
+    /// Native file without locale.
+    pub bytes: &'a [u8],
+}
+
+/// Runs validators in order they were added, stopping at first failure.
+pub(crate) fn validate(
+    validators: &[Arc<dyn StoreValidator>],
+    source: &ValidatedSource<'_>,
+    native: &ValidatedNative<'_>,
+) -> Result<(), StoreError> {
+    for validator in validators {
+        if let Err(err) = validator.validate(source, native) {
+            return Err(StoreError::ValidationFailed {
+                validator: validator.name().into(),
+                source: err,
+            });
+        }
+    }
+    Ok(())
+}
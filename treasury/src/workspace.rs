@@ -0,0 +1,309 @@
+use {
+    crate::{
+        asset::Asset,
+        treasury::{AssetData, AssetStat, FetchError, OpenError, SaveError, Treasury},
+    },
+    std::path::{Path, PathBuf},
+    uuid::Uuid,
+};
+
+#[cfg(feature = "import")]
+use {crate::treasury::StoreError, goods_treasury_import::FormatId};
+
+/// Name of workspace manifest file in workspace root.
+pub const WORKSPACE_MANIFEST: &str = "treasury-workspace.json";
+
+/// Member treasury listed in workspace manifest.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct MemberManifest {
+    name: Box<str>,
+
+    /// Root of the member treasury relative to workspace root.
+    path: PathBuf,
+
+    /// Sources with paths relative to workspace root starting with
+    /// any of these prefixes are stored into this member.
+    #[serde(default)]
+    prefixes: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct WorkspaceManifest {
+    members: Vec<MemberManifest>,
+}
+
+/// Treasury of the workspace.
+pub struct WorkspaceMember {
+    name: Box<str>,
+    root: PathBuf,
+    prefixes: Vec<PathBuf>,
+    treasury: Treasury,
+}
+
+impl WorkspaceMember {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns root directory of the member treasury.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn treasury(&self) -> &Treasury {
+        &self.treasury
+    }
+
+    pub fn treasury_mut(&mut self) -> &mut Treasury {
+        &mut self.treasury
+    }
+
+    /// Returns length of the longest prefix matching the source, if any.
+    fn matches(&self, source: &Path) -> Option<usize> {
+        self.prefixes
+            .iter()
+            .filter(|prefix| source.starts_with(prefix))
+            .map(|prefix| prefix.components().count())
+            .max()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkspaceOpenError {
+    #[error("Failed to open workspace manifest '{path}'")]
+    ManifestOpenError {
+        path: Box<Path>,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to deserialize workspace manifest '{path}'")]
+    JsonError {
+        path: Box<Path>,
+        source: serde_json::Error,
+    },
+
+    #[error("Failed to open workspace member '{name}'")]
+    MemberOpenError { name: Box<str>, source: OpenError },
+
+    #[error("Asset '{uuid}' is registered in both '{first}' and '{second}' members")]
+    UuidConflict {
+        uuid: Uuid,
+        first: Box<str>,
+        second: Box<str>,
+    },
+}
+
+#[cfg(feature = "import")]
+#[derive(Debug, thiserror::Error)]
+pub enum WorkspaceStoreError {
+    #[error("No workspace member is configured for source '{path}'")]
+    NoMember { path: Box<Path> },
+
+    #[error("Failed to store asset into workspace member '{name}'")]
+    StoreError { name: Box<str>, source: StoreError },
+
+    #[error("Asset '{uuid}' stored into '{name}' is already registered in '{other}'")]
+    UuidConflict {
+        uuid: Uuid,
+        name: Box<str>,
+        other: Box<str>,
+    },
+}
+
+/// Multiple treasuries opened as one asset database with single UUID space,
+/// e.g. to split assets of core, characters and environments along repository boundaries.
+///
+/// Members are listed in [`WORKSPACE_MANIFEST`] file in workspace root:
+///
+/// ```json
+/// {
+///     "members": [
+///         { "name": "core", "path": "core", "prefixes": ["assets/core"] },
+///         { "name": "characters", "path": "characters", "prefixes": ["assets/characters"] }
+///     ]
+/// }
+/// ```
+///
+/// Sources are stored into the member with the longest prefix matching source path
+/// relative to workspace root, or the member which root contains the source.
+/// Assets are fetched from whichever member has them.
+/// Loaders read workspace assets with a `TreasurySource` per member.
+pub struct Workspace {
+    root: PathBuf,
+    members: Vec<WorkspaceMember>,
+}
+
+impl Workspace {
+    /// Opens workspace from its manifest and all member treasuries.
+    ///
+    /// Fails if same uuid is registered in more than one member.
+    #[tracing::instrument(skip(root), fields(root = %root.as_ref().display()))]
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, WorkspaceOpenError> {
+        let root = root.as_ref();
+        let manifest_path = root.join(WORKSPACE_MANIFEST);
+
+        let file = std::fs::File::open(&manifest_path).map_err(|source| {
+            WorkspaceOpenError::ManifestOpenError {
+                source,
+                path: manifest_path.clone().into(),
+            }
+        })?;
+
+        let manifest: WorkspaceManifest =
+            serde_json::from_reader(file).map_err(|source| WorkspaceOpenError::JsonError {
+                source,
+                path: manifest_path.clone().into(),
+            })?;
+
+        let mut members: Vec<WorkspaceMember> = Vec::new();
+        for member in manifest.members {
+            let member_root = root.join(&member.path);
+            let treasury = Treasury::open(&member_root).map_err(|source| {
+                WorkspaceOpenError::MemberOpenError {
+                    name: member.name.clone(),
+                    source,
+                }
+            })?;
+
+            for asset in treasury.list(&[] as &[&str], None) {
+                if let Some(other) = members.iter().find(|m| m.treasury.contains(asset.uuid())) {
+                    return Err(WorkspaceOpenError::UuidConflict {
+                        uuid: asset.uuid(),
+                        first: other.name.clone(),
+                        second: member.name,
+                    });
+                }
+            }
+
+            members.push(WorkspaceMember {
+                name: member.name,
+                root: member_root,
+                prefixes: member.prefixes,
+                treasury,
+            });
+        }
+
+        Ok(Workspace {
+            root: root.into(),
+            members,
+        })
+    }
+
+    /// Returns root directory of the workspace.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn members(&self) -> &[WorkspaceMember] {
+        &self.members
+    }
+
+    pub fn member(&self, name: &str) -> Option<&WorkspaceMember> {
+        self.members.iter().find(|m| *m.name == *name)
+    }
+
+    /// Returns member that has the asset.
+    pub fn member_of(&self, uuid: Uuid) -> Option<&WorkspaceMember> {
+        self.members.iter().find(|m| m.treasury.contains(uuid))
+    }
+
+    /// Saves manifests of all member treasuries.
+    pub fn save(&self) -> Result<(), SaveError> {
+        for member in &self.members {
+            member.treasury.save()?;
+        }
+        Ok(())
+    }
+
+    /// Returns member the source is stored into.
+    /// Relative source path is relative to workspace root.
+    pub fn route(&self, source: impl AsRef<Path>) -> Option<&WorkspaceMember> {
+        let source = self.root.join(source);
+        let relative = source.strip_prefix(&self.root).unwrap_or(&source);
+
+        self.members
+            .iter()
+            .filter_map(|m| m.matches(relative).map(|len| (len, m)))
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, m)| m)
+            .or_else(|| {
+                self.members
+                    .iter()
+                    .filter(|m| source.starts_with(&m.root))
+                    .max_by_key(|m| m.root.components().count())
+            })
+    }
+
+    /// Imports asset into workspace member routed by source path.
+    /// Relative source path is relative to workspace root.
+    #[cfg(feature = "import")]
+    pub fn store(
+        &self,
+        source: impl AsRef<Path>,
+        source_format: impl Into<FormatId>,
+        native_format: impl Into<FormatId>,
+        tags: &[impl AsRef<str>],
+    ) -> Result<Uuid, WorkspaceStoreError> {
+        let source = self.root.join(source);
+        let member = self
+            .route(&source)
+            .ok_or_else(|| WorkspaceStoreError::NoMember {
+                path: source.clone().into(),
+            })?;
+
+        let uuid = member
+            .treasury
+            .store(&source, source_format, native_format, tags)
+            .map_err(|source| WorkspaceStoreError::StoreError {
+                name: member.name.clone(),
+                source,
+            })?;
+
+        let other = self
+            .members
+            .iter()
+            .find(|m| !std::ptr::eq(*m, member) && m.treasury.contains(uuid));
+
+        if let Some(other) = other {
+            member.treasury.remove(uuid);
+            return Err(WorkspaceStoreError::UuidConflict {
+                uuid,
+                name: member.name.clone(),
+                other: other.name.clone(),
+            });
+        }
+
+        Ok(uuid)
+    }
+
+    /// Fetches asset in native format from member that has it.
+    pub fn fetch(&mut self, uuid: &Uuid, locale: Option<&str>) -> Result<AssetData, FetchError> {
+        match self.members.iter_mut().find(|m| m.treasury.contains(*uuid)) {
+            None => Err(FetchError::NotFound),
+            Some(member) => member.treasury.fetch(uuid, locale),
+        }
+    }
+
+    /// Returns metadata of the asset from member that has it.
+    pub fn stat(&self, uuid: Uuid) -> Result<AssetStat, FetchError> {
+        match self.member_of(uuid) {
+            None => Err(FetchError::NotFound),
+            Some(member) => member.treasury.stat(uuid),
+        }
+    }
+
+    /// Returns assets information of all members.
+    pub fn list(&self, tags: &[impl AsRef<str>], native_format: Option<&str>) -> Vec<Asset> {
+        self.members
+            .iter()
+            .flat_map(|m| m.treasury.list(tags, native_format))
+            .collect()
+    }
+
+    /// Removes asset from member that has it.
+    pub fn remove(&self, uuid: Uuid) {
+        if let Some(member) = self.member_of(uuid) {
+            member.treasury.remove(uuid);
+        }
+    }
+}